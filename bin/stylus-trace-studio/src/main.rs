@@ -4,18 +4,26 @@
 //! Generates flamegraphs and detailed profiles from transaction traces.
 
 use anyhow::{Context, Result};
-use clap::{Args, Parser, Subcommand};
+use clap::{Args, CommandFactory, Parser, Subcommand};
+use clap_complete::Shell;
 use env_logger::Env;
 use log::info;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
+use stylus_trace_core::aggregator::{AnonymousFrames, HotPathSort};
 use stylus_trace_core::commands::{
-    display_schema, display_version, execute_capture, validate_args, validate_profile_file,
-    CaptureArgs,
+    display_schema, display_version, execute_capture, matrix_profile_files, merge_profile_files,
+    validate_args, validate_profile_file, CaptureArgs,
 };
-use stylus_trace_core::flamegraph::FlamegraphConfig;
-use stylus_trace_core::output::json::read_profile;
+use stylus_trace_core::flamegraph::{ColorMode, FlamegraphConfig};
+use stylus_trace_core::output::json::{is_stdout_path, read_profile, JsonStyle};
 use stylus_trace_core::output::viewer::{generate_viewer, open_browser};
+use stylus_trace_core::parser::GasUnit;
+use stylus_trace_core::parser::HostIoType;
+use stylus_trace_core::rpc::{default_cache_dir, CallRequest};
+use stylus_trace_core::utils::config::{
+    DEFAULT_MAX_TRACE_STEPS, DEFAULT_TESTNODE_RPC, SIMULATED_CALL_LABEL,
+};
 
 /// Stylus Trace Studio - Performance profiling for Arbitrum Stylus
 #[derive(Parser, Debug)]
@@ -29,9 +37,19 @@ pub struct Cli {
     /// Enable verbose logging
     #[arg(short, long, global = true)]
     pub verbose: bool,
+
+    /// Disable colored output (also disabled by NO_COLOR or when stdout is not a terminal)
+    #[arg(long, global = true)]
+    pub no_color: bool,
+
+    /// Base directory for outputs given as bare file names (written to <DIR>/capture, <DIR>/diff or <DIR>/merge)
+    #[arg(long, global = true, default_value = "artifacts")]
+    pub output_dir: PathBuf,
 }
 
 /// Available commands
+// Parsed once at startup, so the size of the `Capture` variant does not matter
+#[allow(clippy::large_enum_variant)]
 #[derive(Subcommand, Debug)]
 pub enum Commands {
     /// Capture and profile a transaction
@@ -40,22 +58,98 @@ pub enum Commands {
         #[arg(short, long, default_value = "http://localhost:8547")]
         rpc: String,
 
-        /// Transaction hash to profile
-        #[arg(short, long)]
-        tx: String,
+        /// RPC request timeout in seconds (raise for heavy transactions on busy nodes)
+        #[arg(long, default_value = "30")]
+        timeout: u64,
+
+        /// Directory for cached traces (defaults to $STYLUS_TRACE_CACHE_DIR or ~/.cache/stylus-trace)
+        #[arg(long)]
+        cache_dir: Option<PathBuf>,
+
+        /// Always fetch the trace from RPC and do not read or write the cache
+        #[arg(long, conflicts_with = "refresh_cache")]
+        no_cache: bool,
+
+        /// Re-fetch the trace from RPC and overwrite the cached copy
+        #[arg(long)]
+        refresh_cache: bool,
+
+        /// Transaction hash to profile (omit when simulating a call with --to)
+        #[arg(short, long, required_unless_present = "to", conflicts_with = "to")]
+        tx: Option<String>,
+
+        /// Simulate an un-mined call to this contract with debug_traceCall instead of tracing a transaction
+        #[arg(long)]
+        to: Option<String>,
+
+        /// Sender address for the simulated call
+        #[arg(long, requires = "to")]
+        from: Option<String>,
+
+        /// Calldata (hex) for the simulated call
+        #[arg(long, requires = "to")]
+        data: Option<String>,
 
-        /// Output path for JSON profile (placed in artifacts/capture/ by default)
+        /// Value in wei (decimal or 0x hex) for the simulated call
+        #[arg(long, requires = "to")]
+        value: Option<String>,
+
+        /// Block to simulate the call at (tag such as latest, or a hex number)
+        #[arg(long, default_value = "latest")]
+        block: String,
+
+        /// Output path for JSON profile (bare file names go to <output-dir>/capture/; `-` writes to stdout)
         #[arg(short, long, default_value = "profile.json")]
         output: PathBuf,
 
-        /// Output path for SVG flamegraph (placed in artifacts/capture/ by default)
+        /// Output path for SVG flamegraph (bare file names go to <output-dir>/capture/)
         #[arg(short, long, default_missing_value = "flamegraph.svg", num_args = 0..=1)]
         flamegraph: Option<PathBuf>,
 
+        /// Output path for nested call-tree JSON (bare file names go to <output-dir>/capture/)
+        #[arg(long)]
+        tree: Option<PathBuf>,
+
+        /// Output path for a compact machine-readable summary JSON (bare file names go to <output-dir>/capture/)
+        #[arg(long)]
+        summary_json: Option<PathBuf>,
+
+        /// Output path for newline-delimited hot paths JSON (bare file names go to <output-dir>/capture/)
+        #[arg(long)]
+        ndjson: Option<PathBuf>,
+
+        /// Output path for a speedscope JSON profile, viewable at speedscope.app (bare file names go to <output-dir>/capture/)
+        #[arg(long, value_name = "PATH")]
+        speedscope: Option<PathBuf>,
+
+        /// Debug: write the raw collapsed stacks (stack, weight, last_pc) as JSON (bare file names go to <output-dir>/capture/)
+        #[arg(long, value_name = "PATH")]
+        dump_stacks: Option<PathBuf>,
+
+        /// Profile JSON layout: pretty (readable) or compact (smallest)
+        #[arg(long, value_enum, default_value = "pretty")]
+        json_style: JsonStyle,
+
+        /// Spaces per indentation level for pretty profile JSON
+        #[arg(long, value_name = "N")]
+        json_indent: Option<usize>,
+
+        /// Free-text name stored in the profile and shown instead of the tx hash (e.g. "with-cache")
+        #[arg(long, value_name = "NAME")]
+        label: Option<String>,
+
         /// Number of top hot paths to include
         #[arg(long, default_value = "20")]
         top_paths: usize,
 
+        /// Hot-path ordering in the profile and summary
+        #[arg(long, value_enum, default_value_t = HotPathSort::Gas)]
+        sort: HotPathSort,
+
+        /// Reverse the hot-path ordering
+        #[arg(long)]
+        reverse: bool,
+
         /// Flamegraph title
         #[arg(long)]
         title: Option<String>,
@@ -64,10 +158,42 @@ pub enum Commands {
         #[arg(long, default_value = "1200")]
         width: usize,
 
+        /// Flamegraph coloring: by category, or category faded by each frame's self gas (heat)
+        #[arg(long, value_enum, default_value = "category")]
+        color_by: ColorMode,
+
+        /// Append each frame's gas (ink with --ink) to its flamegraph label when the frame is wide enough
+        #[arg(long)]
+        label_gas: bool,
+
+        /// Mark flamegraph frames that an analyzer insight refers to (e.g. cold storage reads) with a ⚠ badge
+        #[arg(long = "annotate-insights-on-flamegraph")]
+        annotate_insights: bool,
+
+        /// Leave out the footer and <metadata> recording tool version, transaction and timestamp in the flamegraph
+        #[arg(long)]
+        no_watermark: bool,
+
+        /// HostIO type to color as expensive (crimson) in the flamegraph; repeatable, replaces the default storage_store/storage_flush set
+        #[arg(long = "expensive-hostio", value_name = "TYPE", value_parser = parse_hostio_type)]
+        expensive_hostio: Vec<HostIoType>,
+
         /// Print text summary to stdout
         #[arg(long)]
         summary: bool,
 
+        /// Add a rollup of hot-path gas by the first N stack segments (e.g. module/function) to the summary
+        #[arg(
+            long = "group-hot-paths-by-prefix",
+            value_name = "N",
+            requires = "summary"
+        )]
+        group_hot_paths: Option<usize>,
+
+        /// Add a plain-language note on the cost of each summary hot path's HostIO operation
+        #[arg(long, requires = "summary")]
+        explain: bool,
+
         /// Use Stylus Ink units (scaled by 10,000)
         #[arg(long)]
         ink: bool,
@@ -76,10 +202,42 @@ pub enum Commands {
         #[arg(long)]
         tracer: Option<String>,
 
+        /// Tracer config JSON object passed as `tracerConfig` (e.g. '{"onlyTopCall": true}')
+        #[arg(long)]
+        tracer_config: Option<String>,
+
+        /// If the tracer is not supported by the node, fall back to the first supported one (stylusTracer, then callTracer)
+        #[arg(long)]
+        auto_tracer: bool,
+
+        /// Unit of raw trace gas values for ambiguous traces (defaults to the detected format's unit)
+        #[arg(long, value_enum)]
+        assume_unit: Option<GasUnit>,
+
+        /// Fail instead of zeroing steps whose end ink exceeds start ink
+        #[arg(long)]
+        strict_ink: bool,
+
+        /// Validate arguments, RPC connectivity and tracer support without writing any files
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Capture the transaction twice and report whether (and where) the two captures differ
+        #[arg(long, alias = "compare-to-self", conflicts_with = "dry_run")]
+        repeatability: bool,
+
+        /// Target a local nitro-testnode (http://localhost:8547): checks the node is Nitro and skips the trace cache
+        #[arg(long, conflicts_with = "rpc")]
+        testnode: bool,
+
         /// Path to baseline profile for on-the-fly diffing
         #[arg(long)]
         baseline: Option<PathBuf>,
 
+        /// Transaction hash to fetch via RPC and use as the on-the-fly diff baseline
+        #[arg(long = "baseline-tx", conflicts_with = "baseline")]
+        baseline_tx: Option<String>,
+
         /// Simple increase threshold percentage (e.g., 5.0). Applies to Gas, HostIOs, and Hot Paths.
         #[arg(short = 'p', long = "threshold-percent")]
         threshold_percent: Option<f64>,
@@ -95,6 +253,86 @@ pub enum Commands {
         /// Open interactive web viewer
         #[arg(long)]
         view: bool,
+
+        /// Redact long hex blobs (addresses, calldata) from written artifacts
+        #[arg(long)]
+        redact: bool,
+
+        /// Redaction config file (TOML) with custom patterns; implies --redact
+        #[arg(long = "redact-config")]
+        redact_config: Option<PathBuf>,
+
+        /// Contract ABI JSON; frames that are 4-byte selectors are relabelled with function signatures
+        #[arg(long = "selector-map")]
+        selector_map: Option<PathBuf>,
+
+        /// Show intrinsic (non-execution) gas as an explicit flamegraph frame
+        #[arg(long = "show-intrinsic")]
+        show_intrinsic: bool,
+
+        /// Add approximate per-type HostIO frames to the flamegraph (overlaps execution gas)
+        #[arg(long = "hostio-stacks")]
+        hostio_stacks: bool,
+
+        /// Render only the N heaviest flamegraph subtrees (below the first frame that branches) and merge the rest into `other`; the profile JSON keeps every stack
+        #[arg(long = "max-hot-paths-in-svg", value_name = "N")]
+        max_svg_subtrees: Option<usize>,
+
+        /// Fold consecutive identical (recursive) frames in the flamegraph, e.g. `f;f;f;g` -> `f;g`
+        #[arg(long = "collapse-recursion")]
+        collapse_recursion: bool,
+
+        /// Label each frame folded by --collapse-recursion with its repeat count, e.g. `f (x3)`
+        #[arg(long = "annotate-recursion", requires = "collapse_recursion")]
+        annotate_recursion: bool,
+
+        /// Drop flamegraph frames matching this regex, moving their gas to the parent (repeatable)
+        #[arg(long = "exclude", value_name = "PATTERN")]
+        exclude: Vec<String>,
+
+        /// Keep only flamegraph frames matching this regex; others fold into their nearest kept ancestor (repeatable)
+        #[arg(long = "include-only", value_name = "PATTERN")]
+        include_only: Vec<String>,
+
+        /// Partition execution by the active contract and report gas per called contract
+        #[arg(long = "split-by-contract")]
+        split_by_contract: bool,
+
+        /// Label delegatecall frames with the implementation address and keep them in the caller's context
+        #[arg(long = "follow-delegatecalls")]
+        follow_delegatecalls: bool,
+
+        /// Drop anonymous `call`/`unknown` frames and charge their gas to the parent frame
+        #[arg(long = "hide-anonymous", conflicts_with = "label_anonymous")]
+        hide_anonymous: bool,
+
+        /// Name anonymous frames after their position: `call (depth N)` and `unknown (pc 0x…)`
+        #[arg(long = "label-anonymous")]
+        label_anonymous: bool,
+
+        /// Contract WASM built with debug info, used to map PCs to source locations
+        #[arg(long, value_name = "PATH")]
+        wasm: Option<PathBuf>,
+
+        /// Print gas aggregated per source file (needs --wasm)
+        #[arg(long = "group-by-file", requires = "wasm")]
+        group_by_file: bool,
+
+        /// HostIO types listed in the summary histogram and baseline diff (0 = all)
+        #[arg(long = "top-hostio", value_name = "N")]
+        top_hostio: Option<usize>,
+
+        /// Keep the N most frequent HostIO types in the profile and fold the rest into `other` (0 = all)
+        #[arg(long = "top-hostio-types", value_name = "N")]
+        top_hostio_types: Option<usize>,
+
+        /// Decimal places for percentages in the summary, baseline diff and flamegraph tooltips
+        #[arg(long, value_name = "N")]
+        precision: Option<usize>,
+
+        /// Fail when the trace has more execution steps than this, guarding against runaway traces (0 = no limit)
+        #[arg(long, value_name = "N", default_value_t = DEFAULT_MAX_TRACE_STEPS)]
+        max_trace_steps: usize,
     },
 
     /// Compare two transaction profiles and detect regressions
@@ -116,6 +354,32 @@ pub enum Commands {
         /// Path to profile JSON file
         #[arg(short, long)]
         file: PathBuf,
+
+        /// Report current-schema fields missing from the profile (and defaulted), unknown fields, and newer profile versions
+        #[arg(long)]
+        schema_check: bool,
+    },
+
+    /// Average repeated captures of a transaction into one profile
+    Merge {
+        /// Profile JSON files to merge
+        #[arg(required = true, num_args = 2..)]
+        profiles: Vec<PathBuf>,
+
+        /// Output path for the merged profile JSON
+        #[arg(short, long, default_value = "merged.json")]
+        output: PathBuf,
+    },
+
+    /// Compare the total gas of several profiles pairwise
+    Matrix {
+        /// Profile JSON files to compare
+        #[arg(required = true, num_args = 2..)]
+        profiles: Vec<PathBuf>,
+
+        /// Output path for the comparison matrix JSON
+        #[arg(short, long)]
+        output: Option<PathBuf>,
     },
 
     /// CI configuration and management
@@ -133,20 +397,56 @@ pub enum Commands {
 
     /// Display version information
     Version,
+
+    /// Generate a shell completion script on stdout
+    Completions {
+        /// Shell to generate completions for
+        #[arg(value_enum)]
+        shell: Shell,
+    },
 }
 
 #[derive(Args, Debug)]
 pub struct DiffSubArgs {
-    /// Path to the baseline profile JSON
-    pub baseline: PathBuf,
+    /// Path to the baseline profile JSON (with --baseline-dir, --baseline-glob or --baseline-git, pass only the target)
+    #[arg(required_unless_present_any = ["baseline_dir", "baseline_glob", "baseline_git"])]
+    pub baseline: Option<PathBuf>,
 
     /// Path to the target profile JSON
-    pub target: PathBuf,
+    pub target: Option<PathBuf>,
+
+    /// Directory of baselines; the baseline is selected by contract address or file name
+    #[arg(long)]
+    pub baseline_dir: Option<PathBuf>,
+
+    /// Use the latest file (by name) matching this glob as the baseline, e.g. 'baselines/mytx-*.json'
+    #[arg(long, value_name = "PATTERN", conflicts_with = "baseline_dir")]
+    pub baseline_glob: Option<String>,
+
+    /// Read the baseline as it existed at a git ref via `git show`, e.g. 'main:baselines/token.json'
+    #[arg(
+        long,
+        value_name = "REF:PATH",
+        conflicts_with_all = ["baseline_dir", "baseline_glob", "update_baseline"]
+    )]
+    pub baseline_git: Option<String>,
+
+    /// Overwrite the selected baseline with the target when the diff passes
+    #[arg(long)]
+    pub update_baseline: bool,
+
+    /// Exit with an error when the diff status is WARNING, not only FAILED
+    #[arg(long)]
+    pub fail_on_warning: bool,
 
     /// Optional threshold configuration file (TOML)
     #[arg(short, long)]
     pub threshold: Option<PathBuf>,
 
+    /// Read thresholds from STYLUS_* environment variables (above the TOML file, below CLI flags)
+    #[arg(long = "threshold-env")]
+    pub threshold_env: bool,
+
     /// Simple increase threshold percentage (e.g., 5.0). Applies to Gas, HostIOs, and Hot Paths.
     #[arg(short = 'p', long = "threshold-percent")]
     pub threshold_percent: Option<f64>,
@@ -163,6 +463,10 @@ pub struct DiffSubArgs {
     #[arg(short, long, default_value_t = true)]
     pub summary: bool,
 
+    /// Print only the header, gas delta, HostIO totals and status; skips the HostIO, hot-path and insight tables
+    #[arg(long)]
+    pub summary_only: bool,
+
     /// Path to write the diff report JSON
     #[arg(short, long, default_value = "diff_report.json")]
     pub output: Option<PathBuf>,
@@ -171,25 +475,102 @@ pub struct DiffSubArgs {
     #[arg(short = 'f', long, default_missing_value = "diff.svg", num_args = 0..=1)]
     pub flamegraph: Option<PathBuf>,
 
+    /// Path to write baseline and target flamegraphs side by side on a common gas scale (SVG)
+    #[arg(long = "compare-svg-side-by-side", value_name = "PATH", default_missing_value = "side_by_side.svg", num_args = 0..=1)]
+    pub side_by_side: Option<PathBuf>,
+
     /// Open interactive side-by-side web viewer
     #[arg(long)]
     pub view: bool,
+
+    /// Compare HostIO calls only. Skips gas and hot-path comparison in analysis, thresholds, and output.
+    #[arg(long = "compare-hostio-only")]
+    pub compare_hostio_only: bool,
+
+    /// Match hot paths by exact stack string instead of normalizing frame names across tracers
+    #[arg(long)]
+    pub strict_paths: bool,
+
+    /// Drop insights with this tag from the summary and JSON report (repeatable)
+    #[arg(long = "suppress-insight", value_name = "TAG")]
+    pub suppress_insight: Vec<String>,
+
+    /// Only list HostIO types and hot paths that increased in the terminal summary (JSON report is unfiltered)
+    #[arg(long)]
+    pub only_regressions: bool,
+
+    /// Number of HostIO types listed in the terminal summary (0 = all)
+    #[arg(long = "top-hostio", value_name = "N", default_value_t = 5)]
+    pub top_hostio: usize,
+
+    /// Cumulative gas budget file (JSON); each run's gas change is charged against it and it is updated when the diff passes
+    #[arg(long, value_name = "PATH", conflicts_with = "compare_hostio_only")]
+    pub budget_file: Option<PathBuf>,
+
+    /// Reference HostIO costs (TOML, gas per call); types whose average falls outside the range are reported as insights
+    #[arg(long, value_name = "PATH")]
+    pub cost_table: Option<PathBuf>,
+
+    /// Flag read_args/write_result gas above this share of total gas as a large buffer copy (percent)
+    #[arg(long, value_name = "PERCENT")]
+    pub memory_gas_share: Option<f64>,
+
+    /// Flag log emission above this share of total gas as excessive (percent)
+    #[arg(long, value_name = "PERCENT")]
+    pub log_gas_share: Option<f64>,
+
+    /// Flag log emission above this share of HostIO calls as excessive (percent)
+    #[arg(long, value_name = "PERCENT")]
+    pub log_call_share: Option<f64>,
+
+    /// Only compare hot paths holding at least this share of total gas in either profile (percent)
+    #[arg(long = "min-path-percent", value_name = "PERCENT")]
+    pub min_path_percent: Option<f64>,
+
+    /// Decimal places for percentages in the terminal summary and diff flamegraph tooltips
+    #[arg(long, value_name = "N")]
+    pub precision: Option<usize>,
 }
 
 fn main() -> Result<()> {
     let cli = Cli::parse();
     setup_logging(cli.verbose);
+    if cli.no_color {
+        colored::control::set_override(false);
+    }
 
     match cli.command {
-        Commands::Capture { .. } => handle_capture(cli.command)?,
-        Commands::Diff(ref args) => handle_diff(args)?,
-        Commands::View { ref tx, ref rpc } => handle_view(tx, rpc)?,
-        Commands::Validate { file } => {
-            validate_profile_file(file).context("Failed to validate profile")?
+        Commands::Capture { .. } => handle_capture(cli.command, &cli.output_dir)?,
+        Commands::Diff(ref args) => handle_diff(args, &cli.output_dir, cli.no_color)?,
+        Commands::View { ref tx, ref rpc } => handle_view(tx, rpc, &cli.output_dir)?,
+        Commands::Validate { file, schema_check } => {
+            validate_profile_file(file, schema_check).context("Failed to validate profile")?
+        }
+        Commands::Merge { profiles, output } => {
+            let profiles: Vec<PathBuf> = profiles
+                .into_iter()
+                .map(|p| resolve_artifact_path(p, &cli.output_dir, "capture"))
+                .collect();
+            let output = resolve_artifact_path(output, &cli.output_dir, "merge");
+            merge_profile_files(&profiles, &output).context("Failed to merge profiles")?
+        }
+        Commands::Matrix { profiles, output } => {
+            let profiles: Vec<PathBuf> = profiles
+                .into_iter()
+                .map(|p| resolve_artifact_path(p, &cli.output_dir, "capture"))
+                .collect();
+            let output = output.map(|p| resolve_artifact_path(p, &cli.output_dir, "diff"));
+            matrix_profile_files(&profiles, output.as_deref())
+                .context("Failed to build diff matrix")?
         }
         Commands::Ci { subcommand } => handle_ci(subcommand)?,
         Commands::Schema { show } => display_schema(show),
         Commands::Version => display_version(),
+        Commands::Completions { shell } => {
+            let mut command = Cli::command();
+            let name = command.get_name().to_string();
+            clap_complete::generate(shell, &mut command, name, &mut std::io::stdout());
+        }
     }
 
     Ok(())
@@ -258,37 +639,104 @@ fn setup_logging(verbose: bool) {
 }
 
 /// Handle the capture command logic
-fn handle_capture(command: Commands) -> Result<()> {
+fn handle_capture(command: Commands, output_dir: &Path) -> Result<()> {
     if let Commands::Capture {
         rpc,
+        timeout,
+        cache_dir,
+        no_cache,
+        refresh_cache,
         tx,
+        to,
+        from,
+        data,
+        value,
+        block,
         mut output,
         mut flamegraph,
+        tree,
+        summary_json,
+        ndjson,
+        speedscope,
+        dump_stacks,
+        json_style,
+        json_indent,
+        label,
         top_paths,
+        sort,
+        reverse,
         title,
         width,
+        color_by,
+        label_gas,
+        annotate_insights,
+        no_watermark,
+        expensive_hostio,
         summary,
+        group_hot_paths,
+        explain,
         ink,
         tracer,
+        tracer_config,
+        auto_tracer,
+        assume_unit,
+        strict_ink,
+        dry_run,
+        repeatability,
+        testnode,
         baseline,
+        baseline_tx,
         threshold_percent,
         gas_threshold,
         hostio_threshold,
         view,
+        redact,
+        redact_config,
+        selector_map,
+        show_intrinsic,
+        hostio_stacks,
+        max_svg_subtrees,
+        collapse_recursion,
+        annotate_recursion,
+        exclude,
+        include_only,
+        split_by_contract,
+        follow_delegatecalls,
+        hide_anonymous,
+        label_anonymous,
+        wasm,
+        group_by_file,
+        top_hostio,
+        top_hostio_types,
+        precision,
+        max_trace_steps,
     } = command
     {
-        // Enforce artifacts/ directory for relative paths
-        output = resolve_artifact_path(output, "capture");
+        // Bare file names are placed under <output-dir>/capture; `-` is stdout
+        if !is_stdout_path(&output) {
+            output = resolve_artifact_path(output, output_dir, "capture");
+        }
 
         if let Some(path) = flamegraph {
-            flamegraph = Some(resolve_artifact_path(path, "capture"));
+            flamegraph = Some(resolve_artifact_path(path, output_dir, "capture"));
         }
 
-        let baseline = baseline.map(|p| resolve_artifact_path(p, "capture"));
+        let tree = tree.map(|p| resolve_artifact_path(p, output_dir, "capture"));
+        let summary_json = summary_json.map(|p| resolve_artifact_path(p, output_dir, "capture"));
+        let ndjson = ndjson.map(|p| resolve_artifact_path(p, output_dir, "capture"));
+        let speedscope = speedscope.map(|p| resolve_artifact_path(p, output_dir, "capture"));
+        let dump_stacks = dump_stacks.map(|p| resolve_artifact_path(p, output_dir, "capture"));
+        let baseline = baseline.map(|p| resolve_artifact_path(p, output_dir, "capture"));
 
         // Build flamegraph configuration if requested
         let flamegraph_config = flamegraph.as_ref().map(|_| {
-            let mut config = FlamegraphConfig::new().with_ink(ink);
+            let mut config = FlamegraphConfig::new()
+                .with_ink(ink)
+                .with_color_by(color_by)
+                .with_label_gas(label_gas);
+            if !expensive_hostio.is_empty() {
+                config = config.with_expensive_hostio(expensive_hostio);
+            }
             config.width = width;
             if let Some(t) = title {
                 config = config.with_title(t);
@@ -297,21 +745,81 @@ fn handle_capture(command: Commands) -> Result<()> {
         });
 
         let args = CaptureArgs {
-            rpc_url: rpc,
-            transaction_hash: tx,
+            rpc_url: if testnode {
+                DEFAULT_TESTNODE_RPC.to_string()
+            } else {
+                rpc
+            },
+            timeout_secs: timeout,
+            cache_dir: (!no_cache).then(|| cache_dir.unwrap_or_else(default_cache_dir)),
+            call: to.map(|to| CallRequest {
+                from,
+                to,
+                data,
+                value,
+            }),
+            call_block: block,
+            refresh_cache,
+            transaction_hash: tx.unwrap_or_else(|| SIMULATED_CALL_LABEL.to_string()),
             output_json: output,
             output_svg: flamegraph,
+            output_tree: tree,
+            output_summary_json: summary_json,
+            output_ndjson: ndjson,
+            output_speedscope: speedscope,
+            dump_stacks,
+            json_style,
+            json_indent,
+            label,
             top_paths,
+            sort,
+            reverse,
             flamegraph_config,
+            annotate_insights,
+            watermark: !no_watermark,
             print_summary: summary,
+            group_hot_paths,
+            explain,
             tracer,
+            tracer_config,
+            auto_tracer,
+            assume_unit,
+            strict_ink,
+            dry_run,
+            repeatability,
+            testnode,
             ink,
             baseline,
+            baseline_tx,
             threshold_percent,
             gas_threshold,
             hostio_threshold,
-            wasm: None,
+            wasm,
             view,
+            redact,
+            redact_config,
+            selector_map,
+            show_intrinsic,
+            hostio_stacks,
+            max_svg_subtrees,
+            collapse_recursion,
+            annotate_recursion,
+            exclude_frames: exclude,
+            include_only_frames: include_only,
+            split_by_contract,
+            follow_delegatecalls,
+            anonymous_frames: if hide_anonymous {
+                AnonymousFrames::Hide
+            } else if label_anonymous {
+                AnonymousFrames::Label
+            } else {
+                AnonymousFrames::Keep
+            },
+            group_by_file,
+            top_hostio,
+            top_hostio_types,
+            precision,
+            max_trace_steps,
         };
 
         validate_args(&args).context("Invalid capture arguments")?;
@@ -322,24 +830,65 @@ fn handle_capture(command: Commands) -> Result<()> {
 }
 
 /// Handle the diff command logic
-fn handle_diff(args: &DiffSubArgs) -> Result<()> {
+fn handle_diff(args: &DiffSubArgs, output_dir: &Path, no_color: bool) -> Result<()> {
+    // With --baseline-dir/--baseline-glob/--baseline-git the single positional argument is the target
+    let auto_baseline =
+        args.baseline_dir.is_some() || args.baseline_glob.is_some() || args.baseline_git.is_some();
+    let (baseline, target) = match (auto_baseline, &args.baseline, &args.target) {
+        (true, Some(target), None) => (PathBuf::new(), target.clone()),
+        (true, _, Some(_)) => {
+            anyhow::bail!(
+                "With --baseline-dir, --baseline-glob or --baseline-git, pass only the target profile"
+            )
+        }
+        (false, Some(baseline), Some(target)) => (
+            resolve_artifact_path(baseline.clone(), output_dir, "capture"),
+            target.clone(),
+        ),
+        _ => anyhow::bail!("Both a baseline and a target profile are required"),
+    };
+
     let studio_args = stylus_trace_core::commands::models::DiffArgs {
-        baseline: resolve_artifact_path(args.baseline.clone(), "capture"),
-        target: resolve_artifact_path(args.target.clone(), "capture"),
+        baseline,
+        target: resolve_artifact_path(target, output_dir, "capture"),
         threshold_file: args.threshold.clone(),
+        threshold_env: args.threshold_env,
         threshold_percent: args.threshold_percent,
         summary: args.summary,
+        summary_only: args.summary_only,
         output: args
             .output
             .as_ref()
-            .map(|p| resolve_artifact_path(p.clone(), "diff")),
+            .map(|p| resolve_artifact_path(p.clone(), output_dir, "diff")),
         output_svg: args
             .flamegraph
             .as_ref()
-            .map(|p| resolve_artifact_path(p.clone(), "diff")),
+            .map(|p| resolve_artifact_path(p.clone(), output_dir, "diff")),
+        side_by_side_svg: args
+            .side_by_side
+            .as_ref()
+            .map(|p| resolve_artifact_path(p.clone(), output_dir, "diff")),
         gas_threshold: args.gas_threshold,
         hostio_threshold: args.hostio_threshold,
         view: args.view,
+        hostio_only: args.compare_hostio_only,
+        only_regressions: args.only_regressions,
+        baseline_dir: args.baseline_dir.clone(),
+        baseline_glob: args.baseline_glob.clone(),
+        baseline_git: args.baseline_git.clone(),
+        update_baseline: args.update_baseline,
+        fail_on_warning: args.fail_on_warning,
+        no_color,
+        strict_paths: args.strict_paths,
+        suppress_insights: args.suppress_insight.clone(),
+        top_hostio: args.top_hostio,
+        budget_file: args.budget_file.clone(),
+        cost_table: args.cost_table.clone(),
+        memory_gas_share: args.memory_gas_share,
+        log_gas_share: args.log_gas_share,
+        log_call_share: args.log_call_share,
+        min_path_percent: args.min_path_percent,
+        precision: args.precision,
     };
 
     stylus_trace_core::commands::diff::execute_diff(studio_args)
@@ -348,7 +897,7 @@ fn handle_diff(args: &DiffSubArgs) -> Result<()> {
 }
 
 /// Handle the view command logic
-fn handle_view(tx_or_path: &str, rpc: &str) -> Result<()> {
+fn handle_view(tx_or_path: &str, rpc: &str, output_dir: &Path) -> Result<()> {
     let path = PathBuf::from(tx_or_path);
 
     // Check if it's an existing JSON file
@@ -360,7 +909,7 @@ fn handle_view(tx_or_path: &str, rpc: &str) -> Result<()> {
         open_browser(&viewer_path)?;
     } else if tx_or_path.starts_with("0x") && tx_or_path.len() == 66 {
         info!("Capturing and viewing transaction: {}", tx_or_path);
-        let output = resolve_artifact_path(PathBuf::from("profile.json"), "capture");
+        let output = resolve_artifact_path(PathBuf::from("profile.json"), output_dir, "capture");
         let args = CaptureArgs {
             rpc_url: rpc.to_string(),
             transaction_hash: tx_or_path.to_string(),
@@ -376,15 +925,25 @@ fn handle_view(tx_or_path: &str, rpc: &str) -> Result<()> {
     Ok(())
 }
 
-/// Resolves a path to the artifacts/<category> directory if it's a simple filename
-fn resolve_artifact_path(path: PathBuf, category: &str) -> PathBuf {
+/// Resolves a bare file name to `<output_dir>/<category>/<name>`
+///
+/// Paths with any directory component (including `./name`) are used as given.
+fn resolve_artifact_path(path: PathBuf, output_dir: &Path, category: &str) -> PathBuf {
     if path
         .parent()
         .map(|p| p.as_os_str().is_empty())
         .unwrap_or(true)
     {
-        PathBuf::from("artifacts").join(category).join(path)
+        output_dir.join(category).join(path)
     } else {
         path
     }
 }
+
+/// Parses a HostIO type name or alias (e.g. `storage_load`, `sload`)
+fn parse_hostio_type(name: &str) -> Result<HostIoType, String> {
+    match name.parse::<HostIoType>() {
+        Ok(HostIoType::Other) | Err(_) => Err(format!("unknown HostIO type '{}'", name)),
+        Ok(io_type) => Ok(io_type),
+    }
+}
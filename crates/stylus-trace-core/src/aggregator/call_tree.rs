@@ -0,0 +1,113 @@
+//! Build a nested call tree from parsed trace data.
+//!
+//! Unlike collapsed stacks, the call tree keeps the hierarchy explicit and
+//! records both self gas (consumed by the frame itself) and total gas
+//! (including all descendants) for every node.
+//!
+//! Example: "main;execute;storage_read 1000" becomes
+//! main (total 1000) -> execute (total 1000) -> storage_read (self 1000)
+
+use super::stack_builder::{build_collapsed_stacks, CollapsedStack};
+use crate::parser::ParsedTrace;
+use log::debug;
+use serde::{Deserialize, Serialize};
+
+/// A single node in the call tree
+///
+/// **Public** - serialized for `--tree` exports
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CallTreeNode {
+    /// Frame name (operation or function)
+    pub name: String,
+
+    /// Gas consumed by this frame itself, excluding children
+    pub self_gas: u64,
+
+    /// Gas consumed by this frame including all descendants
+    pub total_gas: u64,
+
+    /// Child frames, sorted by total gas (descending)
+    pub children: Vec<CallTreeNode>,
+}
+
+impl CallTreeNode {
+    /// Create a new empty node
+    ///
+    /// **Public** - constructor
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            self_gas: 0,
+            total_gas: 0,
+            children: Vec::new(),
+        }
+    }
+
+    /// Insert a stack path with its weight into the tree
+    fn insert(&mut self, stack: &[&str], weight: u64) {
+        self.total_gas = self.total_gas.saturating_add(weight);
+
+        let Some((head, tail)) = stack.split_first() else {
+            self.self_gas = self.self_gas.saturating_add(weight);
+            return;
+        };
+
+        let index = match self.children.iter().position(|c| c.name == *head) {
+            Some(index) => index,
+            None => {
+                self.children.push(CallTreeNode::new(*head));
+                self.children.len() - 1
+            }
+        };
+        self.children[index].insert(tail, weight);
+    }
+
+    /// Sort children recursively by total gas (descending), then by name
+    fn sort(&mut self) {
+        self.children
+            .sort_by(|a, b| b.total_gas.cmp(&a.total_gas).then(a.name.cmp(&b.name)));
+        for child in &mut self.children {
+            child.sort();
+        }
+    }
+}
+
+/// Build a call tree from parsed trace
+///
+/// **Public** - main entry point for tree building
+///
+/// # Arguments
+/// * `parsed_trace` - Parsed trace data from parser
+///
+/// # Returns
+/// Root node of the call tree
+pub fn build_call_tree(parsed_trace: &ParsedTrace) -> CallTreeNode {
+    let stacks = build_collapsed_stacks(parsed_trace);
+    build_call_tree_from_stacks(&stacks)
+}
+
+/// Build a call tree from already collapsed stacks
+///
+/// **Public** - avoids rebuilding stacks when they are already available
+///
+/// # Arguments
+/// * `stacks` - Collapsed stacks from stack_builder
+///
+/// # Returns
+/// Root node of the call tree
+pub fn build_call_tree_from_stacks(stacks: &[CollapsedStack]) -> CallTreeNode {
+    debug!("Building call tree from {} stacks", stacks.len());
+
+    let mut root = CallTreeNode::new("root");
+    for stack in stacks {
+        let mut parts: Vec<&str> = stack.stack.split(';').collect();
+        // Skip redundant root if present
+        if parts.first() == Some(&"root") {
+            parts.remove(0);
+        }
+        root.insert(&parts, stack.weight);
+    }
+    root.sort();
+
+    root
+}
@@ -0,0 +1,85 @@
+//! Frame filtering for collapsed stacks.
+//!
+//! Runtime glue (allocator, panic handlers, ...) can dominate a flamegraph
+//! without being actionable. Filtering removes matching frames and folds
+//! their gas into the nearest remaining ancestor, so totals are preserved.
+//!
+//! Example: excluding `alloc` turns "main;alloc;memcpy 10" into "main;memcpy 10",
+//! and "main;alloc 5" into "main 5".
+
+use super::stack_builder::{rewrite_stacks, CollapsedStack};
+use log::debug;
+use regex::Regex;
+
+/// Compiled frame include/exclude rules
+///
+/// **Public** - used by capture for `--exclude` and `--include-only`
+#[derive(Debug, Clone, Default)]
+pub struct FrameFilter {
+    exclude: Vec<Regex>,
+    include_only: Vec<Regex>,
+}
+
+impl FrameFilter {
+    /// Compile a filter from regular expressions matched against frame names
+    ///
+    /// **Public** - constructor
+    ///
+    /// # Arguments
+    /// * `exclude` - Frames matching any of these are removed
+    /// * `include_only` - If non-empty, only frames matching one of these are kept
+    ///
+    /// # Errors
+    /// * `regex::Error` - A pattern is not a valid regular expression
+    pub fn new(exclude: &[String], include_only: &[String]) -> Result<Self, regex::Error> {
+        let compile = |patterns: &[String]| {
+            patterns
+                .iter()
+                .map(|p| Regex::new(p))
+                .collect::<Result<Vec<_>, _>>()
+        };
+
+        Ok(Self {
+            exclude: compile(exclude)?,
+            include_only: compile(include_only)?,
+        })
+    }
+
+    /// Whether the filter has no rules (applying it is a no-op)
+    pub fn is_empty(&self) -> bool {
+        self.exclude.is_empty() && self.include_only.is_empty()
+    }
+
+    /// Whether a single frame survives the filter
+    pub fn keeps(&self, frame: &str) -> bool {
+        if self.exclude.iter().any(|re| re.is_match(frame)) {
+            return false;
+        }
+        self.include_only.is_empty() || self.include_only.iter().any(|re| re.is_match(frame))
+    }
+
+    /// Drop filtered frames from every stack, reattributing gas to the parent
+    ///
+    /// Stacks that become identical are merged; stacks left without any frame
+    /// are dropped (their gas has no ancestor to go to).
+    ///
+    /// # Returns
+    /// Filtered stacks sorted by weight (descending)
+    pub fn apply(&self, stacks: &[CollapsedStack]) -> Vec<CollapsedStack> {
+        if self.is_empty() {
+            return stacks.to_vec();
+        }
+
+        let filtered = rewrite_stacks(stacks, |stack| {
+            let frames: Vec<&str> = stack.split(';').filter(|f| self.keeps(f)).collect();
+            (!frames.is_empty()).then(|| frames.join(";"))
+        });
+
+        debug!(
+            "Frame filter reduced {} stacks to {}",
+            stacks.len(),
+            filtered.len()
+        );
+        filtered
+    }
+}
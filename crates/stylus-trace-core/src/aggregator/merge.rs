@@ -0,0 +1,213 @@
+//! Merge repeated captures into a single averaged profile.
+//!
+//! Capturing the same transaction several times and averaging the results
+//! gives a steadier diff baseline than one noisy run. Totals and HostIO
+//! metrics are averaged across all samples; hot paths are averaged over the
+//! samples too, but only paths present in every sample are kept.
+//!
+//! Example: total gas 100, 110 and 120 merge to 110 with a standard
+//! deviation of ~8.16.
+
+use super::stack_builder::CollapsedStack;
+use crate::parser::schema::{avg_gas_per_call, HostIoSummary, HotPath, Profile, SampleStats};
+use crate::utils::config::{generated_at, SCHEMA_VERSION};
+use log::debug;
+use std::collections::{BTreeMap, HashMap};
+
+/// Average several profiles of the same transaction into one
+///
+/// **Public** - used to build a stable baseline from repeated captures
+///
+/// Metadata (transaction hash, label, contract, block) is kept only when every
+/// sample agrees on it. `all_stacks` is averaged (a stack missing from a
+/// sample counts as zero) when every sample carries stacks.
+///
+/// # Arguments
+/// * `profiles` - Profiles to merge, typically captures of one transaction
+///
+/// # Returns
+/// The averaged profile with `samples` statistics, or `None` if `profiles`
+/// is empty
+pub fn merge_profiles(profiles: &[Profile]) -> Option<Profile> {
+    let first = profiles.first()?;
+    debug!("Merging {} profiles", profiles.len());
+
+    let total_gas: Vec<u64> = profiles.iter().map(|p| p.total_gas).collect();
+    let hostio_calls: Vec<u64> = profiles
+        .iter()
+        .map(|p| p.hostio_summary.total_calls)
+        .collect();
+    let hostio_gas: Vec<u64> = profiles
+        .iter()
+        .map(|p| p.hostio_summary.total_hostio_gas)
+        .collect();
+
+    let (hot_paths, hot_path_gas_stddev, dropped_hot_paths) = merge_hot_paths(profiles);
+    let by_type = merge_hostio_types(profiles, |s| &s.by_type);
+    let gas_by_type = merge_hostio_types(profiles, |s| &s.gas_by_type);
+
+    let samples = SampleStats {
+        count: profiles.len(),
+        total_gas_stddev: stddev(&total_gas),
+        hostio_calls_stddev: stddev(&hostio_calls),
+        hostio_gas_stddev: stddev(&hostio_gas),
+        hot_path_gas_stddev,
+        dropped_hot_paths,
+    };
+
+    Some(Profile {
+        version: SCHEMA_VERSION.to_string(),
+        transaction_hash: common_value(profiles, |p| Some(p.transaction_hash.clone()))
+            .unwrap_or_else(|| first.transaction_hash.clone()),
+        label: common_value(profiles, |p| p.label.clone()),
+        contract_address: common_value(profiles, |p| p.contract_address.clone()),
+        block_number: common_value(profiles, |p| p.block_number),
+        total_gas: mean(&total_gas),
+        // One estimated sample makes the average an estimate too
+        total_gas_source: profiles
+            .iter()
+            .map(|p| p.total_gas_source)
+            .find(|source| !source.is_reported())
+            .unwrap_or_default(),
+        hostio_summary: HostIoSummary {
+            total_calls: mean(&hostio_calls),
+            avg_gas_per_call: avg_gas_per_call(&by_type, &gas_by_type),
+            by_type,
+            total_hostio_gas: mean(&hostio_gas),
+            gas_by_type,
+        },
+        contracts: None,
+        // Slot reads belong to one transaction's execution
+        repeated_slot_reads: None,
+        hot_paths,
+        all_stacks: merge_stacks(profiles),
+        samples: Some(samples),
+        generated_at: generated_at(),
+    })
+}
+
+/// Average hot paths present in every profile
+///
+/// **Private** - internal helper for merge_profiles
+///
+/// # Returns
+/// Kept paths sorted by mean gas (descending), their gas standard deviations,
+/// and the number of distinct paths dropped
+fn merge_hot_paths(profiles: &[Profile]) -> (Vec<HotPath>, BTreeMap<String, f64>, usize) {
+    let mut by_stack: BTreeMap<&str, Vec<&HotPath>> = BTreeMap::new();
+    for profile in profiles {
+        for path in &profile.hot_paths {
+            by_stack.entry(path.stack.as_str()).or_default().push(path);
+        }
+    }
+
+    let mut kept = Vec::new();
+    let mut deviations = BTreeMap::new();
+    let mut dropped = 0;
+
+    for (stack, samples) in by_stack {
+        if samples.len() != profiles.len() {
+            dropped += 1;
+            continue;
+        }
+
+        let gas: Vec<u64> = samples.iter().map(|p| p.gas).collect();
+        let cumulative: Vec<u64> = samples.iter().map(|p| p.cumulative_gas).collect();
+        let percentage = samples.iter().map(|p| p.percentage).sum::<f64>() / samples.len() as f64;
+
+        deviations.insert(stack.to_string(), stddev(&gas));
+        kept.push(HotPath {
+            gas: mean(&gas),
+            cumulative_gas: mean(&cumulative),
+            percentage,
+            ..samples[0].clone()
+        });
+    }
+
+    kept.sort_by(|a, b| b.gas.cmp(&a.gas).then_with(|| a.stack.cmp(&b.stack)));
+    (kept, deviations, dropped)
+}
+
+/// Average a per-type HostIO map (a missing type counts as zero)
+///
+/// **Private** - internal helper for merge_profiles
+fn merge_hostio_types(
+    profiles: &[Profile],
+    field: impl Fn(&HostIoSummary) -> &HashMap<String, u64>,
+) -> HashMap<String, u64> {
+    let mut totals: HashMap<String, u128> = HashMap::new();
+    for profile in profiles {
+        for (io_type, count) in field(&profile.hostio_summary) {
+            *totals.entry(io_type.clone()).or_insert(0) += *count as u128;
+        }
+    }
+
+    totals
+        .into_iter()
+        .map(|(io_type, total)| (io_type, rounded_mean(total, profiles.len())))
+        .collect()
+}
+
+/// Average full stacks when every profile has them
+///
+/// **Private** - internal helper for merge_profiles
+fn merge_stacks(profiles: &[Profile]) -> Option<Vec<CollapsedStack>> {
+    let mut totals: HashMap<&str, (u128, Option<u64>)> = HashMap::new();
+    for profile in profiles {
+        for stack in profile.all_stacks.as_ref()? {
+            let entry = totals.entry(stack.stack.as_str()).or_insert((0, None));
+            entry.0 += stack.weight as u128;
+            entry.1 = entry.1.or(stack.last_pc);
+        }
+    }
+
+    let mut stacks: Vec<CollapsedStack> = totals
+        .into_iter()
+        .map(|(stack, (total, pc))| {
+            CollapsedStack::new(stack.to_string(), rounded_mean(total, profiles.len()), pc)
+        })
+        .filter(|s| s.weight > 0)
+        .collect();
+    stacks.sort_by(|a, b| b.weight.cmp(&a.weight).then_with(|| a.stack.cmp(&b.stack)));
+    Some(stacks)
+}
+
+/// Value shared by every profile, or `None` if any differ or are missing
+///
+/// **Private** - internal helper for merge_profiles
+fn common_value<T: PartialEq>(
+    profiles: &[Profile],
+    get: impl Fn(&Profile) -> Option<T>,
+) -> Option<T> {
+    let mut values = profiles.iter().map(get);
+    let first = values.next()??;
+    values.all(|v| v.as_ref() == Some(&first)).then_some(first)
+}
+
+/// Rounded arithmetic mean (sums in u128 so it cannot overflow)
+fn mean(values: &[u64]) -> u64 {
+    rounded_mean(values.iter().map(|&v| v as u128).sum(), values.len())
+}
+
+fn rounded_mean(total: u128, count: usize) -> u64 {
+    if count == 0 {
+        return 0;
+    }
+    let count = count as u128;
+    ((total + count / 2) / count).min(u64::MAX as u128) as u64
+}
+
+/// Population standard deviation
+fn stddev(values: &[u64]) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    let n = values.len() as f64;
+    let mean = values.iter().map(|&v| v as f64).sum::<f64>() / n;
+    let variance = values
+        .iter()
+        .map(|&v| (v as f64 - mean).powi(2))
+        .sum::<f64>()
+        / n;
+    variance.sqrt()
+}
@@ -3,7 +3,7 @@
 //! Hot paths are the execution paths that consume the most gas.
 //! These are the primary targets for optimization.
 
-use super::stack_builder::CollapsedStack;
+use super::stack_builder::{is_synthetic_hostio_stack, CollapsedStack};
 use crate::parser::schema::{GasCategory, HotPath};
 use log::debug;
 
@@ -30,15 +30,77 @@ pub fn calculate_hot_paths(
     );
 
     // Total weight of these stacks is our base for percentages
-    let execution_total: u64 = stacks.iter().map(|s| s.weight).sum();
+    let execution_total: u64 = stacks.iter().map(|s| s.weight).fold(0, u64::saturating_add);
 
     stacks
         .iter()
         .take(top_n)
-        .map(|stack| create_hot_path(stack, execution_total))
+        .map(|stack| {
+            let mut hot_path = create_hot_path(stack, execution_total);
+            hot_path.cumulative_gas = calculate_cumulative_gas(stacks, &stack.stack);
+            hot_path
+        })
         .collect()
 }
 
+/// Sum the weights of a stack and every stack it is a prefix of
+///
+/// **Public** - cumulative (self + descendants) gas for a collapsed stack
+///
+/// # Arguments
+/// * `stacks` - All collapsed stacks of the transaction
+/// * `prefix` - Stack to accumulate (e.g., "main;execute")
+pub fn calculate_cumulative_gas(stacks: &[CollapsedStack], prefix: &str) -> u64 {
+    stacks
+        .iter()
+        .filter(|s| {
+            s.stack == prefix
+                || (s.stack.starts_with(prefix) && s.stack[prefix.len()..].starts_with(';'))
+        })
+        .map(|s| s.weight)
+        .fold(0, u64::saturating_add)
+}
+
+/// Ordering applied to hot paths before they are written or printed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum HotPathSort {
+    /// Gas consumed (descending)
+    #[default]
+    Gas,
+    /// Percentage of execution gas (descending)
+    Percent,
+    /// Stack string (alphabetical)
+    Name,
+    /// Gas category (most expensive categories first)
+    Category,
+}
+
+/// Reorder hot paths in place
+///
+/// **Public** - used by capture for `--sort` / `--reverse`
+///
+/// The sort is stable, so paths that compare equal keep their gas ordering.
+///
+/// # Arguments
+/// * `hot_paths` - Hot paths to reorder
+/// * `order` - Sort key
+/// * `reverse` - Invert the natural order of the sort key
+pub fn sort_hot_paths(hot_paths: &mut [HotPath], order: HotPathSort, reverse: bool) {
+    hot_paths.sort_by(|a, b| {
+        let ordering = match order {
+            HotPathSort::Gas => b.gas.cmp(&a.gas),
+            HotPathSort::Percent => b.percentage.total_cmp(&a.percentage),
+            HotPathSort::Name => a.stack.cmp(&b.stack),
+            HotPathSort::Category => a.category.cmp(&b.category),
+        };
+        if reverse {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    });
+}
+
 /// Create a HotPath from a CollapsedStack
 ///
 pub fn create_hot_path(stack: &CollapsedStack, denominator: u64) -> HotPath {
@@ -57,6 +119,7 @@ pub fn create_hot_path(stack: &CollapsedStack, denominator: u64) -> HotPath {
     HotPath {
         stack: stack.stack.clone(),
         gas: stack.weight,
+        cumulative_gas: stack.weight,
         percentage,
         category,
         source_hint: stack.last_pc.map(|pc| crate::parser::schema::SourceHint {
@@ -111,17 +174,25 @@ pub fn categorize_stack_leaf(leaf: &str) -> GasCategory {
 ///
 /// **Public** - provides summary statistics
 ///
+/// Synthetic HostIO breakdown stacks are ignored so they cannot inflate totals.
+///
 /// # Arguments
 /// * `stacks` - Collapsed stacks
 ///
 /// # Returns
 /// Statistics about gas distribution
 pub fn calculate_gas_distribution(stacks: &[CollapsedStack]) -> GasDistribution {
+    let stacks: Vec<&CollapsedStack> = stacks
+        .iter()
+        .filter(|s| !is_synthetic_hostio_stack(s))
+        .collect();
+
     if stacks.is_empty() {
         return GasDistribution::default();
     }
 
-    let total: u64 = stacks.iter().map(|s| s.weight).sum();
+    // Saturate rather than overflow on pathological (e.g. synthetic) weights
+    let total: u64 = stacks.iter().map(|s| s.weight).fold(0, u64::saturating_add);
     let count = stacks.len();
     let mean = total / count.max(1) as u64;
 
@@ -140,7 +211,7 @@ pub fn calculate_gas_distribution(stacks: &[CollapsedStack]) -> GasDistribution
         .iter()
         .take(top_10_percent_count)
         .map(|s| s.weight)
-        .sum();
+        .fold(0, u64::saturating_add);
 
     GasDistribution {
         total_gas: total,
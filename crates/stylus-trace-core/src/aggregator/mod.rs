@@ -2,12 +2,29 @@
 //!
 //! This module transforms parsed execution traces into:
 //! - Collapsed stack format (for flamegraph generation)
+//! - Nested call trees (self vs total gas per frame)
+//! - Frame filtering (drop non-actionable frames)
+//! - Merging repeated captures into an averaged profile
 //! - Hot path analysis (top gas consumers)
+//! - Gas per source file (via DWARF source maps)
 //! - Gas distribution statistics
 
+pub mod call_tree;
+pub mod frame_filter;
+pub mod merge;
 pub mod metrics;
+pub mod source_files;
 pub mod stack_builder;
 
 // Re-export main types and functions
-pub use metrics::{calculate_gas_distribution, calculate_hot_paths};
-pub use stack_builder::build_collapsed_stacks;
+pub use call_tree::{build_call_tree, build_call_tree_from_stacks, CallTreeNode};
+pub use frame_filter::FrameFilter;
+pub use merge::merge_profiles;
+pub use metrics::{calculate_gas_distribution, calculate_hot_paths, sort_hot_paths, HotPathSort};
+pub use source_files::{gas_by_source_file, FileGas};
+pub use stack_builder::{
+    build_collapsed_stacks, build_contract_stacks, build_stacks_with, collapse_recursion,
+    contract_gas_breakdown, for_each_step_stack, label_selectors, label_stack_selectors,
+    prune_to_heaviest_subtrees, AnonymousFrames, StackOptions, DELEGATECALL_FRAME_PREFIX,
+    OTHER_SUBTREES_FRAME,
+};
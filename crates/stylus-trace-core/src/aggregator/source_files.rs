@@ -0,0 +1,73 @@
+//! Gas aggregation by source file.
+//!
+//! Maps each collapsed stack's `last_pc` to the source file that contains it
+//! and sums gas per file. This gives a file-level view of where gas goes,
+//! which is often more actionable than individual frames in larger
+//! codebases.
+
+use super::stack_builder::CollapsedStack;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// File name used for stacks whose PC is missing or cannot be resolved
+pub const UNKNOWN_SOURCE_FILE: &str = "unknown";
+
+/// Gas attributed to a single source file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FileGas {
+    /// Source file path as recorded in the debug info
+    pub file: String,
+
+    /// Gas spent at PCs belonging to this file
+    pub gas: u64,
+
+    /// Percentage of the aggregated gas
+    pub percentage: f64,
+}
+
+/// Sum gas per source file
+///
+/// **Public** - used by capture for `--group-by-file`
+///
+/// Stacks without a PC, or whose PC the resolver cannot place, are grouped
+/// under `UNKNOWN_SOURCE_FILE`.
+///
+/// # Arguments
+/// * `stacks` - Collapsed stacks carrying `last_pc`
+/// * `resolve_file` - Maps a PC to its source file (e.g. via `SourceMapper::lookup`)
+///
+/// # Returns
+/// Files sorted by gas (descending), then by name
+pub fn gas_by_source_file(
+    stacks: &[CollapsedStack],
+    resolve_file: impl Fn(u64) -> Option<String>,
+) -> Vec<FileGas> {
+    let mut by_file: HashMap<String, u64> = HashMap::new();
+    for stack in stacks {
+        let file = stack
+            .last_pc
+            .and_then(&resolve_file)
+            .unwrap_or_else(|| UNKNOWN_SOURCE_FILE.to_string());
+        let gas = by_file.entry(file).or_insert(0);
+        *gas = gas.saturating_add(stack.weight);
+    }
+
+    let total = by_file
+        .values()
+        .fold(0, |acc: u64, gas| acc.saturating_add(*gas));
+    let mut files: Vec<FileGas> = by_file
+        .into_iter()
+        .map(|(file, gas)| FileGas {
+            file,
+            gas,
+            percentage: if total > 0 {
+                (gas as f64 / total as f64) * 100.0
+            } else {
+                0.0
+            },
+        })
+        .collect();
+
+    files.sort_by(|a, b| b.gas.cmp(&a.gas).then_with(|| a.file.cmp(&b.file)));
+    files
+}
@@ -6,10 +6,14 @@
 //! Example: "main;execute_tx;storage_read 1000"
 //! This means: main called execute_tx which called storage_read, consuming 1000 gas.
 
+use crate::parser::hostio::HostIoStats;
+use crate::parser::schema::ContractGas;
+use crate::parser::selector_map::SelectorMap;
+use crate::parser::stylus_trace::ExecutionStep;
 use crate::parser::{HostIoType, ParsedTrace};
 use log::debug;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 /// A single collapsed stack entry
 ///
@@ -55,6 +59,97 @@ impl CollapsedStack {
 /// 3. Build stack strings for each gas-consuming operation
 /// 4. Aggregate by unique stack (sum weights)
 pub fn build_collapsed_stacks(parsed_trace: &ParsedTrace) -> Vec<CollapsedStack> {
+    build_stacks_with(parsed_trace, StackOptions::default())
+}
+
+/// Frame used for the entry contract when the transaction target is unknown
+const ENTRY_CONTRACT_FRAME: &str = "entry";
+
+/// Frame used for a sub-call whose callee address was not reported
+const UNKNOWN_CONTRACT_FRAME: &str = "unknown_contract";
+
+/// Build collapsed stacks partitioned by the active contract
+///
+/// **Public** - used by capture for `--split-by-contract`
+///
+/// Every stack starts with the entry contract (the transaction target),
+/// and each nested call frame is named after its callee address instead of
+/// the generic `call` frame. The callee is taken from the `address` of the
+/// call step that precedes the depth increase.
+///
+/// # Arguments
+/// * `parsed_trace` - Parsed trace data from parser
+///
+/// # Returns
+/// Vector of collapsed stacks, one per unique (contract path, operation)
+pub fn build_contract_stacks(parsed_trace: &ParsedTrace) -> Vec<CollapsedStack> {
+    build_stacks_with(
+        parsed_trace,
+        StackOptions {
+            split_by_contract: true,
+            ..StackOptions::default()
+        },
+    )
+}
+
+/// Prefix of frames entered through a followed `delegatecall`
+///
+/// The rest of the frame is the implementation address, e.g.
+/// `delegatecall:0x…`.
+pub const DELEGATECALL_FRAME_PREFIX: &str = "delegatecall:";
+
+/// How execution steps are grouped into stacks
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StackOptions {
+    /// Name call frames after the active contract (see build_contract_stacks)
+    pub split_by_contract: bool,
+
+    /// Name frames entered by `delegatecall` after the implementation
+    /// contract instead of treating them as an opaque call
+    pub follow_delegatecalls: bool,
+
+    /// How depth-filler `call` frames and `unknown` operations are shown
+    pub anonymous_frames: AnonymousFrames,
+}
+
+/// Treatment of frames that carry no symbol
+///
+/// Without function names, every depth increase becomes a generic `call`
+/// frame and steps without an operation become `unknown`. On traces lacking
+/// symbols these pile up and hide the structure of the graph.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AnonymousFrames {
+    /// Keep the plain `call` and `unknown` frames
+    #[default]
+    Keep,
+    /// Name them after their position: `call (depth N)` and `unknown (pc 0x…)`
+    Label,
+    /// Drop them and charge their gas to the parent frame
+    Hide,
+}
+
+/// Build collapsed stacks with explicit options
+///
+/// **Public** - used by capture to combine `--split-by-contract` and
+/// `--follow-delegatecalls`
+///
+/// With `follow_delegatecalls`, a frame entered by a delegatecall is named
+/// `delegatecall:<implementation>` and stays nested under the calling
+/// contract, whose storage context it runs in. `contract_gas_breakdown`
+/// charges such frames to that caller.
+///
+/// `anonymous_frames` controls the generic `call` frames and `unknown`
+/// operations. Hidden `call` frames are skipped so their children attach to
+/// the frame above; a hidden `unknown` step is charged to its enclosing
+/// frame (or stays `unknown` when there is none).
+///
+/// # Arguments
+/// * `parsed_trace` - Parsed trace data from parser
+/// * `options` - Frame naming options
+///
+/// # Returns
+/// Vector of collapsed stacks, one per unique execution path
+pub fn build_stacks_with(parsed_trace: &ParsedTrace, options: StackOptions) -> Vec<CollapsedStack> {
     debug!(
         "Building collapsed stacks from {} execution steps",
         parsed_trace.execution_steps.len()
@@ -63,27 +158,85 @@ pub fn build_collapsed_stacks(parsed_trace: &ParsedTrace) -> Vec<CollapsedStack>
     // Map to aggregate stacks: stack_string -> (total_weight, last_pc)
     let mut stack_map: HashMap<String, (u64, u64)> = HashMap::new();
 
+    for_each_step_stack(parsed_trace, options, |step, stack_str| {
+        // Accumulate all gas costs
+        let entry = stack_map.entry(stack_str).or_insert((0, 0));
+        entry.0 = entry.0.saturating_add(step.gas_cost);
+        entry.1 = step.pc;
+    });
+
+    // Convert map to vector and sort by weight (descending)
+    let mut stacks: Vec<CollapsedStack> = stack_map
+        .into_iter()
+        .map(|(stack, (weight, pc))| CollapsedStack::new(stack, weight, Some(pc)))
+        .collect();
+
+    stacks.sort_by_key(|s| std::cmp::Reverse(s.weight));
+    debug!("Built {} unique collapsed stacks", stacks.len());
+
+    stacks
+}
+
+/// Walk the execution steps with the stack each one is charged to
+///
+/// **Public** - used by build_stacks_with and to find the stacks of
+/// repeated slot reads
+///
+/// The stack string of a step is the one `build_stacks_with` aggregates it
+/// under for the same options, so callers can point at flamegraph frames.
+///
+/// # Arguments
+/// * `parsed_trace` - Parsed trace data from parser
+/// * `options` - Frame naming options
+/// * `visit` - Called for every step, in order, with its stack string
+pub fn for_each_step_stack<'a>(
+    parsed_trace: &'a ParsedTrace,
+    options: StackOptions,
+    mut visit: impl FnMut(&'a ExecutionStep, String),
+) {
+    let split_by_contract = options.split_by_contract;
+
     // Current call stack (tracks function hierarchy)
     let mut call_stack: Vec<String> = Vec::new();
 
+    // Contract split: the entry contract sits at the depth of the first step
+    let base_depth = parsed_trace
+        .execution_steps
+        .first()
+        .map_or(0, |step| step.depth as usize);
+    let entry_contract = parsed_trace
+        .contract_address
+        .as_deref()
+        .unwrap_or(ENTRY_CONTRACT_FRAME);
+    // Callee of the most recent call step, and whether it was a delegatecall
+    let mut pending_callee: Option<(Option<&str>, bool)> = None;
+
     // Process each execution step
     for step in &parsed_trace.execution_steps {
         // Get operation name and map to HostIO name if it's an opcode
-        let raw_op = step
-            .function
-            .as_deref()
-            .or(step.op.as_deref())
-            .unwrap_or("unknown");
+        let named_op = step.function.as_deref().or(step.op.as_deref());
+        let raw_op = named_op.unwrap_or("unknown");
 
         // Handle formats like "call;SSTORE"
         let op_part = raw_op.split(';').next_back().unwrap_or(raw_op);
 
-        let operation = HostIoType::from_opcode(op_part)
-            .map(map_hostio_to_label)
-            .unwrap_or(raw_op);
+        let io_type = HostIoType::from_opcode(op_part);
+        let operation = match (named_op, options.anonymous_frames) {
+            (None, AnonymousFrames::Label) => format!("unknown (pc {:#x})", step.pc),
+            (None, AnonymousFrames::Hide) => String::new(),
+            _ => io_type
+                .map(map_hostio_to_label)
+                .unwrap_or(raw_op)
+                .to_string(),
+        };
 
         // Handle depth changes properly
-        let current_depth = step.depth as usize;
+        let current_depth = if split_by_contract {
+            // One frame per active contract, entry contract included
+            step.depth as usize - base_depth.min(step.depth as usize) + 1
+        } else {
+            step.depth as usize
+        };
 
         // If depth decreased, we returned from function calls
         if current_depth < call_stack.len() {
@@ -93,32 +246,386 @@ pub fn build_collapsed_stacks(parsed_trace: &ParsedTrace) -> Vec<CollapsedStack>
         // If depth increased, we entered a new call
         // (This happens if we missed some steps or have shallow tracing)
         while call_stack.len() < current_depth {
-            call_stack.push("call".to_string());
+            let callee = if call_stack.is_empty() {
+                None
+            } else {
+                pending_callee.take()
+            };
+            let frame = match callee {
+                Some((address, true)) if options.follow_delegatecalls => format!(
+                    "{}{}",
+                    DELEGATECALL_FRAME_PREFIX,
+                    address.unwrap_or(UNKNOWN_CONTRACT_FRAME)
+                ),
+                _ if !split_by_contract => match options.anonymous_frames {
+                    AnonymousFrames::Keep => "call".to_string(),
+                    AnonymousFrames::Label => format!("call (depth {})", call_stack.len() + 1),
+                    // Empty frames keep the depth bookkeeping but are not rendered
+                    AnonymousFrames::Hide => String::new(),
+                },
+                _ if call_stack.is_empty() => entry_contract.to_string(),
+                Some((Some(address), _)) => address.to_string(),
+                _ => UNKNOWN_CONTRACT_FRAME.to_string(),
+            };
+            call_stack.push(frame);
+        }
+
+        // A call step names the contract entered by the next depth increase
+        let is_delegatecall = io_type == Some(HostIoType::DelegateCall);
+        if step.address.is_some() || is_delegatecall {
+            pending_callee = Some((step.address.as_deref(), is_delegatecall));
         }
 
         // Build the full stack string with current operation
-        let stack_str = if call_stack.is_empty() {
-            operation.to_string()
-        } else {
-            format!("{};{}", call_stack.join(";"), operation)
+        let mut frames: Vec<&str> = call_stack
+            .iter()
+            .map(String::as_str)
+            .chain(std::iter::once(operation.as_str()))
+            .filter(|frame| !frame.is_empty())
+            .collect();
+        if frames.is_empty() {
+            frames.push("unknown");
+        }
+        visit(step, frames.join(";"));
+    }
+}
+
+/// Per-contract gas breakdown from contract-partitioned stacks
+///
+/// **Public** - used by capture for `--split-by-contract`
+///
+/// Each stack's weight is attributed to its innermost contract frame (the
+/// frame just above the operation leaf), so the breakdown reports self gas
+/// per contract and sums to the execution gas. Followed delegatecall frames
+/// are skipped, so their gas counts towards the calling contract.
+///
+/// # Arguments
+/// * `stacks` - Stacks from build_contract_stacks
+///
+/// # Returns
+/// Contracts sorted by gas (descending), then by address
+pub fn contract_gas_breakdown(stacks: &[CollapsedStack]) -> Vec<ContractGas> {
+    let mut by_contract: HashMap<&str, u64> = HashMap::new();
+    for stack in stacks {
+        let mut frames = stack.stack.rsplit(';');
+        frames.next(); // operation leaf
+        let contract = frames
+            .find(|frame| !frame.starts_with(DELEGATECALL_FRAME_PREFIX))
+            .unwrap_or(ENTRY_CONTRACT_FRAME);
+        let gas = by_contract.entry(contract).or_insert(0);
+        *gas = gas.saturating_add(stack.weight);
+    }
+
+    let total: u64 = by_contract
+        .values()
+        .fold(0, |acc, gas| acc.saturating_add(*gas));
+    let mut contracts: Vec<ContractGas> = by_contract
+        .into_iter()
+        .map(|(address, gas)| ContractGas {
+            address: address.to_string(),
+            gas,
+            percentage: if total > 0 {
+                (gas as f64 / total as f64) * 100.0
+            } else {
+                0.0
+            },
+        })
+        .collect();
+
+    contracts.sort_by(|a, b| b.gas.cmp(&a.gas).then_with(|| a.address.cmp(&b.address)));
+    contracts
+}
+
+/// Relabel selector frames with function signatures from an ABI
+///
+/// **Public** - used by capture for `--selector-map`
+///
+/// Frames that are exactly a 4-byte selector (e.g. `0xa9059cbb`) become the
+/// matching signature (e.g. `transfer(address,uint256)`). Unknown selectors
+/// and all other frames are left unchanged.
+///
+/// # Arguments
+/// * `stacks` - Collapsed stacks to relabel in place
+/// * `selectors` - Selector map loaded from the contract ABI
+pub fn label_selectors(stacks: &mut [CollapsedStack], selectors: &SelectorMap) {
+    for stack in stacks {
+        if let Some(labeled) = label_stack_selectors(&stack.stack, selectors) {
+            stack.stack = labeled;
+        }
+    }
+}
+
+/// Relabel the selector frames of a single stack string
+///
+/// **Public** - used by capture to label the stacks of repeated slot reads
+/// like the collapsed stacks
+///
+/// # Returns
+/// The relabeled stack, or None if no frame is a known selector
+pub fn label_stack_selectors(stack: &str, selectors: &SelectorMap) -> Option<String> {
+    if !stack
+        .split(';')
+        .any(|frame| selectors.lookup(frame).is_some())
+    {
+        return None;
+    }
+
+    Some(
+        stack
+            .split(';')
+            .map(|frame| selectors.lookup(frame).unwrap_or(frame))
+            .collect::<Vec<_>>()
+            .join(";"),
+    )
+}
+
+/// Append a synthetic intrinsic-gas frame to a set of collapsed stacks
+///
+/// **Public** - used by capture when `--show-intrinsic` is requested
+///
+/// The frame is sized to `total_gas - sum(weights)` so that flamegraph
+/// proportions reflect the transaction's real gas rather than execution only.
+/// If there is no intrinsic gas left over, the stacks are returned unchanged.
+///
+/// # Arguments
+/// * `stacks` - Collapsed stacks from build_collapsed_stacks
+/// * `total_gas` - Total gas used by the transaction (in Ink)
+pub fn with_intrinsic_frame(stacks: &[CollapsedStack], total_gas: u64) -> Vec<CollapsedStack> {
+    let execution_gas: u64 = stacks.iter().map(|s| s.weight).fold(0, u64::saturating_add);
+    let intrinsic_gas = total_gas.saturating_sub(execution_gas);
+
+    let mut result = stacks.to_vec();
+    if intrinsic_gas > 0 {
+        result.push(CollapsedStack::new(
+            "root;intrinsic".to_string(),
+            intrinsic_gas,
+            None,
+        ));
+        result.sort_by_key(|s| std::cmp::Reverse(s.weight));
+    }
+
+    result
+}
+
+/// Fold consecutive identical frames in each stack into a single frame
+///
+/// **Public** - used by capture when `--collapse-recursion` is requested
+/// (`annotate` follows `--annotate-recursion`)
+///
+/// `f;f;f;g` becomes `f;g` (or `f (x3);g` with `annotate`). Stacks that become
+/// identical after folding are merged by summing their weights; the program
+/// counter of the heaviest contributor is kept.
+///
+/// # Arguments
+/// * `stacks` - Collapsed stacks from build_collapsed_stacks
+/// * `annotate` - Append `(xN)` to frames that were folded N > 1 times
+///
+/// # Returns
+/// Folded stacks sorted by weight (descending)
+pub fn collapse_recursion(stacks: &[CollapsedStack], annotate: bool) -> Vec<CollapsedStack> {
+    rewrite_stacks(stacks, |stack| Some(fold_recursive_frames(stack, annotate)))
+}
+
+/// Frame that replaces the subtrees dropped by prune_to_heaviest_subtrees
+pub const OTHER_SUBTREES_FRAME: &str = "other";
+
+/// Keep the `max_subtrees` heaviest subtrees and merge the rest into a
+/// single `other` frame
+///
+/// **Public** - used by capture when `--max-hot-paths-in-svg` is requested
+///
+/// Frames shared by every stack (a leading `root`, the `call` wrapper of a
+/// structLogs capture, or the entry contract with `--split-by-contract`) are
+/// kept, and the children of the first frame that branches are ranked by
+/// total weight. Merged subtrees lose their inner frames, so only the
+/// rendered flamegraph should be pruned, not the profile's stacks.
+///
+/// # Arguments
+/// * `stacks` - Collapsed stacks to render
+/// * `max_subtrees` - Subtrees to keep (`0` keeps all of them)
+///
+/// # Returns
+/// Stacks sorted by weight (descending); unchanged if nothing was dropped
+pub fn prune_to_heaviest_subtrees(
+    stacks: &[CollapsedStack],
+    max_subtrees: usize,
+) -> Vec<CollapsedStack> {
+    let depth = branching_depth(stacks);
+    // Subtree a stack belongs to; `None` for stacks ending above the branch
+    let subtree = |stack: &str| stack.split(';').nth(depth).map(str::to_string);
+
+    let mut weights: HashMap<String, u64> = HashMap::new();
+    for stack in stacks {
+        if let Some(frame) = subtree(&stack.stack) {
+            let weight = weights.entry(frame).or_insert(0);
+            *weight = weight.saturating_add(stack.weight);
+        }
+    }
+    if max_subtrees == 0 || weights.len() <= max_subtrees {
+        return stacks.to_vec();
+    }
+
+    let mut ranked: Vec<(String, u64)> = weights.into_iter().collect();
+    ranked.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    let kept: HashSet<String> = ranked
+        .into_iter()
+        .take(max_subtrees)
+        .map(|(frame, _)| frame)
+        .collect();
+
+    let mut pruned = rewrite_stacks(stacks, |stack| match subtree(stack) {
+        Some(frame) if !kept.contains(&frame) => {
+            let mut frames: Vec<&str> = stack.split(';').take(depth).collect();
+            frames.push(OTHER_SUBTREES_FRAME);
+            Some(frames.join(";"))
+        }
+        _ => Some(stack.to_string()),
+    });
+    // The merged frame has no single program counter
+    if !kept.contains(OTHER_SUBTREES_FRAME) {
+        for stack in &mut pruned {
+            if subtree(&stack.stack).as_deref() == Some(OTHER_SUBTREES_FRAME) {
+                stack.last_pc = None;
+            }
+        }
+    }
+    pruned
+}
+
+/// Number of leading frames shared by every stack
+///
+/// **Private** - internal helper for prune_to_heaviest_subtrees
+fn branching_depth(stacks: &[CollapsedStack]) -> usize {
+    let frames: Vec<Vec<&str>> = stacks
+        .iter()
+        .map(|s| s.stack.split(';').collect())
+        .collect();
+
+    let mut depth = 0;
+    loop {
+        let mut at_depth = frames.iter().filter_map(|f| f.get(depth));
+        let Some(first) = at_depth.next() else {
+            return depth;
         };
+        if at_depth.any(|frame| frame != first) {
+            return depth;
+        }
+        depth += 1;
+    }
+}
 
-        // Accumulate all gas costs
-        let entry = stack_map.entry(stack_str).or_insert((0, 0));
-        entry.0 += step.gas_cost;
-        entry.1 = step.pc;
+/// Rewrite every stack string and merge stacks that become identical
+///
+/// **Crate** - shared by stack transforms such as collapse_recursion
+///
+/// `rewrite` returns the new stack string, or `None` to drop the stack.
+/// Merged stacks sum their weights and keep the program counter of the
+/// heaviest contributor. The result is sorted by weight (descending).
+pub(crate) fn rewrite_stacks(
+    stacks: &[CollapsedStack],
+    rewrite: impl Fn(&str) -> Option<String>,
+) -> Vec<CollapsedStack> {
+    let mut merged: HashMap<String, (u64, u64, Option<u64>)> = HashMap::new();
+
+    for stack in stacks {
+        let Some(key) = rewrite(&stack.stack) else {
+            continue;
+        };
+        let entry = merged.entry(key).or_insert((0, 0, None));
+        entry.0 = entry.0.saturating_add(stack.weight);
+        if stack.weight >= entry.1 {
+            entry.1 = stack.weight;
+            entry.2 = stack.last_pc;
+        }
     }
 
-    // Convert map to vector and sort by weight (descending)
-    let mut stacks: Vec<CollapsedStack> = stack_map
+    let mut result: Vec<CollapsedStack> = merged
         .into_iter()
-        .map(|(stack, (weight, pc))| CollapsedStack::new(stack, weight, Some(pc)))
+        .map(|(stack, (weight, _, pc))| CollapsedStack::new(stack, weight, pc))
         .collect();
 
-    stacks.sort_by(|a, b| b.weight.cmp(&a.weight));
-    debug!("Built {} unique collapsed stacks", stacks.len());
+    result.sort_by(|a, b| b.weight.cmp(&a.weight).then_with(|| a.stack.cmp(&b.stack)));
+    result
+}
 
-    stacks
+/// Fold runs of identical frames in a single `;`-separated stack
+fn fold_recursive_frames(stack: &str, annotate: bool) -> String {
+    let mut frames: Vec<String> = Vec::new();
+    let mut previous: Option<&str> = None;
+    let mut run = 0;
+
+    for frame in stack.split(';') {
+        if previous == Some(frame) {
+            run += 1;
+            continue;
+        }
+        if let Some(prev) = previous {
+            frames.push(annotate_frame(prev, run, annotate));
+        }
+        previous = Some(frame);
+        run = 1;
+    }
+    if let Some(prev) = previous {
+        frames.push(annotate_frame(prev, run, annotate));
+    }
+
+    frames.join(";")
+}
+
+fn annotate_frame(frame: &str, run: usize, annotate: bool) -> String {
+    if annotate && run > 1 {
+        format!("{} (x{})", frame, run)
+    } else {
+        frame.to_string()
+    }
+}
+
+/// Root frame of synthetic HostIO breakdown stacks
+///
+/// The label marks the frames as approximate: their gas is taken from the
+/// aggregate HostIO statistics and overlaps with the real execution stacks.
+pub const SYNTHETIC_HOSTIO_FRAME: &str = "hostio (approx)";
+
+/// Append synthetic `hostio (approx);<type>` stacks to a set of collapsed stacks
+///
+/// **Public** - used by capture when `--hostio-stacks` is requested
+///
+/// Each HostIO type gets one stack weighted by the gas attributed to it in
+/// `hostio_stats`. Because that gas is already part of the execution stacks,
+/// these frames double-count and are only meant as a visual breakdown.
+///
+/// # Arguments
+/// * `stacks` - Collapsed stacks from build_collapsed_stacks
+/// * `hostio_stats` - Aggregated HostIO statistics from the parser
+pub fn with_hostio_stacks(
+    stacks: &[CollapsedStack],
+    hostio_stats: &HostIoStats,
+) -> Vec<CollapsedStack> {
+    let mut result = stacks.to_vec();
+
+    for (io_type, gas) in hostio_stats.gas_breakdown() {
+        if gas > 0 {
+            result.push(CollapsedStack::new(
+                format!("{};{}", SYNTHETIC_HOSTIO_FRAME, io_type.as_str()),
+                gas,
+                None,
+            ));
+        }
+    }
+
+    result.sort_by_key(|s| std::cmp::Reverse(s.weight));
+    result
+}
+
+/// Check whether a stack is a synthetic HostIO breakdown stack
+///
+/// **Public** - lets metrics exclude approximate stacks from totals
+pub fn is_synthetic_hostio_stack(stack: &CollapsedStack) -> bool {
+    stack
+        .stack
+        .split(';')
+        .next()
+        .is_some_and(|root| root == SYNTHETIC_HOSTIO_FRAME)
 }
 
 /// Map HostIO type to human-readable label
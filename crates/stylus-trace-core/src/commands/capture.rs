@@ -8,23 +8,63 @@
 //! 5. Calculates metrics
 //! 6. Writes output files
 
-use crate::aggregator::stack_builder::CollapsedStack;
-use crate::aggregator::{build_collapsed_stacks, calculate_gas_distribution, calculate_hot_paths};
+use crate::aggregator::stack_builder::{with_hostio_stacks, with_intrinsic_frame, CollapsedStack};
+use crate::aggregator::{
+    build_call_tree_from_stacks, build_stacks_with, calculate_gas_distribution,
+    calculate_hot_paths, collapse_recursion, contract_gas_breakdown, gas_by_source_file,
+    label_selectors, label_stack_selectors, prune_to_heaviest_subtrees, sort_hot_paths,
+    source_files::UNKNOWN_SOURCE_FILE, FrameFilter, StackOptions,
+};
 use crate::commands::models::{CaptureArgs, GasDisplay};
+use crate::commands::observer::{CaptureObserver, CaptureStage};
 use crate::diff::{
-    check_thresholds, generate_diff, render_terminal_diff, GasThresholds, ThresholdConfig,
+    analyze_profile, check_thresholds, compare_captures, generate_diff,
+    render_terminal_diff_filtered, GasThresholds, RepeatabilityReport, ThresholdConfig,
+};
+use crate::flamegraph::{
+    generate_flamegraph, generate_hostio_histogram, generate_hot_path_explanations,
+    generate_hot_path_groups, generate_text_summary, FlamegraphConfig,
 };
-use crate::flamegraph::{generate_flamegraph, generate_text_summary};
-use crate::output::json::{read_profile, write_profile};
-use crate::output::svg::write_svg;
+use crate::output::json::{
+    is_stdout_path, read_profile, write_call_tree, write_capture_summary, write_collapsed_stacks,
+    write_hot_paths_ndjson, write_profile_styled, JsonStyle, DEFAULT_JSON_INDENT,
+};
+use crate::output::redact::{load_redaction_config, RedactionConfig, Redactor};
+use crate::output::speedscope;
+use crate::output::svg::{write_svg_validated, SvgWatermark};
 use crate::parser::{
-    parse_trace, schema::HotPath, source_map::SourceMapper, to_profile, ParsedTrace,
+    load_selector_map, parse_trace_with_limit, repeated_slot_reads,
+    schema::{CaptureSummary, HotPath, Profile, SlotReads},
+    source_map::SourceMapper,
+    stylus_trace::parse_gas_value,
+    to_profile, ParsedTrace, SelectorMap,
+};
+use crate::rpc::{websocket::is_websocket_url, CallRequest, RpcClient, TraceCache, TraceCacheKey};
+use crate::utils::config::{
+    generated_at, DEFAULT_BREAKDOWN_PERCENT_PRECISION, DEFAULT_DIFF_PERCENT_PRECISION,
+    DEFAULT_TOP_HOSTIO, KNOWN_TRACERS,
 };
-use crate::rpc::RpcClient;
+use crate::utils::error::{RpcError, SourceMapError};
+use crate::utils::text::format_percent;
 use anyhow::{Context, Result};
 use log::{debug, info, warn};
 use std::path::PathBuf;
-use std::time::Instant;
+use std::time::{Duration, Instant};
+
+/// Print human-readable output to stdout, or to stderr when the profile
+/// itself is being written to stdout (`--output -`)
+macro_rules! console {
+    ($to_stderr:expr) => {
+        console!($to_stderr, "")
+    };
+    ($to_stderr:expr, $($arg:tt)*) => {
+        if $to_stderr {
+            eprintln!($($arg)*)
+        } else {
+            println!($($arg)*)
+        }
+    };
+}
 
 /// Execute the capture command
 ///
@@ -89,76 +129,91 @@ use std::time::Instant;
 /// execute_capture(args)?;
 /// ```
 pub fn execute_capture(args: CaptureArgs) -> Result<()> {
-    let start_time = Instant::now();
-
-    info!(
-        "Starting capture for transaction: {}",
-        args.transaction_hash
-    );
-    info!("RPC endpoint: {}", args.rpc_url);
-
-    info!("Fetching trace from RPC...");
-    let raw_trace = fetch_trace(
-        &args.rpc_url,
-        &args.transaction_hash,
-        args.tracer.as_deref(),
-    )
-    .context("Failed to fetch trace from RPC")?;
-
-    info!("Parsing trace data...");
-    let parsed_trace =
-        parse_trace(&args.transaction_hash, &raw_trace).context("Failed to parse trace data")?;
-
-    debug!(
-        "Parsed trace: {} gas used, {} execution steps",
-        parsed_trace.total_gas_used,
-        parsed_trace.execution_steps.len()
-    );
-
-    let mapper = initialize_source_mapper(args.wasm.as_ref());
+    execute_capture_with_observer(args, &|_: &CaptureStage| {})
+}
 
-    info!("Building collapsed stacks...");
-    let stacks = build_collapsed_stacks(&parsed_trace);
-    debug!("Built {} unique stacks", stacks.len());
+/// Execute the capture command, reporting progress to an observer
+///
+/// **Public** - for library users (GUIs, TUIs, test harnesses) that need to
+/// follow the pipeline without a log backend
+///
+/// The observer sees each `CaptureStage` in order. Dry runs and
+/// repeatability checks report no stages.
+///
+/// # Arguments
+/// * `args` - Capture command arguments
+/// * `observer` - Notified as each stage completes
+///
+/// # Errors
+/// Same as `execute_capture`
+///
+/// # Example
+/// ```ignore
+/// execute_capture_with_observer(args, &|stage: &CaptureStage| {
+///     println!("{:?}", stage);
+/// })?;
+/// ```
+pub fn execute_capture_with_observer(
+    args: CaptureArgs,
+    observer: &dyn CaptureObserver,
+) -> Result<()> {
+    if args.dry_run {
+        return execute_dry_run(&args);
+    }
+    if args.repeatability {
+        return execute_repeatability_check(&args);
+    }
 
-    let gas_dist = calculate_gas_distribution(&stacks);
-    info!("Gas distribution: {}", gas_dist.summary());
+    let start_time = Instant::now();
 
-    info!("Calculating top {} hot paths...", args.top_paths);
-    let hot_paths = calculate_hot_paths(&stacks, 0, args.top_paths);
+    let CaptureRun {
+        parsed_trace,
+        redactor,
+        generated_at,
+        stacks,
+        hot_paths,
+        flamegraph_stacks,
+        flamegraph_config,
+        mapper,
+        selectors,
+        svg: svg_content,
+    } = run_capture(&args, observer, args.output_svg.is_some())?;
 
-    let svg_content = if args.output_svg.is_some() {
-        info!("Generating flamegraph...");
-        let config = args.flamegraph_config.as_ref();
-        Some(
-            generate_flamegraph(&stacks, config, mapper.as_ref())
-                .context("Failed to generate flamegraph")?,
-        )
-    } else {
-        None
-    };
+    if let Some(dump_path) = &args.dump_stacks {
+        write_collapsed_stacks(&stacks, dump_path).context("Failed to write collapsed stacks")?;
+        info!("✓ Collapsed stacks written to: {}", dump_path.display());
+    }
 
-    write_outputs(
+    let profile = cap_hostio_types(
         &args,
-        &parsed_trace,
-        &stacks,
-        hot_paths.clone(),
-        mapper.as_ref(),
-        svg_content,
-    )?;
+        labeled_profile(
+            &args,
+            &parsed_trace,
+            hot_paths.clone(),
+            Some(stacks.clone()),
+            mapper.as_ref(),
+            redactor.as_ref(),
+            &generated_at,
+        ),
+    );
+    write_outputs(&args, &parsed_trace, &stacks, &profile, svg_content)?;
+    observer.on_stage(&CaptureStage::Written {
+        profile: args.output_json.clone(),
+    });
 
-    if let Some(baseline_path) = &args.baseline {
+    if let Some(baseline) = load_baseline_profile(&args, selectors.as_ref())? {
         info!(
             "Performing on-the-fly diff against baseline: {}...",
-            baseline_path.display()
+            baseline.transaction_hash
         );
-        let baseline = read_profile(baseline_path)
-            .context("Failed to read baseline profile for on-the-fly diffing")?;
-        let profile = to_profile(
+        let profile = labeled_profile(
+            &args,
             &parsed_trace,
             calculate_hot_paths(&stacks, 0, args.top_paths),
             Some(stacks.clone()),
             mapper.as_ref(),
+            redactor.as_ref(),
+            &generated_at,
         );
 
         let mut report =
@@ -169,32 +224,64 @@ pub fn execute_capture(args: CaptureArgs) -> Result<()> {
                 gas: GasThresholds {
                     max_increase_percent: Some(percent),
                     max_increase_absolute: None,
+                    noise_floor_gas: None,
                 },
                 ..Default::default()
             };
             check_thresholds(&mut report, &thresholds);
         }
 
-        println!("{}", render_terminal_diff(&report));
+        let top_hostio = args.top_hostio.unwrap_or(DEFAULT_TOP_HOSTIO);
+        console!(
+            is_stdout_path(&args.output_json),
+            "{}",
+            render_terminal_diff_filtered(
+                &report,
+                false,
+                top_hostio,
+                args.precision.unwrap_or(DEFAULT_DIFF_PERCENT_PRECISION)
+            )
+        );
     }
 
     if args.print_summary {
-        print_transaction_summary(&args, &parsed_trace, &stacks, mapper.as_ref());
+        print_transaction_summary(
+            &args,
+            &parsed_trace,
+            &stacks,
+            &hot_paths,
+            mapper.as_ref(),
+            redactor.as_ref(),
+        );
+    }
+
+    if args.group_by_file {
+        print_file_gas(&args, &stacks, mapper.as_ref());
     }
 
     if args.view {
         info!("Generating interactive web viewer...");
         let viewer_path = args.output_json.with_extension("html");
-        let profile = to_profile(
-            &parsed_trace,
-            hot_paths,
-            Some(stacks.to_vec()),
-            mapper.as_ref(),
+        let profile = cap_hostio_types(
+            &args,
+            labeled_profile(
+                &args,
+                &parsed_trace,
+                hot_paths,
+                Some(stacks.to_vec()),
+                mapper.as_ref(),
+                redactor.as_ref(),
+                &generated_at,
+            ),
         );
         // Generate SVG for the flamegraph tab in the viewer.
         // We attempt this even if --output-svg was not requested; failure is non-fatal.
-        let viewer_svg =
-            generate_flamegraph(&stacks, args.flamegraph_config.as_ref(), mapper.as_ref()).ok();
+        let viewer_svg = generate_flamegraph(
+            &flamegraph_stacks,
+            flamegraph_config.as_ref(),
+            mapper.as_ref(),
+        )
+        .ok();
         crate::output::viewer::generate_viewer(&profile, viewer_svg.as_deref(), &viewer_path)?;
         info!("✓ Viewer generated at: {}", viewer_path.display());
         crate::output::viewer::open_browser(&viewer_path)?;
@@ -204,9 +291,505 @@ pub fn execute_capture(args: CaptureArgs) -> Result<()> {
         "Capture completed in {:.2}s",
         start_time.elapsed().as_secs_f64()
     );
+    observer.on_stage(&CaptureStage::Completed {
+        elapsed: start_time.elapsed(),
+    });
     Ok(())
 }
 
+/// A capture computed in memory
+#[derive(Debug, Clone)]
+pub struct CapturedProfile {
+    /// The profile that `capture` would write as JSON
+    pub profile: Profile,
+
+    /// The rendered flamegraph SVG, `None` when the trace has no stacks
+    pub svg: Option<String>,
+}
+
+/// Run the capture pipeline and return the profile without writing files
+///
+/// **Public** - for library users that want the `Profile` itself rather
+/// than files on disk
+///
+/// Output paths, `--dry-run`, `--repeatability`, `--baseline`, `--summary`
+/// and `--view` are ignored; only the arguments that shape the profile and
+/// flamegraph take effect. The SVG is always rendered.
+///
+/// # Arguments
+/// * `args` - Capture command arguments
+///
+/// # Returns
+/// The labelled profile, including all stacks, and the flamegraph SVG
+///
+/// # Errors
+/// * RPC connection failures
+/// * Trace parsing errors
+/// * Flamegraph generation errors
+///
+/// # Example
+/// ```ignore
+/// let captured = capture_to_profile(&args)?;
+/// println!("{} gas", captured.profile.total_gas);
+/// ```
+pub fn capture_to_profile(args: &CaptureArgs) -> Result<CapturedProfile> {
+    capture_to_profile_with_observer(args, &|_: &CaptureStage| {})
+}
+
+/// Run the capture pipeline in memory, reporting progress to an observer
+///
+/// **Public** - combines `capture_to_profile` with stage reporting
+///
+/// The observer sees every stage up to `FlamegraphGenerated`; nothing is
+/// written, so `Written` and `Completed` are never reported.
+///
+/// # Errors
+/// Same as `capture_to_profile`
+pub fn capture_to_profile_with_observer(
+    args: &CaptureArgs,
+    observer: &dyn CaptureObserver,
+) -> Result<CapturedProfile> {
+    let run = run_capture(args, observer, true)?;
+    let profile = cap_hostio_types(
+        args,
+        labeled_profile(
+            args,
+            &run.parsed_trace,
+            run.hot_paths,
+            Some(run.stacks),
+            run.mapper.as_ref(),
+            run.redactor.as_ref(),
+            &run.generated_at,
+        ),
+    );
+    Ok(CapturedProfile {
+        profile,
+        svg: run.svg,
+    })
+}
+
+/// Intermediate results of the capture pipeline, before any file is written
+///
+/// **Private** - shared by execute_capture and capture_to_profile
+struct CaptureRun {
+    parsed_trace: ParsedTrace,
+    redactor: Option<Redactor>,
+    /// Timestamp shared by the profile and the flamegraph watermark
+    generated_at: String,
+    stacks: Vec<CollapsedStack>,
+    hot_paths: Vec<HotPath>,
+    flamegraph_stacks: Vec<CollapsedStack>,
+    flamegraph_config: Option<FlamegraphConfig>,
+    mapper: Option<SourceMapper>,
+    selectors: Option<SelectorMap>,
+    svg: Option<String>,
+}
+
+/// Fetch, parse and aggregate a trace, rendering the flamegraph if asked
+///
+/// **Private** - the computation half of the capture command
+fn run_capture(
+    args: &CaptureArgs,
+    observer: &dyn CaptureObserver,
+    render_svg: bool,
+) -> Result<CaptureRun> {
+    if args.testnode {
+        check_testnode(args)?;
+    }
+
+    info!(
+        "Starting capture for transaction: {}",
+        args.transaction_hash
+    );
+    info!("RPC endpoint: {}", args.rpc_url);
+
+    info!("Fetching trace from RPC...");
+    let (raw_trace, tracer) =
+        with_tracer_fallback(args, fetch_target_trace).context("Failed to fetch trace from RPC")?;
+    let generated_at = generated_at();
+    observer.on_stage(&CaptureStage::Fetched { tracer });
+
+    info!("Parsing trace data...");
+    let mut parsed_trace = parse_trace_with_limit(
+        &args.transaction_hash,
+        &raw_trace,
+        args.assume_unit,
+        args.max_trace_steps,
+    )
+    .context("Failed to parse trace data")?;
+
+    if args.strict_ink && parsed_trace.zeroed_ink_steps > 0 {
+        anyhow::bail!(
+            "{} steps had non-decreasing ink (end ink > start ink); refusing to zero them in --strict-ink mode",
+            parsed_trace.zeroed_ink_steps
+        );
+    }
+
+    attach_transaction_metadata(args, &mut parsed_trace);
+
+    debug!(
+        "Parsed trace: {} gas used, {} execution steps",
+        parsed_trace.total_gas_used,
+        parsed_trace.execution_steps.len()
+    );
+    observer.on_stage(&CaptureStage::Parsed {
+        steps: parsed_trace.execution_steps.len(),
+        total_gas: parsed_trace.total_gas_used,
+    });
+
+    let mapper = initialize_source_mapper(args.wasm.as_ref());
+
+    let selectors = initialize_selector_map(args)?;
+
+    info!("Building collapsed stacks...");
+    let mut stacks = build_stacks(args, &parsed_trace, selectors.as_ref());
+    debug!("Built {} unique stacks", stacks.len());
+    parsed_trace.slot_reads = Some(slot_reads(args, &parsed_trace, selectors.as_ref()));
+
+    // Redact before anything derived from the stacks is written or displayed
+    let redactor = initialize_redactor(args)?;
+    if let Some(redactor) = &redactor {
+        info!("Redacting sensitive data from stack frames...");
+        redactor.redact_stacks(&mut stacks);
+        if let Some(address) = &mut parsed_trace.contract_address {
+            *address = redactor.redact(address);
+        }
+    }
+
+    observer.on_stage(&CaptureStage::StacksBuilt {
+        stacks: stacks.len(),
+    });
+
+    if args.split_by_contract {
+        parsed_trace.contract_gas = Some(contract_gas_breakdown(&stacks));
+    }
+
+    let gas_dist = calculate_gas_distribution(&stacks);
+    info!("Gas distribution: {}", gas_dist.summary());
+
+    info!("Calculating top {} hot paths...", args.top_paths);
+    let mut hot_paths = calculate_hot_paths(&stacks, 0, args.top_paths);
+    sort_hot_paths(&mut hot_paths, args.sort, args.reverse);
+
+    // Stacks rendered into flamegraphs (optionally including synthetic frames)
+    let mut flamegraph_stacks = if args.collapse_recursion {
+        collapse_recursion(&stacks, args.annotate_recursion)
+    } else {
+        stacks.clone()
+    };
+    let frame_filter = build_frame_filter(args)?;
+    if !frame_filter.is_empty() {
+        flamegraph_stacks = frame_filter.apply(&flamegraph_stacks);
+    }
+    if args.show_intrinsic {
+        flamegraph_stacks = with_intrinsic_frame(&flamegraph_stacks, parsed_trace.total_gas_used);
+    }
+    if args.hostio_stacks {
+        warn!("HostIO breakdown frames are approximate and overlap execution stacks");
+        flamegraph_stacks = with_hostio_stacks(&flamegraph_stacks, &parsed_trace.hostio_stats);
+    }
+    if let Some(max) = args.max_svg_subtrees {
+        flamegraph_stacks = prune_to_heaviest_subtrees(&flamegraph_stacks, max);
+    }
+
+    let flamegraph_config = flamegraph_config(
+        args,
+        &parsed_trace,
+        &stacks,
+        mapper.as_ref(),
+        redactor.as_ref(),
+        &generated_at,
+    );
+
+    let svg = if render_svg && flamegraph_stacks.is_empty() {
+        warn!("Trace has no execution stacks; skipping flamegraph generation");
+        None
+    } else if render_svg {
+        info!("Generating flamegraph...");
+        let config = flamegraph_config.as_ref();
+        let svg = generate_flamegraph(&flamegraph_stacks, config, mapper.as_ref())
+            .context("Failed to generate flamegraph")?;
+        observer.on_stage(&CaptureStage::FlamegraphGenerated);
+        Some(svg)
+    } else {
+        None
+    };
+
+    Ok(CaptureRun {
+        parsed_trace,
+        redactor,
+        generated_at,
+        stacks,
+        hot_paths,
+        flamegraph_stacks,
+        flamegraph_config,
+        mapper,
+        selectors,
+        svg,
+    })
+}
+
+/// Check that a capture would succeed without writing any output
+///
+/// **Public** - used for `capture --dry-run`
+///
+/// Validates the arguments, checks RPC connectivity with `web3_clientVersion`
+/// and verifies the tracer responds (and parses) for the target transaction.
+/// The trace cache is neither read nor written.
+///
+/// # Errors
+/// * Invalid arguments
+/// * RPC endpoint unreachable
+/// * Tracer unsupported or trace unparseable
+pub fn execute_dry_run(args: &CaptureArgs) -> Result<()> {
+    validate_args(args)?;
+    println!("✓ Arguments valid");
+
+    let client = RpcClient::with_timeout(&args.rpc_url, Duration::from_secs(args.timeout_secs))
+        .context("Failed to create RPC client")?;
+    let version = client
+        .client_version()
+        .with_context(|| format!("RPC endpoint {} is not reachable", args.rpc_url))?;
+    println!("✓ RPC reachable: {} ({})", args.rpc_url, version);
+    if args.testnode && !RpcClient::is_nitro_client_version(&version) {
+        println!("⚠️  {} is not an Arbitrum Nitro node", args.rpc_url);
+    }
+
+    // Always hit the node: the point is to verify the tracer, not the cache
+    let uncached = CaptureArgs {
+        cache_dir: None,
+        ..args.clone()
+    };
+    let (raw_trace, tracer) = with_tracer_fallback(&uncached, fetch_target_trace)
+        .with_context(|| format!("Tracer '{}' did not respond", requested_tracer(args)))?;
+    let parsed_trace = parse_trace_with_limit(
+        &args.transaction_hash,
+        &raw_trace,
+        args.assume_unit,
+        args.max_trace_steps,
+    )
+    .context("Trace returned by the tracer could not be parsed")?;
+    println!(
+        "✓ Tracer '{}' responded for {} ({} steps)",
+        tracer,
+        args.transaction_hash,
+        parsed_trace.execution_steps.len()
+    );
+
+    println!("Dry run passed: ready to capture. No files were written.");
+    Ok(())
+}
+
+/// Capture the target twice and report whether the results match
+///
+/// **Public** - used for `capture --repeatability`
+///
+/// Both captures bypass the trace cache so each one hits the node. Any
+/// difference points at nondeterminism in the node or tracer, and bounds how
+/// tight gas thresholds can usefully be. Nothing is written.
+///
+/// # Errors
+/// * Invalid arguments
+/// * Either fetch or parse fails
+pub fn execute_repeatability_check(args: &CaptureArgs) -> Result<()> {
+    validate_args(args)?;
+
+    let uncached = CaptureArgs {
+        cache_dir: None,
+        ..args.clone()
+    };
+    let mut captures = Vec::with_capacity(2);
+    for run in 1..=2 {
+        info!("Repeatability capture {} of 2...", run);
+        let (raw_trace, _) = with_tracer_fallback(&uncached, fetch_target_trace)
+            .with_context(|| format!("Failed to fetch trace for capture {}", run))?;
+        let parsed_trace = parse_trace_with_limit(
+            &args.transaction_hash,
+            &raw_trace,
+            args.assume_unit,
+            args.max_trace_steps,
+        )
+        .with_context(|| format!("Failed to parse trace for capture {}", run))?;
+        captures.push(parsed_trace);
+    }
+
+    let report = compare_captures(&captures[0], &captures[1]);
+    print_repeatability_report(args, &report);
+    Ok(())
+}
+
+/// Print the outcome of a repeatability check
+///
+/// **Private** - internal helper for execute_repeatability_check
+fn print_repeatability_report(args: &CaptureArgs, report: &RepeatabilityReport) {
+    /// Stack differences listed before the rest are summarized
+    const MAX_LISTED_STACKS: usize = 10;
+
+    let display = GasDisplay::new(args.ink);
+    if report.is_identical() {
+        println!(
+            "✓ Captures are identical ({} steps, {} {})",
+            report.first_steps,
+            display.format(report.first_total_gas),
+            display.unit()
+        );
+        return;
+    }
+
+    println!("⚠️  Captures differ: the node or tracer is not deterministic for this transaction");
+    println!(
+        "  Total {}: {} -> {} ({:.4}% variance)",
+        display.unit(),
+        display.format(report.first_total_gas),
+        display.format(report.second_total_gas),
+        report.gas_variance_percent()
+    );
+    println!("  Steps: {} -> {}", report.first_steps, report.second_steps);
+
+    if let Some(divergence) = &report.divergence {
+        let describe =
+            |step: &Option<String>| step.clone().unwrap_or_else(|| "(end of trace)".to_string());
+        println!("  First divergence at step {}:", divergence.index);
+        println!("    first:  {}", describe(&divergence.first));
+        println!("    second: {}", describe(&divergence.second));
+    }
+
+    if !report.stack_differences.is_empty() {
+        println!("  Differing stacks:");
+        for difference in report.stack_differences.iter().take(MAX_LISTED_STACKS) {
+            println!(
+                "    {}: {} -> {}",
+                difference.stack,
+                display.format(difference.first_weight),
+                display.format(difference.second_weight)
+            );
+        }
+        let rest = report
+            .stack_differences
+            .len()
+            .saturating_sub(MAX_LISTED_STACKS);
+        if rest > 0 {
+            println!("    ... and {} more", rest);
+        }
+    }
+}
+
+/// Check that the `--testnode` endpoint is up and is an Arbitrum Nitro node
+///
+/// **Private** - internal helper for execute_capture
+///
+/// A non-Nitro node only produces a warning, since it may still serve the
+/// requested tracer; an unreachable endpoint fails with a hint to start the
+/// testnode.
+fn check_testnode(args: &CaptureArgs) -> Result<()> {
+    let client = RpcClient::with_timeout(&args.rpc_url, Duration::from_secs(args.timeout_secs))
+        .context("Failed to create RPC client")?;
+    let version = client.client_version().with_context(|| {
+        format!(
+            "No node answered at {}. Is nitro-testnode running? Start it with `./test-node.bash --init`",
+            args.rpc_url
+        )
+    })?;
+
+    if RpcClient::is_nitro_client_version(&version) {
+        info!("Connected to nitro-testnode ({})", version);
+    } else {
+        warn!(
+            "{} reports '{}', which is not an Arbitrum Nitro node; Stylus traces need nitro-testnode or another Nitro node",
+            args.rpc_url, version
+        );
+    }
+    Ok(())
+}
+
+/// Resolve the baseline profile for on-the-fly diffing, if one was requested.
+///
+/// A `--baseline` file is read from disk, while `--baseline-tx` is fetched and
+/// parsed through the same pipeline as the target transaction. Either is
+/// redacted when the target is.
+///
+/// **Private** - internal helper for execute_capture
+fn load_baseline_profile(
+    args: &CaptureArgs,
+    selectors: Option<&SelectorMap>,
+) -> Result<Option<Profile>> {
+    let redactor = initialize_redactor(args)?;
+
+    if let Some(baseline_path) = &args.baseline {
+        let mut baseline = read_profile(baseline_path)
+            .context("Failed to read baseline profile for on-the-fly diffing")?;
+        if let Some(redactor) = &redactor {
+            redactor.redact_profile(&mut baseline);
+        }
+        return Ok(Some(baseline));
+    }
+
+    let Some(baseline_tx) = &args.baseline_tx else {
+        return Ok(None);
+    };
+
+    info!("Fetching baseline transaction: {}...", baseline_tx);
+    let (raw_trace, _) = with_tracer_fallback(args, |args| fetch_trace(args, baseline_tx))
+        .context("Failed to fetch baseline trace from RPC")?;
+    let mut parsed_trace = parse_trace_with_limit(
+        baseline_tx,
+        &raw_trace,
+        args.assume_unit,
+        args.max_trace_steps,
+    )
+    .context("Failed to parse baseline trace data")?;
+
+    let mut stacks = build_stacks(args, &parsed_trace, selectors);
+    parsed_trace.slot_reads = Some(slot_reads(args, &parsed_trace, selectors));
+    // Redacted like the target so the diff never shows raw baseline frames
+    if let Some(redactor) = &redactor {
+        redactor.redact_stacks(&mut stacks);
+    }
+    let hot_paths = calculate_hot_paths(&stacks, 0, args.top_paths);
+
+    let mut baseline = to_profile(&parsed_trace, hot_paths, Some(stacks), None);
+    if let Some(redactor) = &redactor {
+        redactor.redact_profile(&mut baseline);
+    }
+    Ok(Some(baseline))
+}
+
+/// Initialize the Redactor if `--redact` or a redaction config was requested.
+///
+/// **Private** - internal helper for execute_capture
+fn initialize_redactor(args: &CaptureArgs) -> Result<Option<Redactor>> {
+    let config = match &args.redact_config {
+        Some(path) => load_redaction_config(path).context("Failed to load redaction config")?,
+        None if args.redact => RedactionConfig::default(),
+        None => return Ok(None),
+    };
+
+    let redactor = Redactor::new(&config).context("Failed to compile redaction rules")?;
+    Ok(Some(redactor))
+}
+
+/// Compile the `--exclude` / `--include-only` frame filter.
+///
+/// **Private** - internal helper for execute_capture and validate_args
+fn build_frame_filter(args: &CaptureArgs) -> Result<FrameFilter> {
+    FrameFilter::new(&args.exclude_frames, &args.include_only_frames)
+        .context("Invalid frame filter pattern")
+}
+
+/// Load the selector map if `--selector-map` was given.
+///
+/// **Private** - internal helper for execute_capture
+fn initialize_selector_map(args: &CaptureArgs) -> Result<Option<SelectorMap>> {
+    let Some(path) = &args.selector_map else {
+        return Ok(None);
+    };
+
+    let selectors = load_selector_map(path)
+        .with_context(|| format!("Failed to load selector map {}", path.display()))?;
+    info!("Loaded {} function selectors", selectors.len());
+    Ok(Some(selectors))
+}
+
 /// Initialize SourceMapper if WASM path is provided.
 ///
 /// NOTE: This is a reserved feature. While it successfully loads WASM/DWARF,
@@ -219,92 +802,430 @@ fn initialize_source_mapper(wasm_path: Option<&PathBuf>) -> Option<SourceMapper>
     );
     match SourceMapper::new(wasm_path) {
         Ok(m) => Some(m),
+        Err(SourceMapError::NoDebugInfo) => {
+            info!("{}", SourceMapError::NoDebugInfo);
+            None
+        }
         Err(e) => {
-            warn!("Failed to load WASM binary for source mapping: {}", e);
-            warn!("Continuing without source mapping information.");
+            warn!("Source mapping disabled: {}", e);
             None
         }
     }
 }
 
-/// Write output files (JSON profile and optional SVG flamegraph).
+/// Build collapsed stacks, partitioned by contract and relabelled when requested.
+///
+/// **Private** - keeps the capture and baseline stacks comparable
+fn build_stacks(
+    args: &CaptureArgs,
+    parsed_trace: &ParsedTrace,
+    selectors: Option<&SelectorMap>,
+) -> Vec<CollapsedStack> {
+    let mut stacks = build_stacks_with(parsed_trace, stack_options(args));
+    if let Some(selectors) = selectors {
+        label_selectors(&mut stacks, selectors);
+    }
+    stacks
+}
+
+/// Repeated slot reads whose stacks are named like those of build_stacks.
 ///
 /// **Private** - internal helper for execute_capture
-fn write_outputs(
+fn slot_reads(
+    args: &CaptureArgs,
+    parsed_trace: &ParsedTrace,
+    selectors: Option<&SelectorMap>,
+) -> Vec<SlotReads> {
+    let mut slot_reads = repeated_slot_reads(parsed_trace, stack_options(args));
+    if let Some(selectors) = selectors {
+        for stack in slot_reads.iter_mut().flat_map(|reads| &mut reads.stacks) {
+            if let Some(labeled) = label_stack_selectors(stack, selectors) {
+                *stack = labeled;
+            }
+        }
+    }
+    slot_reads
+}
+
+/// Stack options selected by the capture flags.
+///
+/// **Private** - internal helper for build_stacks and slot_reads
+fn stack_options(args: &CaptureArgs) -> StackOptions {
+    StackOptions {
+        split_by_contract: args.split_by_contract,
+        follow_delegatecalls: args.follow_delegatecalls,
+        anonymous_frames: args.anonymous_frames,
+    }
+}
+
+/// Flamegraph configuration, with the watermark and insight markers added
+/// when requested.
+///
+/// **Private** - internal helper for execute_capture
+///
+/// With `--annotate-insights-on-flamegraph`, the analyzer runs on the
+/// captured stacks and each insight's description is attached to the frames
+/// of the stacks it refers to.
+fn flamegraph_config(
     args: &CaptureArgs,
     parsed_trace: &ParsedTrace,
     stacks: &[CollapsedStack],
+    mapper: Option<&SourceMapper>,
+    redactor: Option<&Redactor>,
+    generated_at: &str,
+) -> Option<FlamegraphConfig> {
+    if !args.annotate_insights && !args.watermark && args.precision.is_none() {
+        return args.flamegraph_config.clone();
+    }
+
+    let mut config = args.flamegraph_config.clone().unwrap_or_default();
+    if let Some(precision) = args.precision {
+        config = config.with_percent_precision(precision);
+    }
+    if args.watermark {
+        // A shared SVG must not reveal more than the redacted stacks do
+        let transaction_hash = match redactor {
+            Some(redactor) => redactor.redact(&parsed_trace.transaction_hash),
+            None => parsed_trace.transaction_hash.clone(),
+        };
+        config = config.with_watermark(SvgWatermark {
+            transaction_hash,
+            generated_at: generated_at.to_string(),
+        });
+    }
+
+    if args.annotate_insights {
+        let profile = labeled_profile(
+            args,
+            parsed_trace,
+            Vec::new(),
+            Some(stacks.to_vec()),
+            mapper,
+            redactor,
+            generated_at,
+        );
+        let insights = analyze_profile(&profile);
+        info!("Marking {} insights on the flamegraph", insights.len());
+
+        for insight in insights {
+            for stack in &insight.stacks {
+                config = config.with_annotation(stack.clone(), insight.description.clone());
+            }
+        }
+    }
+    Some(config)
+}
+
+/// Build the target profile, carrying the `--label` if one was given.
+///
+/// **Private** - internal helper for execute_capture
+///
+/// The HostIO types are not capped here, so the baseline diff and insights
+/// see every type; see cap_hostio_types for the profiles handed out.
+///
+/// With a redactor the whole profile is redacted, including the parts
+/// derived after the stacks (source hints and repeated slot reads).
+fn labeled_profile(
+    args: &CaptureArgs,
+    parsed_trace: &ParsedTrace,
     hot_paths: Vec<HotPath>,
+    stacks: Option<Vec<CollapsedStack>>,
     mapper: Option<&SourceMapper>,
+    redactor: Option<&Redactor>,
+    generated_at: &str,
+) -> Profile {
+    let mut profile = to_profile(parsed_trace, hot_paths, stacks, mapper);
+    profile.generated_at = generated_at.to_string();
+    if let Some(redactor) = redactor {
+        redactor.redact_profile(&mut profile);
+    }
+    profile.label = args.label.clone();
+    profile
+}
+
+/// Fold the HostIO types beyond `--top-hostio-types` into `other`.
+///
+/// **Private** - applied to the written, viewed and returned profiles only
+fn cap_hostio_types(args: &CaptureArgs, mut profile: Profile) -> Profile {
+    if let Some(max_types) = args.top_hostio_types {
+        profile.hostio_summary.keep_top_types(max_types);
+    }
+    profile
+}
+
+/// Write output files (JSON profile, optional SVG flamegraph and call tree).
+///
+/// **Private** - internal helper for execute_capture
+fn write_outputs(
+    args: &CaptureArgs,
+    parsed_trace: &ParsedTrace,
+    stacks: &[CollapsedStack],
+    profile: &Profile,
     svg_content: Option<String>,
 ) -> Result<()> {
     info!("Writing output files...");
 
-    let profile = to_profile(parsed_trace, hot_paths, Some(stacks.to_vec()), mapper);
-
-    write_profile(&profile, &args.output_json).context("Failed to write profile JSON")?;
+    write_profile_styled(
+        profile,
+        &args.output_json,
+        args.json_style,
+        args.json_indent.unwrap_or(DEFAULT_JSON_INDENT),
+    )
+    .context("Failed to write profile JSON")?;
     info!("✓ Profile written to: {}", args.output_json.display());
 
     if let (Some(svg), Some(svg_path)) = (svg_content, &args.output_svg) {
-        write_svg(&svg, svg_path).context("Failed to write flamegraph SVG")?;
+        write_svg_validated(&svg, svg_path).context("Failed to write flamegraph SVG")?;
         info!("✓ Flamegraph written to: {}", svg_path.display());
     }
 
+    if let Some(tree_path) = &args.output_tree {
+        let tree = build_call_tree_from_stacks(stacks);
+        write_call_tree(&tree, tree_path).context("Failed to write call tree JSON")?;
+        info!("✓ Call tree written to: {}", tree_path.display());
+    }
+
+    if let Some(summary_path) = &args.output_summary_json {
+        let summary = build_capture_summary(parsed_trace, stacks, &profile.hot_paths);
+        write_capture_summary(&summary, summary_path)
+            .context("Failed to write capture summary JSON")?;
+        info!("✓ Summary written to: {}", summary_path.display());
+    }
+
+    if let Some(ndjson_path) = &args.output_ndjson {
+        write_hot_paths_ndjson(profile, ndjson_path).context("Failed to write hot paths NDJSON")?;
+        info!("✓ Hot paths NDJSON written to: {}", ndjson_path.display());
+    }
+
+    if let Some(speedscope_path) = &args.output_speedscope {
+        let name = profile
+            .label
+            .as_deref()
+            .unwrap_or(&profile.transaction_hash);
+        speedscope::write(stacks, name, speedscope_path)
+            .context("Failed to write speedscope profile")?;
+        info!(
+            "✓ Speedscope profile written to: {}",
+            speedscope_path.display()
+        );
+    }
+
     Ok(())
 }
 
-/// Print a human-readable transaction summary to stdout.
+/// Build the machine-readable capture summary.
+///
+/// **Private** - internal helper for write_outputs
+fn build_capture_summary(
+    parsed_trace: &ParsedTrace,
+    stacks: &[CollapsedStack],
+    hot_paths: &[HotPath],
+) -> CaptureSummary {
+    let execution_gas: u64 = stacks.iter().map(|s| s.weight).fold(0, u64::saturating_add);
+
+    CaptureSummary {
+        transaction_hash: parsed_trace.transaction_hash.clone(),
+        total_gas: parsed_trace.total_gas_used,
+        execution_gas,
+        intrinsic_gas: parsed_trace.total_gas_used.saturating_sub(execution_gas),
+        hostio_calls: parsed_trace.hostio_stats.total_calls(),
+        unique_paths: stacks.len(),
+        top_paths: hot_paths.to_vec(),
+    }
+}
+
+/// Print a human-readable transaction summary to stdout (stderr with `--output -`).
 ///
 /// **Private** - internal helper for execute_capture
 fn print_transaction_summary(
     args: &CaptureArgs,
     parsed_trace: &ParsedTrace,
     stacks: &[CollapsedStack],
+    hot_paths: &[HotPath],
     mapper: Option<&SourceMapper>,
+    redactor: Option<&Redactor>,
 ) {
-    let total_execution_gas: u64 = stacks.iter().map(|s| s.weight).sum();
+    let total_execution_gas: u64 = stacks.iter().map(|s| s.weight).fold(0, u64::saturating_add);
     let intrinsic_gas = parsed_trace
         .total_gas_used
         .saturating_sub(total_execution_gas);
 
+    let to_stderr = is_stdout_path(&args.output_json);
     let display = GasDisplay::new(args.ink);
-    let profile = to_profile(
+    // Percentage precision for the text summary, groups and histogram
+    let summary_config = args
+        .precision
+        .map(|precision| FlamegraphConfig::new().with_percent_precision(precision));
+    let breakdown_precision = args
+        .precision
+        .unwrap_or(DEFAULT_BREAKDOWN_PERCENT_PRECISION);
+    let mut profile = to_profile(
         parsed_trace,
-        calculate_hot_paths(stacks, 0, args.top_paths),
+        hot_paths.to_vec(),
         None, // Stacks not needed for summary
         mapper,
     );
+    if let Some(redactor) = redactor {
+        redactor.redact_profile(&mut profile);
+    }
 
-    println!("\n━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
-    println!("  📊 STYLUS TRANSACTION PROFILE SUMMARY");
-    println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
-    println!("  Transaction: {}", args.transaction_hash);
-    println!(
+    console!(
+        to_stderr,
+        "\n━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━"
+    );
+    console!(to_stderr, "  📊 STYLUS TRANSACTION PROFILE SUMMARY");
+    console!(
+        to_stderr,
+        "━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━"
+    );
+    console!(to_stderr, "  Transaction: {}", args.transaction_hash);
+    if let Some(label) = &args.label {
+        console!(to_stderr, "  Label:       {}", label);
+    }
+    if let Some(address) = &profile.contract_address {
+        console!(to_stderr, "  Contract:    {}", address);
+    }
+    if let Some(block) = profile.block_number {
+        console!(to_stderr, "  Block:       {}", block);
+    }
+    console!(
+        to_stderr,
         "  Total Gas:   {:>12} {}",
         display.format(parsed_trace.total_gas_used),
         display.unit()
     );
-    println!(
+    if !parsed_trace.total_gas_source.is_reported() {
+        console!(
+            to_stderr,
+            "  ⚠️  Total gas {}; the trace reported no total",
+            parsed_trace.total_gas_source
+        );
+    }
+    console!(
+        to_stderr,
         "  ├─ Execution:{:>12} {}",
         display.format(total_execution_gas),
         display.unit()
     );
-    println!(
+    console!(
+        to_stderr,
         "  └─ Intrinsic:{:>12} {}",
         display.format(intrinsic_gas),
         display.unit()
     );
-    println!(
+    console!(
+        to_stderr,
         "  HostIO Calls: {}",
         parsed_trace.hostio_stats.total_calls()
     );
-    println!("  Unique Paths: {}", stacks.len());
-    println!();
-    println!(
+    console!(to_stderr, "  Unique Paths: {}", stacks.len());
+    if let Some(contracts) = &parsed_trace.contract_gas {
+        console!(to_stderr, "  Gas by Contract:");
+        for contract in contracts {
+            console!(
+                to_stderr,
+                "    {:<44} {:>12} {} ({})",
+                contract.address,
+                display.format(contract.gas),
+                display.unit(),
+                format_percent(contract.percentage, breakdown_precision)
+            );
+        }
+    }
+    if let Some(slots) = &profile.repeated_slot_reads {
+        console!(to_stderr, "  Repeated Storage Reads:");
+        for slot in slots.iter().take(5) {
+            console!(
+                to_stderr,
+                "    {} {:<66} {:>4}x  {:>12} {} cacheable",
+                slot.contract.as_deref().unwrap_or("-"),
+                slot.slot,
+                slot.reads,
+                display.format(slot.cacheable_gas),
+                display.unit()
+            );
+        }
+        if slots.len() > 5 {
+            console!(to_stderr, "    ... {} more slots", slots.len() - 5);
+        }
+    }
+    if parsed_trace.zeroed_ink_steps > 0 {
+        console!(
+            to_stderr,
+            "  ⚠️  {} steps had non-decreasing ink and were zeroed",
+            parsed_trace.zeroed_ink_steps
+        );
+    }
+    console!(to_stderr);
+    console!(
+        to_stderr,
         "{}",
-        generate_text_summary(&profile.hot_paths, 10, args.ink)
+        generate_text_summary(&profile.hot_paths, 10, args.ink, summary_config.as_ref())
     );
-    println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n");
+    if let Some(depth) = args.group_hot_paths {
+        console!(to_stderr);
+        console!(
+            to_stderr,
+            "{}",
+            generate_hot_path_groups(&profile.hot_paths, depth, args.ink, summary_config.as_ref())
+        );
+    }
+    if args.explain {
+        console!(to_stderr);
+        console!(
+            to_stderr,
+            "{}",
+            generate_hot_path_explanations(&profile.hot_paths, 10)
+        );
+    }
+    console!(to_stderr);
+    console!(
+        to_stderr,
+        "{}",
+        generate_hostio_histogram(
+            &parsed_trace.hostio_stats,
+            args.ink,
+            args.top_hostio.unwrap_or(0),
+            summary_config.as_ref()
+        )
+    );
+    console!(
+        to_stderr,
+        "━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n"
+    );
+}
+
+/// Print gas aggregated per source file (`--group-by-file`).
+///
+/// **Private** - internal helper for execute_capture
+fn print_file_gas(args: &CaptureArgs, stacks: &[CollapsedStack], mapper: Option<&SourceMapper>) {
+    let Some(mapper) = mapper else {
+        warn!("--group-by-file needs source maps; no usable --wasm debug info was loaded");
+        return;
+    };
+
+    let files = gas_by_source_file(stacks, |pc| mapper.lookup(pc).map(|loc| loc.file));
+    if files.iter().all(|f| f.file == UNKNOWN_SOURCE_FILE) {
+        warn!("No stack PCs resolved to source files; the tracer may not report PC offsets");
+    }
+
+    let to_stderr = is_stdout_path(&args.output_json);
+    let display = GasDisplay::new(args.ink);
+    console!(to_stderr, "\n  📁 GAS BY SOURCE FILE");
+    for file in &files {
+        console!(
+            to_stderr,
+            "    {:<44} {:>12} {} ({})",
+            file.file,
+            display.format(file.gas),
+            display.unit(),
+            format_percent(
+                file.percentage,
+                args.precision
+                    .unwrap_or(DEFAULT_BREAKDOWN_PERCENT_PRECISION)
+            )
+        );
+    }
 }
 
 /// Helper for formatting gas/ink units for display.
@@ -313,16 +1234,336 @@ fn print_transaction_summary(
 /// Fetch trace from RPC endpoint
 ///
 /// **Private** - internal helper for execute_capture
-fn fetch_trace(rpc_url: &str, tx_hash: &str, tracer: Option<&str>) -> Result<serde_json::Value> {
-    let client = RpcClient::new(rpc_url).context("Failed to create RPC client")?;
+fn fetch_trace(args: &CaptureArgs, tx_hash: &str) -> Result<serde_json::Value> {
+    let tracer_config = args
+        .tracer_config
+        .as_deref()
+        .map(parse_tracer_config)
+        .transpose()?;
+
+    // Testnode resets reuse transaction hashes, so cached traces may be stale
+    let cache = args
+        .cache_dir
+        .as_ref()
+        .filter(|_| !args.testnode)
+        .map(TraceCache::new);
+    let key = TraceCacheKey {
+        rpc_url: &args.rpc_url,
+        tx_hash,
+        tracer: tracer_name(args),
+        tracer_config: tracer_config.as_ref(),
+    };
+
+    if let Some(cache) = &cache {
+        if args.refresh_cache {
+            debug!("Refreshing cached trace for {}", tx_hash);
+        } else if let Some(trace) = cache.load(&key) {
+            info!("Using cached trace from {}", cache.dir().display());
+            return Ok(trace);
+        }
+    }
+
+    let client = RpcClient::with_timeout(&args.rpc_url, Duration::from_secs(args.timeout_secs))
+        .context("Failed to create RPC client")?;
 
     let trace = client
-        .debug_trace_transaction_with_tracer(tx_hash, tracer)
+        .debug_trace_transaction_with_config(tx_hash, tracer_name(args), tracer_config.as_ref())
         .context(format!("Failed to fetch trace for transaction {}", tx_hash))?;
 
+    if let Some(cache) = &cache {
+        if let Err(e) = cache.store(&key, &trace) {
+            warn!("Could not write trace cache: {}", e);
+        }
+    }
+
     Ok(trace)
 }
 
+/// Fetch the trace being profiled: a simulated call or a mined transaction.
+///
+/// **Private** - simulated calls are never cached, since `latest` moves
+fn fetch_target_trace(args: &CaptureArgs) -> Result<serde_json::Value> {
+    let Some(call) = &args.call else {
+        return fetch_trace(args, &args.transaction_hash);
+    };
+
+    let call = normalize_call(call)?;
+    let tracer_config = args
+        .tracer_config
+        .as_deref()
+        .map(parse_tracer_config)
+        .transpose()?;
+
+    let client = RpcClient::with_timeout(&args.rpc_url, Duration::from_secs(args.timeout_secs))
+        .context("Failed to create RPC client")?;
+
+    client
+        .debug_trace_call(
+            &call,
+            &args.call_block,
+            tracer_name(args),
+            tracer_config.as_ref(),
+        )
+        .context(format!("Failed to trace simulated call to {}", call.to))
+}
+
+/// Tracers probed, in order, by `--auto-tracer`
+const FALLBACK_TRACERS: &[&str] = &["stylusTracer", "callTracer"];
+
+/// Run a trace fetch, recovering from an unsupported tracer
+///
+/// **Private** - wraps fetch_trace / fetch_target_trace
+///
+/// Without `auto_tracer`, an unsupported tracer fails with a message
+/// suggesting `--tracer callTracer`, `--auto-tracer` or a node upgrade. With
+/// it, the tracers in `FALLBACK_TRACERS` that were not already tried are
+/// tried in order (dropping any `tracerConfig`, which is tracer-specific).
+///
+/// # Returns
+/// The raw trace and the name of the tracer that produced it
+fn with_tracer_fallback(
+    args: &CaptureArgs,
+    fetch: impl Fn(&CaptureArgs) -> Result<serde_json::Value>,
+) -> Result<(serde_json::Value, String)> {
+    let requested = requested_tracer(args);
+    let error = match fetch(args) {
+        Ok(trace) => return Ok((trace, requested.to_string())),
+        Err(e) if is_tracer_not_supported(&e) => e,
+        Err(e) => return Err(e),
+    };
+
+    if !args.auto_tracer {
+        return Err(error.context(format!(
+            "Tracer '{}' is not supported by {}. Retry with `--tracer callTracer` \
+             (gas totals only, no per-step stacks), pass --auto-tracer to use the first \
+             tracer the node supports, or point --rpc at an Arbitrum Nitro node with stylusTracer",
+            requested, args.rpc_url
+        )));
+    }
+
+    // Each tracer is tried once, so the requested one is not retried
+    let mut tried = vec![requested];
+    for &tracer in FALLBACK_TRACERS {
+        if tried.contains(&tracer) {
+            continue;
+        }
+        warn!(
+            "Tracer '{}' is not supported by {}; trying '{}'",
+            tried[tried.len() - 1],
+            args.rpc_url,
+            tracer
+        );
+        tried.push(tracer);
+        let fallback = CaptureArgs {
+            tracer: Some(tracer.to_string()),
+            tracer_config: None,
+            ..args.clone()
+        };
+        match fetch(&fallback) {
+            Ok(trace) => return Ok((trace, tracer.to_string())),
+            Err(e) if is_tracer_not_supported(&e) => continue,
+            Err(e) => return Err(e),
+        }
+    }
+
+    Err(error.context(format!(
+        "No supported tracer found at {} (tried {})",
+        args.rpc_url,
+        tried.join(", ")
+    )))
+}
+
+/// Tracer name sent to the node for these arguments
+///
+/// **Private** - mirrors the RPC client's `stylusTracer` default
+fn requested_tracer(args: &CaptureArgs) -> &str {
+    tracer_name(args).unwrap_or("stylusTracer")
+}
+
+/// The `--tracer` value with known tracers in their canonical spelling
+///
+/// **Private** - shared by fetch_trace, fetch_target_trace and requested_tracer
+fn tracer_name(args: &CaptureArgs) -> Option<&str> {
+    args.tracer
+        .as_deref()
+        .map(|name| normalize_tracer_name(name).unwrap_or(name))
+}
+
+/// Canonical spelling of a known tracer name, matched case-insensitively
+///
+/// **Public** - `stylustracer` resolves to `stylusTracer`
+///
+/// # Returns
+/// The entry of `KNOWN_TRACERS` matching `name`, or None for custom tracers
+pub fn normalize_tracer_name(name: &str) -> Option<&'static str> {
+    KNOWN_TRACERS
+        .iter()
+        .find(|known| known.eq_ignore_ascii_case(name.trim()))
+        .copied()
+}
+
+/// Closest known tracer to an unrecognized name
+///
+/// **Public** - used to hint at typos in `--tracer`
+///
+/// # Returns
+/// The known tracer within a few edits of `name` (ignoring case), or None
+/// when nothing is close enough to be a likely typo
+pub fn suggest_tracer_name(name: &str) -> Option<&'static str> {
+    let name = name.trim().to_ascii_lowercase();
+    let max_distance = (name.chars().count() / 3).max(1);
+    KNOWN_TRACERS
+        .iter()
+        .map(|known| (edit_distance(&name, &known.to_ascii_lowercase()), *known))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, known)| known)
+}
+
+/// Levenshtein distance between two strings, counted in chars
+///
+/// **Private** - internal helper for suggest_tracer_name
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// Warn about a `--tracer` value that is not spelled like a known tracer
+///
+/// **Private** - internal helper for validate_args; custom tracers are
+/// still allowed, so this never fails
+fn warn_on_tracer_name(name: &str) {
+    if KNOWN_TRACERS.contains(&name) {
+        return;
+    }
+    if let Some(known) = normalize_tracer_name(name) {
+        warn!("Tracer '{}' normalized to '{}'", name, known);
+    } else if let Some(suggestion) = suggest_tracer_name(name) {
+        warn!(
+            "Unknown tracer '{}'; did you mean '{}'? Passing it to the node as-is",
+            name, suggestion
+        );
+    } else {
+        warn!(
+            "Unknown tracer '{}' (known: {}); passing it to the node as a custom tracer",
+            name,
+            KNOWN_TRACERS.join(", ")
+        );
+    }
+}
+
+/// Whether an error chain contains `RpcError::TracerNotSupported`
+///
+/// **Private** - internal helper for with_tracer_fallback
+fn is_tracer_not_supported(error: &anyhow::Error) -> bool {
+    error.chain().any(|e| {
+        matches!(
+            e.downcast_ref::<RpcError>(),
+            Some(RpcError::TracerNotSupported)
+        )
+    })
+}
+
+/// Validate a simulated call and convert its value to a hex quantity.
+///
+/// **Private** - shared by validate_args and fetch_target_trace
+///
+/// `value` may be given in decimal or `0x` hex wei.
+fn normalize_call(call: &CallRequest) -> Result<CallRequest> {
+    validate_address(&call.to, "--to")?;
+    if let Some(from) = &call.from {
+        validate_address(from, "--from")?;
+    }
+
+    if let Some(data) = &call.data {
+        let hex = data.strip_prefix("0x").unwrap_or(data);
+        if hex.len() % 2 != 0 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            anyhow::bail!("--data must be an even-length hex string");
+        }
+    }
+
+    let value = call
+        .value
+        .as_deref()
+        .map(|raw| {
+            let wei = match raw.strip_prefix("0x") {
+                Some(hex) => u128::from_str_radix(hex, 16),
+                None => raw.parse::<u128>(),
+            }
+            .with_context(|| format!("--value '{}' is not a valid amount in wei", raw))?;
+            Ok::<_, anyhow::Error>(format!("0x{:x}", wei))
+        })
+        .transpose()?;
+
+    Ok(CallRequest {
+        data: call
+            .data
+            .as_ref()
+            .map(|d| format!("0x{}", d.trim_start_matches("0x"))),
+        value,
+        ..call.clone()
+    })
+}
+
+/// Validate a 20-byte hex address (with or without 0x prefix)
+///
+/// **Private** - internal helper for normalize_call
+fn validate_address(address: &str, label: &str) -> Result<()> {
+    let hex = address.strip_prefix("0x").unwrap_or(address);
+    if hex.len() != 40 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        anyhow::bail!("{} must be a 20-byte hex address", label);
+    }
+    Ok(())
+}
+
+/// Parse a `--tracer-config` value, which must be a JSON object.
+///
+/// **Private** - shared by validate_args and fetch_trace
+fn parse_tracer_config(raw: &str) -> Result<serde_json::Value> {
+    let config: serde_json::Value =
+        serde_json::from_str(raw).context("Tracer config is not valid JSON")?;
+
+    if !config.is_object() {
+        anyhow::bail!("Tracer config must be a JSON object, e.g. '{{\"onlyTopCall\": true}}'");
+    }
+
+    Ok(config)
+}
+
+/// Attach contract address and block number from `eth_getTransactionByHash`.
+///
+/// **Private** - metadata is best-effort; failures only log a warning
+fn attach_transaction_metadata(args: &CaptureArgs, parsed_trace: &mut ParsedTrace) {
+    // A simulated call has no transaction to look up
+    if let Some(call) = &args.call {
+        parsed_trace.contract_address = Some(call.to.clone());
+        parsed_trace.block_number = parse_gas_value(&args.call_block).ok();
+        return;
+    }
+
+    let info = RpcClient::with_timeout(&args.rpc_url, Duration::from_secs(args.timeout_secs))
+        .and_then(|client| client.get_transaction_info(&args.transaction_hash));
+
+    match info {
+        Ok(info) => {
+            parsed_trace.contract_address = info.to;
+            parsed_trace.block_number = info
+                .block_number
+                .and_then(|block| parse_gas_value(&block).ok());
+        }
+        Err(e) => warn!("Could not fetch transaction metadata: {}", e),
+    }
+}
+
 /// Validate capture arguments
 ///
 /// **Public** - can be called before execute_capture for early validation
@@ -338,27 +1579,56 @@ pub fn validate_args(args: &CaptureArgs) -> Result<()> {
         anyhow::bail!("RPC URL cannot be empty");
     }
 
-    if !args.rpc_url.starts_with("http://") && !args.rpc_url.starts_with("https://") {
-        anyhow::bail!("RPC URL must start with http:// or https://");
+    if !args.rpc_url.starts_with("http://")
+        && !args.rpc_url.starts_with("https://")
+        && !is_websocket_url(&args.rpc_url)
+    {
+        anyhow::bail!("RPC URL must start with http://, https://, ws:// or wss://");
     }
 
-    // Validate transaction hash
-    if args.transaction_hash.is_empty() {
-        anyhow::bail!("Transaction hash cannot be empty");
+    // Validate transaction hash, or the call being simulated instead
+    match &args.call {
+        Some(call) => {
+            normalize_call(call)?;
+        }
+        None => validate_tx_hash(&args.transaction_hash, "Transaction hash")?,
     }
 
-    // Basic hex validation (with or without 0x prefix)
-    let tx_hash = args
-        .transaction_hash
-        .strip_prefix("0x")
-        .unwrap_or(&args.transaction_hash);
+    if let Some(tracer) = &args.tracer {
+        warn_on_tracer_name(tracer);
+    }
+
+    // Validate tracer config before anything is sent
+    if let Some(raw) = &args.tracer_config {
+        parse_tracer_config(raw)?;
+    }
+
+    // Validate frame filter patterns
+    build_frame_filter(args)?;
 
-    if tx_hash.len() != 64 {
-        anyhow::bail!("Transaction hash must be 32 bytes (64 hex characters)");
+    if args.json_indent.is_some() && args.json_style == JsonStyle::Compact {
+        anyhow::bail!("--json-indent only applies to --json-style pretty");
     }
 
-    if !tx_hash.chars().all(|c| c.is_ascii_hexdigit()) {
-        anyhow::bail!("Transaction hash contains invalid characters");
+    if args.repeatability && args.dry_run {
+        anyhow::bail!("--repeatability and --dry-run cannot be used together");
+    }
+
+    if args.view && is_stdout_path(&args.output_json) {
+        anyhow::bail!("--view needs a file --output to place the viewer next to");
+    }
+
+    // Validate baseline transaction hash
+    if let Some(baseline_tx) = &args.baseline_tx {
+        if args.baseline.is_some() {
+            anyhow::bail!("--baseline and --baseline-tx cannot be used together");
+        }
+        validate_tx_hash(baseline_tx, "Baseline transaction hash")?;
+    }
+
+    // Validate RPC timeout
+    if args.timeout_secs == 0 {
+        anyhow::bail!("RPC timeout must be greater than 0 seconds");
     }
 
     // Validate top_paths
@@ -372,3 +1642,25 @@ pub fn validate_args(args: &CaptureArgs) -> Result<()> {
 
     Ok(())
 }
+
+/// Validate a transaction hash (with or without 0x prefix)
+///
+/// **Private** - internal helper for validate_args
+fn validate_tx_hash(hash: &str, label: &str) -> Result<()> {
+    if hash.is_empty() {
+        anyhow::bail!("{} cannot be empty", label);
+    }
+
+    // Basic hex validation (with or without 0x prefix)
+    let hex = hash.strip_prefix("0x").unwrap_or(hash);
+
+    if hex.len() != 64 {
+        anyhow::bail!("{} must be 32 bytes (64 hex characters)", label);
+    }
+
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        anyhow::bail!("{} contains invalid characters", label);
+    }
+
+    Ok(())
+}
@@ -2,26 +2,90 @@
 //! Orchestrates the comparison of two profiles and reports deltas/violations.
 
 use super::models::DiffArgs;
+use crate::aggregator::stack_builder::CollapsedStack;
 use crate::diff::{
-    check_thresholds, generate_diff, load_thresholds, render_terminal_diff, GasThresholds,
-    HostIOThresholds, ThresholdConfig,
+    apply_env_thresholds, check_budget, check_cost_table, check_thresholds,
+    generate_diff_with_options, load_budget, load_cost_table, load_thresholds,
+    render_terminal_diff_filtered, render_terminal_diff_summary, restrict_to_hostio, save_budget,
+    suppress_insights, DiffOptions, GasThresholds, HostIOThresholds, ThresholdConfig,
+    BUDGET_METRIC, INSIGHT_TAGS,
 };
+use crate::flamegraph::FlamegraphConfig;
 use crate::output::json::read_profile;
+use crate::output::AtomicFile;
 use crate::parser::schema::Profile;
+use crate::utils::config::{DEFAULT_DIFF_PERCENT_PRECISION, PROFILE_GAS_UNIT};
 use anyhow::{Context, Result};
 use colored::*;
-use log::info;
+use log::{info, warn};
 use std::fs;
+use std::path::{Path, PathBuf};
 
 /// Execute the diff command
 pub fn execute_diff(args: DiffArgs) -> Result<()> {
-    // Step 1: Load profiles
-    let baseline: Profile =
-        read_profile(&args.baseline).context("Failed to read baseline profile")?;
+    if args.no_color {
+        colored::control::set_override(false);
+    }
+
+    // Step 1: Load profiles (auto-selecting the baseline from a directory if requested)
     let target: Profile = read_profile(&args.target).context("Failed to read target profile")?;
+    let (baseline_path, baseline) = if let Some(spec) = &args.baseline_git {
+        if args.update_baseline {
+            anyhow::bail!("--update-baseline cannot rewrite a baseline read from git");
+        }
+        (PathBuf::new(), read_git_baseline(spec, Path::new("."))?)
+    } else {
+        let baseline_path = match (&args.baseline_dir, &args.baseline_glob) {
+            (Some(dir), _) => select_baseline(dir, &args.target, &target)?,
+            (None, Some(pattern)) => select_latest_baseline(pattern)?,
+            (None, None) => args.baseline.clone(),
+        };
+        let baseline: Profile =
+            read_profile(&baseline_path).context("Failed to read baseline profile")?;
+        (baseline_path, baseline)
+    };
 
     // Step 2: Generate diff
-    let mut report = generate_diff(&baseline, &target).context("Failed to generate diff")?;
+    let mut options = DiffOptions {
+        normalize_frames: !args.strict_paths,
+        min_path_percent: args.min_path_percent,
+        ..DiffOptions::default()
+    };
+    if let Some(share) = args.memory_gas_share {
+        options.analyzer.memory_gas_share_percent = share;
+    }
+    if let Some(share) = args.log_gas_share {
+        options.analyzer.log_gas_share_percent = share;
+    }
+    if let Some(share) = args.log_call_share {
+        options.analyzer.log_call_share_percent = share;
+    }
+    let mut report = generate_diff_with_options(&baseline, &target, &options)
+        .context("Failed to generate diff")?;
+
+    // HostIO-only mode: drop gas and hot-path comparison entirely
+    if args.hostio_only {
+        restrict_to_hostio(&mut report);
+    }
+
+    // Reference cost table: flag HostIO types priced outside the expected range
+    if let Some(path) = &args.cost_table {
+        let table = load_cost_table(path).context("Failed to load cost table")?;
+        report.insights.extend(check_cost_table(&target, &table));
+    }
+
+    if !args.suppress_insights.is_empty() {
+        for tag in &args.suppress_insights {
+            if !INSIGHT_TAGS.contains(&tag.as_str()) {
+                warn!(
+                    "Unknown insight tag '{}' (known tags: {})",
+                    tag,
+                    INSIGHT_TAGS.join(", ")
+                );
+            }
+        }
+        suppress_insights(&mut report, &args.suppress_insights);
+    }
 
     // Step 3: Handle thresholds
     let mut thresholds = if let Some(path) = &args.threshold_file {
@@ -37,6 +101,11 @@ pub fn execute_diff(args: DiffArgs) -> Result<()> {
         }
     };
 
+    // Environment overrides sit between the file and CLI flags in precedence
+    if args.threshold_env {
+        apply_env_thresholds(&mut thresholds).context("Invalid threshold environment variable")?;
+    }
+
     // Override with simple percent if provided (Simple Mode)
     if let Some(percent) = args.threshold_percent {
         // Enforce strict overrides: clear granular limits and absolute values
@@ -83,6 +152,16 @@ pub fn execute_diff(args: DiffArgs) -> Result<()> {
     // Step 4: Check violations
     check_thresholds(&mut report, &thresholds);
 
+    // Charge this run against the cumulative gas budget, if one is tracked
+    let budget = match &args.budget_file {
+        Some(path) => {
+            let budget = load_budget(path)
+                .with_context(|| format!("Failed to load budget file {}", path.display()))?;
+            Some((path, check_budget(&mut report, &budget)))
+        }
+        None => None,
+    };
+
     // Step 5: Write output if requested
     if let Some(path) = &args.output {
         // Create parent directories if needed
@@ -94,34 +173,79 @@ pub fn execute_diff(args: DiffArgs) -> Result<()> {
         }
 
         let json = serde_json::to_string_pretty(&report)?;
-        fs::write(path, json).context("Failed to write diff report JSON")?;
+        AtomicFile::write(path, json).context("Failed to write diff report JSON")?;
         println!(
             "📊 Diff report written to {}",
             path.display().to_string().cyan()
         );
     }
 
+    // Tooltip precision for the SVG outputs (each graph's default when unset)
+    let svg_config = args
+        .precision
+        .map(|precision| FlamegraphConfig::new().with_percent_precision(precision));
+
     if let Some(path) = &args.output_svg {
-        let baseline_stacks = baseline.all_stacks.as_ref().ok_or_else(|| {
-            anyhow::anyhow!("Baseline profile missing full execution stacks. Please re-capture.")
-        })?;
-        let target_stacks = target.all_stacks.as_ref().ok_or_else(|| {
-            anyhow::anyhow!("Target profile missing full execution stacks. Please re-capture.")
-        })?;
+        let (baseline_stacks, target_stacks) = full_stacks(&baseline, &target)?;
 
-        let svg = crate::flamegraph::generate_diff_flamegraph(baseline_stacks, target_stacks, None)
-            .context("Failed to generate diff flamegraph")?;
+        let svg = crate::flamegraph::generate_diff_flamegraph(
+            baseline_stacks,
+            target_stacks,
+            svg_config.as_ref(),
+        )
+        .context("Failed to generate diff flamegraph")?;
 
-        crate::output::svg::write_svg(&svg, path).context("Failed to write diff flamegraph SVG")?;
+        crate::output::svg::write_svg_validated(&svg, path)
+            .context("Failed to write diff flamegraph SVG")?;
         println!(
             "🔥 Visual diff written to {}",
             path.display().to_string().cyan()
         );
     }
 
+    if let Some(path) = &args.side_by_side_svg {
+        let (baseline_stacks, target_stacks) = full_stacks(&baseline, &target)?;
+
+        let svg = crate::flamegraph::generate_side_by_side_flamegraph(
+            baseline_stacks,
+            target_stacks,
+            svg_config.as_ref(),
+        )
+        .context("Failed to generate side-by-side flamegraph")?;
+
+        crate::output::svg::write_svg_validated(&svg, path)
+            .context("Failed to write side-by-side flamegraph SVG")?;
+        println!(
+            "🔥 Side-by-side flamegraphs written to {}",
+            path.display().to_string().cyan()
+        );
+    }
+
     // Step 6: Terminal Summary
-    if args.summary {
-        println!("{}", render_terminal_diff(&report));
+    let precision = args.precision.unwrap_or(DEFAULT_DIFF_PERCENT_PRECISION);
+    if args.summary_only {
+        println!("{}", render_terminal_diff_summary(&report, precision));
+    } else if args.summary {
+        println!(
+            "{}",
+            render_terminal_diff_filtered(
+                &report,
+                args.only_regressions,
+                args.top_hostio,
+                precision
+            )
+        );
+    }
+
+    if let Some((_, budget)) = &budget {
+        println!(
+            "💰 Gas budget: {} {unit} of {} {unit} used ({} {unit} remaining, {} runs)",
+            budget.spent_gas,
+            budget.allowed_gas(),
+            budget.remaining_gas(),
+            budget.runs,
+            unit = PROFILE_GAS_UNIT
+        );
     }
 
     if args.view {
@@ -140,7 +264,9 @@ pub fn execute_diff(args: DiffArgs) -> Result<()> {
             .all_stacks
             .as_ref()
             .zip(target.all_stacks.as_ref())
-            .and_then(|(b, t)| crate::flamegraph::generate_diff_flamegraph(b, t, None).ok());
+            .and_then(|(b, t)| {
+                crate::flamegraph::generate_diff_flamegraph(b, t, svg_config.as_ref()).ok()
+            });
 
         crate::output::viewer::generate_diff_viewer(
             &baseline,
@@ -155,8 +281,253 @@ pub fn execute_diff(args: DiffArgs) -> Result<()> {
 
     // Step 7: Final Status Exit Code Handling (implicit)
     if report.summary.status == "FAILED" {
+        if report
+            .threshold_violations
+            .iter()
+            .any(|v| v.metric == BUDGET_METRIC)
+        {
+            return Err(anyhow::anyhow!("Cumulative gas budget exhausted"));
+        }
         return Err(anyhow::anyhow!("Regression detected against thresholds"));
     }
 
+    if args.fail_on_warning && report.summary.status == "WARNING" {
+        return Err(anyhow::anyhow!(
+            "Threshold warnings detected (--fail-on-warning)"
+        ));
+    }
+
+    if let Some((path, budget)) = &budget {
+        save_budget(budget, path)
+            .with_context(|| format!("Failed to update budget file {}", path.display()))?;
+    }
+
+    if args.update_baseline {
+        // Replaced atomically so a killed CI job never leaves a truncated baseline
+        let target = fs::read(&args.target).context("Failed to read target profile")?;
+        AtomicFile::write(&baseline_path, target).context("Failed to update baseline profile")?;
+        println!(
+            "📌 Baseline updated: {}",
+            baseline_path.display().to_string().cyan()
+        );
+    }
+
     Ok(())
 }
+
+/// Read a baseline profile as it existed at a git ref
+///
+/// **Public** - used by the diff command for `--baseline-git`
+///
+/// `spec` is `<ref>:<path>` as accepted by `git show`, e.g.
+/// `main:baselines/token.json`. The path is relative to the repository root,
+/// or to `repo_dir` when written as `./path`.
+///
+/// # Arguments
+/// * `spec` - Git ref and profile path, separated by `:`
+/// * `repo_dir` - Directory `git` runs in (any directory inside the repository)
+///
+/// # Errors
+/// Returns an error if `spec` is malformed, git cannot run, the ref is
+/// unknown, the file did not exist at that ref, or its content is not a
+/// profile
+pub fn read_git_baseline(spec: &str, repo_dir: &Path) -> Result<Profile> {
+    let (git_ref, path) = spec
+        .split_once(':')
+        .filter(|(git_ref, path)| !git_ref.is_empty() && !path.is_empty())
+        .with_context(|| {
+            format!(
+                "Invalid --baseline-git '{}': expected <ref>:<path>, e.g. main:baselines/profile.json",
+                spec
+            )
+        })?;
+
+    // Exit codes are checked before messages, which git may localize
+    let git = |args: &[&str]| {
+        std::process::Command::new("git")
+            .args(args)
+            .current_dir(repo_dir)
+            .env("LC_ALL", "C")
+            .output()
+            .with_context(|| format!("Failed to run `git {}`; is git installed?", args[0]))
+    };
+
+    let commit = format!("{}^{{commit}}", git_ref);
+    if !git(&["rev-parse", "--verify", "--quiet", &commit])?
+        .status
+        .success()
+    {
+        anyhow::bail!("Unknown git ref '{}' in --baseline-git", git_ref);
+    }
+    if !git(&["cat-file", "-e", spec])?.status.success() {
+        anyhow::bail!("Baseline '{}' did not exist at git ref '{}'", path, git_ref);
+    }
+
+    let output = git(&["show", spec])?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "`git show {}` failed: {}",
+            spec,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    let profile: Profile = serde_json::from_slice(&output.stdout).with_context(|| {
+        format!(
+            "Baseline '{}' at git ref '{}' is not a valid profile",
+            path, git_ref
+        )
+    })?;
+    info!("Using baseline {} from git ref {}", path, git_ref);
+    Ok(profile)
+}
+
+/// Select the baseline profile for `target` from a directory of baselines
+///
+/// Matching order:
+/// 1. A profile in `dir` recording the same `contract_address` as the target
+///    (preferring `<contract_address>.json` if several match)
+/// 2. A file in `dir` with the same file name as the target
+/// 3. `<contract_address>.json` in `dir`
+///
+/// # Errors
+/// Returns an error if `dir` cannot be read or no baseline matches
+pub fn select_baseline(dir: &Path, target_path: &Path, target: &Profile) -> Result<PathBuf> {
+    let entries = fs::read_dir(dir)
+        .with_context(|| format!("Failed to read baseline directory {}", dir.display()))?;
+
+    if let Some(address) = &target.contract_address {
+        let mut matches: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .filter(|path| {
+                read_profile(path).is_ok_and(|profile| {
+                    profile
+                        .contract_address
+                        .is_some_and(|a| a.eq_ignore_ascii_case(address))
+                })
+            })
+            .collect();
+        matches.sort();
+
+        let by_name = matches.iter().find(|path| {
+            path.file_stem()
+                .and_then(|stem| stem.to_str())
+                .is_some_and(|stem| stem.eq_ignore_ascii_case(address))
+        });
+
+        match (by_name, matches.as_slice()) {
+            (Some(path), _) | (None, [path]) => {
+                info!("Selected baseline by contract address: {}", path.display());
+                return Ok(path.clone());
+            }
+            (None, []) => {}
+            (None, _) => anyhow::bail!(
+                "Multiple baselines in {} match contract {}; name one {}.json",
+                dir.display(),
+                address,
+                address
+            ),
+        }
+    }
+
+    let mut candidates = Vec::new();
+    if let Some(name) = target_path.file_name() {
+        candidates.push(dir.join(name));
+    }
+    if let Some(address) = &target.contract_address {
+        candidates.push(dir.join(format!("{}.json", address)));
+    }
+
+    if let Some(path) = candidates.into_iter().find(|path| path.is_file()) {
+        info!("Selected baseline by file name: {}", path.display());
+        return Ok(path);
+    }
+
+    anyhow::bail!(
+        "No baseline found in {} for target {} (contract: {})",
+        dir.display(),
+        target_path.display(),
+        target.contract_address.as_deref().unwrap_or("unknown")
+    )
+}
+
+/// Select the latest baseline whose path matches a glob pattern
+///
+/// Wildcards (`*`, `?`) are allowed in the file name only, e.g.
+/// `baselines/mytx-*.json`. "Latest" is the lexicographically greatest file
+/// name, so timestamped names (`mytx-20240101T120000.json`) sort by time;
+/// modification times are not used because CI checkouts do not preserve them.
+///
+/// # Errors
+/// Returns an error if the directory cannot be read, the directory part
+/// contains wildcards, or no file matches
+pub fn select_latest_baseline(pattern: &str) -> Result<PathBuf> {
+    let pattern_path = Path::new(pattern);
+    let file_pattern = pattern_path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .with_context(|| format!("Baseline glob '{}' has no file name", pattern))?;
+    let dir = match pattern_path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    if dir.to_string_lossy().contains(['*', '?']) {
+        anyhow::bail!(
+            "Baseline glob '{}' may only use wildcards in the file name",
+            pattern
+        );
+    }
+
+    let matcher = glob_to_regex(file_pattern);
+    let latest = fs::read_dir(dir)
+        .with_context(|| format!("Failed to read baseline directory {}", dir.display()))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.is_file())
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| matcher.is_match(name))
+        })
+        .max_by(|a, b| a.file_name().cmp(&b.file_name()))
+        .with_context(|| format!("No baseline matches '{}'", pattern))?;
+
+    info!(
+        "Selected latest baseline matching '{}': {}",
+        pattern,
+        latest.display()
+    );
+    Ok(latest)
+}
+
+/// Compile a file-name glob (`*` and `?` wildcards) into an anchored regex
+///
+/// **Private** - internal helper for select_latest_baseline
+fn glob_to_regex(glob: &str) -> regex::Regex {
+    let mut pattern = String::from("^");
+    for c in glob.chars() {
+        match c {
+            '*' => pattern.push_str(".*"),
+            '?' => pattern.push('.'),
+            _ => pattern.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    pattern.push('$');
+    regex::Regex::new(&pattern).expect("escaped glob is a valid regex")
+}
+
+/// Full execution stacks of both profiles, needed for visual diffs
+///
+/// **Private** - internal helper for execute_diff
+fn full_stacks<'a>(
+    baseline: &'a Profile,
+    target: &'a Profile,
+) -> Result<(&'a [CollapsedStack], &'a [CollapsedStack])> {
+    let baseline_stacks = baseline.all_stacks.as_deref().ok_or_else(|| {
+        anyhow::anyhow!("Baseline profile missing full execution stacks. Please re-capture.")
+    })?;
+    let target_stacks = target.all_stacks.as_deref().ok_or_else(|| {
+        anyhow::anyhow!("Target profile missing full execution stacks. Please re-capture.")
+    })?;
+    Ok((baseline_stacks, target_stacks))
+}
@@ -7,10 +7,19 @@ pub mod capture;
 pub mod ci;
 pub mod diff;
 pub mod models;
+pub mod observer;
 pub mod utils;
 
 // Re-export main command functions
-pub use capture::{execute_capture, validate_args};
+pub use capture::{
+    capture_to_profile, capture_to_profile_with_observer, execute_capture,
+    execute_capture_with_observer, execute_dry_run, execute_repeatability_check,
+    normalize_tracer_name, suggest_tracer_name, validate_args, CapturedProfile,
+};
 pub use ci::execute_ci_init;
 pub use models::{CaptureArgs, CiInitArgs};
-pub use utils::{display_schema, display_version, validate_profile_file};
+pub use observer::{CaptureObserver, CaptureStage};
+pub use utils::{
+    display_schema, display_version, matrix_profile_files, merge_profile_files,
+    validate_profile_file,
+};
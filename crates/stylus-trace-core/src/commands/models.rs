@@ -1,4 +1,9 @@
+use crate::aggregator::{AnonymousFrames, HotPathSort};
 use crate::flamegraph::FlamegraphConfig;
+use crate::output::JsonStyle;
+use crate::parser::GasUnit;
+use crate::rpc::CallRequest;
+use crate::utils::config::{DEFAULT_MAX_TRACE_STEPS, DEFAULT_RPC_TIMEOUT, DEFAULT_TOP_HOSTIO};
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
@@ -10,33 +15,115 @@ pub struct CaptureArgs {
     /// RPC endpoint URL
     pub rpc_url: String,
 
+    /// RPC request timeout in seconds
+    pub timeout_secs: u64,
+
+    /// Directory for cached traces (None = caching disabled)
+    pub cache_dir: Option<PathBuf>,
+
+    /// Simulate this call with `debug_traceCall` instead of tracing a mined transaction
+    pub call: Option<CallRequest>,
+
+    /// Block to simulate `call` at (tag or hex number)
+    pub call_block: String,
+
+    /// Re-fetch the trace and overwrite any cached copy
+    pub refresh_cache: bool,
+
     /// Transaction hash to profile
     pub transaction_hash: String,
 
+    /// Free-text label stored in the profile and shown instead of the hash
+    pub label: Option<String>,
+
     /// Output path for JSON profile
     pub output_json: PathBuf,
 
     /// Output path for SVG flamegraph (optional)
     pub output_svg: Option<PathBuf>,
 
+    /// Output path for nested call-tree JSON (optional)
+    pub output_tree: Option<PathBuf>,
+
+    /// Output path for compact machine-readable summary JSON (optional)
+    pub output_summary_json: Option<PathBuf>,
+
+    /// Output path for newline-delimited hot paths JSON (optional)
+    pub output_ndjson: Option<PathBuf>,
+
+    /// Output path for a speedscope JSON profile (optional)
+    pub output_speedscope: Option<PathBuf>,
+
+    /// Output path for the raw collapsed stacks JSON (optional, for debugging)
+    pub dump_stacks: Option<PathBuf>,
+
+    /// Layout of the profile JSON
+    pub json_style: JsonStyle,
+
+    /// Indentation of pretty profile JSON (`None` = 2 spaces)
+    pub json_indent: Option<usize>,
+
     /// Number of top hot paths to include in profile
     pub top_paths: usize,
 
+    /// Ordering of hot paths in the profile and summary
+    pub sort: HotPathSort,
+
+    /// Reverse the hot-path ordering
+    pub reverse: bool,
+
     /// Flamegraph configuration
     pub flamegraph_config: Option<FlamegraphConfig>,
 
+    /// Mark flamegraph frames referenced by analyzer insights
+    pub annotate_insights: bool,
+
+    /// Embed the tool version, transaction and timestamp in the flamegraph
+    pub watermark: bool,
+
     /// Print text summary to stdout
     pub print_summary: bool,
 
+    /// Add a hot-path rollup by the first N stack segments to the summary
+    pub group_hot_paths: Option<usize>,
+
+    /// Explain the cost of each summary hot path's leaf HostIO operation
+    pub explain: bool,
+
     /// Optional tracer name (None = default opcode tracer)
     pub tracer: Option<String>,
 
+    /// Optional tracer config as a JSON object string (sent as `tracerConfig`)
+    pub tracer_config: Option<String>,
+
+    /// Probe other tracers when the requested one is not supported by the node
+    pub auto_tracer: bool,
+
+    /// Unit of raw trace gas values (None = native unit of the detected format)
+    pub assume_unit: Option<GasUnit>,
+
+    /// Fail when any step reports end ink greater than start ink
+    pub strict_ink: bool,
+
+    /// Check arguments, RPC connectivity and tracer support without writing files
+    pub dry_run: bool,
+
+    /// Capture the target twice and report whether the results differ
+    pub repeatability: bool,
+
+    /// Target a local nitro-testnode: verify it is a Nitro node and skip the
+    /// trace cache (testnode resets reuse transaction hashes)
+    pub testnode: bool,
+
     /// Show Stylus Ink units (scaled by 10,000)
     pub ink: bool,
 
     /// Path to baseline profile for on-the-fly diffing
     pub baseline: Option<std::path::PathBuf>,
 
+    /// Transaction hash to fetch via RPC and use as the on-the-fly diff baseline
+    pub baseline_tx: Option<String>,
+
     /// Simple gas increase threshold percentage for on-the-fly diffing
     pub threshold_percent: Option<f64>,
 
@@ -51,26 +138,129 @@ pub struct CaptureArgs {
 
     /// Open interactive web viewer
     pub view: bool,
+
+    /// Redact long hex blobs and other sensitive data before writing profiles
+    pub redact: bool,
+
+    /// Optional redaction config file (TOML); implies `redact`
+    pub redact_config: Option<PathBuf>,
+
+    /// Contract ABI (JSON) used to relabel selector frames with function signatures
+    pub selector_map: Option<PathBuf>,
+
+    /// Add a synthetic `root;intrinsic` frame so flamegraph widths match total gas
+    pub show_intrinsic: bool,
+
+    /// Add approximate `hostio (approx);<type>` frames to flamegraphs
+    pub hostio_stacks: bool,
+
+    /// Render only the N heaviest top-level flamegraph subtrees, merging the
+    /// rest into `other` (the profile keeps every stack)
+    pub max_svg_subtrees: Option<usize>,
+
+    /// Fold consecutive identical (recursive) frames in flamegraphs
+    pub collapse_recursion: bool,
+
+    /// Suffix folded frames with their repeat count, e.g. `f (x3)`
+    pub annotate_recursion: bool,
+
+    /// Regexes for flamegraph frames to drop (gas moves to the parent frame)
+    pub exclude_frames: Vec<String>,
+
+    /// Regexes for the only flamegraph frames to keep (empty keeps all)
+    pub include_only_frames: Vec<String>,
+
+    /// Partition stacks by the active contract and report gas per contract
+    pub split_by_contract: bool,
+
+    /// Name delegatecall frames after the implementation contract
+    pub follow_delegatecalls: bool,
+
+    /// Treatment of anonymous `call` / `unknown` frames
+    pub anonymous_frames: AnonymousFrames,
+
+    /// Report gas per source file using the `wasm` source maps
+    pub group_by_file: bool,
+
+    /// HostIO type rows in the summary outputs (`0` = all; `None` keeps each
+    /// output's default)
+    pub top_hostio: Option<usize>,
+
+    /// HostIO types kept in the profile's summary, the rest folded into
+    /// `other` (`0` or `None` keeps all)
+    pub top_hostio_types: Option<usize>,
+
+    /// Decimal places for percentages in the summary, baseline diff and
+    /// flamegraph tooltips (`None` = each output's default)
+    pub precision: Option<usize>,
+
+    /// Refuse traces with more execution steps than this (`0` = no limit)
+    pub max_trace_steps: usize,
 }
 
 impl Default for CaptureArgs {
     fn default() -> Self {
         Self {
             rpc_url: "http://localhost:8547".to_string(),
+            timeout_secs: DEFAULT_RPC_TIMEOUT.as_secs(),
+            cache_dir: None,
+            call: None,
+            call_block: "latest".to_string(),
+            refresh_cache: false,
             transaction_hash: String::new(),
+            label: None,
             output_json: PathBuf::from("profile.json"),
             output_svg: Some(PathBuf::from("flamegraph.svg")),
+            output_tree: None,
+            output_summary_json: None,
+            output_ndjson: None,
+            output_speedscope: None,
+            dump_stacks: None,
+            json_style: JsonStyle::Pretty,
+            json_indent: None,
             top_paths: 20,
+            sort: HotPathSort::default(),
+            reverse: false,
             flamegraph_config: None,
+            annotate_insights: false,
+            watermark: true,
             print_summary: false,
+            group_hot_paths: None,
+            explain: false,
             tracer: None,
+            tracer_config: None,
+            auto_tracer: false,
+            assume_unit: None,
+            strict_ink: false,
+            dry_run: false,
+            repeatability: false,
+            testnode: false,
             ink: false,
             wasm: None,
             baseline: None,
+            baseline_tx: None,
             threshold_percent: None,
             gas_threshold: None,
             hostio_threshold: None,
             view: false,
+            redact: false,
+            redact_config: None,
+            selector_map: None,
+            show_intrinsic: false,
+            hostio_stacks: false,
+            max_svg_subtrees: None,
+            collapse_recursion: false,
+            annotate_recursion: false,
+            exclude_frames: Vec::new(),
+            include_only_frames: Vec::new(),
+            split_by_contract: false,
+            follow_delegatecalls: false,
+            anonymous_frames: AnonymousFrames::Keep,
+            group_by_file: false,
+            top_hostio: None,
+            top_hostio_types: None,
+            precision: None,
+            max_trace_steps: DEFAULT_MAX_TRACE_STEPS,
         }
     }
 }
@@ -112,6 +302,9 @@ pub struct DiffArgs {
     /// Optional threshold configuration file (TOML)
     pub threshold_file: Option<PathBuf>,
 
+    /// Apply threshold overrides from `STYLUS_*` environment variables
+    pub threshold_env: bool,
+
     /// Simple gas increase threshold percentage (e.g., 5.0)
     pub threshold_percent: Option<f64>,
 
@@ -124,14 +317,79 @@ pub struct DiffArgs {
     /// Print a human-readable summary to the terminal
     pub summary: bool,
 
+    /// Print only the header, gas delta, HostIO totals and status (no tables)
+    pub summary_only: bool,
+
     /// Path to write the diff report JSON
     pub output: Option<PathBuf>,
 
     /// Path to write the visual diff flamegraph SVG
     pub output_svg: Option<PathBuf>,
 
+    /// Path to write baseline and target flamegraphs side by side (SVG)
+    pub side_by_side_svg: Option<PathBuf>,
+
     /// Open interactive web viewer
     pub view: bool,
+
+    /// Restrict analysis, thresholds and output to HostIO deltas
+    pub hostio_only: bool,
+
+    /// Only show entries whose delta increased in the terminal summary
+    pub only_regressions: bool,
+
+    /// Directory to auto-select the baseline from (overrides `baseline`)
+    pub baseline_dir: Option<PathBuf>,
+
+    /// Glob whose latest match is the baseline (overrides `baseline`)
+    pub baseline_glob: Option<String>,
+
+    /// `<ref>:<path>` of a baseline read with `git show` (overrides `baseline`)
+    pub baseline_git: Option<String>,
+
+    /// Overwrite the baseline with the target when the diff passes
+    pub update_baseline: bool,
+
+    /// Treat a WARNING status as a failure
+    pub fail_on_warning: bool,
+
+    /// Disable ANSI colors in the terminal summary
+    pub no_color: bool,
+
+    /// Match hot paths by exact stack string (no frame-name normalization)
+    pub strict_paths: bool,
+
+    /// Insight tags to drop from the report (e.g. `storage_tax`)
+    pub suppress_insights: Vec<String>,
+
+    /// HostIO type rows in the terminal summary (`0` = all)
+    pub top_hostio: usize,
+
+    /// Cumulative gas budget file, charged and updated on each passing run
+    pub budget_file: Option<PathBuf>,
+
+    /// Reference HostIO cost table (TOML) checked against the target profile
+    pub cost_table: Option<PathBuf>,
+
+    /// Share of total gas (percent) in read_args/write_result that triggers
+    /// a `memory_copy` insight (`None` = default)
+    pub memory_gas_share: Option<f64>,
+
+    /// Share of total gas (percent) in log emission that triggers a
+    /// `log_emission` insight (`None` = default)
+    pub log_gas_share: Option<f64>,
+
+    /// Share of HostIO calls (percent) that are log emissions before a
+    /// `log_emission` insight (`None` = default)
+    pub log_call_share: Option<f64>,
+
+    /// Only compare hot paths holding at least this share (percent) of total
+    /// gas in either profile
+    pub min_path_percent: Option<f64>,
+
+    /// Decimal places for percentages in the terminal summary and diff
+    /// flamegraphs (`None` = default)
+    pub precision: Option<usize>,
 }
 
 impl Default for DiffArgs {
@@ -140,13 +398,34 @@ impl Default for DiffArgs {
             baseline: PathBuf::new(),
             target: PathBuf::new(),
             threshold_file: None,
+            threshold_env: false,
             threshold_percent: None,
             gas_threshold: None,
             hostio_threshold: None,
             summary: true,
+            summary_only: false,
             output: None,
             output_svg: None,
+            side_by_side_svg: None,
             view: false,
+            hostio_only: false,
+            only_regressions: false,
+            baseline_dir: None,
+            baseline_glob: None,
+            baseline_git: None,
+            update_baseline: false,
+            fail_on_warning: false,
+            no_color: false,
+            strict_paths: false,
+            suppress_insights: Vec::new(),
+            top_hostio: DEFAULT_TOP_HOSTIO,
+            budget_file: None,
+            cost_table: None,
+            memory_gas_share: None,
+            log_gas_share: None,
+            log_call_share: None,
+            min_path_percent: None,
+            precision: None,
         }
     }
 }
@@ -0,0 +1,63 @@
+//! Progress notifications for library users of the capture pipeline.
+//!
+//! `execute_capture` only logs its progress. GUIs, TUIs and test harnesses
+//! can pass a `CaptureObserver` to `execute_capture_with_observer` instead and
+//! react to each stage as it completes. Any `Fn(&CaptureStage)` closure is an
+//! observer.
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// A completed step of the capture pipeline
+#[derive(Debug, Clone, PartialEq)]
+pub enum CaptureStage {
+    /// The raw trace was fetched from the RPC (or the trace cache)
+    Fetched {
+        /// Tracer that produced the trace (after any fallback)
+        tracer: String,
+    },
+
+    /// The raw trace was parsed
+    Parsed {
+        /// Number of execution steps
+        steps: usize,
+        /// Total gas used (in ink)
+        total_gas: u64,
+    },
+
+    /// Collapsed stacks were built (after selector labelling and redaction)
+    StacksBuilt {
+        /// Number of unique stacks
+        stacks: usize,
+    },
+
+    /// The flamegraph SVG was rendered (only emitted when one is requested)
+    FlamegraphGenerated,
+
+    /// The profile and any requested artifacts were written
+    Written {
+        /// Path of the profile JSON (`-` for stdout)
+        profile: PathBuf,
+    },
+
+    /// The capture finished successfully
+    Completed {
+        /// Wall-clock time of the whole capture
+        elapsed: Duration,
+    },
+}
+
+/// Receives capture progress notifications
+///
+/// **Public** - implemented by library users; closures taking
+/// `&CaptureStage` implement it automatically
+pub trait CaptureObserver {
+    /// Called once for each stage, in pipeline order
+    fn on_stage(&self, stage: &CaptureStage);
+}
+
+impl<F: Fn(&CaptureStage)> CaptureObserver for F {
+    fn on_stage(&self, stage: &CaptureStage) {
+        self(stage)
+    }
+}
@@ -1,10 +1,17 @@
-use crate::output::read_profile;
+use crate::aggregator::merge_profiles;
+use crate::diff::{build_diff_matrix, render_diff_matrix};
+use crate::output::{check_profile_schema_file, read_profile, write_profile, AtomicFile};
 use crate::utils::config::SCHEMA_VERSION;
-use anyhow::Result;
-use std::path::PathBuf;
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
 
 /// Validate a profile JSON file
-pub fn validate_profile_file(file_path: PathBuf) -> Result<()> {
+///
+/// With `schema_check`, also reports fields of the current schema that the
+/// profile lacks (and that were silently defaulted), fields this tool does
+/// not know, and whether the profile comes from a newer schema version.
+pub fn validate_profile_file(file_path: PathBuf, schema_check: bool) -> Result<()> {
     println!("Validating profile: {}", file_path.display());
 
     let profile = read_profile(&file_path)?;
@@ -12,10 +19,125 @@ pub fn validate_profile_file(file_path: PathBuf) -> Result<()> {
     println!("✓ Valid profile JSON");
     println!("  Version: {}", profile.version);
     println!("  Transaction: {}", profile.transaction_hash);
+    if let Some(address) = &profile.contract_address {
+        println!("  Contract: {}", address);
+    }
+    if let Some(block) = profile.block_number {
+        println!("  Block: {}", block);
+    }
     println!("  Total Gas: {}", profile.total_gas);
+    if !profile.total_gas_source.is_reported() {
+        println!(
+            "  ⚠️  Total gas is {}; the trace reported no total, so it excludes unattributed gas",
+            profile.total_gas_source
+        );
+    }
     println!("  HostIO Calls: {}", profile.hostio_summary.total_calls);
     println!("  Hot Paths: {}", profile.hot_paths.len());
 
+    if schema_check {
+        let check = check_profile_schema_file(&file_path)?;
+        println!();
+        println!("Schema check (tool schema v{}):", SCHEMA_VERSION);
+        if check.newer_than_tool {
+            println!(
+                "  ⚠️  Profile version {} is newer than this tool's schema; newer data may be ignored",
+                check.version
+            );
+        }
+        for field in &check.defaulted {
+            println!("  ⚠️  Missing, defaulted: {}", field);
+        }
+        for field in &check.unknown {
+            println!("  ⚠️  Unknown, ignored: {}", field);
+        }
+        if !check.not_recorded.is_empty() {
+            println!(
+                "  Not recorded (optional): {}",
+                check.not_recorded.join(", ")
+            );
+        }
+        if check.is_clean() {
+            println!("  ✓ All current schema fields present");
+        }
+    }
+
+    Ok(())
+}
+
+/// Average several captured profiles into one and write it to `output`
+///
+/// **Public** - used by the `merge` command
+///
+/// # Errors
+/// * No input profiles
+/// * An input cannot be read or the merged profile cannot be written
+pub fn merge_profile_files(inputs: &[PathBuf], output: &Path) -> Result<()> {
+    let profiles = inputs
+        .iter()
+        .map(|path| {
+            read_profile(path).with_context(|| format!("Failed to read {}", path.display()))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let merged = merge_profiles(&profiles).context("No profiles to merge")?;
+    write_profile(&merged, output)?;
+
+    println!(
+        "✓ Merged {} profiles into {}",
+        profiles.len(),
+        output.display()
+    );
+    println!("  Total Gas: {}", merged.total_gas);
+    if let Some(samples) = &merged.samples {
+        println!("  Total Gas Std Dev: {:.1}", samples.total_gas_stddev);
+        println!(
+            "  Hot Paths: {} kept, {} dropped (not in every sample)",
+            merged.hot_paths.len(),
+            samples.dropped_hot_paths
+        );
+    }
+
+    Ok(())
+}
+
+/// Compare every pair of profiles and print a matrix of total gas changes
+///
+/// **Public** - used by the `matrix` command
+///
+/// Each profile is named after its `--label`, or its file name when it has
+/// none. With `output`, the matrix is also written as JSON.
+///
+/// # Errors
+/// * A profile cannot be read or the matrix JSON cannot be written
+/// * Profiles with incompatible schema versions
+pub fn matrix_profile_files(inputs: &[PathBuf], output: Option<&Path>) -> Result<()> {
+    let profiles = inputs
+        .iter()
+        .map(|path| {
+            let profile =
+                read_profile(path).with_context(|| format!("Failed to read {}", path.display()))?;
+            let name = profile.label.clone().unwrap_or_else(|| {
+                path.file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_else(|| path.display().to_string())
+            });
+            Ok((name, profile))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let matrix = build_diff_matrix(&profiles).context("Failed to compare profiles")?;
+    print!("{}", render_diff_matrix(&matrix));
+
+    if let Some(path) = output {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_string_pretty(&matrix)?;
+        AtomicFile::write(path, json).context("Failed to write diff matrix JSON")?;
+        println!("✓ Matrix written to: {}", path.display());
+    }
+
     Ok(())
 }
 
@@ -29,16 +151,27 @@ pub fn display_schema(show_details: bool) {
         println!("Schema Structure:");
         println!("  version: string          - Schema version (e.g., '1.0.0')");
         println!("  transaction_hash: string - Transaction hash");
+        println!("  contract_address: string? - Called contract address");
+        println!("  block_number: number?    - Block the transaction was included in");
         println!("  total_gas: number        - Total gas used");
+        println!("  total_gas_source: string? - 'derived_from_steps' if the trace had no total");
         println!("  hostio_summary: object   - HostIO event statistics");
         println!("    total_calls: number    - Total HostIO calls");
         println!("    by_type: object        - Breakdown by HostIO type");
         println!("    total_hostio_gas: number - Gas consumed by HostIO");
+        println!("  repeated_slot_reads: array? - Storage slots read more than once");
+        println!("    contract: string?      - Contract whose storage was read");
+        println!("    slot: string           - Storage slot key");
+        println!("    reads: number          - Number of reads of the slot");
+        println!("    gas: number            - Gas of all reads");
+        println!("    cacheable_gas: number  - Gas of every read after the first");
         println!("  hot_paths: array         - Top gas-consuming execution paths");
         println!("    stack: string          - Stack trace");
-        println!("    gas: number            - Gas consumed");
+        println!("    gas: number            - Self gas (exact stack only)");
+        println!("    cumulative_gas: number - Gas including descendant stacks");
         println!("    percentage: number     - Percentage of total gas");
         println!("    source_hint: object?   - Source location (if available)");
+        println!("  samples: object?         - Sample count and std devs (merged profiles)");
         println!("  generated_at: string     - ISO 8601 timestamp");
     } else {
         println!("Use --show for detailed schema information");
@@ -1,10 +1,58 @@
 use crate::aggregator::stack_builder::CollapsedStack;
 use crate::diff::schema::{AnalysisInsight, InsightSeverity};
 use crate::parser::schema::Profile;
+use crate::parser::HostIoType;
+use crate::utils::config::{
+    DEFAULT_LOG_CALL_SHARE_PERCENT, DEFAULT_LOG_GAS_SHARE_PERCENT,
+    DEFAULT_MEMORY_GAS_SHARE_PERCENT, PROFILE_GAS_UNIT,
+};
 use std::collections::HashMap;
 
+/// Tags emitted by `analyze_profile`, usable with `--suppress-insight`
+pub const INSIGHT_TAGS: &[&str] = &[
+    "redundant_call",
+    "storage_tax",
+    "storage_write_impact",
+    "deep_recursion",
+    "memory_copy",
+    "repeated_slot_read",
+    "log_emission",
+    super::cost_table::COST_TABLE_TAG,
+];
+
+/// Tunable thresholds for `analyze_profile_with`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AnalyzerOptions {
+    /// Share of total gas (percent) in read_args/write_result that is
+    /// reported as a large buffer copy
+    pub memory_gas_share_percent: f64,
+
+    /// Share of total gas (percent) in log emission that is reported as
+    /// excessive event emission
+    pub log_gas_share_percent: f64,
+
+    /// Share of HostIO calls (percent) that are log emissions before they
+    /// are reported as excessive event emission
+    pub log_call_share_percent: f64,
+}
+
+impl Default for AnalyzerOptions {
+    fn default() -> Self {
+        Self {
+            memory_gas_share_percent: DEFAULT_MEMORY_GAS_SHARE_PERCENT,
+            log_gas_share_percent: DEFAULT_LOG_GAS_SHARE_PERCENT,
+            log_call_share_percent: DEFAULT_LOG_CALL_SHARE_PERCENT,
+        }
+    }
+}
+
 /// Analyze a profile for qualitative insights
 pub fn analyze_profile(target: &Profile) -> Vec<AnalysisInsight> {
+    analyze_profile_with(target, &AnalyzerOptions::default())
+}
+
+/// Analyze a profile for qualitative insights with explicit thresholds
+pub fn analyze_profile_with(target: &Profile, options: &AnalyzerOptions) -> Vec<AnalysisInsight> {
     let mut insights = Vec::new();
     let stacks = target.all_stacks.as_deref().unwrap_or(&[]);
 
@@ -14,6 +62,23 @@ pub fn analyze_profile(target: &Profile) -> Vec<AnalysisInsight> {
     // Heuristic 2: Cold/Warm Storage Tax Analysis (using stack weights)
     analyze_storage_tax(stacks, target.total_gas, &mut insights);
 
+    // Heuristic 3: Deep recursion / repeated call cycles (using stack shapes)
+    detect_recursion(stacks, target.total_gas, &mut insights);
+
+    // Heuristic 4: Large calldata / return-data copies (using stack weights)
+    analyze_memory_gas(
+        stacks,
+        target.total_gas,
+        options.memory_gas_share_percent,
+        &mut insights,
+    );
+
+    // Heuristic 5: Repeated reads of the same storage slot (using slot keys)
+    detect_repeated_slot_reads(target, &mut insights);
+
+    // Heuristic 6: Excessive event emission (using HostIO totals)
+    analyze_log_emission(target, options, &mut insights);
+
     insights
 }
 
@@ -54,6 +119,7 @@ fn detect_redundant_host_calls(profile: &Profile, insights: &mut Vec<AnalysisIns
                 ),
                 severity: calculate_insight_severity(total_calls, gas_impact_pct),
                 tag: Some("redundant_call".to_string()),
+                stacks: stats.stacks,
             });
         }
     }
@@ -62,22 +128,27 @@ fn detect_redundant_host_calls(profile: &Profile, insights: &mut Vec<AnalysisIns
 struct StackStats {
     unique_stacks: usize,
     total_weight: u64,
+    stacks: Vec<String>,
 }
 
 fn collect_stack_stats(stacks: &[CollapsedStack], label: &str) -> StackStats {
     let mut occurrences_by_stack: HashMap<&str, u64> = HashMap::new();
-    let mut total_weight = 0;
+    let mut total_weight: u64 = 0;
 
     for stack in stacks {
         if stack.stack.contains(label) {
             *occurrences_by_stack.entry(&stack.stack).or_insert(0) += 1;
-            total_weight += stack.weight;
+            total_weight = total_weight.saturating_add(stack.weight);
         }
     }
 
+    let mut matching: Vec<String> = occurrences_by_stack.keys().map(|s| s.to_string()).collect();
+    matching.sort();
+
     StackStats {
         unique_stacks: occurrences_by_stack.len(),
         total_weight,
+        stacks: matching,
     }
 }
 
@@ -138,6 +209,8 @@ struct StorageStats {
     warm_read_gas: u64,
     write_gas: u64,
     cold_count: u64,
+    cold_stacks: Vec<String>,
+    write_stacks: Vec<String>,
 }
 
 fn collect_storage_stats(stacks: &[CollapsedStack]) -> StorageStats {
@@ -146,18 +219,22 @@ fn collect_storage_stats(stacks: &[CollapsedStack]) -> StorageStats {
         warm_read_gas: 0,
         write_gas: 0,
         cold_count: 0,
+        cold_stacks: Vec::new(),
+        write_stacks: Vec::new(),
     };
 
     for stack in stacks {
         if stack.stack.contains("storage_load") {
             if stack.weight >= 2000 {
-                stats.cold_read_gas += stack.weight;
+                stats.cold_read_gas = stats.cold_read_gas.saturating_add(stack.weight);
                 stats.cold_count += 1;
+                stats.cold_stacks.push(stack.stack.clone());
             } else {
-                stats.warm_read_gas += stack.weight;
+                stats.warm_read_gas = stats.warm_read_gas.saturating_add(stack.weight);
             }
         } else if stack.stack.contains("storage_store") || stack.stack.contains("storage_cache") {
-            stats.write_gas += stack.weight;
+            stats.write_gas = stats.write_gas.saturating_add(stack.weight);
+            stats.write_stacks.push(stack.stack.clone());
         }
     }
     stats
@@ -201,6 +278,7 @@ fn generate_cold_tax_insight(
             ),
             severity,
             tag: Some("storage_tax".to_string()),
+            stacks: stats.cold_stacks.clone(),
         });
     }
 }
@@ -221,7 +299,258 @@ fn generate_write_impact_insight(
                 ),
                 severity: InsightSeverity::Medium,
                 tag: Some("storage_write_impact".to_string()),
+                stacks: stats.write_stacks.clone(),
             });
         }
     }
 }
+
+/// HostIO frames in the Memory category (ABI input and output copies)
+const MEMORY_HOSTIO_LABELS: &[&str] = &["read_args", "write_result"];
+
+/// Flags read_args/write_result gas above `share_percent` of total gas
+fn analyze_memory_gas(
+    stacks: &[CollapsedStack],
+    total_gas: u64,
+    share_percent: f64,
+    insights: &mut Vec<AnalysisInsight>,
+) {
+    if total_gas == 0 {
+        return;
+    }
+
+    let mut memory_gas: u64 = 0;
+    let mut memory_stacks = Vec::new();
+    for label in MEMORY_HOSTIO_LABELS {
+        let stats = collect_stack_stats(stacks, label);
+        memory_gas = memory_gas.saturating_add(stats.total_weight);
+        memory_stacks.extend(stats.stacks);
+    }
+    memory_stacks.sort();
+    memory_stacks.dedup();
+
+    let memory_pct = (memory_gas as f64 / total_gas as f64) * 100.0;
+    if memory_gas == 0 || memory_pct <= share_percent {
+        return;
+    }
+
+    let severity = if memory_pct > share_percent * 2.0 {
+        InsightSeverity::High
+    } else {
+        InsightSeverity::Medium
+    };
+
+    insights.push(AnalysisInsight {
+        category: "Memory".to_string(),
+        description: format!(
+            "Large buffer copies: read_args/write_result consume {:.1}% of total gas. Avoid copying unneeded calldata and keep return data compact.",
+            memory_pct
+        ),
+        severity,
+        tag: Some("memory_copy".to_string()),
+        stacks: memory_stacks,
+    });
+}
+
+/// Frames that emit logs (Stylus HostIO name and EVM opcodes)
+const LOG_HOSTIO_LABELS: &[&str] = &["emit_log", "LOG0", "LOG1", "LOG2", "LOG3", "LOG4"];
+
+/// Flags log emission above `log_gas_share_percent` of total gas or
+/// `log_call_share_percent` of HostIO calls
+///
+/// Severity follows the gas impact: High past twice the gas share, Medium
+/// past it, and Low when only the call share is exceeded.
+fn analyze_log_emission(
+    profile: &Profile,
+    options: &AnalyzerOptions,
+    insights: &mut Vec<AnalysisInsight>,
+) {
+    let summary = &profile.hostio_summary;
+    let log_key = HostIoType::Log.as_str();
+    let log_calls = summary.by_type.get(log_key).copied().unwrap_or(0);
+    if log_calls == 0 {
+        return;
+    }
+    let log_gas = summary.gas_by_type.get(log_key).copied().unwrap_or(0);
+
+    let share = |part: u64, total: u64| {
+        if total > 0 {
+            (part as f64 / total as f64) * 100.0
+        } else {
+            0.0
+        }
+    };
+    let gas_pct = share(log_gas, profile.total_gas);
+    let call_pct = share(log_calls, summary.total_calls);
+    let gas_share = options.log_gas_share_percent;
+    if gas_pct <= gas_share && call_pct <= options.log_call_share_percent {
+        return;
+    }
+
+    let severity = if gas_pct > gas_share * 2.0 {
+        InsightSeverity::High
+    } else if gas_pct > gas_share {
+        InsightSeverity::Medium
+    } else {
+        InsightSeverity::Low
+    };
+
+    let stacks = profile.all_stacks.as_deref().unwrap_or(&[]);
+    let mut log_stacks: Vec<String> = LOG_HOSTIO_LABELS
+        .iter()
+        .flat_map(|label| collect_stack_stats(stacks, label).stacks)
+        .collect();
+    log_stacks.sort();
+    log_stacks.dedup();
+
+    insights.push(AnalysisInsight {
+        category: "Events".to_string(),
+        description: format!(
+            "Excessive event emission: {} log calls ({:.1}% of HostIO calls) consume {:.1}% of total gas. Emit fewer events, drop unneeded indexed topics, or batch data into one log.",
+            log_calls, call_pct, gas_pct
+        ),
+        severity,
+        tag: Some("log_emission".to_string()),
+        stacks: log_stacks,
+    });
+}
+
+/// Most repeated storage slots reported, by cacheable gas
+const MAX_REPEATED_SLOT_INSIGHTS: usize = 5;
+
+/// Reports storage slots read more than once, with the gas caching would save
+///
+/// Unlike the label-based redundancy check this keys on the actual slots, so
+/// it only fires when the tracer reported slot keys (`repeated_slot_reads`).
+fn detect_repeated_slot_reads(profile: &Profile, insights: &mut Vec<AnalysisInsight>) {
+    let Some(slots) = &profile.repeated_slot_reads else {
+        return;
+    };
+
+    for slot in slots.iter().take(MAX_REPEATED_SLOT_INSIGHTS) {
+        let saved_pct = if profile.total_gas > 0 {
+            (slot.cacheable_gas as f64 / profile.total_gas as f64) * 100.0
+        } else {
+            0.0
+        };
+
+        insights.push(AnalysisInsight {
+            category: "Storage".to_string(),
+            description: format!(
+                "Repeated storage read: slot {}{} is read {} times. Caching the first read would save {} {} ({:.2}% total gas).",
+                slot.slot,
+                slot.contract.as_deref().map(|c| format!(" of {}", c)).unwrap_or_default(),
+                slot.reads, slot.cacheable_gas, PROFILE_GAS_UNIT, saved_pct
+            ),
+            severity: calculate_insight_severity(slot.reads, saved_pct),
+            tag: Some("repeated_slot_read".to_string()),
+            stacks: slot.stacks.clone(),
+        });
+    }
+}
+
+/// Minimum consecutive repetitions of a frame (or cycle) to report
+const MIN_RECURSION_REPEATS: usize = 5;
+
+/// Longest call cycle considered (1 = direct recursion, 2 = `a -> b -> a`, ...)
+const MAX_RECURSION_PERIOD: usize = 3;
+
+struct RecursionStats {
+    max_repeats: usize,
+    total_weight: u64,
+    stacks: Vec<String>,
+}
+
+/// Detects frames or short call cycles that repeat consecutively within a stack
+fn detect_recursion(
+    stacks: &[CollapsedStack],
+    total_gas: u64,
+    insights: &mut Vec<AnalysisInsight>,
+) {
+    let mut patterns: HashMap<Vec<&str>, RecursionStats> = HashMap::new();
+
+    for stack in stacks {
+        let frames: Vec<&str> = stack.stack.split(';').collect();
+        if let Some((pattern, repeats)) = longest_repetition(&frames) {
+            if repeats >= MIN_RECURSION_REPEATS {
+                let stats = patterns.entry(pattern).or_insert(RecursionStats {
+                    max_repeats: 0,
+                    total_weight: 0,
+                    stacks: Vec::new(),
+                });
+                stats.max_repeats = stats.max_repeats.max(repeats);
+                stats.total_weight = stats.total_weight.saturating_add(stack.weight);
+                stats.stacks.push(stack.stack.clone());
+            }
+        }
+    }
+
+    // Heaviest patterns first for stable, useful ordering
+    let mut patterns: Vec<_> = patterns.into_iter().collect();
+    patterns.sort_by(|(a_pattern, a), (b_pattern, b)| {
+        b.total_weight
+            .cmp(&a.total_weight)
+            .then_with(|| a_pattern.cmp(b_pattern))
+    });
+
+    for (pattern, stats) in patterns {
+        let impact_pct = if total_gas > 0 {
+            (stats.total_weight as f64 / total_gas as f64) * 100.0
+        } else {
+            0.0
+        };
+
+        let severity = if impact_pct > 5.0 {
+            InsightSeverity::High
+        } else if impact_pct > 1.0 {
+            InsightSeverity::Medium
+        } else {
+            InsightSeverity::Low
+        };
+
+        let description = if pattern.len() == 1 {
+            format!(
+                "Deep recursion: `{}` calls itself {} times in a row ({:.2}% total gas). Consider an iterative implementation or memoization.",
+                pattern[0], stats.max_repeats, impact_pct
+            )
+        } else {
+            format!(
+                "Repeated call cycle: `{}` recurs {} times in a row ({:.2}% total gas). Consider iteration or memoizing the repeated subtree.",
+                pattern.join(" -> "),
+                stats.max_repeats,
+                impact_pct
+            )
+        };
+
+        insights.push(AnalysisInsight {
+            category: "Execution".to_string(),
+            description,
+            severity,
+            tag: Some("deep_recursion".to_string()),
+            stacks: stats.stacks,
+        });
+    }
+}
+
+/// Finds the frame sequence with the most consecutive repetitions in a stack
+///
+/// Shorter cycles win ties, so `a;a;a;a` is reported as `a` x4 rather than `a;a` x2.
+fn longest_repetition<'a>(frames: &[&'a str]) -> Option<(Vec<&'a str>, usize)> {
+    let mut best: Option<(Vec<&str>, usize)> = None;
+
+    for period in 1..=MAX_RECURSION_PERIOD {
+        for start in 0..frames.len().saturating_sub(period) {
+            let window = &frames[start..start + period];
+            let mut repeats = 1;
+            while frames[start + repeats * period..].starts_with(window) {
+                repeats += 1;
+            }
+
+            if repeats > 1 && best.as_ref().is_none_or(|(_, r)| repeats > *r) {
+                best = Some((window.to_vec(), repeats));
+            }
+        }
+    }
+
+    best
+}
@@ -0,0 +1,127 @@
+//! Cumulative gas budgets.
+//!
+//! Instead of a hard per-run threshold, a budget allows total gas to drift up
+//! to a percentage of a reference value over many runs (e.g. "total gas may
+//! grow 2% over the release"). Each run's gas change is charged against the
+//! budget; improvements credit it back. The budget file is a small JSON
+//! document that is rewritten after every passing run:
+//!
+//! ```json
+//! { "max_increase_percent": 2.0 }
+//! ```
+//!
+//! `reference_gas` is filled in from the first baseline compared against it.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+use super::schema::{DiffReport, ThresholdViolation};
+use super::threshold::create_summary;
+use super::DiffError;
+use crate::output::AtomicFile;
+use crate::utils::error::OutputError;
+
+/// Violation metric reported when the budget is exhausted
+pub const BUDGET_METRIC: &str = "budget.gas";
+
+/// Cumulative gas budget tracked across diff runs
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GasBudget {
+    /// Total gas may grow by at most this percentage of `reference_gas`
+    pub max_increase_percent: f64,
+
+    /// Total gas when the budget started (taken from the first baseline)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reference_gas: Option<u64>,
+
+    /// Net gas increase charged so far (never below zero)
+    #[serde(default)]
+    pub spent_gas: u64,
+
+    /// Number of passing runs recorded against the budget
+    #[serde(default)]
+    pub runs: u64,
+}
+
+impl GasBudget {
+    /// Total gas increase the budget allows
+    pub fn allowed_gas(&self) -> u64 {
+        let reference = self.reference_gas.unwrap_or(0) as f64;
+        (reference * self.max_increase_percent / 100.0) as u64
+    }
+
+    /// Budget left before runs start failing (negative once exhausted)
+    pub fn remaining_gas(&self) -> i64 {
+        self.allowed_gas() as i64 - self.spent_gas as i64
+    }
+}
+
+/// Load a gas budget from a JSON file
+///
+/// # Errors
+/// * `DiffError::IoError` - If the file cannot be read
+/// * `DiffError::BudgetParseFailed` - If the JSON is invalid
+/// * `DiffError::InvalidBudget` - If `max_increase_percent` is negative or not finite
+pub fn load_budget(path: impl AsRef<Path>) -> Result<GasBudget, DiffError> {
+    let contents = fs::read_to_string(path)?;
+    let budget: GasBudget = serde_json::from_str(&contents)?;
+    if !budget.max_increase_percent.is_finite() || budget.max_increase_percent < 0.0 {
+        return Err(DiffError::InvalidBudget(format!(
+            "max_increase_percent must be a non-negative number, got {}",
+            budget.max_increase_percent
+        )));
+    }
+    Ok(budget)
+}
+
+/// Write a gas budget back to its JSON file
+///
+/// # Errors
+/// * `DiffError::IoError` - If the file cannot be written
+/// * `DiffError::BudgetParseFailed` - If serialization fails
+pub fn save_budget(budget: &GasBudget, path: impl AsRef<Path>) -> Result<(), DiffError> {
+    let json = serde_json::to_string_pretty(budget)?;
+    AtomicFile::write(path, json).map_err(|e| match e {
+        OutputError::WriteFailed(io) => DiffError::IoError(io),
+        other => DiffError::IoError(std::io::Error::other(other)),
+    })
+}
+
+/// Charge a diff's gas change against the budget
+///
+/// Adds a `budget.gas` error violation to the report (and recomputes its
+/// summary) when the run would overspend the budget. The returned budget
+/// includes this run and should be saved only if the diff passes.
+///
+/// # Arguments
+/// * `diff` - Diff report to charge (thresholds already checked)
+/// * `budget` - Budget state before this run
+///
+/// # Returns
+/// The budget after this run
+pub fn check_budget(diff: &mut DiffReport, budget: &GasBudget) -> GasBudget {
+    let gas = &diff.deltas.gas;
+    let spent = (budget.spent_gas as i64).saturating_add(gas.absolute_change);
+
+    let updated = GasBudget {
+        reference_gas: Some(budget.reference_gas.unwrap_or(gas.baseline)),
+        spent_gas: spent.max(0) as u64,
+        runs: budget.runs + 1,
+        ..budget.clone()
+    };
+
+    if updated.remaining_gas() < 0 {
+        diff.threshold_violations.push(ThresholdViolation {
+            metric: BUDGET_METRIC.to_string(),
+            threshold: updated.allowed_gas() as f64,
+            actual: updated.spent_gas as f64,
+            severity: "error".to_string(),
+        });
+        let warning = diff.summary.warning.take();
+        diff.summary = create_summary(&diff.threshold_violations);
+        diff.summary.warning = warning;
+    }
+
+    updated
+}
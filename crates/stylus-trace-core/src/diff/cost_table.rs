@@ -0,0 +1,187 @@
+//! Reference HostIO cost tables.
+//!
+//! A cost table maps HostIO types to the gas a single call is expected to
+//! cost. Observed per-call averages outside that range become insights, so
+//! raw measurements can be checked against known Stylus pricing:
+//!
+//! ```toml
+//! [storage_load]
+//! min = 2000
+//! max = 2200
+//!
+//! [native_keccak256]
+//! max = 150
+//! ```
+//!
+//! Keys are HostIO type names (aliases such as `sload` are accepted). Costs
+//! are in gas, not ink.
+
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::Path;
+
+use super::schema::{AnalysisInsight, InsightSeverity};
+use super::DiffError;
+use crate::parser::schema::Profile;
+use crate::parser::HostIoType;
+
+/// Insight tag for per-call costs outside the reference range
+pub const COST_TABLE_TAG: &str = "hostio_cost";
+
+/// Expected gas range for a single call of one HostIO type
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CostRange {
+    /// Lowest expected gas per call
+    pub min: Option<f64>,
+
+    /// Highest expected gas per call
+    pub max: Option<f64>,
+}
+
+/// Expected per-call gas for each listed HostIO type
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CostTable {
+    pub costs: HashMap<HostIoType, CostRange>,
+}
+
+/// Load a reference cost table from a TOML file
+///
+/// # Errors
+/// * `DiffError::IoError` - If the file cannot be read
+/// * `DiffError::InvalidCostTable` - If the TOML is invalid, names an unknown
+///   HostIO type, or has a negative or inverted range
+pub fn load_cost_table(path: impl AsRef<Path>) -> Result<CostTable, DiffError> {
+    let contents = fs::read_to_string(path)?;
+    let raw: BTreeMap<String, CostRange> =
+        toml::from_str(&contents).map_err(|e| DiffError::InvalidCostTable(e.to_string()))?;
+
+    let mut costs = HashMap::new();
+    for (name, range) in raw {
+        let io_type: HostIoType = name.parse().unwrap_or(HostIoType::Other);
+        if io_type == HostIoType::Other {
+            return Err(DiffError::InvalidCostTable(format!(
+                "unknown HostIO type '{}'",
+                name
+            )));
+        }
+
+        let bounds = [range.min, range.max];
+        if bounds.iter().flatten().any(|v| !v.is_finite() || *v < 0.0) {
+            return Err(DiffError::InvalidCostTable(format!(
+                "'{}' costs must be non-negative numbers",
+                name
+            )));
+        }
+        if let (Some(min), Some(max)) = (range.min, range.max) {
+            if min > max {
+                return Err(DiffError::InvalidCostTable(format!(
+                    "'{}' has min {} above max {}",
+                    name, min, max
+                )));
+            }
+        }
+        costs.insert(io_type, range);
+    }
+
+    Ok(CostTable { costs })
+}
+
+/// Flag HostIO types whose average gas per call is outside the table's range
+///
+/// # Arguments
+/// * `profile` - Profile carrying per-type HostIO gas
+/// * `table` - Expected per-call gas ranges
+///
+/// # Returns
+/// One `hostio_cost` insight per out-of-range type, most expensive first
+pub fn check_cost_table(profile: &Profile, table: &CostTable) -> Vec<AnalysisInsight> {
+    let mut flagged: Vec<(f64, AnalysisInsight)> = Vec::new();
+
+    for (io_type, range) in &table.costs {
+        let Some(avg_ink) = profile.hostio_summary.avg_gas_for_type(io_type.as_str()) else {
+            continue;
+        };
+        let avg = avg_ink / 10_000.0;
+
+        let (ratio, bound, relation) = match (range.min, range.max) {
+            (_, Some(max)) if avg > max => (cost_multiple(avg, max), max, "maximum"),
+            (Some(min), _) if avg < min => (cost_multiple(avg, min), min, "minimum"),
+            _ => continue,
+        };
+
+        let above = relation == "maximum";
+        let severity = match ratio {
+            _ if !above => InsightSeverity::Info,
+            r if r >= 3.0 => InsightSeverity::High,
+            r if r >= 1.5 => InsightSeverity::Medium,
+            _ => InsightSeverity::Low,
+        };
+
+        flagged.push((
+            avg,
+            AnalysisInsight {
+                category: "HostIO".to_string(),
+                description: format!(
+                    "{} averaged {:.1} gas per call, {} the expected {} of {}{}",
+                    io_type,
+                    avg,
+                    if ratio.is_finite() {
+                        format!("{:.1}x", ratio)
+                    } else {
+                        "above".to_string()
+                    },
+                    relation,
+                    bound,
+                    cost_hint(*io_type, above)
+                ),
+                severity,
+                tag: Some(COST_TABLE_TAG.to_string()),
+                stacks: stacks_ending_in(profile, *io_type),
+            },
+        ));
+    }
+
+    flagged.sort_by(|a, b| b.0.total_cmp(&a.0));
+    flagged.into_iter().map(|(_, insight)| insight).collect()
+}
+
+/// Profile stacks whose leaf frame is a call of `io_type`
+fn stacks_ending_in(profile: &Profile, io_type: HostIoType) -> Vec<String> {
+    profile
+        .all_stacks
+        .as_deref()
+        .unwrap_or_default()
+        .iter()
+        .filter(|s| {
+            let leaf = s.stack.rsplit(';').next().unwrap_or_default();
+            leaf.parse::<HostIoType>().ok() == Some(io_type)
+        })
+        .map(|s| s.stack.clone())
+        .collect()
+}
+
+/// Observed cost as a multiple of the bound (infinite for a zero bound)
+fn cost_multiple(observed: f64, bound: f64) -> f64 {
+    if bound > 0.0 {
+        observed / bound
+    } else {
+        f64::INFINITY
+    }
+}
+
+/// Likely cause of an out-of-range cost, where one is well known
+fn cost_hint(io_type: HostIoType, above: bool) -> &'static str {
+    match (io_type, above) {
+        (HostIoType::StorageLoad, true) => " (likely all cold reads)",
+        (HostIoType::StorageLoad, false) => " (likely warm or cached reads)",
+        (HostIoType::StorageFlush | HostIoType::StorageStore, true) => {
+            " (likely writes to fresh slots)"
+        }
+        (HostIoType::Call | HostIoType::StaticCall | HostIoType::DelegateCall, true) => {
+            " (likely cold callee accounts)"
+        }
+        _ => "",
+    }
+}
@@ -2,15 +2,42 @@
 //! Generates complete diff reports by comparing two profiles.
 
 use crate::parser::schema::Profile;
-use chrono::Utc;
+use crate::utils::config::generated_at;
 
+use super::analyzer::{analyze_profile_with, AnalyzerOptions};
 use super::normalizer::{
-    are_profiles_identical, calculate_gas_delta, calculate_hostio_delta, check_compatibility,
-    compare_hot_paths,
+    calculate_category_gas_changes, calculate_gas_delta, calculate_hostio_delta,
+    check_compatibility, compare_hot_paths, compare_hot_paths_normalized,
+    identical_profiles_warning, total_gas_source_warning,
 };
-use super::schema::{Deltas, DiffReport, DiffSummary, ProfileMetadata};
+use super::schema::{Deltas, DiffReport, DiffSummary, GasDelta, HotPathsDelta, ProfileMetadata};
 use super::DiffError;
 
+/// Options controlling how profiles are compared
+#[derive(Debug, Clone, Copy)]
+pub struct DiffOptions {
+    /// Canonicalize frame names (HostIO aliases, mangled symbols) before
+    /// matching hot paths. Disable for strict, exact-string comparisons.
+    pub normalize_frames: bool,
+
+    /// Thresholds for the heuristic insights added to the report
+    pub analyzer: AnalyzerOptions,
+
+    /// Only compare hot paths holding at least this share (percent) of the
+    /// total gas in the baseline or the target
+    pub min_path_percent: Option<f64>,
+}
+
+impl Default for DiffOptions {
+    fn default() -> Self {
+        Self {
+            normalize_frames: true,
+            analyzer: AnalyzerOptions::default(),
+            min_path_percent: None,
+        }
+    }
+}
+
 /// Generate a complete diff report comparing two profiles
 ///
 /// # Arguments
@@ -33,18 +60,37 @@ use super::DiffError;
 /// let diff = generate_diff(&baseline, &target)?;
 /// ```
 pub fn generate_diff(baseline: &Profile, target: &Profile) -> Result<DiffReport, DiffError> {
+    generate_diff_with_options(baseline, target, &DiffOptions::default())
+}
+
+/// Generate a diff report with explicit comparison options
+///
+/// # Arguments
+/// * `baseline` - The baseline profile to compare against
+/// * `target` - The target profile to compare
+/// * `options` - Comparison options (e.g. frame normalization)
+///
+/// # Errors
+/// * `DiffError::IncompatibleVersions` - If schema versions don't match
+pub fn generate_diff_with_options(
+    baseline: &Profile,
+    target: &Profile,
+    options: &DiffOptions,
+) -> Result<DiffReport, DiffError> {
     // Step 1: Check compatibility
     check_compatibility(baseline, target)?;
 
     // Step 2: Extract metadata
     let baseline_meta = ProfileMetadata {
         transaction_hash: baseline.transaction_hash.clone(),
+        label: baseline.label.clone(),
         total_gas: baseline.total_gas,
         generated_at: baseline.generated_at.clone(),
     };
 
     let target_meta = ProfileMetadata {
         transaction_hash: target.transaction_hash.clone(),
+        label: target.label.clone(),
         total_gas: target.total_gas,
         generated_at: target.generated_at.clone(),
     };
@@ -52,9 +98,22 @@ pub fn generate_diff(baseline: &Profile, target: &Profile) -> Result<DiffReport,
     // Step 3: Calculate all deltas
     let gas_delta = calculate_gas_delta(baseline.total_gas, target.total_gas);
 
-    let hostio_delta = calculate_hostio_delta(&baseline.hostio_summary, &target.hostio_summary);
+    let mut hostio_delta = calculate_hostio_delta(&baseline.hostio_summary, &target.hostio_summary);
+    hostio_delta.by_category = calculate_category_gas_changes(baseline, target);
 
-    let hot_paths_delta = compare_hot_paths(&baseline.hot_paths, &target.hot_paths);
+    let mut hot_paths_delta = if options.normalize_frames {
+        compare_hot_paths_normalized(&baseline.hot_paths, &target.hot_paths)
+    } else {
+        compare_hot_paths(&baseline.hot_paths, &target.hot_paths)
+    };
+    if let Some(min_percent) = options.min_path_percent {
+        retain_significant_paths(
+            &mut hot_paths_delta,
+            baseline.total_gas,
+            target.total_gas,
+            min_percent,
+        );
+    }
 
     let deltas = Deltas {
         gas: gas_delta,
@@ -63,30 +122,85 @@ pub fn generate_diff(baseline: &Profile, target: &Profile) -> Result<DiffReport,
     };
 
     // Step 4: Create summary (no thresholds yet)
-    let mut summary = DiffSummary {
+    let summary = DiffSummary {
         has_regressions: false,
         violation_count: 0,
         status: "PASSED".to_string(),
-        warning: None,
+        // Flag profiles that look identical (same tx re-run, or likely a mix-up)
+        warning: identical_profiles_warning(baseline, target)
+            .or_else(|| total_gas_source_warning(baseline, target)),
     };
 
-    // Check if profiles are identical
-    if are_profiles_identical(baseline, target) {
-        summary.warning = Some("Baseline and target profiles are identical".to_string());
-    }
-
     // Step 5: Heuristic Analysis (Option 4)
-    let insights = super::analyzer::analyze_profile(target);
+    let insights = analyze_profile_with(target, &options.analyzer);
 
     // Step 6: Build the report
     Ok(DiffReport {
         diff_version: "1.0.0".to_string(),
-        generated_at: Utc::now().to_rfc3339(),
+        generated_at: generated_at(),
         baseline: baseline_meta,
         target: target_meta,
         deltas,
+        hostio_only: false,
         threshold_violations: Vec::new(), // Will be populated by check_thresholds
         insights,
         summary,
     })
 }
+
+/// Drop hot paths below `min_percent` of total gas
+///
+/// **Private** - internal helper for generate_diff_with_options
+///
+/// A common path is kept if it reaches the share in either profile, so a
+/// path that grew from trivial to significant is still compared. Paths
+/// present in one profile only are measured against that profile's total.
+fn retain_significant_paths(
+    delta: &mut HotPathsDelta,
+    baseline_total: u64,
+    target_total: u64,
+    min_percent: f64,
+) {
+    let significant =
+        |gas: u64, total: u64| total > 0 && (gas as f64 / total as f64) * 100.0 >= min_percent;
+    delta.common_paths.retain(|path| {
+        significant(path.baseline_gas, baseline_total) || significant(path.target_gas, target_total)
+    });
+    delta
+        .baseline_only
+        .retain(|path| significant(path.gas, baseline_total));
+    delta
+        .target_only
+        .retain(|path| significant(path.gas, target_total));
+}
+
+/// Restrict a diff report to HostIO deltas only
+///
+/// Clears the gas and hot-path deltas so they are neither rendered nor
+/// checked against thresholds. Useful when gas is too noisy to compare
+/// (e.g. across node versions) but HostIO call counts are stable.
+///
+/// # Arguments
+/// * `report` - Diff report to restrict in place
+pub fn restrict_to_hostio(report: &mut DiffReport) {
+    report.hostio_only = true;
+    report.deltas.gas = GasDelta::default();
+    report.deltas.hot_paths = HotPathsDelta::default();
+}
+
+/// Remove insights whose tag is in `tags`
+///
+/// Lets teams silence heuristics for accepted patterns; suppressed insights
+/// are dropped from both the terminal output and the JSON report.
+///
+/// # Arguments
+/// * `report` - Diff report to filter in place
+/// * `tags` - Insight tags to drop (e.g. `storage_tax`)
+pub fn suppress_insights(report: &mut DiffReport, tags: &[String]) {
+    report.insights.retain(|insight| {
+        insight
+            .tag
+            .as_ref()
+            .is_none_or(|tag| !tags.iter().any(|t| t == tag))
+    });
+}
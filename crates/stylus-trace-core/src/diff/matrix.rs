@@ -0,0 +1,123 @@
+//! Pairwise total-gas comparison of several profiles.
+//!
+//! When several variants of a contract are captured, comparing them two at
+//! a time hides which one is cheapest overall. This module diffs every pair
+//! with `generate_diff` and lays the total gas changes out as a matrix.
+
+use super::engine::generate_diff;
+use super::DiffError;
+use crate::parser::schema::Profile;
+use crate::utils::config::PROFILE_GAS_UNIT;
+use serde::{Deserialize, Serialize};
+
+/// Total gas changes between every pair of profiles
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DiffMatrix {
+    /// Name of each profile, in input order
+    pub profiles: Vec<String>,
+
+    /// Total gas of each profile (in ink)
+    pub total_gas: Vec<u64>,
+
+    /// `percent_change[i][j]` is the total gas change from profile `i`
+    /// (baseline) to profile `j` (target); the diagonal is zero
+    pub percent_change: Vec<Vec<f64>>,
+}
+
+impl DiffMatrix {
+    /// Index of the profile with the lowest total gas (first one on ties)
+    pub fn cheapest(&self) -> Option<usize> {
+        self.total_gas
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, gas)| **gas)
+            .map(|(index, _)| index)
+    }
+}
+
+/// Diff every pair of profiles
+///
+/// **Public** - used by the `matrix` command
+///
+/// # Arguments
+/// * `profiles` - Profiles with the name shown for each in the matrix
+///
+/// # Returns
+/// An NxN matrix of total gas percent changes
+///
+/// # Errors
+/// * `DiffError::IncompatibleVersions` - If two profiles' schema versions differ
+pub fn build_diff_matrix(profiles: &[(String, Profile)]) -> Result<DiffMatrix, DiffError> {
+    let mut percent_change = vec![vec![0.0; profiles.len()]; profiles.len()];
+    for (i, (_, baseline)) in profiles.iter().enumerate() {
+        for (j, (_, target)) in profiles.iter().enumerate() {
+            if i != j {
+                percent_change[i][j] = generate_diff(baseline, target)?.deltas.gas.percent_change;
+            }
+        }
+    }
+
+    Ok(DiffMatrix {
+        profiles: profiles.iter().map(|(name, _)| name.clone()).collect(),
+        total_gas: profiles.iter().map(|(_, p)| p.total_gas).collect(),
+        percent_change,
+    })
+}
+
+/// Render the matrix as an ASCII table
+///
+/// **Public** - used by the `matrix` command
+///
+/// Rows are baselines and columns are targets, so a negative cell means the
+/// column's profile is cheaper than the row's. The cheapest profile is named
+/// below the table.
+pub fn render_diff_matrix(matrix: &DiffMatrix) -> String {
+    let corner = "baseline \\ target";
+    let name_width = matrix
+        .profiles
+        .iter()
+        .map(|name| name.chars().count())
+        .chain(std::iter::once(corner.len()))
+        .max()
+        .unwrap_or_default();
+    let cell_width = matrix
+        .profiles
+        .iter()
+        .map(|name| name.chars().count())
+        .max()
+        .unwrap_or_default()
+        .max(9);
+
+    let mut out = String::new();
+    out.push_str(&format!("{:<name_width$}", corner));
+    for name in &matrix.profiles {
+        out.push_str(&format!(" | {:>cell_width$}", name));
+    }
+    out.push('\n');
+    out.push_str(&"-".repeat(name_width));
+    for _ in &matrix.profiles {
+        out.push_str(&format!("-+-{}", "-".repeat(cell_width)));
+    }
+    out.push('\n');
+
+    for (i, row) in matrix.percent_change.iter().enumerate() {
+        out.push_str(&format!("{:<name_width$}", matrix.profiles[i]));
+        for (j, percent) in row.iter().enumerate() {
+            let cell = if i == j {
+                "-".to_string()
+            } else {
+                format!("{:+.2}%", percent)
+            };
+            out.push_str(&format!(" | {:>cell_width$}", cell));
+        }
+        out.push('\n');
+    }
+
+    if let Some(best) = matrix.cheapest() {
+        out.push_str(&format!(
+            "\nLowest total gas: {} ({} {})\n",
+            matrix.profiles[best], matrix.total_gas[best], PROFILE_GAS_UNIT
+        ));
+    }
+    out
+}
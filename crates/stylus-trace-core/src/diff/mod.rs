@@ -17,24 +17,42 @@
 //! ```
 
 mod analyzer;
+mod budget;
+mod cost_table;
 mod engine;
+mod matrix;
 mod normalizer;
 mod output;
+mod repeatability;
 mod schema;
 mod threshold;
 
 // Public API exports
-pub use analyzer::analyze_profile;
-pub use engine::generate_diff;
-pub use normalizer::{calculate_gas_delta, calculate_hostio_type_changes, safe_percentage};
-pub use output::render_terminal_diff;
+pub use analyzer::{analyze_profile, analyze_profile_with, AnalyzerOptions, INSIGHT_TAGS};
+pub use budget::{check_budget, load_budget, save_budget, GasBudget, BUDGET_METRIC};
+pub use cost_table::{check_cost_table, load_cost_table, CostRange, CostTable, COST_TABLE_TAG};
+pub use engine::{
+    generate_diff, generate_diff_with_options, restrict_to_hostio, suppress_insights, DiffOptions,
+};
+pub use matrix::{build_diff_matrix, render_diff_matrix, DiffMatrix};
+pub use normalizer::{
+    are_metrics_identical, are_profiles_identical, calculate_category_gas_changes,
+    calculate_gas_delta, calculate_hostio_type_changes, canonicalize_frame, canonicalize_stack,
+    identical_profiles_warning, safe_percentage, total_gas_source_warning,
+};
+pub use output::{
+    render_terminal_diff, render_terminal_diff_filtered, render_terminal_diff_summary,
+};
+pub use repeatability::{compare_captures, RepeatabilityReport, StackDifference, StepDivergence};
 pub use schema::{
-    Deltas, DiffReport, DiffSummary, GasDelta, HostIOTypeChange, HostIoDelta, HotPathComparison,
-    HotPathsDelta, ProfileMetadata, ThresholdViolation,
+    AnalysisInsight, CategoryGasChange, Deltas, DiffReport, DiffSummary, GasDelta,
+    HostIOTypeChange, HostIoDelta, HotPathComparison, HotPathsDelta, InsightSeverity,
+    ProfileMetadata, ThresholdViolation,
 };
 pub use threshold::{
-    check_gas_thresholds, check_thresholds, create_summary, load_thresholds, GasThresholds,
-    HostIOThresholds, HotPathThresholds, ThresholdConfig,
+    apply_env_thresholds, apply_threshold_overrides, check_gas_thresholds, check_thresholds,
+    create_summary, load_thresholds, GasThresholds, HostIOThresholds, HotPathThresholds,
+    ThresholdConfig,
 };
 
 pub use crate::utils::error::DiffError;
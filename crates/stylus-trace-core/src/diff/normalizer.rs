@@ -3,10 +3,16 @@
 //! Handles the math for computing differences between profiles,
 //! including edge cases like division by zero.
 
+use crate::aggregator::stack_builder::map_hostio_to_label;
+use crate::flamegraph::generator::NodeCategory;
 use crate::parser::schema::{HostIoSummary, HotPath, Profile};
+use crate::parser::HostIoType;
+use std::borrow::Cow;
 use std::collections::HashMap;
 
-use super::schema::{GasDelta, HostIOTypeChange, HostIoDelta, HotPathComparison, HotPathsDelta};
+use super::schema::{
+    CategoryGasChange, GasDelta, HostIOTypeChange, HostIoDelta, HotPathComparison, HotPathsDelta,
+};
 
 /// Calculate gas delta between two profiles
 ///
@@ -53,8 +59,9 @@ pub fn calculate_hostio_delta(
     let gas_percent_change = safe_percentage(gas_change, baseline_total_gas);
 
     // By-type changes
-    let by_type_changes =
+    let mut by_type_changes =
         calculate_hostio_type_changes(&baseline_summary.by_type, &target_summary.by_type);
+    apply_avg_gas_per_call(&mut by_type_changes, baseline_summary, target_summary);
 
     HostIoDelta {
         baseline_total_calls,
@@ -66,6 +73,92 @@ pub fn calculate_hostio_delta(
         target_total_gas,
         gas_change,
         gas_percent_change,
+        by_category: Vec::new(),
+    }
+}
+
+/// HostIO categories reported in the category rollup, in display order
+const HOSTIO_CATEGORIES: &[&str] = &["Storage", "Crypto", "Call", "Memory", "System"];
+
+/// Calculate the net gas change per HostIO category
+///
+/// Gas is attributed by the category of each stack's leaf frame (using the
+/// flamegraph `NodeCategory` mapping). Full stacks are used when the profile
+/// has them; otherwise the hot paths' self gas is used as an approximation.
+/// User code and root frames are not HostIO and are left out.
+///
+/// # Returns
+/// One entry per category present in either profile, largest change first
+pub fn calculate_category_gas_changes(
+    baseline: &Profile,
+    target: &Profile,
+) -> Vec<CategoryGasChange> {
+    let baseline_gas = category_gas(baseline);
+    let target_gas = category_gas(target);
+
+    let mut changes: Vec<CategoryGasChange> = HOSTIO_CATEGORIES
+        .iter()
+        .filter_map(|category| {
+            let baseline = *baseline_gas.get(category).unwrap_or(&0);
+            let target = *target_gas.get(category).unwrap_or(&0);
+            if baseline == 0 && target == 0 {
+                return None;
+            }
+
+            let gas_change = (target as i64) - (baseline as i64);
+            Some(CategoryGasChange {
+                category: category.to_string(),
+                baseline_gas: baseline,
+                target_gas: target,
+                gas_change,
+                percent_change: safe_percentage(gas_change, baseline),
+            })
+        })
+        .collect();
+
+    // Stable sort keeps display order for equal changes
+    changes.sort_by_key(|c| std::cmp::Reverse(c.gas_change.unsigned_abs()));
+    changes
+}
+
+/// Sum leaf-frame gas by HostIO category for one profile
+///
+/// **Private** - internal helper for calculate_category_gas_changes
+fn category_gas(profile: &Profile) -> HashMap<&'static str, u64> {
+    let weights: Vec<(&str, u64)> = match &profile.all_stacks {
+        Some(stacks) => stacks
+            .iter()
+            .map(|s| (s.stack.as_str(), s.weight))
+            .collect(),
+        None => profile
+            .hot_paths
+            .iter()
+            .map(|p| (p.stack.as_str(), p.gas))
+            .collect(),
+    };
+
+    let mut gas = HashMap::new();
+    for (stack, weight) in weights {
+        let leaf = stack.rsplit(';').next().unwrap_or(stack);
+        if let Some(category) = hostio_category_name(NodeCategory::from_name(leaf)) {
+            let total = gas.entry(category).or_insert(0u64);
+            *total = total.saturating_add(weight);
+        }
+    }
+    gas
+}
+
+/// Rollup name for a node category, or `None` for non-HostIO frames
+///
+/// **Private** - both storage tiers roll up into "Storage"
+fn hostio_category_name(category: NodeCategory) -> Option<&'static str> {
+    match category {
+        NodeCategory::StorageExpensive | NodeCategory::StorageNormal => Some("Storage"),
+        NodeCategory::Crypto => Some("Crypto"),
+        NodeCategory::Call => Some("Call"),
+        NodeCategory::Memory => Some("Memory"),
+        NodeCategory::System => Some("System"),
+        NodeCategory::UserCode | NodeCategory::Root => None,
     }
 }
 
@@ -97,6 +190,7 @@ pub fn calculate_hostio_type_changes(
                     baseline,
                     target,
                     delta,
+                    ..Default::default()
                 },
             );
         }
@@ -105,6 +199,35 @@ pub fn calculate_hostio_type_changes(
     changes
 }
 
+/// Fill in per-call gas averages for each HostIO type change
+///
+/// **Private** - internal helper for calculate_hostio_delta
+///
+/// Flags `per_call_regression` when the average gas per call rose while
+/// the call count fell, i.e. each call got more expensive rather than the
+/// type being called more often.
+fn apply_avg_gas_per_call(
+    changes: &mut HashMap<String, HostIOTypeChange>,
+    baseline_summary: &HostIoSummary,
+    target_summary: &HostIoSummary,
+) {
+    for (hostio_type, change) in changes.iter_mut() {
+        let baseline_avg = baseline_summary.avg_gas_for_type(hostio_type);
+        let target_avg = target_summary.avg_gas_for_type(hostio_type);
+
+        change.baseline_avg_gas_per_call = baseline_avg;
+        change.target_avg_gas_per_call = target_avg;
+
+        if let (Some(baseline_avg), Some(target_avg)) = (baseline_avg, target_avg) {
+            if baseline_avg > 0.0 {
+                change.avg_gas_per_call_percent_change =
+                    Some((target_avg - baseline_avg) / baseline_avg * 100.0);
+            }
+            change.per_call_regression = target_avg > baseline_avg && change.delta < 0;
+        }
+    }
+}
+
 /// Compare hot paths between two profiles
 ///
 /// # Arguments
@@ -165,6 +288,91 @@ pub fn compare_hot_paths(baseline_paths: &[HotPath], target_paths: &[HotPath]) -
     }
 }
 
+/// Compare hot paths after canonicalizing their frame names
+///
+/// Different tracers name the same operation differently (`SLOAD` vs
+/// `storage_load_bytes32`, mangled vs demangled symbols). Stacks are matched on
+/// their canonical form so semantically equal paths align; paths that collapse
+/// onto the same canonical stack have their gas summed.
+///
+/// # Arguments
+/// * `baseline_paths` - Hot paths from baseline
+/// * `target_paths` - Hot paths from target
+///
+/// # Returns
+/// HotPathsDelta whose common paths carry canonical stack names
+pub fn compare_hot_paths_normalized(
+    baseline_paths: &[HotPath],
+    target_paths: &[HotPath],
+) -> HotPathsDelta {
+    let baseline_map = gas_by_canonical_stack(baseline_paths);
+    let target_map = gas_by_canonical_stack(target_paths);
+
+    let mut common_paths = Vec::new();
+    for (stack, &baseline_gas) in &baseline_map {
+        if let Some(&target_gas) = target_map.get(stack) {
+            let gas_change = (target_gas as i64) - (baseline_gas as i64);
+            let percent_change = safe_percentage(gas_change, baseline_gas);
+
+            common_paths.push(HotPathComparison {
+                stack: stack.clone(),
+                baseline_gas,
+                target_gas,
+                gas_change,
+                percent_change,
+            });
+        }
+    }
+
+    let baseline_only: Vec<HotPath> = baseline_paths
+        .iter()
+        .filter(|hp| !target_map.contains_key(&canonicalize_stack(&hp.stack)))
+        .cloned()
+        .collect();
+
+    let target_only: Vec<HotPath> = target_paths
+        .iter()
+        .filter(|hp| !baseline_map.contains_key(&canonicalize_stack(&hp.stack)))
+        .cloned()
+        .collect();
+
+    HotPathsDelta {
+        common_paths,
+        baseline_only,
+        target_only,
+    }
+}
+
+/// Sum hot-path gas per canonical stack
+fn gas_by_canonical_stack(paths: &[HotPath]) -> HashMap<String, u64> {
+    let mut map = HashMap::new();
+    for hp in paths {
+        let gas = map.entry(canonicalize_stack(&hp.stack)).or_insert(0u64);
+        *gas = gas.saturating_add(hp.gas);
+    }
+    map
+}
+
+/// Canonicalize every frame of a `;`-separated stack
+pub fn canonicalize_stack(stack: &str) -> String {
+    stack
+        .split(';')
+        .map(canonicalize_frame)
+        .collect::<Vec<_>>()
+        .join(";")
+}
+
+/// Canonicalize a single frame name
+///
+/// HostIO aliases map to their `map_hostio_to_label` name; anything else is
+/// demangled (Rust, then C++) if it looks like a mangled symbol.
+pub fn canonicalize_frame(frame: &str) -> String {
+    match frame.parse::<HostIoType>() {
+        Ok(io_type) if io_type != HostIoType::Other => map_hostio_to_label(io_type).to_string(),
+        _ => addr2line::demangle_auto(Cow::Borrowed(frame), None).into_owned(),
+    }
+}
+
 /// Calculate percentage change safely (handles division by zero)
 ///
 /// # Arguments
@@ -210,7 +418,80 @@ pub fn check_compatibility(baseline: &Profile, target: &Profile) -> Result<(), s
 /// * `target` - Target profile
 ///
 /// # Returns
-/// true if the profiles have identical transaction hashes
+/// true if the profiles share a transaction hash and all metrics match
 pub fn are_profiles_identical(baseline: &Profile, target: &Profile) -> bool {
-    baseline.transaction_hash == target.transaction_hash && baseline.total_gas == target.total_gas
+    baseline.transaction_hash == target.transaction_hash && are_metrics_identical(baseline, target)
+}
+
+/// Check if two profiles have structurally identical metrics
+///
+/// Compares total gas, the HostIO summary and the hot paths (stack and gas),
+/// ignoring transaction hash and generation time.
+///
+/// # Arguments
+/// * `baseline` - Baseline profile
+/// * `target` - Target profile
+///
+/// # Returns
+/// true if every compared metric matches
+pub fn are_metrics_identical(baseline: &Profile, target: &Profile) -> bool {
+    let b_hostio = &baseline.hostio_summary;
+    let t_hostio = &target.hostio_summary;
+
+    baseline.total_gas == target.total_gas
+        && b_hostio.total_calls == t_hostio.total_calls
+        && b_hostio.total_hostio_gas == t_hostio.total_hostio_gas
+        && b_hostio.by_type == t_hostio.by_type
+        && hot_path_signature(&baseline.hot_paths) == hot_path_signature(&target.hot_paths)
+}
+
+/// Order-independent (stack, gas) view of hot paths for comparison
+fn hot_path_signature(paths: &[HotPath]) -> Vec<(&str, u64)> {
+    let mut signature: Vec<(&str, u64)> = paths.iter().map(|p| (p.stack.as_str(), p.gas)).collect();
+    signature.sort_unstable();
+    signature
+}
+
+/// Warn when only one profile's total gas was derived from step costs
+///
+/// A derived total leaves out gas the steps do not account for, so the total
+/// gas delta against a reported total is not meaningful.
+///
+/// # Returns
+/// Warning message for the diff summary, or None if both totals have the
+/// same provenance
+pub fn total_gas_source_warning(baseline: &Profile, target: &Profile) -> Option<String> {
+    if baseline.total_gas_source == target.total_gas_source {
+        return None;
+    }
+    Some(format!(
+        "Baseline total gas is {} but target total gas is {}; the total gas delta is not comparable",
+        baseline.total_gas_source, target.total_gas_source
+    ))
+}
+
+/// Describe why two profiles look identical, if they do
+///
+/// # Arguments
+/// * `baseline` - Baseline profile
+/// * `target` - Target profile
+///
+/// # Returns
+/// Warning message for the diff summary, or None if the profiles differ
+pub fn identical_profiles_warning(baseline: &Profile, target: &Profile) -> Option<String> {
+    if !are_metrics_identical(baseline, target) {
+        return None;
+    }
+
+    if baseline.transaction_hash == target.transaction_hash {
+        Some(
+            "Baseline and target profiles are identical (same transaction and metrics)".to_string(),
+        )
+    } else {
+        Some(
+            "Baseline and target are different transactions with identical metrics; \
+             check that the intended profiles are being compared"
+                .to_string(),
+        )
+    }
 }
@@ -3,24 +3,78 @@
 //! Provides human-readable summaries of profile comparisons
 //! with visual cues (emojis) for regressions and improvements.
 
-use super::schema::DiffReport;
+use super::schema::{DiffReport, HotPathComparison};
+use crate::utils::config::{DEFAULT_DIFF_PERCENT_PRECISION, DEFAULT_TOP_HOSTIO, PROFILE_GAS_UNIT};
+use crate::utils::text::{format_percent, format_percent_change, truncate_start};
 use colored::*;
 
 /// Render a human-readable summary of a diff report for the terminal
 pub fn render_terminal_diff(report: &DiffReport) -> String {
+    render_terminal_diff_filtered(
+        report,
+        false,
+        DEFAULT_TOP_HOSTIO,
+        DEFAULT_DIFF_PERCENT_PRECISION,
+    )
+}
+
+/// Render a terminal summary, optionally listing only regressions
+///
+/// With `only_regressions`, HostIO types and hot paths whose delta is zero or
+/// negative are omitted. `top_hostio` caps the HostIO type rows (`0` lists
+/// all of them) and every percentage has `precision` decimal places. The
+/// underlying report is left untouched.
+pub fn render_terminal_diff_filtered(
+    report: &DiffReport,
+    only_regressions: bool,
+    top_hostio: usize,
+    precision: usize,
+) -> String {
     let mut out = String::new();
 
     out.push_str(&render_header(report));
-    out.push_str(&render_gas_delta(report));
-    out.push_str(&render_hostio_summary(report));
-    out.push_str(&render_hostio_details(report));
-    out.push_str(&render_hot_paths(report));
+    if !report.hostio_only {
+        out.push_str(&render_gas_delta(report, precision));
+    }
+    out.push_str(&render_hostio_summary(report, precision));
+    out.push_str(&render_hostio_details(report, only_regressions, top_hostio));
+    out.push_str(&render_hostio_categories(
+        report,
+        only_regressions,
+        precision,
+    ));
+    out.push_str(&render_hostio_per_call(
+        report,
+        only_regressions,
+        top_hostio,
+        precision,
+    ));
+    if !report.hostio_only {
+        out.push_str(&render_hot_paths(report, only_regressions, precision));
+    }
     out.push_str(&render_insights(report));
     out.push_str(&render_status(report));
 
     out
 }
 
+/// Render only the header, gas delta, HostIO totals and status
+///
+/// For terse CI logs: the HostIO, hot-path and insight tables are skipped.
+/// Percentages have `precision` decimal places.
+pub fn render_terminal_diff_summary(report: &DiffReport, precision: usize) -> String {
+    let mut out = String::new();
+
+    out.push_str(&render_header(report));
+    if !report.hostio_only {
+        out.push_str(&render_gas_delta(report, precision));
+    }
+    out.push_str(&render_hostio_summary(report, precision));
+    out.push_str(&render_status(report));
+
+    out
+}
+
 fn render_insights(report: &DiffReport) -> String {
     let mut out = String::new();
 
@@ -52,116 +106,267 @@ fn render_header(report: &DiffReport) -> String {
     out.push_str("\n📊 ");
     out.push_str(&"Profile Comparison Summary".bold().to_string());
     out.push_str("\n---------------------------------------------------\n");
-    out.push_str(&format!("Baseline: {}\n", report.baseline.transaction_hash));
-    out.push_str(&format!("Target:   {}\n", report.target.transaction_hash));
+    out.push_str(&format!("Baseline: {}\n", report.baseline.display_name()));
+    out.push_str(&format!("Target:   {}\n", report.target.display_name()));
     out.push_str("---------------------------------------------------\n\n");
     out
 }
 
-fn render_gas_delta(report: &DiffReport) -> String {
+fn render_gas_delta(report: &DiffReport, precision: usize) -> String {
     let gas_delta = &report.deltas.gas;
     let symbol = get_delta_symbol(gas_delta.absolute_change);
     format!(
-        "{} Total Gas: {} -> {} ({:+.2}%)\n",
-        symbol, gas_delta.baseline, gas_delta.target, gas_delta.percent_change
+        "{} Total Gas: {} {unit} -> {} {unit} ({})\n",
+        symbol,
+        gas_delta.baseline,
+        gas_delta.target,
+        format_percent_change(gas_delta.percent_change, precision),
+        unit = PROFILE_GAS_UNIT
     )
 }
 
-fn render_hostio_summary(report: &DiffReport) -> String {
+fn render_hostio_summary(report: &DiffReport, precision: usize) -> String {
     let hostio_delta = &report.deltas.hostio;
     let symbol = get_delta_symbol(hostio_delta.total_calls_change);
     format!(
-        "{} HostIO Calls: {} -> {} ({:+.2}%)\n",
+        "{} HostIO Calls: {} calls -> {} calls ({})\n",
         symbol,
         hostio_delta.baseline_total_calls,
         hostio_delta.target_total_calls,
-        hostio_delta.total_calls_percent_change
+        format_percent_change(hostio_delta.total_calls_percent_change, precision)
     )
 }
 
-fn render_hostio_details(report: &DiffReport) -> String {
+fn render_hostio_details(report: &DiffReport, only_regressions: bool, top_n: usize) -> String {
     let mut out = String::new();
     let hostio_delta = &report.deltas.hostio;
 
-    if !hostio_delta.by_type_changes.is_empty() {
-        out.push_str("\nTop HostIO Changes:\n");
-        let mut changes: Vec<_> = hostio_delta.by_type_changes.iter().collect();
-        changes.sort_by(|a, b| b.1.delta.abs().cmp(&a.1.delta.abs()));
+    let mut changes: Vec<_> = hostio_delta
+        .by_type_changes
+        .iter()
+        .filter(|(_, c)| !only_regressions || c.delta > 0)
+        .collect();
+
+    if !changes.is_empty() {
+        out.push_str(if only_regressions {
+            "\nTop HostIO Increases:\n"
+        } else {
+            "\nTop HostIO Changes:\n"
+        });
+        changes.sort_by_key(|(_, c)| std::cmp::Reverse(c.delta.abs()));
 
-        for (hostio_type, change) in changes.iter().take(5) {
+        let shown = if top_n == 0 { changes.len() } else { top_n };
+        for (hostio_type, change) in changes.iter().take(shown) {
             let symbol = if change.delta > 0 { "📈" } else { "📉" };
             out.push_str(&format!(
-                "  {} {}: {} -> {} ({:+})\n",
+                "  {} {}: {} calls -> {} calls ({:+})\n",
                 symbol, hostio_type, change.baseline, change.target, change.delta
             ));
         }
+        if changes.len() > shown {
+            out.push_str(&format!(
+                "  ... {} more (use --top-hostio 0 to list all)\n",
+                changes.len() - shown
+            ));
+        }
     }
     out
 }
 
-fn render_hot_paths(report: &DiffReport) -> String {
+fn render_hostio_categories(
+    report: &DiffReport,
+    only_regressions: bool,
+    precision: usize,
+) -> String {
     let mut out = String::new();
-    let hot_paths = &report.deltas.hot_paths;
+    let categories: Vec<_> = report
+        .deltas
+        .hostio
+        .by_category
+        .iter()
+        .filter(|c| !only_regressions || c.gas_change > 0)
+        .collect();
 
-    if !hot_paths.common_paths.is_empty() {
-        out.push_str(&render_hot_path_comparison_table(report));
+    if !categories.is_empty() {
+        out.push_str("\nHostIO Gas by Category:\n");
+        for change in categories {
+            out.push_str(&format!(
+                "  {} {}: {} {unit} -> {} {unit} ({})\n",
+                get_delta_symbol(change.gas_change),
+                change.category,
+                change.baseline_gas,
+                change.target_gas,
+                format_percent_change(change.percent_change, precision),
+                unit = PROFILE_GAS_UNIT
+            ));
+        }
     }
     out
 }
 
-fn render_hot_path_comparison_table(report: &DiffReport) -> String {
+fn render_hostio_per_call(
+    report: &DiffReport,
+    only_regressions: bool,
+    top_n: usize,
+    precision: usize,
+) -> String {
+    let mut out = String::new();
+    let mut rows: Vec<_> = report
+        .deltas
+        .hostio
+        .by_type_changes
+        .iter()
+        .filter_map(|(hostio_type, c)| {
+            let baseline = c.baseline_avg_gas_per_call?;
+            let target = c.target_avg_gas_per_call?;
+            let percent = c.avg_gas_per_call_percent_change.unwrap_or(0.0);
+            (percent != 0.0 && (!only_regressions || percent > 0.0)).then_some((
+                hostio_type,
+                baseline,
+                target,
+                percent,
+                c.per_call_regression,
+            ))
+        })
+        .collect();
+
+    if !rows.is_empty() {
+        out.push_str("\nHostIO Gas per Call:\n");
+        rows.sort_by(|a, b| b.3.abs().total_cmp(&a.3.abs()).then_with(|| a.0.cmp(b.0)));
+
+        let shown = if top_n == 0 { rows.len() } else { top_n };
+        for (hostio_type, baseline, target, percent, per_call_regression) in rows.iter().take(shown)
+        {
+            let symbol = if *percent > 0.0 { "📈" } else { "📉" };
+            let note = if *per_call_regression {
+                format!(" {}", "each call costs more despite fewer calls".yellow())
+            } else {
+                String::new()
+            };
+            out.push_str(&format!(
+                "  {} {}: {:.1} {unit} -> {:.1} {unit} avg ({}){}\n",
+                symbol,
+                hostio_type,
+                baseline,
+                target,
+                format_percent_change(*percent, precision),
+                note,
+                unit = PROFILE_GAS_UNIT
+            ));
+        }
+        if rows.len() > shown {
+            out.push_str(&format!(
+                "  ... {} more (use --top-hostio 0 to list all)\n",
+                rows.len() - shown
+            ));
+        }
+    }
+    out
+}
+
+fn render_hot_paths(report: &DiffReport, only_regressions: bool, precision: usize) -> String {
+    let mut out = String::new();
+    let hp_changes: Vec<_> = report
+        .deltas
+        .hot_paths
+        .common_paths
+        .iter()
+        .filter(|hp| !only_regressions || hp.gas_change > 0)
+        .cloned()
+        .collect();
+
+    if !hp_changes.is_empty() {
+        out.push_str(&render_hot_path_comparison_table(hp_changes, precision));
+    }
+    out
+}
+
+/// Width of the change-magnitude bar column
+const CHANGE_BAR_WIDTH: usize = 10;
+
+fn render_hot_path_comparison_table(
+    mut hp_changes: Vec<HotPathComparison>,
+    precision: usize,
+) -> String {
     let mut out = String::new();
-    let hot_paths = &report.deltas.hot_paths;
 
     out.push_str("\n  🚀 HOT PATH COMPARISON\n");
     out.push_str(
-        "  ┏━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━┳━━━━━━━━━━━━━━┳━━━━━━━━━━━━━━┳━━━━━━━━━━━━┓\n",
+        "  ┏━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━┳━━━━━━━━━━━━━━┳━━━━━━━━━━━━━━┳━━━━━━━━━━━━┳━━━━━━━━━━━━┓\n",
     );
     out.push_str(&format!(
-        "  ┃ {:<38} ┃ {:^12} ┃ {:^12} ┃ {:^10} ┃\n",
-        "Execution Stack (Common Changes)", "BASELINE", "TARGET", "DELTA"
+        "  ┃ {:<38} ┃ {:^12} ┃ {:^12} ┃ {:^10} ┃ {:^10} ┃\n",
+        "Execution Stack (Common Changes)", "BASELINE GAS", "TARGET GAS", "DELTA", "CHANGE"
     ));
     out.push_str(
-        "  ┣━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━╋━━━━━━━━━━━━━━╋━━━━━━━━━━━━━━╋━━━━━━━━━━━━┫\n",
+        "  ┣━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━╋━━━━━━━━━━━━━━╋━━━━━━━━━━━━━━╋━━━━━━━━━━━━╋━━━━━━━━━━━━┫\n",
     );
 
-    let mut hp_changes = hot_paths.common_paths.clone();
-    hp_changes.sort_by(|a, b| b.gas_change.abs().cmp(&a.gas_change.abs()));
+    hp_changes.sort_by_key(|hp| std::cmp::Reverse(hp.gas_change.abs()));
+    hp_changes.truncate(10);
 
-    for hp in hp_changes.iter().take(10) {
-        let delta_color = if hp.gas_change > 0 {
-            "\x1b[31;1m" // Bold Red
-        } else if hp.gas_change < 0 {
-            "\x1b[32;1m" // Bold Green
-        } else {
-            "\x1b[0m" // Reset
-        };
-        let reset = "\x1b[0m";
+    // Bars are scaled against the largest change shown
+    let max_percent = hp_changes
+        .iter()
+        .map(|hp| hp.percent_change.abs())
+        .fold(0.0, f64::max);
 
+    for hp in &hp_changes {
         let display_stack = shorten_stack(&hp.stack);
-        let display_stack_fixed = if display_stack.len() > 38 {
-            format!("...{}", &display_stack[display_stack.len() - 35..])
-        } else {
-            format!("{:<38}", display_stack)
-        };
+        let display_stack_fixed = format!("{:<38}", truncate_start(&display_stack, 38));
 
         // Scale to Gas (ink / 10,000) with float precision
         let baseline_gas = hp.baseline_gas as f64 / 10_000.0;
         let target_gas = hp.target_gas as f64 / 10_000.0;
 
+        let delta = format!("{:>10}", format_percent(hp.percent_change, precision));
+        let bar = format!(
+            "{:<width$}",
+            change_bar(hp.percent_change, max_percent),
+            width = CHANGE_BAR_WIDTH
+        );
+
         out.push_str(&format!(
-            "  ┃ {} ┃ {:>12.1} ┃ {:>12.1} ┃ {}{:>9.2}%{} ┃\n",
-            display_stack_fixed, baseline_gas, target_gas, delta_color, hp.percent_change, reset
+            "  ┃ {} ┃ {:>12.1} ┃ {:>12.1} ┃ {} ┃ {} ┃\n",
+            display_stack_fixed,
+            baseline_gas,
+            target_gas,
+            color_by_change(&delta, hp.gas_change),
+            color_by_change(&bar, hp.gas_change)
         ));
     }
 
     out.push_str(
-        "  ┗━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━┻━━━━━━━━━━━━━━┻━━━━━━━━━━━━━━┻━━━━━━━━━━━━┛\n",
+        "  ┗━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━┻━━━━━━━━━━━━━━┻━━━━━━━━━━━━━━┻━━━━━━━━━━━━┻━━━━━━━━━━━━┛\n",
     );
 
     out
 }
 
+/// Build a bar whose length reflects `percent` relative to `max_percent`
+///
+/// Any non-zero change gets at least one block so it stays visible.
+fn change_bar(percent: f64, max_percent: f64) -> String {
+    if percent == 0.0 || max_percent <= 0.0 {
+        return String::new();
+    }
+
+    let ratio = (percent.abs() / max_percent).min(1.0);
+    let width = ((ratio * CHANGE_BAR_WIDTH as f64).round() as usize).max(1);
+    "█".repeat(width)
+}
+
+/// Color text red for increases and green for decreases
+fn color_by_change(text: &str, change: i64) -> ColoredString {
+    if change > 0 {
+        text.red().bold()
+    } else if change < 0 {
+        text.green().bold()
+    } else {
+        text.normal()
+    }
+}
+
 fn render_status(report: &DiffReport) -> String {
     let mut out = String::new();
     out.push_str("\n---------------------------------------------------\n");
@@ -182,6 +387,10 @@ fn render_status(report: &DiffReport) -> String {
     };
     out.push_str(&status_msg.to_string());
     out.push('\n');
+    if let Some(warning) = &report.summary.warning {
+        out.push_str(&format!("⚠️  {}", warning).yellow().to_string());
+        out.push('\n');
+    }
     out
 }
 
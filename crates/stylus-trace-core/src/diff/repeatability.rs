@@ -0,0 +1,141 @@
+//! Repeatability checks between two captures of the same transaction.
+//!
+//! Re-tracing a transaction should be deterministic. When two captures
+//! differ, the node or the tracer is adding noise, and gas thresholds
+//! tighter than that noise will flake. This module compares two parsed
+//! traces step by step and stack by stack to show where they diverge.
+
+use crate::aggregator::stack_builder::build_collapsed_stacks;
+use crate::parser::stylus_trace::{ExecutionStep, ParsedTrace};
+use std::collections::BTreeMap;
+
+/// First execution step at which two captures disagree
+#[derive(Debug, Clone, PartialEq)]
+pub struct StepDivergence {
+    /// Index of the step in the execution trace
+    pub index: usize,
+
+    /// Description of the step in the first capture (`None` past its end)
+    pub first: Option<String>,
+
+    /// Description of the step in the second capture (`None` past its end)
+    pub second: Option<String>,
+}
+
+/// A collapsed stack whose weight differs between captures
+#[derive(Debug, Clone, PartialEq)]
+pub struct StackDifference {
+    pub stack: String,
+    pub first_weight: u64,
+    pub second_weight: u64,
+}
+
+/// Result of comparing two captures of one transaction
+#[derive(Debug, Clone, PartialEq)]
+pub struct RepeatabilityReport {
+    /// Total gas of each capture (in ink)
+    pub first_total_gas: u64,
+    pub second_total_gas: u64,
+
+    /// Number of execution steps in each capture
+    pub first_steps: usize,
+    pub second_steps: usize,
+
+    /// First step that differs, if any
+    pub divergence: Option<StepDivergence>,
+
+    /// Stacks whose weight differs, largest difference first
+    pub stack_differences: Vec<StackDifference>,
+}
+
+impl RepeatabilityReport {
+    /// Whether the two captures are identical
+    pub fn is_identical(&self) -> bool {
+        self.first_total_gas == self.second_total_gas
+            && self.divergence.is_none()
+            && self.stack_differences.is_empty()
+    }
+
+    /// Total gas difference relative to the first capture, in percent
+    pub fn gas_variance_percent(&self) -> f64 {
+        let change = self.second_total_gas as f64 - self.first_total_gas as f64;
+        if self.first_total_gas == 0 {
+            if change == 0.0 {
+                0.0
+            } else {
+                100.0
+            }
+        } else {
+            change.abs() / self.first_total_gas as f64 * 100.0
+        }
+    }
+}
+
+/// Compare two captures of the same transaction
+///
+/// **Public** - used by capture for `--repeatability`
+///
+/// # Arguments
+/// * `first` - Trace from the first capture
+/// * `second` - Trace from the second capture
+///
+/// # Returns
+/// Gas totals, the first diverging step and the differing stacks
+pub fn compare_captures(first: &ParsedTrace, second: &ParsedTrace) -> RepeatabilityReport {
+    let first_steps = &first.execution_steps;
+    let second_steps = &second.execution_steps;
+
+    let divergence = (0..first_steps.len().max(second_steps.len()))
+        .find(|&i| match (first_steps.get(i), second_steps.get(i)) {
+            (Some(a), Some(b)) => describe_step(a) != describe_step(b),
+            _ => true,
+        })
+        .map(|index| StepDivergence {
+            index,
+            first: first_steps.get(index).map(describe_step),
+            second: second_steps.get(index).map(describe_step),
+        });
+
+    let mut weights: BTreeMap<String, (u64, u64)> = BTreeMap::new();
+    for stack in build_collapsed_stacks(first) {
+        weights.entry(stack.stack).or_default().0 = stack.weight;
+    }
+    for stack in build_collapsed_stacks(second) {
+        weights.entry(stack.stack).or_default().1 = stack.weight;
+    }
+
+    let mut stack_differences: Vec<StackDifference> = weights
+        .into_iter()
+        .filter(|(_, (a, b))| a != b)
+        .map(|(stack, (first_weight, second_weight))| StackDifference {
+            stack,
+            first_weight,
+            second_weight,
+        })
+        .collect();
+    stack_differences.sort_by_key(|d| std::cmp::Reverse(d.first_weight.abs_diff(d.second_weight)));
+
+    RepeatabilityReport {
+        first_total_gas: first.total_gas_used,
+        second_total_gas: second.total_gas_used,
+        first_steps: first_steps.len(),
+        second_steps: second_steps.len(),
+        divergence,
+        stack_differences,
+    }
+}
+
+/// One-line description of a step covering every field that is compared
+///
+/// **Private** - internal helper for compare_captures
+fn describe_step(step: &ExecutionStep) -> String {
+    let name = step
+        .function
+        .as_deref()
+        .or(step.op.as_deref())
+        .unwrap_or("unknown");
+    format!(
+        "{} (depth {}, pc {}, cost {})",
+        name, step.depth, step.pc, step.gas_cost
+    )
+}
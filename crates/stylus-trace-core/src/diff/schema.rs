@@ -23,11 +23,15 @@ pub struct DiffReport {
     /// Calculated deltas between profiles
     pub deltas: Deltas,
 
+    /// Whether the report is restricted to HostIO deltas (gas and hot paths skipped)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub hostio_only: bool,
+
     /// List of threshold violations (if any)
     pub threshold_violations: Vec<ThresholdViolation>,
 
-    /// Analysis insights (Option 4: Heuristics)
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    /// Analysis insights (Option 4: Heuristics), always serialized (possibly empty)
+    #[serde(default)]
     pub insights: Vec<AnalysisInsight>,
 
     /// Summary of diff results
@@ -40,6 +44,10 @@ pub struct ProfileMetadata {
     /// Transaction hash
     pub transaction_hash: String,
 
+    /// Profile label, if one was given at capture time
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+
     /// Total gas used
     pub total_gas: u64,
 
@@ -47,6 +55,13 @@ pub struct ProfileMetadata {
     pub generated_at: String,
 }
 
+impl ProfileMetadata {
+    /// Name to show for this profile: the label if set, else the transaction hash
+    pub fn display_name(&self) -> &str {
+        self.label.as_deref().unwrap_or(&self.transaction_hash)
+    }
+}
+
 /// All calculated deltas
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct Deltas {
@@ -105,6 +120,29 @@ pub struct HostIoDelta {
 
     /// Percentage change in HostIO gas
     pub gas_percent_change: f64,
+
+    /// Net gas change per HostIO category (Storage, Crypto, Call, Memory, System)
+    #[serde(default)]
+    pub by_category: Vec<CategoryGasChange>,
+}
+
+/// Net gas change for a HostIO category
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct CategoryGasChange {
+    /// Category name (e.g. "Storage")
+    pub category: String,
+
+    /// Gas attributed to the category in baseline
+    pub baseline_gas: u64,
+
+    /// Gas attributed to the category in target
+    pub target_gas: u64,
+
+    /// Gas change (target - baseline)
+    pub gas_change: i64,
+
+    /// Percentage change
+    pub percent_change: f64,
 }
 
 /// Change in a specific HostIO type
@@ -118,6 +156,22 @@ pub struct HostIOTypeChange {
 
     /// Delta (target - baseline)
     pub delta: i64,
+
+    /// Average gas per call in baseline
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub baseline_avg_gas_per_call: Option<f64>,
+
+    /// Average gas per call in target
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target_avg_gas_per_call: Option<f64>,
+
+    /// Percentage change of the average gas per call
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub avg_gas_per_call_percent_change: Option<f64>,
+
+    /// Each call got more expensive even though the type was called less often
+    #[serde(default)]
+    pub per_call_regression: bool,
 }
 
 /// Hot paths comparison
@@ -199,6 +253,10 @@ pub struct AnalysisInsight {
 
     /// Optional tag for grouping
     pub tag: Option<String>,
+
+    /// Collapsed stacks the insight refers to (used to mark flamegraph frames)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub stacks: Vec<String>,
 }
 
 /// Severity level for analysis insights
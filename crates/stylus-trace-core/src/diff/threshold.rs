@@ -10,6 +10,10 @@ use std::path::Path;
 
 use super::schema::{DiffReport, DiffSummary, ThresholdViolation};
 use super::DiffError;
+use crate::utils::config::{
+    ENV_GAS_MAX_INCREASE_ABSOLUTE, ENV_GAS_MAX_INCREASE_PERCENT, ENV_HOSTIO_MAX_CALLS_PERCENT,
+    ENV_HOT_PATH_WARN_PERCENT,
+};
 
 /// Complete threshold configuration
 #[derive(Debug, Clone, Default, Deserialize, Serialize)]
@@ -35,6 +39,11 @@ pub struct GasThresholds {
 
     /// Maximum allowed absolute gas increase
     pub max_increase_absolute: Option<u64>,
+
+    /// Absolute increases below this amount are treated as run-to-run noise
+    /// and never reported, whatever their percentage
+    #[serde(default)]
+    pub noise_floor_gas: Option<u64>,
 }
 
 /// HostIO-related thresholds
@@ -76,6 +85,67 @@ pub fn load_thresholds(path: impl AsRef<Path>) -> Result<ThresholdConfig, DiffEr
     Ok(config)
 }
 
+/// Apply threshold overrides from `STYLUS_*` environment variables
+///
+/// # Arguments
+/// * `config` - Configuration to update in place (usually loaded from TOML)
+///
+/// # Errors
+/// * `DiffError::InvalidThresholds` - If a variable is set but not a valid number
+pub fn apply_env_thresholds(config: &mut ThresholdConfig) -> Result<(), DiffError> {
+    apply_threshold_overrides(config, |key| std::env::var(key).ok())
+}
+
+/// Apply threshold overrides from an arbitrary key/value source
+///
+/// Unset keys leave the existing value untouched.
+///
+/// # Arguments
+/// * `config` - Configuration to update in place
+/// * `lookup` - Returns the raw value for a variable name, if set
+///
+/// # Errors
+/// * `DiffError::InvalidThresholds` - If a value is not a valid number
+pub fn apply_threshold_overrides(
+    config: &mut ThresholdConfig,
+    lookup: impl Fn(&str) -> Option<String>,
+) -> Result<(), DiffError> {
+    if let Some(percent) = parse_override(&lookup, ENV_GAS_MAX_INCREASE_PERCENT)? {
+        config.gas.max_increase_percent = Some(percent);
+    }
+
+    if let Some(absolute) = parse_override(&lookup, ENV_GAS_MAX_INCREASE_ABSOLUTE)? {
+        config.gas.max_increase_absolute = Some(absolute);
+    }
+
+    if let Some(percent) = parse_override(&lookup, ENV_HOSTIO_MAX_CALLS_PERCENT)? {
+        config.hostio.max_total_calls_increase_percent = Some(percent);
+    }
+
+    if let Some(percent) = parse_override(&lookup, ENV_HOT_PATH_WARN_PERCENT)? {
+        config.hot_paths = Some(HotPathThresholds {
+            warn_individual_increase_percent: Some(percent),
+        });
+    }
+
+    Ok(())
+}
+
+/// Parse a single override value, if present
+fn parse_override<T: std::str::FromStr>(
+    lookup: &impl Fn(&str) -> Option<String>,
+    key: &str,
+) -> Result<Option<T>, DiffError> {
+    let Some(raw) = lookup(key) else {
+        return Ok(None);
+    };
+
+    raw.trim()
+        .parse()
+        .map(Some)
+        .map_err(|_| DiffError::InvalidThresholds(format!("{}={} is not a valid number", key, raw)))
+}
+
 /// Check a diff report against thresholds and update violations
 ///
 /// # Arguments
@@ -97,20 +167,24 @@ pub fn check_thresholds(
 ) -> Vec<ThresholdViolation> {
     let mut violations = Vec::new();
 
-    // Check gas thresholds
-    check_gas_thresholds(&diff.deltas.gas, &config.gas, &mut violations);
+    // Check gas thresholds (skipped for HostIO-only reports)
+    if !diff.hostio_only {
+        check_gas_thresholds(&diff.deltas.gas, &config.gas, &mut violations);
+    }
 
     // Check HostIO thresholds
     check_hostio_thresholds(&diff.deltas.hostio, &config.hostio, &mut violations);
 
-    // Check hot path thresholds
-    if let Some(hp_thresholds) = &config.hot_paths {
+    // Check hot path thresholds (skipped for HostIO-only reports)
+    if let Some(hp_thresholds) = config.hot_paths.as_ref().filter(|_| !diff.hostio_only) {
         check_hot_path_thresholds(&diff.deltas.hot_paths, hp_thresholds, &mut violations);
     }
 
-    // Update diff report
+    // Update diff report, keeping the comparison warning set by the engine
     diff.threshold_violations = violations.clone();
+    let warning = diff.summary.warning.take();
     diff.summary = create_summary(&violations);
+    diff.summary.warning = warning;
 
     violations
 }
@@ -121,6 +195,13 @@ pub fn check_gas_thresholds(
     thresholds: &GasThresholds,
     violations: &mut Vec<ThresholdViolation>,
 ) {
+    // Ignore changes that stay within measurement noise
+    if let Some(floor) = thresholds.noise_floor_gas {
+        if gas_delta.absolute_change < 0 || (gas_delta.absolute_change as u64) < floor {
+            return;
+        }
+    }
+
     // Check percentage increase
     if let Some(max_percent) = thresholds.max_increase_percent {
         if gas_delta.percent_change > max_percent {
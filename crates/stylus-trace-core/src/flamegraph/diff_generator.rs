@@ -6,8 +6,10 @@
 //! - Gray/Yellow: No change
 
 use crate::aggregator::stack_builder::CollapsedStack;
-use crate::flamegraph::generator::{get_truncated_name, FlamegraphConfig};
+use crate::flamegraph::generator::{escape_xml, get_truncated_name, FlamegraphConfig};
+use crate::utils::config::{DEFAULT_DIFF_PERCENT_PRECISION, PROFILE_GAS_UNIT};
 use crate::utils::error::FlamegraphError;
+use crate::utils::text::format_percent_change;
 use log::info;
 use std::collections::HashMap;
 
@@ -30,7 +32,7 @@ impl DiffNode {
     }
 
     fn insert_baseline(&mut self, stack: &[&str], value: u64) {
-        self.baseline_value += value;
+        self.baseline_value = self.baseline_value.saturating_add(value);
         if let Some((head, tail)) = stack.split_first() {
             let child = self
                 .children
@@ -41,7 +43,7 @@ impl DiffNode {
     }
 
     fn insert_target(&mut self, stack: &[&str], value: u64) {
-        self.target_value += value;
+        self.target_value = self.target_value.saturating_add(value);
         if let Some((head, tail)) = stack.split_first() {
             let child = self
                 .children
@@ -85,6 +87,12 @@ pub fn generate_diff_flamegraph(
         root.insert_target(&parts, stack.weight);
     }
 
+    if root.baseline_value == 0 && root.target_value == 0 {
+        return Err(FlamegraphError::NoGasAttributed {
+            stacks: baseline_stacks.len() + target_stacks.len(),
+        });
+    }
+
     let max_depth = calculate_max_depth(&root);
 
     // 2. Render SVG
@@ -107,13 +115,16 @@ pub fn generate_diff_flamegraph(
     svg.push_str(&format!(
         r#"<text x="{}" y="25" font-size="18" text-anchor="middle" font-weight="bold">{} (Diff)</text>"#,
         width / 2,
-        config.title
+        escape_xml(&config.title)
     ));
 
     let mut ctx = DiffRenderContext {
         output: &mut svg,
         line_height: height_per_level,
         graph_height,
+        precision: config
+            .percent_precision
+            .unwrap_or(DEFAULT_DIFF_PERCENT_PRECISION),
     };
 
     render_diff_node(&root, 0, 0.0, width as f64, &mut ctx);
@@ -140,6 +151,8 @@ struct DiffRenderContext<'a> {
     output: &'a mut String,
     line_height: usize,
     graph_height: usize,
+    /// Decimal places for tooltip percent changes
+    precision: usize,
 }
 
 fn render_diff_node(node: &DiffNode, level: usize, x: f64, w: f64, ctx: &mut DiffRenderContext) {
@@ -153,19 +166,23 @@ fn render_diff_node(node: &DiffNode, level: usize, x: f64, w: f64, ctx: &mut Dif
         - (ctx.line_height as f64)
         + 40.0;
 
-    let tooltip = format_diff_tooltip(node);
+    let tooltip = format_diff_tooltip(node, ctx.precision);
 
     ctx.output.push_str(&format!(
         r#"<rect x="{:.2}" y="{:.2}" width="{:.2}" height="{}" fill="{}" stroke="white" stroke-width="0.5" class="func">"#,
         x, y, w, ctx.line_height, color
     ));
-    ctx.output
-        .push_str(&format!(r#"<title>{}</title></rect>"#, tooltip));
+    ctx.output.push_str(&format!(
+        r#"<title>{}</title></rect>"#,
+        escape_xml(&tooltip)
+    ));
 
     if let Some(display_name) = get_truncated_name(&node.name, w) {
         ctx.output.push_str(&format!(
             r#"<text x="{:.2}" y="{:.2}" dx="4" dy="14" font-size="12" fill="black" style="pointer-events:none">{}</text>"#,
-            x, y, display_name
+            x,
+            y,
+            escape_xml(&display_name)
         ));
     }
 
@@ -175,7 +192,8 @@ fn render_diff_node(node: &DiffNode, level: usize, x: f64, w: f64, ctx: &mut Dif
     children_vec.sort_by(|a, b| {
         let a_max = a.target_value.max(a.baseline_value);
         let b_max = b.target_value.max(b.baseline_value);
-        b_max.cmp(&a_max)
+        // Break ties by name so output is reproducible
+        b_max.cmp(&a_max).then_with(|| a.name.cmp(&b.name))
     });
 
     let parent_max = node.target_value.max(node.baseline_value);
@@ -190,16 +208,27 @@ fn render_diff_node(node: &DiffNode, level: usize, x: f64, w: f64, ctx: &mut Dif
     }
 }
 
+/// Frames only present in the target; purple so they don't read as a regression
+const NEW_FRAME_COLOR: &str = "rgb(170, 110, 230)";
+
+/// Frames only present in the baseline; blue so they don't read as an improvement
+const REMOVED_FRAME_COLOR: &str = "rgb(90, 160, 240)";
+
+// Legend swatches for the regression (red) and improvement (green) scales
+const REGRESSION_COLOR: &str = "rgb(255, 100, 100)";
+const IMPROVEMENT_COLOR: &str = "rgb(100, 255, 100)";
+const STABLE_COLOR: &str = "rgb(240, 240, 240)";
+
 fn get_diff_color(baseline: u64, target: u64) -> String {
     if baseline == 0 && target == 0 {
         return "rgb(200, 200, 200)".into();
     }
     if baseline == 0 {
-        return "rgb(255, 100, 100)".into();
-    } // New code (Red)
+        return NEW_FRAME_COLOR.into();
+    }
     if target == 0 {
-        return "rgb(100, 255, 100)".into();
-    } // Removed code (Green)
+        return REMOVED_FRAME_COLOR.into();
+    }
 
     let change = (target as f64 - baseline as f64) / baseline as f64;
 
@@ -212,35 +241,43 @@ fn get_diff_color(baseline: u64, target: u64) -> String {
         let intensity = (change.abs() * 100.0).min(155.0) as u8;
         format!("rgb({}, 255, {})", 200 - intensity, 200 - intensity)
     } else {
-        "rgb(240, 240, 240)".into() // Stable
+        STABLE_COLOR.into()
     }
 }
 
-fn format_diff_tooltip(node: &DiffNode) -> String {
+fn format_diff_tooltip(node: &DiffNode, precision: usize) -> String {
     let baseline = node.baseline_value;
     let target = node.target_value;
 
+    let unit = PROFILE_GAS_UNIT;
+
     if baseline == 0 {
-        return format!("{}: {} (NEW)", node.name, target);
+        return format!("{}: {} {} (NEW)", node.name, target, unit);
     }
     if target == 0 {
-        return format!("{}: {} (REMOVED)", node.name, baseline);
+        return format!("{}: {} {} (REMOVED)", node.name, baseline, unit);
     }
 
     let diff = target as i64 - baseline as i64;
     let percent = (diff as f64 / baseline as f64) * 100.0;
 
     format!(
-        "{}: {} -> {} ({:+.2}%)",
-        node.name, baseline, target, percent
+        "{}: {} {unit} -> {} {unit} ({})",
+        node.name,
+        baseline,
+        target,
+        format_percent_change(percent, precision),
+        unit = unit
     )
 }
 
 fn render_diff_legend(out: &mut String, y: usize) {
     let items = [
-        ("Regression", "rgb(255, 100, 100)"),
-        ("Improvement", "rgb(100, 255, 100)"),
-        ("No Change", "rgb(240, 240, 240)"),
+        ("Regression", REGRESSION_COLOR),
+        ("Improvement", IMPROVEMENT_COLOR),
+        ("No Change", STABLE_COLOR),
+        ("New", NEW_FRAME_COLOR),
+        ("Removed", REMOVED_FRAME_COLOR),
     ];
 
     for (i, (label, color)) in items.iter().enumerate() {
@@ -6,9 +6,13 @@
 //! - Simplified dependency tree
 
 use crate::aggregator::stack_builder::CollapsedStack;
+use crate::output::svg::{SvgWatermark, WATERMARK_HEIGHT};
+use crate::parser::hostio::HostIoStats;
 use crate::parser::source_map::SourceMapper;
 use crate::parser::HostIoType;
+use crate::utils::config::DEFAULT_SUMMARY_PERCENT_PRECISION;
 use crate::utils::error::FlamegraphError;
+use crate::utils::text::{format_percent, format_percent_change, truncate_end, truncate_start};
 use log::info;
 use std::collections::HashMap;
 
@@ -25,9 +29,19 @@ pub enum NodeCategory {
     Root,
 }
 
+/// HostIO types colored as expensive (crimson) unless configured otherwise
+pub const DEFAULT_EXPENSIVE_HOSTIO: &[HostIoType] =
+    &[HostIoType::StorageStore, HostIoType::StorageFlush];
+
 impl NodeCategory {
     /// Classify a node based on its name (used as fallback or for user code)
     pub fn from_name(name: &str) -> Self {
+        Self::from_name_with(name, DEFAULT_EXPENSIVE_HOSTIO)
+    }
+
+    /// Classify a node by name, treating the `expensive` HostIO types as
+    /// `StorageExpensive`
+    pub fn from_name_with(name: &str, expensive: &[HostIoType]) -> Self {
         if name == "root" {
             return Self::Root;
         }
@@ -35,7 +49,7 @@ impl NodeCategory {
         // Try structured signal first (HostIoType enum)
         let io_type = name.parse::<HostIoType>().unwrap_or(HostIoType::Other);
         if io_type != HostIoType::Other {
-            return Self::from_hostio(io_type);
+            return Self::from_hostio_with(io_type, expensive);
         }
 
         // Fallback for non-HostIO system components or user code
@@ -48,9 +62,20 @@ impl NodeCategory {
 
     /// Map structured HostIoType to a visual category
     pub fn from_hostio(io_type: HostIoType) -> Self {
+        Self::from_hostio_with(io_type, DEFAULT_EXPENSIVE_HOSTIO)
+    }
+
+    /// Map a HostIoType to a visual category, treating the `expensive` types
+    /// as `StorageExpensive`
+    pub fn from_hostio_with(io_type: HostIoType, expensive: &[HostIoType]) -> Self {
+        if expensive.contains(&io_type) {
+            return Self::StorageExpensive;
+        }
         match io_type {
-            HostIoType::StorageStore | HostIoType::StorageFlush => Self::StorageExpensive,
-            HostIoType::StorageLoad | HostIoType::StorageCache => Self::StorageNormal,
+            HostIoType::StorageStore
+            | HostIoType::StorageFlush
+            | HostIoType::StorageLoad
+            | HostIoType::StorageCache => Self::StorageNormal,
             HostIoType::NativeKeccak256 => Self::Crypto,
             HostIoType::ReadArgs | HostIoType::WriteResult => Self::Memory,
             HostIoType::Call
@@ -69,12 +94,34 @@ impl NodeCategory {
     }
 }
 
+/// How flamegraph frames are colored
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum ColorMode {
+    /// Color by operation category only
+    #[default]
+    Category,
+    /// Category color faded by the frame's self gas relative to the hottest frame
+    Heat,
+}
+
 /// Flamegraph configuration
 #[derive(Debug, Clone)]
 pub struct FlamegraphConfig {
     pub title: String,
     pub width: usize,
     pub ink: bool,
+    pub color_by: ColorMode,
+    /// Append each frame's gas (or ink) to its label when the frame is wide enough
+    pub label_gas: bool,
+    /// Notes keyed by collapsed stack; the matching frames get a ⚠ badge
+    /// and the notes in their tooltip
+    pub annotations: HashMap<String, Vec<String>>,
+    /// HostIO types colored as expensive (crimson)
+    pub expensive_hostio: Vec<HostIoType>,
+    /// Provenance embedded as `<metadata>` and a footer (`None` = no watermark)
+    pub watermark: Option<SvgWatermark>,
+    /// Decimal places for tooltip percentages (`None` = each graph's default)
+    pub percent_precision: Option<usize>,
 }
 
 impl Default for FlamegraphConfig {
@@ -83,6 +130,12 @@ impl Default for FlamegraphConfig {
             title: "Stylus Transaction Profile".to_string(),
             width: 1200,
             ink: false,
+            color_by: ColorMode::Category,
+            label_gas: false,
+            annotations: HashMap::new(),
+            expensive_hostio: DEFAULT_EXPENSIVE_HOSTIO.to_vec(),
+            watermark: None,
+            percent_precision: None,
         }
     }
 }
@@ -101,11 +154,49 @@ impl FlamegraphConfig {
         self.ink = ink;
         self
     }
+
+    pub fn with_color_by(mut self, color_by: ColorMode) -> Self {
+        self.color_by = color_by;
+        self
+    }
+
+    pub fn with_label_gas(mut self, label_gas: bool) -> Self {
+        self.label_gas = label_gas;
+        self
+    }
+
+    pub fn with_expensive_hostio(mut self, types: impl Into<Vec<HostIoType>>) -> Self {
+        self.expensive_hostio = types.into();
+        self
+    }
+
+    pub fn with_watermark(mut self, watermark: SvgWatermark) -> Self {
+        self.watermark = Some(watermark);
+        self
+    }
+
+    pub fn with_percent_precision(mut self, precision: usize) -> Self {
+        self.percent_precision = Some(precision);
+        self
+    }
+
+    /// Attach a note to the frame at the end of `stack` (e.g. an insight)
+    pub fn with_annotation(mut self, stack: impl Into<String>, note: impl Into<String>) -> Self {
+        let stack = stack.into();
+        let stack = stack
+            .strip_prefix("root;")
+            .map(str::to_string)
+            .unwrap_or(stack);
+        self.annotations.entry(stack).or_default().push(note.into());
+        self
+    }
 }
 
 /// Internal Node structure for building the tree
 struct Node {
     name: String,
+    /// Stack from the root's first child to this frame (`a;b;c`)
+    path: String,
     value: u64,
     pc: Option<u64>,
     category: NodeCategory,
@@ -113,10 +204,11 @@ struct Node {
 }
 
 impl Node {
-    fn new(name: String) -> Self {
-        let category = NodeCategory::from_name(&name);
+    fn new(name: String, path: String, expensive: &[HostIoType]) -> Self {
+        let category = NodeCategory::from_name_with(&name, expensive);
         Self {
             name,
+            path,
             value: 0,
             pc: None,
             category,
@@ -124,21 +216,51 @@ impl Node {
         }
     }
 
-    fn insert(&mut self, stack: &[&str], value: u64, pc: Option<u64>) {
-        self.value += value;
+    fn insert(&mut self, stack: &[&str], value: u64, pc: Option<u64>, expensive: &[HostIoType]) {
+        self.value = self.value.saturating_add(value);
         if pc.is_some() {
             self.pc = pc;
         }
         if let Some((head, tail)) = stack.split_first() {
-            let child = self
-                .children
-                .entry(head.to_string())
-                .or_insert_with(|| Node::new(head.to_string()));
-            child.insert(tail, value, pc);
+            let parent_path = &self.path;
+            let child = self.children.entry(head.to_string()).or_insert_with(|| {
+                let path = if parent_path.is_empty() {
+                    head.to_string()
+                } else {
+                    format!("{};{}", parent_path, head)
+                };
+                Node::new(head.to_string(), path, expensive)
+            });
+            child.insert(tail, value, pc, expensive);
         }
     }
+
+    /// Gas spent directly in this frame (value minus the sum of its children)
+    fn self_value(&self) -> u64 {
+        let children_value: u64 = self
+            .children
+            .values()
+            .map(|c| c.value)
+            .fold(0, u64::saturating_add);
+        self.value.saturating_sub(children_value)
+    }
 }
 
+/// Minimum share of a frame's gas its self-gas must reach to get a "self" sub-frame
+const MIN_SELF_FRAME_FRACTION: f64 = 0.01;
+
+/// Fill color for "self" sub-frames
+const SELF_FRAME_COLOR: &str = "rgb(211, 211, 211)"; // Light Gray
+
+/// Marker drawn on frames referenced by an annotation
+const ANNOTATION_BADGE: &str = "⚠";
+
+/// Narrowest frame (px) that still gets an annotation badge
+const MIN_BADGE_WIDTH: f64 = 16.0;
+
+/// How far (0-1) the coldest frames fade toward white in heat mode
+const HEAT_MAX_FADE: f64 = 0.75;
+
 /// Generate SVG flamegraph from collapsed stacks
 pub fn generate_flamegraph(
     stacks: &[CollapsedStack],
@@ -150,18 +272,17 @@ pub fn generate_flamegraph(
     }
 
     let config = config.cloned().unwrap_or_default();
+    let precision = config
+        .percent_precision
+        .unwrap_or(DEFAULT_SUMMARY_PERCENT_PRECISION);
     info!("Generating custom flamegraph with {} stacks", stacks.len());
 
     // 1. Build Tree
-    let mut root = Node::new("root".to_string());
-    for stack in stacks {
-        // format: "a;b;c" and we have weight separately
-        let mut stack_parts: Vec<&str> = stack.stack.split(';').collect();
-        // Skip redundant root if present
-        if stack_parts.first() == Some(&"root") {
-            stack_parts.remove(0);
-        }
-        root.insert(&stack_parts, stack.weight, stack.last_pc);
+    let root = build_tree(stacks, &config);
+    if root.value == 0 {
+        return Err(FlamegraphError::NoGasAttributed {
+            stacks: stacks.len(),
+        });
     }
 
     // Calculate depth
@@ -173,38 +294,53 @@ pub fn generate_flamegraph(
     let height_per_level = 20;
     let graph_height = (max_depth + 1) * height_per_level;
     let legend_height = 80;
-    let total_height = graph_height + legend_height;
+    let watermark_height = config.watermark.as_ref().map_or(0, |_| WATERMARK_HEIGHT);
+    let total_height = graph_height + legend_height + watermark_height;
 
-    // Header
-    svg_content.push_str(&format!(
-        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" viewBox="0 0 {} {}">"#,
-        width, total_height, width, total_height
-    ));
-
-    // Styles
-    svg_content.push_str(
-        r#"<style>.func { font: 12px sans-serif; } .func:hover { stroke: black; stroke-width: 1; cursor: pointer; opacity: 0.9; }</style>"#
-    );
-
-    // Title
-    svg_content.push_str(&format!(
-        r#"<text x="{}" y="20" font-size="16" text-anchor="middle" font-weight="bold">{}</text>"#,
-        width / 2,
-        config.title
-    ));
+    render_svg_header(&mut svg_content, width, total_height, &config.title);
+    if let Some(watermark) = &config.watermark {
+        svg_content.push_str(&watermark.metadata());
+    }
 
     // Render Nodes (Inverted: Root at bottom)
+    let heat_max = match config.color_by {
+        ColorMode::Heat => Some(max_self_value(&root).max(1)),
+        ColorMode::Category => None,
+    };
+
     let mut ctx = RenderContext {
         output: &mut svg_content,
         line_height: height_per_level,
         graph_height,
         mapper,
+        heat_max,
+        gas_label_unit: config.label_gas.then_some(config.ink),
+        annotations: &config.annotations,
+        root_value: root.value,
+        precision,
     };
 
-    render_node(&root, 0, 0.0, width as f64, &mut ctx);
+    render_node(&root, None, 0, 0.0, width as f64, &mut ctx);
 
     // Render Legend
-    render_legend(&mut svg_content, graph_height);
+    render_legend(&mut svg_content, graph_height, &config.expensive_hostio);
+    if heat_max.is_some() {
+        svg_content.push_str(&format!(
+            r#"<text x="10" y="{}" font-size="12" fill="gray">Heat: paler frames use less gas themselves</text>"#,
+            graph_height + 72
+        ));
+    }
+    if !config.annotations.is_empty() {
+        svg_content.push_str(&format!(
+            r#"<text x="{}" y="{}" font-size="12" fill="gray">{} marks frames with an insight (hover for details)</text>"#,
+            if heat_max.is_some() { 400 } else { 10 },
+            graph_height + 72,
+            ANNOTATION_BADGE
+        ));
+    }
+    if let Some(watermark) = &config.watermark {
+        svg_content.push_str(&watermark.footer(width, total_height - 4));
+    }
 
     svg_content.push_str("</svg>");
 
@@ -215,6 +351,174 @@ pub fn generate_flamegraph(
     Ok(svg_content)
 }
 
+/// Horizontal space (px) around and between the side-by-side panels
+const SIDE_BY_SIDE_GAP: usize = 20;
+
+/// Generate one SVG with the baseline flamegraph on the left and the target
+/// on the right
+///
+/// **Public** - used by `diff --compare-svg-side-by-side`
+///
+/// Both panels share one gas axis: the heavier profile's root spans its full
+/// panel and the lighter one is proportionally narrower, so frame widths can
+/// be compared directly. Tooltip shares stay relative to each profile.
+///
+/// # Errors
+/// * `FlamegraphError::EmptyStacks` - If either profile has no stacks
+/// * `FlamegraphError::NoGasAttributed` - If neither profile's stacks carry gas
+pub fn generate_side_by_side_flamegraph(
+    baseline_stacks: &[CollapsedStack],
+    target_stacks: &[CollapsedStack],
+    config: Option<&FlamegraphConfig>,
+) -> Result<String, FlamegraphError> {
+    if baseline_stacks.is_empty() || target_stacks.is_empty() {
+        return Err(FlamegraphError::EmptyStacks);
+    }
+
+    let config = config.cloned().unwrap_or_default();
+    let precision = config
+        .percent_precision
+        .unwrap_or(DEFAULT_SUMMARY_PERCENT_PRECISION);
+    info!(
+        "Generating side-by-side flamegraph (B:{} stacks, T:{} stacks)",
+        baseline_stacks.len(),
+        target_stacks.len()
+    );
+
+    let baseline = build_tree(baseline_stacks, &config);
+    let target = build_tree(target_stacks, &config);
+    if baseline.value == 0 && target.value == 0 {
+        return Err(FlamegraphError::NoGasAttributed {
+            stacks: baseline_stacks.len() + target_stacks.len(),
+        });
+    }
+    let scale = baseline.value.max(target.value).max(1) as f64;
+
+    let width = config.width;
+    let panel_width = width.saturating_sub(SIDE_BY_SIDE_GAP * 3) as f64 / 2.0;
+    let height_per_level = 20;
+    let max_depth = calculate_max_depth(&baseline).max(calculate_max_depth(&target));
+    let graph_height = (max_depth + 1) * height_per_level;
+    // Panel captions sit between the roots and the legend
+    let caption_height = 25;
+    let legend_height = 80;
+    let total_height = graph_height + caption_height + legend_height;
+
+    let mut svg_content = String::new();
+    render_svg_header(&mut svg_content, width, total_height, &config.title);
+
+    let heat_max = match config.color_by {
+        ColorMode::Heat => Some(
+            max_self_value(&baseline)
+                .max(max_self_value(&target))
+                .max(1),
+        ),
+        ColorMode::Category => None,
+    };
+
+    let change = safe_change_percent(baseline.value, target.value);
+    let panels = [
+        (&baseline, SIDE_BY_SIDE_GAP as f64, "Baseline".to_string()),
+        (
+            &target,
+            (SIDE_BY_SIDE_GAP * 2) as f64 + panel_width,
+            format!("Target ({})", format_percent_change(change, precision)),
+        ),
+    ];
+
+    for (root, x, caption) in panels {
+        let mut ctx = RenderContext {
+            output: &mut svg_content,
+            line_height: height_per_level,
+            graph_height,
+            mapper: None,
+            heat_max,
+            gas_label_unit: config.label_gas.then_some(config.ink),
+            annotations: &config.annotations,
+            root_value: root.value,
+            precision,
+        };
+        let root_width = panel_width * root.value as f64 / scale;
+        render_node(root, None, 0, x, root_width, &mut ctx);
+
+        let value = if config.ink {
+            root.value
+        } else {
+            root.value / 10_000
+        };
+        svg_content.push_str(&format!(
+            r#"<text x="{:.2}" y="{}" font-size="13" font-weight="bold">{}: {} {}</text>"#,
+            x,
+            graph_height + 48,
+            escape_xml(&caption),
+            value,
+            if config.ink { "ink" } else { "gas" }
+        ));
+    }
+
+    render_legend(
+        &mut svg_content,
+        graph_height + caption_height,
+        &config.expensive_hostio,
+    );
+    svg_content.push_str(&format!(
+        r#"<text x="10" y="{}" font-size="12" fill="gray">Both panels share one gas scale: equal widths mean equal gas</text>"#,
+        graph_height + caption_height + 72
+    ));
+    svg_content.push_str("</svg>");
+
+    Ok(svg_content)
+}
+
+/// Percent change from `baseline` to `target` (0 when the baseline is empty)
+fn safe_change_percent(baseline: u64, target: u64) -> f64 {
+    if baseline == 0 {
+        0.0
+    } else {
+        (target as f64 - baseline as f64) / baseline as f64 * 100.0
+    }
+}
+
+/// Build the frame tree from collapsed stacks, dropping a leading `root` frame
+fn build_tree(stacks: &[CollapsedStack], config: &FlamegraphConfig) -> Node {
+    let mut root = Node::new("root".to_string(), String::new(), &config.expensive_hostio);
+    for stack in stacks {
+        // format: "a;b;c" and we have weight separately
+        let mut stack_parts: Vec<&str> = stack.stack.split(';').collect();
+        // Skip redundant root if present
+        if stack_parts.first() == Some(&"root") {
+            stack_parts.remove(0);
+        }
+        root.insert(
+            &stack_parts,
+            stack.weight,
+            stack.last_pc,
+            &config.expensive_hostio,
+        );
+    }
+    root
+}
+
+/// Open the SVG document and add the shared styles and centered title
+fn render_svg_header(out: &mut String, width: usize, height: usize, title: &str) {
+    out.push_str(&format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" viewBox="0 0 {} {}">"#,
+        width, height, width, height
+    ));
+
+    // Styles
+    out.push_str(
+        r#"<style>.func { font: 12px sans-serif; } .func:hover { stroke: black; stroke-width: 1; cursor: pointer; opacity: 0.9; }</style>"#
+    );
+
+    // Title
+    out.push_str(&format!(
+        r#"<text x="{}" y="20" font-size="16" text-anchor="middle" font-weight="bold">{}</text>"#,
+        width / 2,
+        escape_xml(title)
+    ));
+}
+
 fn calculate_max_depth(node: &Node) -> usize {
     if node.children.is_empty() {
         return 0;
@@ -228,20 +532,59 @@ fn calculate_max_depth(node: &Node) -> usize {
     max_child_depth + 1
 }
 
-fn get_node_color(category: NodeCategory) -> &'static str {
+/// Largest self value of any node in the tree
+fn max_self_value(node: &Node) -> u64 {
+    node.children
+        .values()
+        .map(max_self_value)
+        .fold(node.self_value(), u64::max)
+}
+
+fn category_rgb(category: NodeCategory) -> (u8, u8, u8) {
     match category {
-        NodeCategory::StorageExpensive => "rgb(220, 20, 60)", // Crimson
-        NodeCategory::StorageNormal => "rgb(255, 140, 0)",    // Dark Orange
-        NodeCategory::Crypto => "rgb(138, 43, 226)",          // Blue Violet
-        NodeCategory::Memory => "rgb(34, 139, 34)",           // Forest Green
-        NodeCategory::Call => "rgb(70, 130, 180)",            // Steel Blue
-        NodeCategory::System => "rgb(100, 149, 237)",         // Cornflower Blue
-        NodeCategory::Root => "rgb(75, 0, 130)",              // Indigo
-        NodeCategory::UserCode => "rgb(169, 169, 169)",       // Gray
+        NodeCategory::StorageExpensive => (220, 20, 60), // Crimson
+        NodeCategory::StorageNormal => (255, 140, 0),    // Dark Orange
+        NodeCategory::Crypto => (138, 43, 226),          // Blue Violet
+        NodeCategory::Memory => (34, 139, 34),           // Forest Green
+        NodeCategory::Call => (70, 130, 180),            // Steel Blue
+        NodeCategory::System => (100, 149, 237),         // Cornflower Blue
+        NodeCategory::Root => (75, 0, 130),              // Indigo
+        NodeCategory::UserCode => (169, 169, 169),       // Gray
     }
 }
 
-fn get_ansi_color(category: NodeCategory) -> &'static str {
+/// Fill color for a node; with `heat` (0-1) the category color fades toward
+/// white as the intensity drops
+fn get_node_color(category: NodeCategory, heat: Option<f64>) -> String {
+    let (r, g, b) = category_rgb(category);
+    let fade = heat.map_or(0.0, |h| (1.0 - h.clamp(0.0, 1.0)) * HEAT_MAX_FADE);
+    let mix = |c: u8| (c as f64 + (255.0 - c as f64) * fade).round() as u8;
+
+    format!("rgb({}, {}, {})", mix(r), mix(g), mix(b))
+}
+
+/// ANSI reset sequence, or empty when `colored` is false
+fn ansi_reset(colored: bool) -> &'static str {
+    if colored {
+        "\x1b[0m"
+    } else {
+        ""
+    }
+}
+
+/// Whether terminal colors should be emitted
+///
+/// Shares `colored`'s decision so `--no-color`, `NO_COLOR` and non-TTY
+/// stdout disable the manual ANSI codes too.
+fn colors_enabled() -> bool {
+    colored::control::SHOULD_COLORIZE.should_colorize()
+}
+
+fn get_ansi_color(category: NodeCategory, colored: bool) -> &'static str {
+    if !colored {
+        return "";
+    }
+
     match category {
         NodeCategory::StorageExpensive => "\x1b[31;1m", // Bold Red
         NodeCategory::StorageNormal => "\x1b[33m",      // Yellow
@@ -259,14 +602,40 @@ struct RenderContext<'a> {
     line_height: usize,
     graph_height: usize,
     mapper: Option<&'a SourceMapper>,
+    /// Max self value in the tree when coloring by heat
+    heat_max: Option<u64>,
+    /// `Some(ink)` to append frame values to labels (in ink when true)
+    gas_label_unit: Option<bool>,
+    /// Notes keyed by frame path (see FlamegraphConfig::annotations)
+    annotations: &'a HashMap<String, Vec<String>>,
+    /// Total value of the tree, for "% of total" in tooltips
+    root_value: u64,
+    /// Decimal places for tooltip percentages
+    precision: usize,
 }
 
-fn render_node(node: &Node, level: usize, x: f64, w: f64, ctx: &mut RenderContext) {
-    if w < 0.5 {
+/// Render a node and its children
+///
+/// `parent_value` is `None` for the root; it feeds the tooltip's share of
+/// the parent frame.
+fn render_node(
+    node: &Node,
+    parent_value: Option<u64>,
+    level: usize,
+    x: f64,
+    w: f64,
+    ctx: &mut RenderContext,
+) {
+    // Optimization: Don't render invisible blocks. Zero-value nodes are
+    // skipped too, since their children's widths would divide by zero.
+    if w < 0.5 || node.value == 0 {
         return;
-    } // Optimization: Don't render invisible blocks
+    }
 
-    let color = get_node_color(node.category);
+    let heat = ctx
+        .heat_max
+        .map(|max| node.self_value() as f64 / max as f64);
+    let color = get_node_color(node.category, heat);
 
     // Y position (Inverted: Graph Bottom - (Level * Height))
     // We add margin for title (30px)
@@ -275,44 +644,118 @@ fn render_node(node: &Node, level: usize, x: f64, w: f64, ctx: &mut RenderContex
         - (ctx.line_height as f64)
         + 30.0;
 
-    let tooltip = format_tooltip(node, ctx);
+    let notes = ctx.annotations.get(&node.path);
+    let mut tooltip = format_tooltip(node, parent_value, ctx);
+    for note in notes.into_iter().flatten() {
+        tooltip.push_str(&format!("\n{} {}", ANNOTATION_BADGE, note));
+    }
 
     ctx.output.push_str(&format!(
         r#"<rect x="{:.2}" y="{:.2}" width="{:.2}" height="{}" fill="{}" stroke="white" stroke-width="0.5" class="func">"#,
         x, y, w, ctx.line_height, color
     ));
-    ctx.output
-        .push_str(&format!(r#"<title>{}</title></rect>"#, tooltip));
+    ctx.output.push_str(&format!(
+        r#"<title>{}</title></rect>"#,
+        escape_xml(&tooltip)
+    ));
 
-    if let Some(display_name) = get_truncated_name(&node.name, w) {
+    if let Some(display_name) = frame_label(&node.name, node.value, w, ctx) {
         ctx.output.push_str(&format!(
             r#"<text x="{:.2}" y="{:.2}" dx="4" dy="14" font-size="12" fill="white" pointer-events="none">{}</text>"#,
-            x, y, display_name
+            x,
+            y,
+            escape_xml(&display_name)
+        ));
+    }
+
+    if notes.is_some() && w >= MIN_BADGE_WIDTH {
+        ctx.output.push_str(&format!(
+            r#"<text x="{:.2}" y="{:.2}" dx="-14" dy="14" font-size="12" fill="gold" class="insight" pointer-events="none">{}</text>"#,
+            x + w,
+            y,
+            ANNOTATION_BADGE
         ));
     }
 
     // Recurse
     let mut current_x = x;
     let mut children_vec: Vec<&Node> = node.children.values().collect();
-    children_vec.sort_by(|a, b| b.value.cmp(&a.value)); // Sort descending
+    // Sort descending, breaking ties by name so output is reproducible
+    children_vec.sort_by(|a, b| b.value.cmp(&a.value).then_with(|| a.name.cmp(&b.name)));
 
     for child in children_vec {
         let child_w = (child.value as f64 / node.value as f64) * w;
         if child_w > 0.0 {
-            render_node(child, level + 1, current_x, child_w, ctx);
+            render_node(child, Some(node.value), level + 1, current_x, child_w, ctx);
             current_x += child_w;
         }
     }
+
+    // Gas spent in the frame itself rather than delegated to callees
+    let self_value = node.self_value();
+    if !node.children.is_empty() && self_value as f64 >= node.value as f64 * MIN_SELF_FRAME_FRACTION
+    {
+        let self_w = (self_value as f64 / node.value as f64) * w;
+        render_self_frame(node, self_value, level + 1, current_x, self_w, ctx);
+    }
+}
+
+/// Render the "self" sub-frame showing gas not attributed to any child
+fn render_self_frame(
+    node: &Node,
+    self_value: u64,
+    level: usize,
+    x: f64,
+    w: f64,
+    ctx: &mut RenderContext,
+) {
+    if w < 0.5 {
+        return;
+    }
+
+    let y = (ctx.graph_height as f64)
+        - (level as f64 * ctx.line_height as f64)
+        - (ctx.line_height as f64)
+        + 30.0;
+
+    ctx.output.push_str(&format!(
+        r#"<rect x="{:.2}" y="{:.2}" width="{:.2}" height="{}" fill="{}" stroke="white" stroke-width="0.5" stroke-dasharray="2,2" class="func self">"#,
+        x, y, w, ctx.line_height, SELF_FRAME_COLOR
+    ));
+    ctx.output.push_str(&format!(
+        r#"<title>{} (self): {} ink / {} gas{}</title></rect>"#,
+        escape_xml(&node.name),
+        self_value,
+        self_value / 10_000,
+        share_of(self_value, node.value, ctx.root_value, ctx.precision)
+    ));
+
+    if let Some(display_name) = frame_label("self", self_value, w, ctx) {
+        ctx.output.push_str(&format!(
+            r#"<text x="{:.2}" y="{:.2}" dx="4" dy="14" font-size="12" fill="black" pointer-events="none">{}</text>"#,
+            x,
+            y,
+            escape_xml(&display_name)
+        ));
+    }
 }
 
 /// Helper to format a rich tooltip for a node
-fn format_tooltip(node: &Node, ctx: &RenderContext) -> String {
+fn format_tooltip(node: &Node, parent_value: Option<u64>, ctx: &RenderContext) -> String {
     let mut tooltip = format!(
         "{}: {} ink / {} gas",
         node.name,
         node.value,
         node.value / 10_000
     );
+    if let Some(parent_value) = parent_value {
+        tooltip.push_str(&share_of(
+            node.value,
+            parent_value,
+            ctx.root_value,
+            ctx.precision,
+        ));
+    }
 
     if let (Some(pc), Some(mapper)) = (node.pc, ctx.mapper) {
         if let Some(loc) = mapper.lookup(pc) {
@@ -323,19 +766,75 @@ fn format_tooltip(node: &Node, ctx: &RenderContext) -> String {
     tooltip
 }
 
+/// Tooltip suffix with a frame's share of its parent and of the whole graph
+fn share_of(value: u64, parent_value: u64, root_value: u64, precision: usize) -> String {
+    let percent = |total: u64| {
+        if total > 0 {
+            value as f64 / total as f64 * 100.0
+        } else {
+            0.0
+        }
+    };
+    format!(
+        " ({} of parent, {} of total)",
+        format_percent(percent(parent_value), precision),
+        format_percent(percent(root_value), precision)
+    )
+}
+
+/// Frame label text, with the frame's value appended when `--label-gas` is on
+///
+/// The value is only added if the full `name (value unit)` label fits the
+/// frame; otherwise the plain (possibly truncated) name is used.
+fn frame_label(name: &str, value: u64, width: f64, ctx: &RenderContext) -> Option<String> {
+    if let Some(ink) = ctx.gas_label_unit {
+        let label = if ink {
+            format!("{} ({} ink)", name, value)
+        } else {
+            format!("{} ({} gas)", name, value / 10_000)
+        };
+        if width > MIN_LABEL_WIDTH && label.len() <= (width / CHAR_WIDTH) as usize {
+            return Some(label);
+        }
+    }
+    get_truncated_name(name, width)
+}
+
+/// Frames this narrow (px) get no label
+const MIN_LABEL_WIDTH: f64 = 35.0;
+
+/// Approximate width (px) of one 12px label character
+const CHAR_WIDTH: f64 = 7.0;
+
+/// Escape text for use in SVG element content or attribute values
+///
+/// **Public** - frame names come from symbols and source maps (e.g.
+/// `Vec<T>::push`), so they must be escaped before being embedded
+pub fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
 /// Helper to truncate node names based on available width
 /// Calculate truncated name for a node based on width
 pub fn get_truncated_name(name: &str, width: f64) -> Option<String> {
-    const MIN_LABEL_WIDTH: f64 = 35.0;
-    const CHAR_WIDTH: f64 = 7.0;
-
     if width <= MIN_LABEL_WIDTH {
         return None;
     }
 
     let max_chars = (width / CHAR_WIDTH) as usize;
-    if name.len() > max_chars && max_chars > 3 {
-        Some(format!("{}...", &name[0..max_chars.saturating_sub(3)]))
+    if name.chars().count() > max_chars && max_chars > 3 {
+        Some(truncate_end(name, max_chars))
     } else if !name.is_empty() {
         Some(name.to_string())
     } else {
@@ -343,8 +842,14 @@ pub fn get_truncated_name(name: &str, width: f64) -> Option<String> {
     }
 }
 
-fn render_legend(out: &mut String, graph_height: usize) {
+fn render_legend(out: &mut String, graph_height: usize, expensive: &[HostIoType]) {
     let legend_y = graph_height + 50;
+    // Crimson only means "expensive storage" with the default set
+    let expensive_label = if expensive == DEFAULT_EXPENSIVE_HOSTIO {
+        "Storage (Ex)"
+    } else {
+        "Expensive"
+    };
 
     out.push_str(&format!(
         r#"<text x="10" y="{}" font-size="14" font-weight="bold">Legend:</text>"#,
@@ -352,12 +857,13 @@ fn render_legend(out: &mut String, graph_height: usize) {
     ));
 
     let items = [
-        ("Storage (Ex)", "rgb(220, 20, 60)"),
+        (expensive_label, "rgb(220, 20, 60)"),
         ("Storage", "rgb(255, 140, 0)"),
         ("Crypto", "rgb(138, 43, 226)"),
         ("Memory", "rgb(34, 139, 34)"),
         ("Call/Msg", "rgb(70, 130, 180)"),
         ("System", "rgb(100, 149, 237)"),
+        ("Self", SELF_FRAME_COLOR),
     ];
 
     for (i, (label, color)) in items.iter().enumerate() {
@@ -378,16 +884,38 @@ fn render_legend(out: &mut String, graph_height: usize) {
 }
 
 /// Create a rich text summary with percentages and table formatting
+///
+/// Colors follow the terminal: they are dropped for `--no-color`, `NO_COLOR`
+/// and when stdout is not a TTY (e.g. `capture --summary > out.txt`).
+/// Percentages use the config's `percent_precision` when one is given.
 pub fn generate_text_summary(
+    hot_paths: &[crate::parser::schema::HotPath],
+    max_lines: usize,
+    ink_mode: bool,
+    config: Option<&FlamegraphConfig>,
+) -> String {
+    render_text_summary(hot_paths, max_lines, ink_mode, colors_enabled(), config)
+}
+
+/// Render the text summary with colors explicitly on or off
+///
+/// **Public** - the content is identical either way; `colored` only adds ANSI
+/// styling around category names and bars.
+pub fn render_text_summary(
     hot_paths: &[crate::parser::schema::HotPath],
     max_lines: usize,
     _ink_mode: bool,
+    colored: bool,
+    config: Option<&FlamegraphConfig>,
 ) -> String {
+    let precision = summary_precision(config);
     let mut lines = Vec::new();
 
-    lines.extend(render_hot_path_table(hot_paths, max_lines));
+    lines.extend(render_hot_path_table(
+        hot_paths, max_lines, colored, precision,
+    ));
     lines.push("".to_string());
-    lines.extend(render_ascii_flamegraph(hot_paths));
+    lines.extend(render_ascii_flamegraph(hot_paths, colored, precision));
 
     if hot_paths.len() > max_lines {
         lines.push("".to_string());
@@ -401,54 +929,285 @@ pub fn generate_text_summary(
     lines.join("\n")
 }
 
+/// Explain the cost of the HostIO operation at the leaf of each hot path
+///
+/// **Public** - used by capture for `--explain`
+///
+/// Covers the same top `max_lines` paths as the text summary, numbered by
+/// rank. Paths whose leaf is not a known HostIO type are skipped.
+pub fn generate_hot_path_explanations(
+    hot_paths: &[crate::parser::schema::HotPath],
+    max_lines: usize,
+) -> String {
+    let mut lines = vec!["  💡 WHAT THE HOT PATHS COST".to_string()];
+
+    let mut explained = 0;
+    for (rank, path) in hot_paths.iter().take(max_lines).enumerate() {
+        let leaf = path.stack.split(';').next_back().unwrap_or(&path.stack);
+        let Ok(io_type) = leaf.parse::<HostIoType>();
+        let Some(explanation) = io_type.explanation() else {
+            continue;
+        };
+        lines.push(format!(
+            "  {:>2}. {}",
+            rank + 1,
+            truncate_stack(&path.stack, 70)
+        ));
+        lines.push(format!("      {}: {}", io_type, explanation));
+        explained += 1;
+    }
+
+    if explained == 0 {
+        lines.push("  No HostIO operations among the top hot paths.".to_string());
+    }
+    lines.join("\n")
+}
+
+/// Roll hot paths up by their first `depth` stack segments
+///
+/// Colors and precision follow `generate_text_summary`.
+pub fn generate_hot_path_groups(
+    hot_paths: &[crate::parser::schema::HotPath],
+    depth: usize,
+    ink_mode: bool,
+    config: Option<&FlamegraphConfig>,
+) -> String {
+    render_hot_path_groups(hot_paths, depth, ink_mode, colors_enabled(), config)
+}
+
+/// Render the hot-path prefix rollup with colors explicitly on or off
+///
+/// **Public** - each level lists its prefixes hottest first, indented under
+/// their parent, with the summed self gas of every hot path below them.
+/// Only the listed hot paths are counted, so shares are of their total.
+pub fn render_hot_path_groups(
+    hot_paths: &[crate::parser::schema::HotPath],
+    depth: usize,
+    ink_mode: bool,
+    colored: bool,
+    config: Option<&FlamegraphConfig>,
+) -> String {
+    let mut root = PrefixGroup::default();
+    for path in hot_paths {
+        root.gas = root.gas.saturating_add(path.gas);
+        let mut group = &mut root;
+        for segment in path.stack.split(';').take(depth.max(1)) {
+            group = group.children.entry(segment.to_string()).or_default();
+            group.gas = group.gas.saturating_add(path.gas);
+        }
+    }
+
+    let mut lines = vec![format!(
+        "  🗂️  HOT PATHS BY PREFIX (depth {})",
+        depth.max(1)
+    )];
+    if root.gas == 0 {
+        lines.push("  (no hot-path gas recorded)".to_string());
+        return lines.join("\n");
+    }
+
+    let format = PrefixFormat {
+        ink_mode,
+        unit: if ink_mode { "ink" } else { "gas" },
+        colored,
+        precision: summary_precision(config),
+    };
+    render_prefix_groups(&root, root.gas, 0, &format, &mut lines);
+    lines.join("\n")
+}
+
+/// Decimal places for the terminal summaries of a flamegraph config
+///
+/// **Private** - internal helper for the text summary, groups and histogram
+fn summary_precision(config: Option<&FlamegraphConfig>) -> usize {
+    config
+        .and_then(|config| config.percent_precision)
+        .unwrap_or(DEFAULT_SUMMARY_PERCENT_PRECISION)
+}
+
+/// Summed self gas of the hot paths under one stack prefix
+#[derive(Default)]
+struct PrefixGroup {
+    gas: u64,
+    children: HashMap<String, PrefixGroup>,
+}
+
+/// How render_prefix_groups formats each line
+struct PrefixFormat<'a> {
+    ink_mode: bool,
+    unit: &'a str,
+    colored: bool,
+    precision: usize,
+}
+
+/// Append `group`'s children (hottest first) and their subtrees to `lines`
+///
+/// **Private** - internal helper for render_hot_path_groups
+fn render_prefix_groups(
+    group: &PrefixGroup,
+    total: u64,
+    level: usize,
+    format: &PrefixFormat,
+    lines: &mut Vec<String>,
+) {
+    let mut children: Vec<_> = group.children.iter().collect();
+    children.sort_by(|a, b| b.1.gas.cmp(&a.1.gas).then_with(|| a.0.cmp(b.0)));
+
+    let reset = ansi_reset(format.colored);
+    for (name, child) in children {
+        let indent = "  ".repeat(level);
+        let width = 42usize.saturating_sub(indent.len());
+        let color = get_ansi_color(NodeCategory::from_name(name), format.colored);
+        let value = if format.ink_mode {
+            child.gas
+        } else {
+            child.gas / 10_000
+        };
+        lines.push(format!(
+            "  {}{}{:<width$}{} {:>12} {} {:>7}",
+            indent,
+            color,
+            truncate_end(name, width),
+            reset,
+            value,
+            format.unit,
+            format_percent(child.gas as f64 / total as f64 * 100.0, format.precision),
+            width = width
+        ));
+        render_prefix_groups(child, total, level + 1, format, lines);
+    }
+}
+
+/// Render a histogram of HostIO gas by type for terminal output
+///
+/// Each bar is proportional to the type's share of total HostIO gas.
+/// `top_n` caps the number of types listed (`0` lists all of them).
+/// Shares use the config's `percent_precision` when one is given.
+pub fn generate_hostio_histogram(
+    stats: &HostIoStats,
+    ink_mode: bool,
+    top_n: usize,
+    config: Option<&FlamegraphConfig>,
+) -> String {
+    const MAX_BAR_WIDTH: f64 = 40.0;
+    let precision = summary_precision(config);
+
+    let mut lines = Vec::new();
+    lines.push("  📦 HOSTIO GAS BREAKDOWN".to_string());
+
+    let total = stats.total_gas();
+    if total == 0 {
+        lines.push("  (no HostIO gas recorded)".to_string());
+        return lines.join("\n");
+    }
+
+    let unit = if ink_mode { "ink" } else { "gas" };
+    let colored = colors_enabled();
+    let reset = ansi_reset(colored);
+
+    let breakdown = stats.gas_breakdown();
+    let shown = if top_n == 0 { breakdown.len() } else { top_n };
+
+    for &(io_type, gas) in breakdown.iter().take(shown) {
+        let share = gas as f64 / total as f64;
+        let bar = "█".repeat((share * MAX_BAR_WIDTH).round() as usize);
+        let color = get_ansi_color(NodeCategory::from_hostio(io_type), colored);
+        let value = if ink_mode { gas } else { gas / 10_000 };
+        let avg = stats.avg_gas_for_type(io_type).unwrap_or(0.0);
+        let avg = if ink_mode { avg } else { avg / 10_000.0 };
+
+        lines.push(format!(
+            "  {}{:<20}{} {}{:<40}{} {:>6}  ({} {})  {:.1} {}/call",
+            color,
+            io_type.as_str(),
+            reset,
+            color,
+            bar,
+            reset,
+            format_percent(share * 100.0, precision),
+            value,
+            unit,
+            avg,
+            unit
+        ));
+    }
+
+    if breakdown.len() > shown {
+        lines.push(format!(
+            "  ... {} more (use --top-hostio 0 to list all)",
+            breakdown.len() - shown
+        ));
+    }
+
+    lines.join("\n")
+}
+
 /// Helper to render the hot path table for terminal output
 fn render_hot_path_table(
     hot_paths: &[crate::parser::schema::HotPath],
     max_lines: usize,
+    colored: bool,
+    precision: usize,
 ) -> Vec<String> {
     let mut lines = Vec::new();
 
     lines.push("  🚀 EXECUTION HOT PATHS".to_string());
     lines.push(
-        "  ┏━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━┳━━━━━━━━━━━━━━┳━━━━━━━━━━━━━━┳━━━━━━━━━┓"
+        "  ┏━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━┳━━━━━━━━━━━━━━┳━━━━━━━━━━━━━━┳━━━━━━━━━━━━━━┳━━━━━━━━━┓"
             .to_string(),
     );
     lines.push(format!(
-        "  ┃ {:<42} ┃ {:^12} ┃ {:^12} ┃ {:^7} ┃",
-        "Execution Stack (Hottest First)", "GAS", "INK (x10k)", "%"
+        "  ┃ {:<42} ┃ {:^12} ┃ {:^12} ┃ {:^12} ┃ {:^7} ┃",
+        "Execution Stack (Hottest First)", "SELF GAS", "SELF INK", "TOTAL GAS", "SELF %"
     ));
     lines.push(
-        "  ┣━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━╋━━━━━━━━━━━━━━╋━━━━━━━━━━━━━━╋━━━━━━━━━┫"
+        "  ┣━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━╋━━━━━━━━━━━━━━╋━━━━━━━━━━━━━━╋━━━━━━━━━━━━━━╋━━━━━━━━━┫"
             .to_string(),
     );
 
     for path in hot_paths.iter().take(max_lines) {
         let weight_ink = path.gas;
         let weight_gas = path.gas / 10_000;
+        // Older profiles lack cumulative gas; fall back to self gas
+        let cumulative_gas = path.cumulative_gas.max(path.gas) / 10_000;
         let percentage = path.percentage;
 
         let op_name = path.stack.split(';').next_back().unwrap_or(&path.stack);
         let category = NodeCategory::from_name(op_name);
-        let color = get_ansi_color(category);
-        let reset = "\x1b[0m";
+        let color = get_ansi_color(category, colored);
+        let reset = ansi_reset(colored);
 
         let display_stack = truncate_stack(&path.stack, 42);
 
         lines.push(format!(
-            "  ┃ {}{:<42}{} ┃ {:>12} ┃ {:>12} ┃ {:>6.1}% ┃",
-            color, display_stack, reset, weight_gas, weight_ink, percentage
+            "  ┃ {}{:<42}{} ┃ {:>12} ┃ {:>12} ┃ {:>12} ┃ {:>7} ┃",
+            color,
+            display_stack,
+            reset,
+            weight_gas,
+            weight_ink,
+            cumulative_gas,
+            format_percent(percentage, precision)
         ));
     }
 
     lines.push(
-        "  ┗━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━┻━━━━━━━━━━━━━━┻━━━━━━━━━━━━━━┻━━━━━━━━━┛"
+        "  ┗━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━┻━━━━━━━━━━━━━━┻━━━━━━━━━━━━━━┻━━━━━━━━━━━━━━┻━━━━━━━━━┛"
+            .to_string(),
+    );
+    lines.push(
+        "  SELF = gas of the exact stack only · TOTAL = self + all callees (descendant stacks)"
             .to_string(),
     );
     lines
 }
 
 /// Helper to render the ASCII flamegraph visualization
-fn render_ascii_flamegraph(hot_paths: &[crate::parser::schema::HotPath]) -> Vec<String> {
+fn render_ascii_flamegraph(
+    hot_paths: &[crate::parser::schema::HotPath],
+    colored: bool,
+    precision: usize,
+) -> Vec<String> {
     let mut lines = Vec::new();
 
     lines.push("  🔥 SIMPLIFIED FLAMEGRAPH".to_string());
@@ -461,12 +1220,18 @@ fn render_ascii_flamegraph(hot_paths: &[crate::parser::schema::HotPath]) -> Vec<
 
         let op_name = path.stack.split(';').next_back().unwrap_or(&path.stack);
         let category = NodeCategory::from_name(op_name);
-        let color = get_ansi_color(category);
-        let reset = "\x1b[0m";
+        let color = get_ansi_color(category, colored);
+        let reset = ansi_reset(colored);
 
         lines.push(format!(
-            "  └─ {}{:<20}{} {}{:50}{} {:>5.1}%",
-            color, op_name, reset, color, bar, reset, percentage
+            "  └─ {}{:<20}{} {}{:50}{} {:>6}",
+            color,
+            op_name,
+            reset,
+            color,
+            bar,
+            reset,
+            format_percent(percentage, precision)
         ));
     }
     lines
@@ -474,9 +1239,5 @@ fn render_ascii_flamegraph(hot_paths: &[crate::parser::schema::HotPath]) -> Vec<
 
 /// Helper to truncate strings with ellipsis for table display
 fn truncate_stack(s: &str, max_len: usize) -> String {
-    if s.len() > max_len {
-        format!("...{}", &s[s.len().saturating_sub(max_len - 3)..])
-    } else {
-        s.to_string()
-    }
+    truncate_start(s, max_len)
 }
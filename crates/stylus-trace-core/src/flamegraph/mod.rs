@@ -8,4 +8,8 @@ pub mod generator;
 
 // Re-export main types
 pub use diff_generator::generate_diff_flamegraph;
-pub use generator::{generate_flamegraph, generate_text_summary, FlamegraphConfig};
+pub use generator::{
+    generate_flamegraph, generate_hostio_histogram, generate_hot_path_explanations,
+    generate_hot_path_groups, generate_side_by_side_flamegraph, generate_text_summary,
+    render_hot_path_groups, render_text_summary, ColorMode, FlamegraphConfig,
+};
@@ -0,0 +1,99 @@
+//! Atomic file replacement for output writers.
+//!
+//! Output is written to a temporary file in the destination directory and
+//! renamed over the destination only once it is complete. An interrupted run
+//! (Ctrl-C, a killed CI job) therefore leaves either the previous file or
+//! none at all, never a truncated profile that a later read half-accepts.
+
+use crate::utils::error::OutputError;
+use log::debug;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use tempfile::NamedTempFile;
+
+/// A file being written under a temporary name
+///
+/// Nothing appears at the destination until `commit`; dropping the writer
+/// without committing deletes the temporary file.
+pub struct AtomicFile {
+    writer: BufWriter<NamedTempFile>,
+    path: PathBuf,
+}
+
+impl AtomicFile {
+    /// Start writing `path` via a temporary file next to it
+    ///
+    /// The parent directory must already exist.
+    ///
+    /// # Errors
+    /// * `OutputError::WriteFailed` - The temporary file cannot be created
+    pub fn create(path: impl AsRef<Path>) -> Result<Self, OutputError> {
+        let path = path.as_ref().to_path_buf();
+        let dir = match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        };
+
+        let file = tempfile::Builder::new()
+            .prefix(".stylus-trace-")
+            .suffix(".tmp")
+            .tempfile_in(dir)
+            .map_err(OutputError::WriteFailed)?;
+        debug!(
+            "Writing {} via temporary file {}",
+            path.display(),
+            file.path().display()
+        );
+
+        Ok(Self {
+            writer: BufWriter::new(file),
+            path,
+        })
+    }
+
+    /// Write `contents` to `path` atomically, like `std::fs::write`
+    ///
+    /// # Errors
+    /// * `OutputError::WriteFailed` - Creating, writing or renaming failed
+    pub fn write(path: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> Result<(), OutputError> {
+        let mut file = Self::create(path)?;
+        file.write_all(contents.as_ref())
+            .map_err(OutputError::WriteFailed)?;
+        file.commit()
+    }
+
+    /// Flush the content to disk and rename it over the destination
+    ///
+    /// # Errors
+    /// * `OutputError::WriteFailed` - Flushing, syncing or renaming failed
+    pub fn commit(self) -> Result<(), OutputError> {
+        let file = self
+            .writer
+            .into_inner()
+            .map_err(|e| OutputError::WriteFailed(e.into_error()))?;
+        // Temporary files are created private (0600); give outputs the usual mode
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            file.as_file()
+                .set_permissions(std::fs::Permissions::from_mode(0o644))
+                .map_err(OutputError::WriteFailed)?;
+        }
+        file.as_file()
+            .sync_all()
+            .map_err(OutputError::WriteFailed)?;
+        file.persist(&self.path)
+            .map_err(|e| OutputError::WriteFailed(e.error))?;
+        Ok(())
+    }
+}
+
+impl Write for AtomicFile {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.writer.write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.writer.flush()
+    }
+}
@@ -2,13 +2,115 @@
 //!
 //! Writes Profile structs to JSON files with proper formatting.
 
-use crate::parser::schema::Profile;
+use super::atomic::AtomicFile;
+use crate::aggregator::call_tree::CallTreeNode;
+use crate::aggregator::stack_builder::CollapsedStack;
+use crate::parser::schema::{CaptureSummary, Profile};
 use crate::utils::error::OutputError;
 use log::{debug, info};
+use serde::Serialize;
 use std::fs::File;
-use std::io::BufWriter;
+use std::io::Write;
 use std::path::Path;
 
+/// Default indentation (spaces) of pretty-printed profile JSON
+pub const DEFAULT_JSON_INDENT: usize = 2;
+
+/// Output path that stands for standard output (`--output -`)
+pub const STDOUT_PATH: &str = "-";
+
+/// Whether an output path means "write to stdout"
+///
+/// **Public** - used by capture to route the profile and human-readable
+/// output when `--output -` is given
+pub fn is_stdout_path(path: &Path) -> bool {
+    path.as_os_str() == STDOUT_PATH
+}
+
+/// Layout of written profile JSON
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum JsonStyle {
+    /// Indented, one field per line (readable)
+    #[default]
+    Pretty,
+    /// Single line with no whitespace (smallest artifacts)
+    Compact,
+}
+
+/// Write a profile to a JSON file in the given layout
+///
+/// **Public** - used by capture for `--json-style` / `--json-indent`
+///
+/// # Arguments
+/// * `profile` - Profile data to write
+/// * `output_path` - Path to output JSON file
+/// * `style` - Pretty or compact layout
+/// * `indent` - Spaces per indentation level (ignored for compact output)
+///
+/// # Errors
+/// * `OutputError::WriteFailed` - I/O error during write
+/// * `OutputError::SerializationFailed` - JSON serialization error
+/// * `OutputError::InvalidPath` - Path cannot be created or is invalid
+pub fn write_profile_styled(
+    profile: &Profile,
+    output_path: impl AsRef<Path>,
+    style: JsonStyle,
+    indent: usize,
+) -> Result<(), OutputError> {
+    let output_path = output_path.as_ref();
+
+    if is_stdout_path(output_path) {
+        info!("Writing profile to stdout");
+        let mut stdout = std::io::stdout().lock();
+        write_profile_to_writer(profile, &mut stdout, style, indent)?;
+        stdout.write_all(b"\n").map_err(OutputError::WriteFailed)?;
+        return stdout.flush().map_err(OutputError::WriteFailed);
+    }
+
+    match style {
+        JsonStyle::Compact => write_profile_compact(profile, output_path),
+        JsonStyle::Pretty if indent == DEFAULT_JSON_INDENT => write_profile(profile, output_path),
+        JsonStyle::Pretty => {
+            info!("Writing profile to: {}", output_path.display());
+
+            let mut writer = create_output_writer(output_path)?;
+            write_profile_to_writer(profile, &mut writer, style, indent)?;
+            writer.commit()?;
+
+            info!(
+                "Profile written successfully ({} bytes)",
+                calculate_file_size(output_path)
+            );
+            Ok(())
+        }
+    }
+}
+
+/// Serialize a profile to any writer in the given layout
+///
+/// **Public** - shared by file and stdout output
+///
+/// # Errors
+/// * `OutputError::SerializationFailed` - JSON serialization or write error
+pub fn write_profile_to_writer<W: Write>(
+    profile: &Profile,
+    writer: W,
+    style: JsonStyle,
+    indent: usize,
+) -> Result<(), OutputError> {
+    match style {
+        JsonStyle::Compact => serde_json::to_writer(writer, profile),
+        JsonStyle::Pretty => {
+            let indent = " ".repeat(indent);
+            let formatter = serde_json::ser::PrettyFormatter::with_indent(indent.as_bytes());
+            profile.serialize(&mut serde_json::Serializer::with_formatter(
+                writer, formatter,
+            ))
+        }
+    }
+    .map_err(OutputError::SerializationFailed)
+}
+
 /// Write a profile to a JSON file
 ///
 /// **Public** - main entry point for JSON output
@@ -35,10 +137,158 @@ pub fn write_profile(profile: &Profile, output_path: impl AsRef<Path>) -> Result
 
     info!("Writing profile to: {}", output_path.display());
 
-    // Validate path
+    // Validate path, create parent directories and open a temporary file
+    let mut writer = create_output_writer(output_path)?;
+
+    // Serialize to JSON with pretty printing, then move it into place
+    serde_json::to_writer_pretty(&mut writer, profile).map_err(OutputError::SerializationFailed)?;
+    writer.commit()?;
+
+    info!(
+        "Profile written successfully ({} bytes)",
+        calculate_file_size(output_path)
+    );
+
+    Ok(())
+}
+
+/// Write a call tree to a JSON file
+///
+/// **Public** - used by capture for `--tree` exports
+///
+/// # Arguments
+/// * `tree` - Root node of the call tree
+/// * `output_path` - Path to output JSON file
+///
+/// # Errors
+/// * `OutputError::WriteFailed` - I/O error during write
+/// * `OutputError::SerializationFailed` - JSON serialization error
+/// * `OutputError::InvalidPath` - Path cannot be created or is invalid
+pub fn write_call_tree(
+    tree: &CallTreeNode,
+    output_path: impl AsRef<Path>,
+) -> Result<(), OutputError> {
+    let output_path = output_path.as_ref();
+
+    info!("Writing call tree to: {}", output_path.display());
+
+    let mut writer = create_output_writer(output_path)?;
+    serde_json::to_writer_pretty(&mut writer, tree).map_err(OutputError::SerializationFailed)?;
+    writer.commit()?;
+
+    Ok(())
+}
+
+/// Write raw collapsed stacks to a JSON file
+///
+/// **Public** - used by capture for `--dump-stacks` debugging output
+///
+/// # Arguments
+/// * `stacks` - Collapsed stacks (stack, weight, last_pc) as built by the aggregator
+/// * `output_path` - Path to output JSON file
+///
+/// # Errors
+/// * `OutputError::WriteFailed` - I/O error during write
+/// * `OutputError::SerializationFailed` - JSON serialization error
+/// * `OutputError::InvalidPath` - Path cannot be created or is invalid
+pub fn write_collapsed_stacks(
+    stacks: &[CollapsedStack],
+    output_path: impl AsRef<Path>,
+) -> Result<(), OutputError> {
+    let output_path = output_path.as_ref();
+
+    info!("Writing collapsed stacks to: {}", output_path.display());
+
+    let mut writer = create_output_writer(output_path)?;
+    serde_json::to_writer_pretty(&mut writer, stacks).map_err(OutputError::SerializationFailed)?;
+    writer.commit()?;
+
+    Ok(())
+}
+
+/// Write a capture summary to a compact JSON file
+///
+/// **Public** - used by capture for `--summary-json` exports
+///
+/// # Arguments
+/// * `summary` - Machine-readable capture summary
+/// * `output_path` - Path to output JSON file
+///
+/// # Errors
+/// * `OutputError::WriteFailed` - I/O error during write
+/// * `OutputError::SerializationFailed` - JSON serialization error
+/// * `OutputError::InvalidPath` - Path cannot be created or is invalid
+pub fn write_capture_summary(
+    summary: &CaptureSummary,
+    output_path: impl AsRef<Path>,
+) -> Result<(), OutputError> {
+    let output_path = output_path.as_ref();
+
+    info!("Writing capture summary to: {}", output_path.display());
+
+    let mut writer = create_output_writer(output_path)?;
+    serde_json::to_writer(&mut writer, summary).map_err(OutputError::SerializationFailed)?;
+    writer.commit()?;
+
+    Ok(())
+}
+
+/// Write hot paths as newline-delimited JSON
+///
+/// **Public** - used by capture for `--ndjson` exports
+///
+/// The first line is a metadata object (transaction, contract, block, total
+/// gas, hot path count); every following line is one hot path in profile order.
+///
+/// # Arguments
+/// * `profile` - Profile whose hot paths are streamed
+/// * `output_path` - Path to output NDJSON file
+///
+/// # Errors
+/// * `OutputError::WriteFailed` - I/O error during write
+/// * `OutputError::SerializationFailed` - JSON serialization error
+/// * `OutputError::InvalidPath` - Path cannot be created or is invalid
+pub fn write_hot_paths_ndjson(
+    profile: &Profile,
+    output_path: impl AsRef<Path>,
+) -> Result<(), OutputError> {
+    let output_path = output_path.as_ref();
+
+    info!("Writing hot paths NDJSON to: {}", output_path.display());
+
+    let mut writer = create_output_writer(output_path)?;
+
+    let metadata = serde_json::json!({
+        "version": profile.version,
+        "transaction_hash": profile.transaction_hash,
+        "label": profile.label,
+        "contract_address": profile.contract_address,
+        "block_number": profile.block_number,
+        "total_gas": profile.total_gas,
+        "generated_at": profile.generated_at,
+        "hot_path_count": profile.hot_paths.len(),
+    });
+    serde_json::to_writer(&mut writer, &metadata).map_err(OutputError::SerializationFailed)?;
+    writer.write_all(b"\n").map_err(OutputError::WriteFailed)?;
+
+    for path in &profile.hot_paths {
+        serde_json::to_writer(&mut writer, path).map_err(OutputError::SerializationFailed)?;
+        writer.write_all(b"\n").map_err(OutputError::WriteFailed)?;
+    }
+
+    writer.commit()?;
+
+    Ok(())
+}
+
+/// Validate an output path, create its parent directories and open an
+/// atomic writer for it
+///
+/// **Private** - shared by the JSON writers; callers must `commit` the
+/// writer or nothing is written
+pub(crate) fn create_output_writer(output_path: &Path) -> Result<AtomicFile, OutputError> {
     super::validate_path(output_path)?;
 
-    // Create parent directories if needed
     if let Some(parent) = output_path.parent() {
         if !parent.exists() {
             debug!("Creating parent directories: {}", parent.display());
@@ -52,13 +302,35 @@ pub fn write_profile(profile: &Profile, output_path: impl AsRef<Path>) -> Result
         }
     }
 
-    // Open file for writing
-    let file = File::create(output_path).map_err(OutputError::WriteFailed)?;
+    AtomicFile::create(output_path)
+}
+
+/// Write profile as compact JSON (no formatting)
+///
+/// **Public** - useful for when file size matters (CI artifacts, etc.)
+///
+/// # Arguments
+/// * `profile` - Profile data to write
+/// * `output_path` - Path to output JSON file
+///
+/// # Returns
+/// Ok if file written successfully
+///
+/// # Errors
+/// * `OutputError::WriteFailed` - I/O error during write
+/// * `OutputError::SerializationFailed` - JSON serialization error
+/// * `OutputError::InvalidPath` - Path cannot be created or is invalid
+pub fn write_profile_compact(
+    profile: &Profile,
+    output_path: impl AsRef<Path>,
+) -> Result<(), OutputError> {
+    let output_path = output_path.as_ref();
 
-    let writer = BufWriter::new(file);
+    info!("Writing compact profile to: {}", output_path.display());
 
-    // Serialize to JSON with pretty printing
-    serde_json::to_writer_pretty(writer, profile).map_err(OutputError::SerializationFailed)?;
+    let mut writer = create_output_writer(output_path)?;
+    serde_json::to_writer(&mut writer, profile).map_err(OutputError::SerializationFailed)?;
+    writer.commit()?;
 
     info!(
         "Profile written successfully ({} bytes)",
@@ -68,41 +340,21 @@ pub fn write_profile(profile: &Profile, output_path: impl AsRef<Path>) -> Result
     Ok(())
 }
 
-// /// Write profile as compact JSON (no formatting)
-// ///
-// /// **Public** - useful for when file size matters (CI artifacts, etc.)
-// ///
-// /// # Arguments
-// /// * `profile` - Profile data to write
-// /// * `output_path` - Path to output JSON file
-// ///
-// /// # Returns
-// /// Ok if file written successfully
-/*
-pub fn write_profile_compact(
-    profile: &Profile,
-    output_path: impl AsRef<Path>,
-) -> Result<(), OutputError> {
-    // ...
-    Ok(())
-}
-*/
-
-// /// Write profile to a string (for testing or in-memory use)
-// ///
-// /// **Public** - useful for tests and debugging
-// ///
-// /// # Arguments
-// /// * `profile` - Profile to serialize
-// ///
-// /// # Returns
-// // /// JSON string
-/*
+/// Write profile to a string (for testing or in-memory use)
+///
+/// **Public** - useful for tests and debugging
+///
+/// # Arguments
+/// * `profile` - Profile to serialize
+///
+/// # Returns
+/// Pretty-printed JSON string
+///
+/// # Errors
+/// * `OutputError::SerializationFailed` - JSON serialization error
 pub fn profile_to_string(profile: &Profile) -> Result<String, OutputError> {
-    serde_json::to_string_pretty(profile)
-        .map_err(OutputError::SerializationFailed)
+    serde_json::to_string_pretty(profile).map_err(OutputError::SerializationFailed)
 }
-*/
 
 /// Calculate file size in bytes
 ///
@@ -3,15 +3,31 @@
 //! This module handles writing data to disk in various formats:
 //! - JSON profiles (pretty and compact)
 //! - SVG flamegraphs
+//! - Speedscope profiles
 //! - Text summaries
+//! - Redaction of sensitive data before writing
+//!
+//! Files are written atomically (temporary file, then rename), so readers
+//! never see a half-written output.
 
+pub mod atomic;
 pub mod json;
+pub mod redact;
+pub mod schema_check;
+pub mod speedscope;
 pub mod svg;
 pub mod viewer;
 
 // Re-export main functions
-pub use json::{read_profile, write_profile};
-pub use svg::write_svg;
+pub use atomic::AtomicFile;
+pub use json::{
+    is_stdout_path, profile_to_string, read_profile, write_call_tree, write_capture_summary,
+    write_collapsed_stacks, write_hot_paths_ndjson, write_profile, write_profile_compact,
+    write_profile_styled, write_profile_to_writer, JsonStyle, DEFAULT_JSON_INDENT, STDOUT_PATH,
+};
+pub use redact::{load_redaction_config, RedactionConfig, Redactor};
+pub use schema_check::{check_profile_schema, check_profile_schema_file, SchemaCheck};
+pub use svg::{validate_svg, write_svg, write_svg_validated, SvgWatermark, WATERMARK_HEIGHT};
 pub use viewer::{generate_diff_viewer, generate_viewer, open_browser};
 
 use crate::utils::error::OutputError;
@@ -0,0 +1,156 @@
+//! Redaction of sensitive data from profiles before they are written.
+//!
+//! Stack frame names can embed calldata, addresses, or amounts. Redaction
+//! scrubs long hex blobs (and any user-supplied regexes) so profiles can be
+//! published as shared artifacts.
+
+use crate::aggregator::stack_builder::{rewrite_stacks, CollapsedStack};
+use crate::parser::schema::Profile;
+use crate::utils::error::OutputError;
+use log::debug;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// Redaction configuration (loadable from TOML)
+///
+/// # Example
+/// ```toml
+/// min_hex_len = 40
+/// patterns = ["secret_[a-z]+"]
+/// replacement = "[REDACTED]"
+/// ```
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RedactionConfig {
+    /// Hex strings (with or without 0x) at least this long are redacted
+    #[serde(default = "default_min_hex_len")]
+    pub min_hex_len: usize,
+
+    /// Additional regular expressions to redact
+    #[serde(default)]
+    pub patterns: Vec<String>,
+
+    /// Text substituted for every match
+    #[serde(default = "default_replacement")]
+    pub replacement: String,
+}
+
+fn default_min_hex_len() -> usize {
+    40 // Long enough to catch addresses (20 bytes) and larger blobs
+}
+
+fn default_replacement() -> String {
+    "[REDACTED]".to_string()
+}
+
+impl Default for RedactionConfig {
+    fn default() -> Self {
+        Self {
+            min_hex_len: default_min_hex_len(),
+            patterns: Vec::new(),
+            replacement: default_replacement(),
+        }
+    }
+}
+
+/// Load a redaction config from a TOML file
+///
+/// # Errors
+/// * `OutputError::WriteFailed` - File read error (reusing WriteFailed for I/O)
+/// * `OutputError::InvalidRedactionConfig` - TOML is invalid
+pub fn load_redaction_config(path: impl AsRef<Path>) -> Result<RedactionConfig, OutputError> {
+    let contents = fs::read_to_string(path).map_err(OutputError::WriteFailed)?;
+    toml::from_str(&contents).map_err(|e| OutputError::InvalidRedactionConfig(e.to_string()))
+}
+
+/// Compiled redaction rules
+#[derive(Debug, Clone)]
+pub struct Redactor {
+    patterns: Vec<Regex>,
+    replacement: String,
+}
+
+impl Redactor {
+    /// Compile a redactor from its configuration
+    ///
+    /// # Errors
+    /// * `OutputError::InvalidRedactionConfig` - A pattern is not a valid regex
+    pub fn new(config: &RedactionConfig) -> Result<Self, OutputError> {
+        let mut patterns = Vec::with_capacity(config.patterns.len() + 1);
+
+        if config.min_hex_len > 0 {
+            let hex = format!(r"(?:0x)?[0-9a-fA-F]{{{},}}", config.min_hex_len);
+            let regex = Regex::new(&hex).map_err(|e| {
+                OutputError::InvalidRedactionConfig(format!(
+                    "min_hex_len {}: {}",
+                    config.min_hex_len, e
+                ))
+            })?;
+            patterns.push(regex);
+        }
+
+        for pattern in &config.patterns {
+            let regex = Regex::new(pattern).map_err(|e| {
+                OutputError::InvalidRedactionConfig(format!("pattern '{}': {}", pattern, e))
+            })?;
+            patterns.push(regex);
+        }
+
+        Ok(Self {
+            patterns,
+            replacement: config.replacement.clone(),
+        })
+    }
+
+    /// Redact all matches in a single string
+    pub fn redact(&self, value: &str) -> String {
+        self.patterns.iter().fold(value.to_string(), |acc, regex| {
+            regex
+                .replace_all(&acc, self.replacement.as_str())
+                .into_owned()
+        })
+    }
+
+    /// Redact frame names in collapsed stacks
+    ///
+    /// Stacks that become identical once redacted are merged by summing
+    /// their weights, so each redacted stack appears once.
+    pub fn redact_stacks(&self, stacks: &mut Vec<CollapsedStack>) {
+        *stacks = rewrite_stacks(stacks, |stack| Some(self.redact(stack)));
+    }
+
+    /// Redact stack frame names, source hints and storage slots in a profile
+    pub fn redact_profile(&self, profile: &mut Profile) {
+        debug!("Redacting sensitive data from profile");
+
+        if let Some(address) = &mut profile.contract_address {
+            *address = self.redact(address);
+        }
+
+        for contract in profile.contracts.iter_mut().flatten() {
+            contract.address = self.redact(&contract.address);
+        }
+
+        for path in &mut profile.hot_paths {
+            path.stack = self.redact(&path.stack);
+            if let Some(hint) = &mut path.source_hint {
+                hint.file = self.redact(&hint.file);
+                hint.function = hint.function.as_deref().map(|f| self.redact(f));
+            }
+        }
+
+        if let Some(stacks) = &mut profile.all_stacks {
+            self.redact_stacks(stacks);
+        }
+        for slot in profile.repeated_slot_reads.iter_mut().flatten() {
+            slot.slot = self.redact(&slot.slot);
+            slot.contract = slot.contract.as_deref().map(|c| self.redact(c));
+            for stack in &mut slot.stacks {
+                *stack = self.redact(stack);
+            }
+            slot.stacks.sort();
+            slot.stacks.dedup();
+        }
+    }
+}
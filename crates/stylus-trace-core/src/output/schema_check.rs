@@ -0,0 +1,210 @@
+//! Schema skew checks for profile JSON.
+//!
+//! Profiles deserialize leniently: fields added in later schema versions
+//! fall back to serde defaults when missing, and fields this tool does not
+//! know are ignored. That keeps old and new profiles readable, but the
+//! missing data goes unnoticed. This module inspects the raw JSON to report
+//! exactly which fields were absent, defaulted or unknown.
+
+use crate::utils::config::SCHEMA_VERSION;
+use crate::utils::error::OutputError;
+use serde_json::{Map, Value};
+use std::fs::File;
+use std::path::Path;
+
+/// Top-level profile fields this tool requires
+const PROFILE_REQUIRED_FIELDS: &[&str] = &[
+    "version",
+    "transaction_hash",
+    "total_gas",
+    "hostio_summary",
+    "hot_paths",
+    "generated_at",
+];
+
+/// Top-level profile fields that are only written when known
+const PROFILE_OPTIONAL_FIELDS: &[&str] = &[
+    "label",
+    "contract_address",
+    "block_number",
+    "total_gas_source",
+    "contracts",
+    "repeated_slot_reads",
+    "all_stacks",
+    "samples",
+];
+
+/// `hostio_summary` fields this tool requires
+const HOSTIO_REQUIRED_FIELDS: &[&str] = &["total_calls", "by_type", "total_hostio_gas"];
+
+/// `hostio_summary` fields that default to empty maps when missing
+const HOSTIO_DEFAULTED_FIELDS: &[&str] = &["gas_by_type", "avg_gas_per_call"];
+
+/// Hot path fields this tool requires
+const HOT_PATH_REQUIRED_FIELDS: &[&str] = &["stack", "gas", "percentage"];
+
+/// Hot path fields that fall back to a default when missing
+const HOT_PATH_DEFAULTED_FIELDS: &[&str] = &["cumulative_gas", "category"];
+
+/// Hot path fields that are only written when known
+const HOT_PATH_OPTIONAL_FIELDS: &[&str] = &["source_hint"];
+
+/// Result of comparing a profile's JSON against the current schema
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SchemaCheck {
+    /// The profile's `version` field (empty if absent)
+    pub version: String,
+
+    /// Whether the profile was written by a newer schema than this tool's
+    pub newer_than_tool: bool,
+
+    /// Fields that are missing and fell back to a default, with a note on
+    /// how many entries were affected
+    pub defaulted: Vec<String>,
+
+    /// Optional fields that are simply not recorded in this profile
+    pub not_recorded: Vec<String>,
+
+    /// Fields this tool does not know (likely from a newer schema) and ignores
+    pub unknown: Vec<String>,
+}
+
+impl SchemaCheck {
+    /// Whether the profile has every field the current schema defines
+    /// (optional fields aside) and nothing it does not know
+    pub fn is_clean(&self) -> bool {
+        !self.newer_than_tool && self.defaulted.is_empty() && self.unknown.is_empty()
+    }
+}
+
+/// Check a profile JSON file against the current schema
+///
+/// **Public** - used by `validate --schema-check`
+///
+/// # Errors
+/// * `OutputError::WriteFailed` - File read error (reusing WriteFailed for I/O)
+/// * `OutputError::SerializationFailed` - JSON parse error
+pub fn check_profile_schema_file(path: impl AsRef<Path>) -> Result<SchemaCheck, OutputError> {
+    let file = File::open(path.as_ref()).map_err(OutputError::WriteFailed)?;
+    let raw: Value = serde_json::from_reader(file).map_err(OutputError::SerializationFailed)?;
+    Ok(check_profile_schema(&raw))
+}
+
+/// Check raw profile JSON against the current schema
+///
+/// **Public** - pure function over the parsed JSON value
+///
+/// # Returns
+/// The defaulted, unrecorded and unknown fields, and whether the profile's
+/// version is newer than `SCHEMA_VERSION`
+pub fn check_profile_schema(raw: &Value) -> SchemaCheck {
+    let empty = Map::new();
+    let profile = raw.as_object().unwrap_or(&empty);
+
+    let version = profile
+        .get("version")
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_string();
+
+    let mut check = SchemaCheck {
+        newer_than_tool: is_newer_version(&version, SCHEMA_VERSION),
+        version,
+        ..SchemaCheck::default()
+    };
+
+    for field in PROFILE_OPTIONAL_FIELDS {
+        if !profile.contains_key(*field) {
+            check.not_recorded.push(field.to_string());
+        }
+    }
+    check.unknown.extend(unknown_fields(
+        profile,
+        "",
+        &[PROFILE_REQUIRED_FIELDS, PROFILE_OPTIONAL_FIELDS],
+    ));
+
+    if let Some(summary) = profile.get("hostio_summary").and_then(Value::as_object) {
+        // Both maps are skipped when empty, so they only count as defaulted
+        // when the profile recorded HostIO calls
+        let has_calls = summary
+            .get("by_type")
+            .and_then(Value::as_object)
+            .is_some_and(|m| !m.is_empty());
+        for field in HOSTIO_DEFAULTED_FIELDS {
+            if has_calls && !summary.contains_key(*field) {
+                check.defaulted.push(format!("hostio_summary.{}", field));
+            }
+        }
+        check.unknown.extend(unknown_fields(
+            summary,
+            "hostio_summary.",
+            &[HOSTIO_REQUIRED_FIELDS, HOSTIO_DEFAULTED_FIELDS],
+        ));
+    }
+
+    let hot_paths: Vec<&Map<String, Value>> = profile
+        .get("hot_paths")
+        .and_then(Value::as_array)
+        .map(|paths| paths.iter().filter_map(Value::as_object).collect())
+        .unwrap_or_default();
+    for field in HOT_PATH_DEFAULTED_FIELDS {
+        let missing = hot_paths.iter().filter(|p| !p.contains_key(*field)).count();
+        if missing > 0 {
+            check.defaulted.push(format!(
+                "hot_paths[].{} ({} of {} entries)",
+                field,
+                missing,
+                hot_paths.len()
+            ));
+        }
+    }
+    let mut unknown_in_paths: Vec<String> = hot_paths
+        .iter()
+        .flat_map(|path| {
+            unknown_fields(
+                path,
+                "hot_paths[].",
+                &[
+                    HOT_PATH_REQUIRED_FIELDS,
+                    HOT_PATH_DEFAULTED_FIELDS,
+                    HOT_PATH_OPTIONAL_FIELDS,
+                ],
+            )
+        })
+        .collect();
+    unknown_in_paths.sort();
+    unknown_in_paths.dedup();
+    check.unknown.extend(unknown_in_paths);
+
+    check
+}
+
+/// Keys of `object` that appear in none of the `known` field lists
+///
+/// **Private** - internal helper for check_profile_schema
+fn unknown_fields(object: &Map<String, Value>, prefix: &str, known: &[&[&str]]) -> Vec<String> {
+    let mut unknown: Vec<String> = object
+        .keys()
+        .filter(|key| !known.iter().any(|fields| fields.contains(&key.as_str())))
+        .map(|key| format!("{}{}", prefix, key))
+        .collect();
+    unknown.sort();
+    unknown
+}
+
+/// Whether dotted version `version` is newer than `current`
+///
+/// **Private** - unparsable versions are never considered newer
+fn is_newer_version(version: &str, current: &str) -> bool {
+    let parse = |v: &str| {
+        v.trim_start_matches('v')
+            .split('.')
+            .map(|part| part.parse::<u64>().ok())
+            .collect::<Option<Vec<u64>>>()
+    };
+    match (parse(version), parse(current)) {
+        (Some(version), Some(current)) => version > current,
+        _ => false,
+    }
+}
@@ -0,0 +1,142 @@
+//! Speedscope profile export.
+//!
+//! Writes collapsed stacks in speedscope's file format
+//! (<https://www.speedscope.app>) as a single "sampled" profile: each
+//! collapsed stack becomes one sample whose weight is its gas, and frame
+//! names are shared between samples.
+
+use super::json::create_output_writer;
+use crate::aggregator::stack_builder::CollapsedStack;
+use crate::utils::error::OutputError;
+use log::info;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// JSON schema URL speedscope uses to recognize its own format
+pub const SPEEDSCOPE_SCHEMA: &str = "https://www.speedscope.app/file-format-schema.json";
+
+/// Top-level speedscope file
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpeedscopeFile {
+    #[serde(rename = "$schema")]
+    pub schema: &'static str,
+    pub shared: SpeedscopeShared,
+    pub profiles: Vec<SpeedscopeProfile>,
+    pub name: String,
+    pub active_profile_index: usize,
+    pub exporter: String,
+}
+
+/// Frames referenced by index from every profile
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SpeedscopeShared {
+    pub frames: Vec<SpeedscopeFrame>,
+}
+
+/// A single frame (function or HostIO call)
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SpeedscopeFrame {
+    pub name: String,
+}
+
+/// A sampled profile: `samples[i]` (frame indices, root first) weighs `weights[i]`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpeedscopeProfile {
+    #[serde(rename = "type")]
+    pub kind: &'static str,
+    pub name: String,
+    /// Always `none`: weights are gas (in ink), which speedscope has no unit for
+    pub unit: &'static str,
+    pub start_value: u64,
+    pub end_value: u64,
+    pub samples: Vec<Vec<usize>>,
+    pub weights: Vec<u64>,
+}
+
+/// Convert collapsed stacks to a speedscope file
+///
+/// **Public** - pure conversion, used by `write`
+///
+/// # Arguments
+/// * `stacks` - Collapsed stacks (weights in ink)
+/// * `name` - Profile name shown by speedscope (e.g. the transaction hash)
+///
+/// # Returns
+/// A sampled profile with one sample per non-empty stack, in input order
+pub fn to_speedscope(stacks: &[CollapsedStack], name: &str) -> SpeedscopeFile {
+    let mut frames: Vec<SpeedscopeFrame> = Vec::new();
+    let mut frame_index: HashMap<&str, usize> = HashMap::new();
+    let mut samples = Vec::with_capacity(stacks.len());
+    let mut weights = Vec::with_capacity(stacks.len());
+
+    for stack in stacks.iter().filter(|s| s.weight > 0) {
+        let sample: Vec<usize> = stack
+            .stack
+            .split(';')
+            .filter(|frame| !frame.is_empty())
+            .map(|frame| {
+                *frame_index.entry(frame).or_insert_with(|| {
+                    frames.push(SpeedscopeFrame {
+                        name: frame.to_string(),
+                    });
+                    frames.len() - 1
+                })
+            })
+            .collect();
+        if sample.is_empty() {
+            continue;
+        }
+        samples.push(sample);
+        weights.push(stack.weight);
+    }
+
+    SpeedscopeFile {
+        schema: SPEEDSCOPE_SCHEMA,
+        shared: SpeedscopeShared { frames },
+        profiles: vec![SpeedscopeProfile {
+            kind: "sampled",
+            name: name.to_string(),
+            unit: "none",
+            start_value: 0,
+            end_value: weights.iter().fold(0, |acc: u64, w| acc.saturating_add(*w)),
+            samples,
+            weights,
+        }],
+        name: name.to_string(),
+        active_profile_index: 0,
+        exporter: format!("stylus-trace-studio@{}", env!("CARGO_PKG_VERSION")),
+    }
+}
+
+/// Write collapsed stacks as a speedscope JSON file
+///
+/// **Public** - used by capture for `--speedscope` exports
+///
+/// # Arguments
+/// * `stacks` - Collapsed stacks (weights in ink)
+/// * `name` - Profile name shown by speedscope
+/// * `output_path` - Path to output JSON file
+///
+/// # Errors
+/// * `OutputError::WriteFailed` - I/O error during write
+/// * `OutputError::SerializationFailed` - JSON serialization error
+/// * `OutputError::InvalidPath` - Path cannot be created or is invalid
+pub fn write(
+    stacks: &[CollapsedStack],
+    name: &str,
+    output_path: impl AsRef<Path>,
+) -> Result<(), OutputError> {
+    let output_path = output_path.as_ref();
+
+    info!("Writing speedscope profile to: {}", output_path.display());
+
+    let mut writer = create_output_writer(output_path)?;
+    serde_json::to_writer(&mut writer, &to_speedscope(stacks, name))
+        .map_err(OutputError::SerializationFailed)?;
+    writer.commit()?;
+
+    Ok(())
+}
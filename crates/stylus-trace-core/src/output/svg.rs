@@ -1,13 +1,58 @@
 //! SVG flamegraph output writer.
 //!
-//! Writes SVG content to files with proper encoding.
+//! Writes SVG content to files with proper encoding, optionally checking
+//! that it is well-formed XML first, and renders the provenance watermark
+//! embedded in flamegraphs.
 
+use super::atomic::AtomicFile;
+use crate::flamegraph::generator::escape_xml;
 use crate::utils::error::OutputError;
 use log::{debug, info};
-use std::fs::File;
-use std::io::{BufWriter, Write};
+use std::io::Write;
 use std::path::Path;
 
+/// Vertical space reserved below a flamegraph for the watermark footer
+pub const WATERMARK_HEIGHT: usize = 16;
+
+/// Provenance embedded in a flamegraph so it documents itself when shared
+/// without its profile JSON
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SvgWatermark {
+    /// Transaction the flamegraph was captured from
+    pub transaction_hash: String,
+
+    /// Generation timestamp (ISO 8601)
+    pub generated_at: String,
+}
+
+impl SvgWatermark {
+    /// `<metadata>` element carrying the tool version, transaction and timestamp
+    ///
+    /// **Public** - machine-readable counterpart of the footer
+    pub fn metadata(&self) -> String {
+        format!(
+            r#"<metadata><stylus-trace xmlns="https://github.com/CreativesOnchain/Stylus-Trace" version="{}" transaction="{}" generated-at="{}"/></metadata>"#,
+            env!("CARGO_PKG_VERSION"),
+            escape_xml(&self.transaction_hash),
+            escape_xml(&self.generated_at)
+        )
+    }
+
+    /// Subtle footer `<text>`, right-aligned to `width` with its baseline at `y`
+    ///
+    /// **Public** - rendered in the `WATERMARK_HEIGHT` band below the legend
+    pub fn footer(&self, width: usize, y: usize) -> String {
+        format!(
+            r#"<text x="{}" y="{}" font-size="10" fill="gray" opacity="0.7" text-anchor="end">stylus-trace v{} · {} · {}</text>"#,
+            width.saturating_sub(10),
+            y,
+            env!("CARGO_PKG_VERSION"),
+            escape_xml(&self.transaction_hash),
+            escape_xml(&self.generated_at)
+        )
+    }
+}
+
 /// Write SVG content to a file
 ///
 /// **Public** - main entry point for SVG output
@@ -45,17 +90,12 @@ pub fn write_svg(svg_content: &str, output_path: impl AsRef<Path>) -> Result<(),
         }
     }
 
-    // Open file for writing
-    let file = File::create(output_path).map_err(OutputError::WriteFailed)?;
-
-    let mut writer = BufWriter::new(file);
-
-    // Write SVG content
+    // Write to a temporary file and move it into place once complete
+    let mut writer = AtomicFile::create(output_path)?;
     writer
         .write_all(svg_content.as_bytes())
         .map_err(OutputError::WriteFailed)?;
-
-    writer.flush().map_err(OutputError::WriteFailed)?;
+    writer.commit()?;
 
     let file_size = svg_content.len();
     info!(
@@ -66,3 +106,157 @@ pub fn write_svg(svg_content: &str, output_path: impl AsRef<Path>) -> Result<(),
 
     Ok(())
 }
+
+/// Validate SVG content, then write it to a file
+///
+/// **Public** - used by capture and diff so a broken flamegraph surfaces as
+/// an error instead of an unopenable file
+///
+/// # Errors
+/// * `OutputError::InvalidSvg` - Content is not well-formed SVG (nothing is written)
+/// * `OutputError::WriteFailed` - I/O error during write
+/// * `OutputError::InvalidPath` - Path is invalid
+pub fn write_svg_validated(
+    svg_content: &str,
+    output_path: impl AsRef<Path>,
+) -> Result<(), OutputError> {
+    validate_svg(svg_content)?;
+    write_svg(svg_content, output_path)
+}
+
+/// Check that SVG content is well-formed
+///
+/// **Public** - a lightweight XML check, not a full parser
+///
+/// Verifies a single `<svg>` root, balanced and properly nested tags, quoted
+/// attribute values without raw `<`, and that every `&` starts an entity
+/// reference. Comments, CDATA sections, processing instructions and
+/// doctypes are skipped.
+///
+/// # Errors
+/// * `OutputError::InvalidSvg` - Describes the first problem found
+pub fn validate_svg(svg_content: &str) -> Result<(), OutputError> {
+    let invalid = |msg: String| Err(OutputError::InvalidSvg(msg));
+
+    let mut open: Vec<&str> = Vec::new();
+    let mut saw_root = false;
+    let mut rest = svg_content;
+
+    while let Some(lt) = rest.find('<') {
+        check_entities(&rest[..lt])?;
+        rest = &rest[lt..];
+
+        let skipped = [
+            ("<!--", "-->"),
+            ("<![CDATA[", "]]>"),
+            ("<?", "?>"),
+            ("<!", ">"),
+        ]
+        .iter()
+        .find(|(start, _)| rest.starts_with(start));
+        if let Some((start, end)) = skipped {
+            let Some(close) = rest[start.len()..].find(end) else {
+                return invalid(format!("unterminated `{}`", start));
+            };
+            rest = &rest[start.len() + close + end.len()..];
+            continue;
+        }
+
+        let gt = tag_end(rest)?;
+        let tag = &rest[1..gt];
+        rest = &rest[gt + 1..];
+
+        if let Some(name) = tag.strip_prefix('/') {
+            let name = name.trim();
+            match open.pop() {
+                Some(expected) if expected == name => {}
+                Some(expected) => {
+                    return invalid(format!("</{}> closes <{}>", name, expected));
+                }
+                None => return invalid(format!("</{}> has no matching open tag", name)),
+            }
+            continue;
+        }
+
+        let name = tag
+            .split(|c: char| c.is_whitespace() || c == '/')
+            .next()
+            .unwrap_or_default();
+        if name.is_empty() {
+            return invalid("tag without a name (unescaped `<`?)".to_string());
+        }
+        check_entities(tag)?;
+
+        if open.is_empty() {
+            if saw_root {
+                return invalid(format!("<{}> after the root element", name));
+            }
+            if name != "svg" {
+                return invalid(format!("root element is <{}>, expected <svg>", name));
+            }
+            saw_root = true;
+        }
+        if !tag.ends_with('/') {
+            open.push(name);
+        }
+    }
+    check_entities(rest)?;
+
+    if let Some(unclosed) = open.last() {
+        return invalid(format!("<{}> is never closed", unclosed));
+    }
+    if !saw_root {
+        return invalid("no <svg> element".to_string());
+    }
+    Ok(())
+}
+
+/// Index of the `>` closing the tag that starts at `tag[0]`
+///
+/// **Private** - `>` inside quoted attribute values does not end the tag
+fn tag_end(tag: &str) -> Result<usize, OutputError> {
+    let mut quote = None;
+    for (i, c) in tag.char_indices().skip(1) {
+        match (quote, c) {
+            (_, '<') => {
+                return Err(OutputError::InvalidSvg(
+                    "unescaped `<` inside a tag".to_string(),
+                ))
+            }
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), _) if q == c => quote = None,
+            (None, '>') => return Ok(i),
+            _ => {}
+        }
+    }
+    Err(OutputError::InvalidSvg("unterminated tag".to_string()))
+}
+
+/// Check that every `&` in `text` starts an entity reference
+///
+/// **Private** - catches labels that were written without XML escaping
+fn check_entities(text: &str) -> Result<(), OutputError> {
+    for (i, _) in text.match_indices('&') {
+        let after = &text[i + 1..];
+        let end = after.find(';').unwrap_or(after.len());
+        let name = &after[..end];
+        let valid = end < after.len()
+            && match name.strip_prefix('#') {
+                Some(hex) if hex.starts_with('x') => {
+                    hex.len() > 1 && hex[1..].chars().all(|c| c.is_ascii_hexdigit())
+                }
+                Some(dec) => !dec.is_empty() && dec.chars().all(|c| c.is_ascii_digit()),
+                None => {
+                    name.starts_with(|c: char| c.is_ascii_alphabetic())
+                        && name.chars().all(|c| c.is_ascii_alphanumeric())
+                }
+            };
+        if !valid {
+            return Err(OutputError::InvalidSvg(format!(
+                "unescaped `&` near `{}`",
+                text[i..].chars().take(16).collect::<String>()
+            )));
+        }
+    }
+    Ok(())
+}
@@ -611,10 +611,11 @@ function updateUI() {
     const profA = window.app.profileA;
     const profB = window.app.profileB;
 
-    // Hashes
-    document.getElementById('hash-a').textContent = profA.transaction_hash;
+    // Labels (falling back to hashes)
+    const nameOf = (prof) => prof.label || prof.transaction_hash;
+    document.getElementById('hash-a').textContent = nameOf(profA);
     if (profB) {
-        document.getElementById('hash-b').textContent = profB.transaction_hash;
+        document.getElementById('hash-b').textContent = nameOf(profB);
         document.getElementById('gas-label').textContent = 'GAS_DELTA:';
         document.getElementById('hostio-label').textContent = 'HOST_IO_DELTA:';
     } else {
@@ -651,9 +652,10 @@ function updateUI() {
         document.getElementById('hostio-delta').textContent = ioA.toLocaleString();
     }
 
+    const shortName = (prof, len) => prof.label || prof.transaction_hash.slice(0, len) + '...';
     const profileName = profB ?
-        `${profA.transaction_hash.slice(0, 8)}... vs ${profB.transaction_hash.slice(0, 8)}...` :
-        profA.transaction_hash.slice(0, 10) + '...';
+        `${shortName(profA, 8)} vs ${shortName(profB, 8)}` :
+        shortName(profA, 10);
     document.getElementById('profile-name').textContent = profileName;
 
     // Collect symbols for autocomplete with safety guards
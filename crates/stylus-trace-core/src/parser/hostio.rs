@@ -3,12 +3,15 @@
 //! HostIO events represent calls from WASM to the Stylus VM runtime.
 //! Common types: storage_read, storage_write, call, log, etc.
 
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::collections::HashMap;
 
 /// Type of HostIO operation
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
-#[serde(rename_all = "snake_case")]
+///
+/// `as_str` gives the canonical name used everywhere a type is keyed by
+/// string (profile summaries, diffs, serde); `from_str` accepts that name
+/// plus opcode and tracer aliases, so the two round-trip for every variant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum HostIoType {
     StorageLoad,
     StorageStore,
@@ -60,7 +63,127 @@ impl std::str::FromStr for HostIoType {
     }
 }
 
+impl std::fmt::Display for HostIoType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Serialize for HostIoType {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for HostIoType {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        Ok(name.parse().unwrap_or(Self::Other))
+    }
+}
+
 impl HostIoType {
+    /// Every variant, in declaration order
+    pub const ALL: [HostIoType; 19] = [
+        HostIoType::StorageLoad,
+        HostIoType::StorageStore,
+        HostIoType::StorageFlush,
+        HostIoType::StorageCache,
+        HostIoType::Call,
+        HostIoType::StaticCall,
+        HostIoType::DelegateCall,
+        HostIoType::Create,
+        HostIoType::Log,
+        HostIoType::SelfDestruct,
+        HostIoType::AccountBalance,
+        HostIoType::BlockHash,
+        HostIoType::NativeKeccak256,
+        HostIoType::ReadArgs,
+        HostIoType::WriteResult,
+        HostIoType::MsgValue,
+        HostIoType::MsgSender,
+        HostIoType::MsgReentrant,
+        HostIoType::Other,
+    ];
+
+    /// Canonical name used as the key in profile summaries
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HostIoType::StorageLoad => "storage_load",
+            HostIoType::StorageStore => "storage_store",
+            HostIoType::StorageFlush => "storage_flush_cache",
+            HostIoType::StorageCache => "storage_cache",
+            HostIoType::Call => "call",
+            HostIoType::StaticCall => "staticcall",
+            HostIoType::DelegateCall => "delegatecall",
+            HostIoType::Create => "create",
+            HostIoType::Log => "emit_log",
+            HostIoType::SelfDestruct => "selfdestruct",
+            HostIoType::AccountBalance => "account_balance",
+            HostIoType::BlockHash => "block_hash",
+            HostIoType::NativeKeccak256 => "native_keccak256",
+            HostIoType::ReadArgs => "read_args",
+            HostIoType::WriteResult => "write_result",
+            HostIoType::MsgValue => "msg_value",
+            HostIoType::MsgSender => "msg_sender",
+            HostIoType::MsgReentrant => "msg_reentrant",
+            HostIoType::Other => "other",
+        }
+    }
+
+    /// Plain-language note on what the operation costs and how to reduce it
+    ///
+    /// Used by `capture --explain`; `None` for `Other`.
+    pub fn explanation(&self) -> Option<&'static str> {
+        Some(match self {
+            HostIoType::StorageLoad => {
+                "reading a storage slot; the first read of a slot is cold and costs far more than later warm reads, so cache values in memory"
+            }
+            HostIoType::StorageStore => {
+                "writing a storage slot; writing a fresh slot is the most expensive host op, so batch writes and avoid rewriting unchanged values"
+            }
+            HostIoType::StorageFlush => {
+                "persisting cached storage writes at the end of a call; cost grows with every dirty slot, so touch fewer slots"
+            }
+            HostIoType::StorageCache => {
+                "staging a storage write in the VM cache; cheap on its own, but each cached slot is paid for again when flushed"
+            }
+            HostIoType::Call => {
+                "calling another contract; pays a fixed call overhead plus the callee's execution, so batch calls where possible"
+            }
+            HostIoType::StaticCall => {
+                "read-only call to another contract; same overhead as a call, so cache results rather than repeating it"
+            }
+            HostIoType::DelegateCall => {
+                "running another contract's code in this contract's storage; proxy hops add call overhead on every invocation"
+            }
+            HostIoType::Create => {
+                "deploying a contract; one of the most expensive operations, dominated by code size"
+            }
+            HostIoType::Log => {
+                "emitting an event; cost grows with the number of topics and the size of the data"
+            }
+            HostIoType::SelfDestruct => "destroying the contract; rarely needed",
+            HostIoType::AccountBalance => {
+                "reading an account balance; cold accounts cost more, so read it once"
+            }
+            HostIoType::BlockHash => "reading a recent block hash; cheap but not free",
+            HostIoType::NativeKeccak256 => {
+                "hashing in the host; cost grows with input length, so hash fewer bytes or cache mapping keys"
+            }
+            HostIoType::ReadArgs => {
+                "copying calldata into WASM memory; cost grows with calldata size, so keep arguments compact"
+            }
+            HostIoType::WriteResult => {
+                "copying return data out of WASM memory; cost grows with the size of the result"
+            }
+            HostIoType::MsgValue => "reading the call value; cheap, read it once",
+            HostIoType::MsgSender => "reading the caller address; cheap, read it once",
+            HostIoType::MsgReentrant => "checking for reentrancy; cheap",
+            HostIoType::Other => return None,
+        })
+    }
+
     /// Try to map an EVM opcode or instruction to a HostIO type
     pub fn from_opcode(op: &str) -> Option<Self> {
         match op.to_uppercase().as_str() {
@@ -90,6 +213,7 @@ pub struct HostIoEvent {
 #[derive(Debug, Clone)]
 pub struct HostIoStats {
     counts: HashMap<HostIoType, u64>,
+    gas_by_type: HashMap<HostIoType, u64>,
     total_gas: u64,
 }
 
@@ -98,6 +222,7 @@ impl HostIoStats {
     pub fn new() -> Self {
         Self {
             counts: HashMap::new(),
+            gas_by_type: HashMap::new(),
             total_gas: 0,
         }
     }
@@ -105,7 +230,9 @@ impl HostIoStats {
     /// Add a HostIO event to the statistics
     pub fn add_event(&mut self, event: HostIoEvent) {
         *self.counts.entry(event.io_type).or_insert(0) += 1;
-        self.total_gas += event.gas_cost;
+        let gas = self.gas_by_type.entry(event.io_type).or_insert(0);
+        *gas = gas.saturating_add(event.gas_cost);
+        self.total_gas = self.total_gas.saturating_add(event.gas_cost);
     }
 
     /// Get total number of HostIO calls
@@ -118,6 +245,27 @@ impl HostIoStats {
         self.counts.get(&io_type).copied().unwrap_or(0)
     }
 
+    /// Get gas consumed by a specific HostIO type
+    pub fn gas_for_type(&self, io_type: HostIoType) -> u64 {
+        self.gas_by_type.get(&io_type).copied().unwrap_or(0)
+    }
+
+    /// Get average gas per call for a specific HostIO type
+    ///
+    /// Returns `None` if the type was never called
+    pub fn avg_gas_for_type(&self, io_type: HostIoType) -> Option<f64> {
+        let calls = self.count_for_type(io_type);
+        (calls > 0).then(|| self.gas_for_type(io_type) as f64 / calls as f64)
+    }
+
+    /// Get gas consumed per HostIO type, sorted by gas (descending)
+    pub fn gas_breakdown(&self) -> Vec<(HostIoType, u64)> {
+        let mut breakdown: Vec<(HostIoType, u64)> =
+            self.gas_by_type.iter().map(|(k, v)| (*k, *v)).collect();
+        breakdown.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.as_str().cmp(b.0.as_str())));
+        breakdown
+    }
+
     /// Get total gas consumed by HostIO
     pub fn total_gas(&self) -> u64 {
         self.total_gas
@@ -127,39 +275,28 @@ impl HostIoStats {
     pub fn to_map(&self) -> HashMap<String, u64> {
         self.counts
             .iter()
-            .map(|(k, v)| {
-                let name = match k {
-                    HostIoType::StorageLoad => "storage_load",
-                    HostIoType::StorageStore => "storage_store",
-                    HostIoType::StorageFlush => "storage_flush_cache",
-                    HostIoType::StorageCache => "storage_cache",
-                    HostIoType::Call => "call",
-                    HostIoType::StaticCall => "staticcall",
-                    HostIoType::DelegateCall => "delegatecall",
-                    HostIoType::Create => "create",
-                    HostIoType::Log => "emit_log",
-                    HostIoType::SelfDestruct => "selfdestruct",
-                    HostIoType::AccountBalance => "account_balance",
-                    HostIoType::BlockHash => "block_hash",
-                    HostIoType::NativeKeccak256 => "native_keccak256",
-                    HostIoType::ReadArgs => "read_args",
-                    HostIoType::WriteResult => "write_result",
-                    HostIoType::MsgValue => "msg_value",
-                    HostIoType::MsgSender => "msg_sender",
-                    HostIoType::MsgReentrant => "msg_reentrant",
-                    HostIoType::Other => "other",
-                };
-                (name.to_string(), *v)
-            })
+            .map(|(k, v)| (k.as_str().to_string(), *v))
+            .collect()
+    }
+
+    /// Convert gas per type to a map for JSON serialization
+    pub fn to_gas_map(&self) -> HashMap<String, u64> {
+        self.gas_by_type
+            .iter()
+            .map(|(k, v)| (k.as_str().to_string(), *v))
             .collect()
     }
 
     /// Convert to summary for inclusion in the final profile
     pub fn to_summary(&self) -> super::schema::HostIoSummary {
+        let by_type = self.to_map();
+        let gas_by_type = self.to_gas_map();
         super::schema::HostIoSummary {
             total_calls: self.total_calls(),
-            by_type: self.to_map(),
+            avg_gas_per_call: super::schema::avg_gas_per_call(&by_type, &gas_by_type),
+            by_type,
             total_hostio_gas: self.total_gas(),
+            gas_by_type,
         }
     }
 }
@@ -4,13 +4,19 @@
 //! - Parsing raw JSON from stylusTracer
 //! - Extracting HostIO events
 //! - Validating trace format
+//! - Decoding function selectors from contract ABIs
 //! - Defining output schema
 
 pub mod hostio;
 pub mod schema;
+pub mod selector_map;
 pub mod source_map;
 pub mod stylus_trace;
 
 // Re-export main types
 pub use hostio::HostIoType;
-pub use stylus_trace::{parse_trace, to_profile, ParsedTrace};
+pub use selector_map::{load_selector_map, SelectorMap};
+pub use stylus_trace::{
+    normalize_to_ink, parse_trace, parse_trace_with_limit, parse_trace_with_unit,
+    repeated_slot_reads, to_profile, GasUnit, ParsedTrace, TraceFormat,
+};
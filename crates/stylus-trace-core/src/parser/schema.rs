@@ -4,14 +4,15 @@
 //! Schema is versioned to allow future evolution.
 
 use crate::aggregator::stack_builder::CollapsedStack;
+use crate::parser::hostio::HostIoType;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 /// A category describing what type of operation a hot path primarily performs.
 ///
 /// This is computed server-side from `HostIoType` knowledge so the frontend
 /// does not need brittle substring-matching heuristics.
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, Default)]
 #[serde(rename_all = "PascalCase")]
 pub enum GasCategory {
     /// Expensive storage writes (flush/store)
@@ -44,12 +45,36 @@ pub struct Profile {
     /// Transaction hash that was profiled
     pub transaction_hash: String,
 
+    /// Free-text name for the profile (`--label`), e.g. `with-cache`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+
+    /// Address of the called contract (if known)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contract_address: Option<String>,
+
+    /// Block the transaction was included in (if known)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub block_number: Option<u64>,
+
     /// Total gas used by the transaction
     pub total_gas: u64,
 
+    /// Whether `total_gas` was reported by the node or summed from steps
+    #[serde(default, skip_serializing_if = "TotalGasSource::is_reported")]
+    pub total_gas_source: TotalGasSource,
+
     /// Summary of HostIO events by category
     pub hostio_summary: HostIoSummary,
 
+    /// Gas attributed to each contract in the call tree (`--split-by-contract`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contracts: Option<Vec<ContractGas>>,
+
+    /// Storage slots read more than once (only when the tracer reports slot keys)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repeated_slot_reads: Option<Vec<SlotReads>>,
+
     /// Top hot paths (ranked by gas usage)
     pub hot_paths: Vec<HotPath>,
 
@@ -57,21 +82,260 @@ pub struct Profile {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub all_stacks: Option<Vec<CollapsedStack>>,
 
+    /// Sample statistics when this profile averages several captures
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub samples: Option<SampleStats>,
+
     /// Timestamp when profile was generated
     pub generated_at: String,
 }
 
+/// Provenance of a profile's `total_gas`
+///
+/// Traces without a gas field get a total summed from the step costs. That
+/// estimate misses intrinsic and unattributed gas, so it is not comparable
+/// with a total the node reported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum TotalGasSource {
+    /// Taken from the gas field of the trace
+    #[default]
+    Reported,
+    /// Summed from execution step costs because the trace had no gas field
+    DerivedFromSteps,
+}
+
+impl TotalGasSource {
+    /// Whether the total was reported by the node
+    pub fn is_reported(&self) -> bool {
+        *self == TotalGasSource::Reported
+    }
+}
+
+impl std::fmt::Display for TotalGasSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TotalGasSource::Reported => write!(f, "reported by node"),
+            TotalGasSource::DerivedFromSteps => write!(f, "derived from step costs"),
+        }
+    }
+}
+
+/// Spread of the captures averaged into a merged profile
+///
+/// Standard deviations are population deviations in Ink.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SampleStats {
+    /// Number of profiles that were merged
+    pub count: usize,
+
+    /// Standard deviation of `total_gas`
+    pub total_gas_stddev: f64,
+
+    /// Standard deviation of the total HostIO call count
+    pub hostio_calls_stddev: f64,
+
+    /// Standard deviation of the total HostIO gas
+    pub hostio_gas_stddev: f64,
+
+    /// Standard deviation of each kept hot path's self gas, keyed by stack
+    pub hot_path_gas_stddev: BTreeMap<String, f64>,
+
+    /// Hot paths dropped because they were missing from at least one sample
+    pub dropped_hot_paths: usize,
+}
+
+/// Machine-readable capture summary (compact companion to the text summary)
+///
+/// All gas values are in Ink, matching `Profile::total_gas`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CaptureSummary {
+    /// Transaction hash that was profiled
+    pub transaction_hash: String,
+
+    /// Total gas used by the transaction
+    pub total_gas: u64,
+
+    /// Gas attributed to execution stacks
+    pub execution_gas: u64,
+
+    /// Gas not attributed to any execution stack (total - execution)
+    pub intrinsic_gas: u64,
+
+    /// Total number of HostIO calls
+    pub hostio_calls: u64,
+
+    /// Number of unique execution stacks
+    pub unique_paths: usize,
+
+    /// Top hot paths (ranked as in the profile)
+    pub top_paths: Vec<HotPath>,
+}
+
 /// Summary statistics for HostIO events
+///
+/// The per-type maps are written with their keys sorted, so profiles of the
+/// same trace serialize identically.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HostIoSummary {
     /// Total number of HostIO calls
     pub total_calls: u64,
 
     /// Breakdown by HostIO type
+    #[serde(serialize_with = "serialize_sorted")]
     pub by_type: HashMap<String, u64>,
 
     /// Total gas consumed by HostIO operations
     pub total_hostio_gas: u64,
+
+    /// Gas consumed per HostIO type
+    #[serde(
+        default,
+        skip_serializing_if = "HashMap::is_empty",
+        serialize_with = "serialize_sorted"
+    )]
+    pub gas_by_type: HashMap<String, u64>,
+
+    /// Average gas per call for each HostIO type
+    #[serde(
+        default,
+        skip_serializing_if = "HashMap::is_empty",
+        serialize_with = "serialize_sorted"
+    )]
+    pub avg_gas_per_call: HashMap<String, f64>,
+}
+
+/// Serialize a map with its keys in sorted order
+fn serialize_sorted<S: serde::Serializer, V: Serialize>(
+    map: &HashMap<String, V>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    map.iter().collect::<BTreeMap<_, _>>().serialize(serializer)
+}
+
+impl HostIoSummary {
+    /// Average gas per call for a HostIO type
+    ///
+    /// **Public** - used by the diff engine to separate call-count changes
+    /// from per-call cost changes
+    ///
+    /// Falls back to `gas_by_type / by_type` when `avg_gas_per_call` is
+    /// missing (profiles written before it was recorded).
+    ///
+    /// # Returns
+    /// `None` if the type was never called or its gas is unknown
+    pub fn avg_gas_for_type(&self, io_type: &str) -> Option<f64> {
+        if let Some(avg) = self.avg_gas_per_call.get(io_type) {
+            return Some(*avg);
+        }
+        let calls = *self.by_type.get(io_type)?;
+        let gas = *self.gas_by_type.get(io_type)?;
+        (calls > 0).then(|| gas as f64 / calls as f64)
+    }
+
+    /// Keep the `max_types` most frequent HostIO types and fold the rest
+    /// into the `other` bucket
+    ///
+    /// **Public** - used by capture for `--top-hostio-types`
+    ///
+    /// Types are ranked by call count, then by gas, then by name. Folded
+    /// calls and gas are added to `other` (merging with any calls already
+    /// classified as `other`), so the totals are unchanged.
+    ///
+    /// # Arguments
+    /// * `max_types` - Types to keep besides `other` (`0` keeps all)
+    pub fn keep_top_types(&mut self, max_types: usize) {
+        let other = HostIoType::Other.as_str();
+        let mut ranked: Vec<(String, u64)> = self
+            .by_type
+            .iter()
+            .filter(|(io_type, _)| io_type.as_str() != other)
+            .map(|(io_type, calls)| (io_type.clone(), *calls))
+            .collect();
+        if max_types == 0 || ranked.len() <= max_types {
+            return;
+        }
+
+        let gas = |io_type: &str| self.gas_by_type.get(io_type).copied().unwrap_or(0);
+        ranked.sort_by(|a, b| {
+            b.1.cmp(&a.1)
+                .then_with(|| gas(&b.0).cmp(&gas(&a.0)))
+                .then_with(|| a.0.cmp(&b.0))
+        });
+
+        // Profiles written before averages were recorded stay without them
+        let has_averages = !self.avg_gas_per_call.is_empty();
+        for (io_type, calls) in ranked.into_iter().skip(max_types) {
+            self.by_type.remove(&io_type);
+            let other_calls = self.by_type.entry(other.to_string()).or_insert(0);
+            *other_calls = other_calls.saturating_add(calls);
+            if let Some(gas) = self.gas_by_type.remove(&io_type) {
+                let other_gas = self.gas_by_type.entry(other.to_string()).or_insert(0);
+                *other_gas = other_gas.saturating_add(gas);
+            }
+        }
+
+        if has_averages {
+            self.avg_gas_per_call = avg_gas_per_call(&self.by_type, &self.gas_by_type);
+        }
+    }
+}
+
+/// Average gas per call for every type with at least one call
+///
+/// **Public** - shared by HostIO stats and profile merging
+///
+/// # Arguments
+/// * `by_type` - Call counts per HostIO type
+/// * `gas_by_type` - Gas per HostIO type
+pub fn avg_gas_per_call(
+    by_type: &HashMap<String, u64>,
+    gas_by_type: &HashMap<String, u64>,
+) -> HashMap<String, f64> {
+    gas_by_type
+        .iter()
+        .filter_map(|(io_type, gas)| {
+            let calls = *by_type.get(io_type)?;
+            (calls > 0).then(|| (io_type.clone(), *gas as f64 / calls as f64))
+        })
+        .collect()
+}
+
+/// Gas attributed to a single contract in the call tree
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContractGas {
+    /// Contract address (or `entry` / `unknown_contract` when not reported)
+    pub address: String,
+
+    /// Gas consumed while this contract was executing, excluding sub-calls
+    pub gas: u64,
+
+    /// Percentage of execution gas
+    pub percentage: f64,
+}
+
+/// Reads of a storage slot that was loaded more than once in the transaction
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SlotReads {
+    /// Contract whose storage was read (`entry` when the target is unknown)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contract: Option<String>,
+
+    /// Storage slot key (32-byte hex)
+    pub slot: String,
+
+    /// Number of reads of the slot
+    pub reads: u64,
+
+    /// Gas of all reads of the slot (in ink)
+    pub gas: u64,
+
+    /// Gas of every read after the first (in ink), saved by caching the value
+    pub cacheable_gas: u64,
+
+    /// Stacks of the steps that read the slot, as they appear in `all_stacks`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub stacks: Vec<String>,
 }
 
 /// A hot path in the execution (stack trace with gas)
@@ -80,9 +344,13 @@ pub struct HotPath {
     /// Collapsed stack representation (e.g., "main;execute;storage_read")
     pub stack: String,
 
-    /// Gas consumed by this path
+    /// Gas consumed by this exact path (self gas of the leaf frame)
     pub gas: u64,
 
+    /// Gas consumed by this path including all descendant stacks
+    #[serde(default)]
+    pub cumulative_gas: u64,
+
     /// Percentage of total gas
     pub percentage: f64,
 
@@ -0,0 +1,158 @@
+//! Function selector decoding from contract ABIs.
+//!
+//! Frames derived from call selectors appear as raw 4-byte hex
+//! (e.g. `0xa9059cbb`). A selector map built from the contract ABI lets
+//! those frames be relabelled with the function signature
+//! (e.g. `transfer(address,uint256)`).
+
+use crate::utils::error::ParseError;
+use log::debug;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use tiny_keccak::{Hasher, Keccak};
+
+/// Mapping from 4-byte selectors to function signatures
+///
+/// **Public** - used by capture for `--selector-map`
+#[derive(Debug, Clone, Default)]
+pub struct SelectorMap {
+    /// Lowercase `0x`-prefixed selector -> canonical signature
+    signatures: HashMap<String, String>,
+}
+
+impl SelectorMap {
+    /// Build a selector map from a parsed ABI
+    ///
+    /// **Public** - accepts a plain ABI array or a build artifact with an `abi` field
+    ///
+    /// # Errors
+    /// * `ParseError::InvalidAbi` - No ABI array found, or a function entry is malformed
+    pub fn from_abi(abi: &Value) -> Result<Self, ParseError> {
+        let entries = match abi {
+            Value::Array(entries) => entries,
+            Value::Object(obj) => obj.get("abi").and_then(Value::as_array).ok_or_else(|| {
+                ParseError::InvalidAbi("expected an ABI array or an object with `abi`".to_string())
+            })?,
+            _ => {
+                return Err(ParseError::InvalidAbi(
+                    "expected an ABI array or an object with `abi`".to_string(),
+                ))
+            }
+        };
+
+        let mut signatures = HashMap::new();
+        for entry in entries {
+            if entry.get("type").and_then(Value::as_str) != Some("function") {
+                continue;
+            }
+
+            let signature = function_signature(entry)?;
+            signatures.insert(selector_hex(&signature), signature);
+        }
+
+        debug!("Loaded {} function selectors from ABI", signatures.len());
+        Ok(Self { signatures })
+    }
+
+    /// Look up the signature for a frame name, if it is a known selector
+    ///
+    /// Matching is case-insensitive and requires the `0x` prefix.
+    pub fn lookup(&self, frame: &str) -> Option<&str> {
+        if frame.len() != 10 || !frame.starts_with("0x") {
+            return None;
+        }
+        self.signatures
+            .get(&frame.to_lowercase())
+            .map(String::as_str)
+    }
+
+    /// Number of known selectors
+    pub fn len(&self) -> usize {
+        self.signatures.len()
+    }
+
+    /// Whether the map has no selectors
+    pub fn is_empty(&self) -> bool {
+        self.signatures.is_empty()
+    }
+}
+
+/// Load a selector map from an ABI JSON file
+///
+/// **Public** - used by capture for `--selector-map`
+///
+/// # Errors
+/// * `ParseError::AbiReadFailed` - File could not be read
+/// * `ParseError::JsonError` - File is not valid JSON
+/// * `ParseError::InvalidAbi` - JSON is not a usable ABI
+pub fn load_selector_map(path: impl AsRef<Path>) -> Result<SelectorMap, ParseError> {
+    let contents = fs::read_to_string(path).map_err(ParseError::AbiReadFailed)?;
+    let abi: Value = serde_json::from_str(&contents)?;
+    SelectorMap::from_abi(&abi)
+}
+
+/// Canonical signature of an ABI function entry, e.g. `transfer(address,uint256)`
+///
+/// **Private** - internal helper for SelectorMap::from_abi
+fn function_signature(entry: &Value) -> Result<String, ParseError> {
+    let name = entry
+        .get("name")
+        .and_then(Value::as_str)
+        .ok_or_else(|| ParseError::InvalidAbi("function entry without a name".to_string()))?;
+
+    let inputs = match entry.get("inputs") {
+        Some(Value::Array(inputs)) => inputs.as_slice(),
+        _ => &[],
+    };
+
+    Ok(format!("{}({})", name, canonical_types(inputs, name)?))
+}
+
+/// Comma-separated canonical parameter types, expanding tuples
+///
+/// **Private** - internal helper for function_signature
+fn canonical_types(params: &[Value], function: &str) -> Result<String, ParseError> {
+    let types = params
+        .iter()
+        .map(|param| {
+            let ty = param.get("type").and_then(Value::as_str).ok_or_else(|| {
+                ParseError::InvalidAbi(format!("parameter without a type in `{}`", function))
+            })?;
+
+            // `tuple[2]` -> `(<components>)[2]`
+            match ty.strip_prefix("tuple") {
+                Some(suffix) => {
+                    let components = match param.get("components") {
+                        Some(Value::Array(components)) => components.as_slice(),
+                        _ => &[],
+                    };
+                    Ok(format!(
+                        "({}){}",
+                        canonical_types(components, function)?,
+                        suffix
+                    ))
+                }
+                None => Ok(ty.to_string()),
+            }
+        })
+        .collect::<Result<Vec<_>, ParseError>>()?;
+
+    Ok(types.join(","))
+}
+
+/// `0x`-prefixed 4-byte selector of a canonical signature
+///
+/// **Private** - first four bytes of keccak256(signature)
+fn selector_hex(signature: &str) -> String {
+    let mut hasher = Keccak::v256();
+    let mut hash = [0u8; 32];
+    hasher.update(signature.as_bytes());
+    hasher.finalize(&mut hash);
+
+    format!(
+        "0x{:02x}{:02x}{:02x}{:02x}",
+        hash[0], hash[1], hash[2], hash[3]
+    )
+}
@@ -11,8 +11,10 @@
 //!
 //! Translates binary offsets (PCs) to source locations (file:line) using DWARF.
 
+use crate::utils::error::SourceMapError;
 use addr2line::Context;
 use log::{debug, info};
+use object::Object;
 use std::path::Path;
 
 /// A location in the source code
@@ -33,23 +35,31 @@ pub struct SourceMapper {
 
 impl SourceMapper {
     /// Create a new SourceMapper from a WASM file
-    pub fn new<P: AsRef<Path>>(wasm_path: P) -> anyhow::Result<Self> {
+    ///
+    /// # Errors
+    /// * `SourceMapError::ReadFailed` - File could not be read
+    /// * `SourceMapError::InvalidWasm` - File is not a WASM binary
+    /// * `SourceMapError::NoDebugInfo` - Binary was built without DWARF
+    /// * `SourceMapError::CorruptDebugInfo` - DWARF sections exist but cannot be parsed
+    pub fn new<P: AsRef<Path>>(wasm_path: P) -> Result<Self, SourceMapError> {
         let path = wasm_path.as_ref();
         debug!("Loading WASM binary for source mapping: {}", path.display());
 
         let file_data = std::fs::read(path)?;
-        let obj = object::File::parse(&*file_data)?;
+        let obj = object::File::parse(&*file_data)
+            .map_err(|e| SourceMapError::InvalidWasm(e.to_string()))?;
 
-        let context = Context::new(&obj).ok();
-
-        if context.is_none() {
-            info!("No debug information (DWARF) found in the WASM binary. Source-to-line mapping will not be available.");
-            info!("Tip: Compile your contract with `debug = true` in your Cargo.toml release profile.");
-        } else {
-            info!("Debug information loaded successfully. Source-to-line mapping enabled.");
+        if obj.section_by_name(".debug_info").is_none() {
+            return Err(SourceMapError::NoDebugInfo);
         }
 
-        Ok(Self { context })
+        let context =
+            Context::new(&obj).map_err(|e| SourceMapError::CorruptDebugInfo(e.to_string()))?;
+        info!("Debug information loaded successfully. Source-to-line mapping enabled.");
+
+        Ok(Self {
+            context: Some(context),
+        })
     }
 
     /// Factory for an empty mapper (fallback)
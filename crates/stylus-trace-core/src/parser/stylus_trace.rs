@@ -3,25 +3,46 @@
 //! Parses raw JSON from debug_traceTransaction into structured data.
 //! Handles schema validation and extraction of execution steps.
 
-use super::hostio::{extract_hostio_events, HostIoStats};
-use super::schema::Profile;
-use crate::aggregator::stack_builder::CollapsedStack;
+use super::hostio::{extract_hostio_events, HostIoStats, HostIoType};
+use super::schema::{ContractGas, Profile, SlotReads, TotalGasSource};
+use crate::aggregator::stack_builder::{for_each_step_stack, CollapsedStack, StackOptions};
 use crate::utils::config::{
-    GAS_FIELD_NAMES, GAS_TO_INK_MULTIPLIER, MAX_REASONABLE_GAS, SCHEMA_VERSION, STEP_FIELD_NAMES,
+    generated_at, DEFAULT_MAX_TRACE_STEPS, GAS_FIELD_NAMES, GAS_TO_INK_MULTIPLIER,
+    MAX_REASONABLE_GAS, SCHEMA_VERSION, STEP_FIELD_NAMES,
 };
 use crate::utils::error::ParseError;
 use log::{debug, warn};
 use serde::Deserialize;
+use std::collections::HashMap;
 
 /// Detected trace format from RPC
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum TraceFormat {
+pub enum TraceFormat {
     /// Standard EVM trace with structLogs/gasUsed
     StandardEvm,
     /// Stylus tracer format (array of steps with ink values)
     StylusTracer,
 }
 
+impl TraceFormat {
+    /// Unit in which this format reports gas values
+    pub fn native_unit(self) -> GasUnit {
+        match self {
+            TraceFormat::StandardEvm => GasUnit::Gas,
+            TraceFormat::StylusTracer => GasUnit::Ink,
+        }
+    }
+}
+
+/// Unit of raw gas values in a trace
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum GasUnit {
+    /// EVM gas (converted to ink by multiplying by 10,000)
+    Gas,
+    /// Stylus ink (used as-is)
+    Ink,
+}
+
 /// Raw execution step from stylusTracer
 ///
 /// This represents a single step in the WASM execution.
@@ -56,6 +77,14 @@ pub struct ExecutionStep {
     /// Program Counter / Offset (needed for source mapping)
     #[serde(default)]
     pub pc: u64,
+
+    /// Callee address of a call operation (if reported or recoverable)
+    #[serde(default)]
+    pub address: Option<String>,
+
+    /// Slot read by a storage load (recovered from the step's arguments)
+    #[serde(skip)]
+    pub slot: Option<String>,
 }
 
 /// Parsed trace data (internal representation)
@@ -64,8 +93,19 @@ pub struct ExecutionStep {
 pub struct ParsedTrace {
     pub transaction_hash: String,
     pub total_gas_used: u64, // In Ink
+    /// Whether `total_gas_used` came from the trace or was summed from steps
+    pub total_gas_source: TotalGasSource,
     pub execution_steps: Vec<ExecutionStep>,
     pub hostio_stats: HostIoStats,
+    pub contract_address: Option<String>,
+    pub block_number: Option<u64>,
+    /// Steps whose end ink exceeded start ink (cost zeroed instead of underflowing)
+    pub zeroed_ink_steps: usize,
+    /// Gas per contract, filled in by capture with `--split-by-contract`
+    pub contract_gas: Option<Vec<ContractGas>>,
+    /// Repeated slot reads, filled in by capture so their stacks match its
+    /// stack options and selector labels
+    pub slot_reads: Option<Vec<SlotReads>>,
 }
 
 /// Parse raw trace JSON from stylusTracer
@@ -86,23 +126,96 @@ pub struct ParsedTrace {
 pub fn parse_trace(
     tx_hash: &str,
     raw_trace: &serde_json::Value,
+) -> Result<ParsedTrace, ParseError> {
+    parse_trace_with_unit(tx_hash, raw_trace, None)
+}
+
+/// Parse raw trace JSON with an optional unit override
+///
+/// **Public** - used by capture for `--assume-unit`
+///
+/// # Arguments
+/// * `tx_hash` - Transaction hash being profiled
+/// * `raw_trace` - Raw JSON from debug_traceTransaction
+/// * `assume_unit` - Unit of raw gas values; `None` uses the detected format's native unit
+///
+/// # Returns
+/// Parsed trace data ready for aggregation
+///
+/// # Errors
+/// * `ParseError::JsonError` - Invalid JSON structure
+/// * `ParseError::InvalidFormat` - Missing required fields
+/// * `ParseError::TooManySteps` - More than `DEFAULT_MAX_TRACE_STEPS` steps
+pub fn parse_trace_with_unit(
+    tx_hash: &str,
+    raw_trace: &serde_json::Value,
+    assume_unit: Option<GasUnit>,
+) -> Result<ParsedTrace, ParseError> {
+    parse_trace_with_limit(tx_hash, raw_trace, assume_unit, DEFAULT_MAX_TRACE_STEPS)
+}
+
+/// Parse raw trace JSON, refusing traces with too many execution steps
+///
+/// **Public** - used by capture for `--max-trace-steps`
+///
+/// Guards against runaway traces (a misconfigured tracer or a contract
+/// looping until it runs out of gas) exhausting memory in the parser and
+/// aggregator.
+///
+/// # Arguments
+/// * `tx_hash` - Transaction hash being profiled
+/// * `raw_trace` - Raw JSON from debug_traceTransaction
+/// * `assume_unit` - Unit of raw gas values; `None` uses the detected format's native unit
+/// * `max_steps` - Maximum number of execution steps (`0` = no limit)
+///
+/// # Errors
+/// * `ParseError::JsonError` - Invalid JSON structure
+/// * `ParseError::InvalidFormat` - Missing required fields
+/// * `ParseError::TooManySteps` - The trace has more than `max_steps` steps
+pub fn parse_trace_with_limit(
+    tx_hash: &str,
+    raw_trace: &serde_json::Value,
+    assume_unit: Option<GasUnit>,
+    max_steps: usize,
 ) -> Result<ParsedTrace, ParseError> {
     debug!("Parsing trace for transaction: {}", tx_hash);
 
     // Detect and normalize trace format
     let (trace_obj, format) = detect_trace_format(raw_trace)?;
+    let unit = assume_unit.unwrap_or_else(|| format.native_unit());
+    debug!(
+        "Trace format {:?}, interpreting values as {:?}",
+        format, unit
+    );
 
     // Extract total gas used and normalize to Ink
     let mut total_gas_used = extract_total_gas(&trace_obj)?;
-    total_gas_used = normalize_to_ink(total_gas_used, format == TraceFormat::StylusTracer);
+    if unit == GasUnit::Gas && total_gas_used > MAX_REASONABLE_GAS {
+        warn!(
+            "Total gas {} exceeds {} gas; if this tracer reports ink, pass --assume-unit ink",
+            total_gas_used, MAX_REASONABLE_GAS
+        );
+    }
+    total_gas_used = normalize_to_ink(total_gas_used, unit);
 
     // Extract and process execution steps
-    let mut execution_steps = extract_execution_steps(&trace_obj)?;
-    process_execution_steps(&mut execution_steps, format);
+    let mut execution_steps = extract_execution_steps(&trace_obj, max_steps)?;
+    let zeroed_ink_steps = process_execution_steps(&mut execution_steps, unit);
+    if zeroed_ink_steps > 0 {
+        warn!(
+            "{} steps had non-decreasing ink and were zeroed",
+            zeroed_ink_steps
+        );
+    }
 
     // Calculate total gas from steps if not provided
+    let mut total_gas_source = TotalGasSource::Reported;
     if total_gas_used == 0 {
-        total_gas_used = execution_steps.iter().map(|s| s.gas_cost).sum();
+        total_gas_used = execution_steps
+            .iter()
+            .map(|s| s.gas_cost)
+            .fold(0, u64::saturating_add);
+        total_gas_source = TotalGasSource::DerivedFromSteps;
     }
 
     debug!("Parsed {} execution steps", execution_steps.len());
@@ -113,8 +226,14 @@ pub fn parse_trace(
     Ok(ParsedTrace {
         transaction_hash: tx_hash.to_string(),
         total_gas_used,
+        total_gas_source,
         execution_steps,
         hostio_stats,
+        contract_address: None,
+        block_number: None,
+        zeroed_ink_steps,
+        contract_gas: None,
+        slot_reads: None,
     })
 }
 
@@ -151,35 +270,45 @@ fn detect_trace_format(
     }
 }
 
-/// Normalize gas value to Ink units (10,000x multiplier)
+/// Normalize a gas value to Ink units (10,000x multiplier)
 ///
-/// **Private** - internal helper for parse_trace
-fn normalize_to_ink(value: u64, is_already_ink: bool) -> u64 {
-    if is_already_ink {
-        value
-    } else if value < MAX_REASONABLE_GAS {
-        // Value is in gas units, convert to ink
-        value.saturating_mul(GAS_TO_INK_MULTIPLIER)
-    } else {
-        // Value is already in ink units (too large to be gas)
-        value
+/// **Public** - the unit is explicit; values are never reinterpreted by magnitude
+pub fn normalize_to_ink(value: u64, unit: GasUnit) -> u64 {
+    match unit {
+        GasUnit::Gas => value.saturating_mul(GAS_TO_INK_MULTIPLIER),
+        GasUnit::Ink => value,
     }
 }
 
 /// Process execution steps: calculate costs and normalize to Ink
 ///
 /// **Private** - internal helper for parse_trace
-fn process_execution_steps(steps: &mut [ExecutionStep], format: TraceFormat) {
-    for step in steps {
+///
+/// # Returns
+/// Number of steps whose end ink exceeded start ink and were zeroed
+fn process_execution_steps(steps: &mut [ExecutionStep], unit: GasUnit) -> usize {
+    let mut zeroed = 0;
+
+    for (index, step) in steps.iter_mut().enumerate() {
         // If we have explicit ink values, calculate from those
         if let (Some(start), Some(end)) = (step.start_ink, step.end_ink) {
+            if end > start {
+                debug!(
+                    "Step {} ({}) has end ink {} > start ink {}; zeroing cost",
+                    index,
+                    step.op.as_deref().unwrap_or("unknown"),
+                    end,
+                    start
+                );
+                zeroed += 1;
+            }
             step.gas_cost = start.saturating_sub(end);
-        } else if format == TraceFormat::StandardEvm {
-            // Convert EVM gas to ink
-            step.gas_cost = step.gas_cost.saturating_mul(GAS_TO_INK_MULTIPLIER);
+        } else {
+            step.gas_cost = normalize_to_ink(step.gas_cost, unit);
         }
-        // Otherwise, assume gas_cost is already in ink units
     }
+
+    zeroed
 }
 
 /// Extract HostIO statistics, with fallback detection from execution steps
@@ -273,6 +402,9 @@ pub fn extract_total_gas(
 fn parse_json_u64(val: &serde_json::Value) -> Result<u64, ParseError> {
     if let Some(n) = val.as_u64() {
         Ok(n)
+    } else if let Some(f) = val.as_f64() {
+        // Some tracers emit gas as a float (e.g. `150000.0`)
+        float_to_gas(f)
     } else if let Some(s) = val.as_str() {
         parse_gas_value(s)
     } else {
@@ -288,12 +420,13 @@ fn parse_json_u64(val: &serde_json::Value) -> Result<u64, ParseError> {
 /// **Private** - internal extraction logic
 fn extract_execution_steps(
     trace_obj: &serde_json::Map<String, serde_json::Value>,
+    max_steps: usize,
 ) -> Result<Vec<ExecutionStep>, ParseError> {
     // Try multiple possible field names
     for field in STEP_FIELD_NAMES {
         if let Some(steps_value) = trace_obj.get(*field) {
             if let Some(steps_array) = steps_value.as_array() {
-                return parse_steps_array(steps_array);
+                return parse_steps_array(steps_array, max_steps);
             }
         }
     }
@@ -306,12 +439,31 @@ fn extract_execution_steps(
 /// Parse array of execution steps
 ///
 /// **Private** - internal parsing logic
-fn parse_steps_array(steps_array: &[serde_json::Value]) -> Result<Vec<ExecutionStep>, ParseError> {
+///
+/// Fails before allocating anything when the array holds more than
+/// `max_steps` entries (`0` = no limit).
+fn parse_steps_array(
+    steps_array: &[serde_json::Value],
+    max_steps: usize,
+) -> Result<Vec<ExecutionStep>, ParseError> {
+    if max_steps > 0 && steps_array.len() > max_steps {
+        return Err(ParseError::TooManySteps {
+            steps: steps_array.len(),
+            limit: max_steps,
+        });
+    }
+
     let mut steps = Vec::with_capacity(steps_array.len());
 
     for (index, step_value) in steps_array.iter().enumerate() {
         match serde_json::from_value::<ExecutionStep>(step_value.clone()) {
-            Ok(step) => steps.push(step),
+            Ok(mut step) => {
+                if step.address.is_none() {
+                    step.address = evm_call_target(step_value);
+                }
+                step.slot = storage_read_slot(step_value);
+                steps.push(step);
+            }
             Err(e) => {
                 // Log but don't fail - some steps may be malformed
                 warn!("Failed to parse step {}: {}", index, e);
@@ -328,23 +480,225 @@ fn parse_steps_array(steps_array: &[serde_json::Value]) -> Result<Vec<ExecutionS
     Ok(steps)
 }
 
-/// Parse gas value from hex string or decimal
+/// Recover the callee of an EVM call opcode from its structLog stack
 ///
-/// **Private** - internal utility
-/// Parse a gas value from hex or decimal string
+/// **Private** - internal helper for parse_steps_array
+///
+/// The address is the second stack item from the top for CALL, CALLCODE,
+/// DELEGATECALL and STATICCALL.
+fn evm_call_target(step_value: &serde_json::Value) -> Option<String> {
+    let op = step_value.get("op")?.as_str()?;
+    if !matches!(
+        op.to_uppercase().as_str(),
+        "CALL" | "CALLCODE" | "DELEGATECALL" | "STATICCALL"
+    ) {
+        return None;
+    }
+
+    let stack = step_value.get("stack")?.as_array()?;
+    let item = stack.get(stack.len().checked_sub(2)?)?.as_str()?;
+    let hex = item.trim_start_matches("0x");
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let hex = &hex[hex.len().saturating_sub(40)..];
+    Some(format!("0x{:0>40}", hex.to_lowercase()))
+}
+
+/// Recover the slot read by a storage load step
+///
+/// **Private** - internal helper for parse_steps_array
+///
+/// EVM `SLOAD` steps take the slot from the top of their structLog stack.
+/// Stylus `storage_load_bytes32` steps take it from a `slot`/`key` field or
+/// the first 32 bytes of their `args`.
+///
+/// # Returns
+/// The slot as `0x` followed by 64 lowercase hex digits, or None when the
+/// step is not a storage load or carries no usable key
+fn storage_read_slot(step_value: &serde_json::Value) -> Option<String> {
+    let op = step_value
+        .get("op")
+        .or_else(|| step_value.get("name"))?
+        .as_str()?;
+    if op.parse::<HostIoType>().ok()? != HostIoType::StorageLoad {
+        return None;
+    }
+
+    let raw = if op.eq_ignore_ascii_case("SLOAD") {
+        let stack = step_value.get("stack")?.as_array()?;
+        stack.last()?.as_str()?
+    } else {
+        ["slot", "key", "args"]
+            .iter()
+            .find_map(|field| step_value.get(*field)?.as_str())?
+    };
+
+    // Checked before slicing so the byte index below is a char boundary
+    let hex = raw.trim_start_matches("0x");
+    if hex.is_empty() || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let hex = &hex[..hex.len().min(64)];
+    Some(format!("0x{:0>64}", hex.to_lowercase()))
+}
+
+/// Contract name used for the transaction target when its address is unknown
+const ENTRY_CONTRACT: &str = "entry";
+
+/// Count storage slots that were read more than once
+///
+/// **Public** - feeds the profile's `repeated_slot_reads`
+///
+/// Slots are keyed on the contract whose storage is read and the slot, so
+/// slot 0 of a proxy and slot 0 of the token it calls are counted apart.
+/// The storage owner is tracked through call depth the same way
+/// `build_stacks_with` names contract frames: a call step's `address` is
+/// the contract entered on the next depth increase, and delegatecalls keep
+/// the caller's storage. Reads in a sub-call whose callee was not reported
+/// are skipped, since their storage cannot be told apart.
+///
+/// Each repeated slot lists the stacks of its reads, named with `options`
+/// so they match the stacks built with the same options.
+///
+/// # Arguments
+/// * `parsed_trace` - Parsed trace data from parser
+/// * `options` - Frame naming options of the profile's stacks
+///
+/// # Returns
+/// Repeated slots, most cacheable gas first (ties by contract and slot)
+pub fn repeated_slot_reads(parsed_trace: &ParsedTrace, options: StackOptions) -> Vec<SlotReads> {
+    let steps = &parsed_trace.execution_steps;
+    let base_depth = steps.first().map_or(0, |step| step.depth as usize);
+    let entry = parsed_trace
+        .contract_address
+        .as_deref()
+        .unwrap_or(ENTRY_CONTRACT);
+
+    // Storage owner per active call frame (None when the callee is unknown)
+    let mut owners: Vec<Option<&str>> = Vec::new();
+    // Callee of the most recent call step, and whether it was a delegatecall
+    let mut pending_callee: Option<(Option<&str>, bool)> = None;
+    let mut by_slot: HashMap<(&str, &str), SlotReads> = HashMap::new();
+    // Index of every read step and the slot it read
+    let mut read_steps: Vec<(usize, (&str, &str))> = Vec::new();
+
+    for (index, step) in steps.iter().enumerate() {
+        let depth = step.depth as usize - base_depth.min(step.depth as usize) + 1;
+        owners.truncate(depth);
+        while owners.len() < depth {
+            let owner = match (owners.last(), pending_callee.take()) {
+                (None, _) => Some(entry),
+                (Some(caller), Some((_, true))) => *caller,
+                (Some(_), Some((address, false))) => address,
+                (Some(_), None) => None,
+            };
+            owners.push(owner);
+        }
+
+        let op = step
+            .function
+            .as_deref()
+            .or(step.op.as_deref())
+            .unwrap_or("");
+        let is_delegatecall = op
+            .rsplit(';')
+            .next()
+            .and_then(HostIoType::from_opcode)
+            .is_some_and(|io| io == HostIoType::DelegateCall);
+        if step.address.is_some() || is_delegatecall {
+            pending_callee = Some((step.address.as_deref(), is_delegatecall));
+        }
+
+        let (Some(slot), Some(Some(contract))) = (step.slot.as_deref(), owners.last()) else {
+            continue;
+        };
+        let reads = by_slot
+            .entry((contract, slot))
+            .or_insert_with(|| SlotReads {
+                contract: Some(contract.to_string()),
+                slot: slot.to_string(),
+                reads: 0,
+                gas: 0,
+                cacheable_gas: 0,
+                stacks: Vec::new(),
+            });
+        if reads.reads > 0 {
+            reads.cacheable_gas = reads.cacheable_gas.saturating_add(step.gas_cost);
+        }
+        reads.reads += 1;
+        reads.gas = reads.gas.saturating_add(step.gas_cost);
+        read_steps.push((index, (contract, slot)));
+    }
+
+    by_slot.retain(|_, reads| reads.reads > 1);
+    if !by_slot.is_empty() {
+        let mut pending = read_steps
+            .into_iter()
+            .filter(|(_, key)| by_slot.contains_key(key))
+            .collect::<Vec<_>>()
+            .into_iter()
+            .peekable();
+        let mut index = 0;
+        for_each_step_stack(parsed_trace, options, |_, stack| {
+            if let Some((_, key)) = pending.next_if(|(step, _)| *step == index) {
+                if let Some(reads) = by_slot.get_mut(&key) {
+                    reads.stacks.push(stack);
+                }
+            }
+            index += 1;
+        });
+        for reads in by_slot.values_mut() {
+            reads.stacks.sort();
+            reads.stacks.dedup();
+        }
+    }
+
+    let mut repeated: Vec<SlotReads> = by_slot.into_values().collect();
+    repeated.sort_by(|a, b| {
+        b.cacheable_gas
+            .cmp(&a.cacheable_gas)
+            .then_with(|| a.contract.cmp(&b.contract))
+            .then_with(|| a.slot.cmp(&b.slot))
+    });
+    repeated
+}
+
+/// Parse a gas value from a hex, decimal, float or scientific-notation string
+///
+/// **Public** - used by the parser and tests
+///
+/// Fractional values (`150000.0`, `1.5e5`) are rounded to the nearest integer.
 pub fn parse_gas_value(value: &str) -> Result<u64, ParseError> {
     // Handle hex values (0x prefix)
     if let Some(hex_str) = value.strip_prefix("0x") {
         u64::from_str_radix(hex_str, 16)
             .map_err(|e| ParseError::InvalidFormat(format!("Invalid hex gas value: {}", e)))
+    } else if let Ok(gas) = value.parse::<u64>() {
+        Ok(gas)
     } else {
-        // Try parsing as decimal
-        value
-            .parse::<u64>()
-            .map_err(|e| ParseError::InvalidFormat(format!("Invalid decimal gas value: {}", e)))
+        // Fall back to float / scientific notation
+        let float = value
+            .parse::<f64>()
+            .map_err(|e| ParseError::InvalidFormat(format!("Invalid decimal gas value: {}", e)))?;
+        float_to_gas(float)
     }
 }
 
+/// Round a float gas value to u64, rejecting negative, non-finite and
+/// out-of-range values
+///
+/// **Private** - internal utility
+fn float_to_gas(value: f64) -> Result<u64, ParseError> {
+    if !value.is_finite() || value < 0.0 || value >= u64::MAX as f64 {
+        return Err(ParseError::InvalidFormat(format!(
+            "Gas value {} is not a non-negative integer in range",
+            value
+        )));
+    }
+    Ok(value.round() as u64)
+}
+
 /// Convert parsed trace to output profile format
 ///
 /// **Public** - used by commands to create final output
@@ -357,8 +711,6 @@ pub fn to_profile(
     all_stacks: Option<Vec<CollapsedStack>>,
     mapper: Option<&super::source_map::SourceMapper>,
 ) -> Profile {
-    use chrono::Utc;
-
     // Enrich hot paths with source information if mapper is available
     if let Some(mapper) = mapper {
         enrich_source_hints(&mut hot_paths, mapper);
@@ -367,11 +719,24 @@ pub fn to_profile(
     Profile {
         version: SCHEMA_VERSION.to_string(),
         transaction_hash: parsed_trace.transaction_hash.clone(),
+        label: None,
+        contract_address: parsed_trace.contract_address.clone(),
+        block_number: parsed_trace.block_number,
         total_gas: parsed_trace.total_gas_used,
+        total_gas_source: parsed_trace.total_gas_source,
         hostio_summary: parsed_trace.hostio_stats.to_summary(),
+        contracts: parsed_trace.contract_gas.clone(),
+        repeated_slot_reads: Some(
+            parsed_trace
+                .slot_reads
+                .clone()
+                .unwrap_or_else(|| repeated_slot_reads(parsed_trace, StackOptions::default())),
+        )
+        .filter(|reads| !reads.is_empty()),
         hot_paths,
         all_stacks,
-        generated_at: Utc::now().to_rfc3339(),
+        samples: None,
+        generated_at: generated_at(),
     }
 }
 
@@ -0,0 +1,140 @@
+//! On-disk cache for raw trace responses.
+//!
+//! Traces of mined transactions never change, so repeated captures of the
+//! same transaction can skip the RPC round-trip. Entries are keyed by
+//! RPC URL, transaction hash, tracer and tracer config.
+
+use crate::output::AtomicFile;
+use crate::utils::config::ENV_TRACE_CACHE_DIR;
+use crate::utils::error::OutputError;
+use log::debug;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Directory-backed trace cache
+///
+/// **Public** - used by capture when caching is enabled
+#[derive(Debug, Clone)]
+pub struct TraceCache {
+    dir: PathBuf,
+}
+
+/// Identifies a single cached trace
+///
+/// **Public** - all fields contribute to the cache key
+#[derive(Debug, Clone, Copy)]
+pub struct TraceCacheKey<'a> {
+    pub rpc_url: &'a str,
+    pub tx_hash: &'a str,
+    pub tracer: Option<&'a str>,
+    pub tracer_config: Option<&'a serde_json::Value>,
+}
+
+impl TraceCacheKey<'_> {
+    /// File name for this entry: `<tx_hash>-<fingerprint>.json`
+    ///
+    /// The fingerprint (FNV-1a) is stable across builds, unlike std's hasher.
+    fn file_name(&self) -> String {
+        let config = self
+            .tracer_config
+            .map(|c| c.to_string())
+            .unwrap_or_default();
+        let parts = [
+            self.rpc_url,
+            self.tx_hash,
+            self.tracer.unwrap_or(""),
+            config.as_str(),
+        ];
+
+        let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+        for part in parts {
+            // Separator byte keeps ("ab", "c") distinct from ("a", "bc")
+            for byte in part.bytes().chain(std::iter::once(0xff)) {
+                hash ^= u64::from(byte);
+                hash = hash.wrapping_mul(0x0100_0000_01b3);
+            }
+        }
+
+        let tx = self.tx_hash.trim_start_matches("0x").to_lowercase();
+        format!("0x{}-{:016x}.json", tx, hash)
+    }
+}
+
+impl TraceCache {
+    /// Create a cache rooted at `dir` (created lazily on first store)
+    ///
+    /// **Public** - constructor
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Directory holding the cache entries
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Load a cached trace, if present and readable
+    ///
+    /// Unreadable or corrupt entries are treated as misses.
+    pub fn load(&self, key: &TraceCacheKey) -> Option<serde_json::Value> {
+        let path = self.dir.join(key.file_name());
+        let contents = fs::read_to_string(&path).ok()?;
+
+        match serde_json::from_str(&contents) {
+            Ok(trace) => {
+                debug!("Trace cache hit: {}", path.display());
+                Some(trace)
+            }
+            Err(e) => {
+                debug!(
+                    "Ignoring corrupt trace cache entry {}: {}",
+                    path.display(),
+                    e
+                );
+                None
+            }
+        }
+    }
+
+    /// Store a trace in the cache, replacing any existing entry
+    ///
+    /// # Errors
+    /// * I/O error creating the cache directory or writing the entry
+    pub fn store(&self, key: &TraceCacheKey, trace: &serde_json::Value) -> io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+
+        // Write via a unique temp file so concurrent readers never see a
+        // partial entry and concurrent writers never share a temp file
+        let path = self.dir.join(key.file_name());
+        AtomicFile::write(&path, trace.to_string()).map_err(|e| match e {
+            OutputError::WriteFailed(io) => io,
+            other => io::Error::other(other),
+        })?;
+
+        debug!("Stored trace in cache: {}", path.display());
+        Ok(())
+    }
+}
+
+/// Default cache directory
+///
+/// **Public** - used by the CLI when `--cache-dir` is not given
+///
+/// Resolution order: `STYLUS_TRACE_CACHE_DIR`, `$XDG_CACHE_HOME/stylus-trace`,
+/// `$HOME/.cache/stylus-trace`, then `.stylus-trace-cache` in the working directory.
+pub fn default_cache_dir() -> PathBuf {
+    let var = |name: &str| std::env::var_os(name).filter(|v| !v.is_empty());
+
+    if let Some(dir) = var(ENV_TRACE_CACHE_DIR) {
+        return PathBuf::from(dir);
+    }
+    if let Some(dir) = var("XDG_CACHE_HOME") {
+        return PathBuf::from(dir).join("stylus-trace");
+    }
+    if let Some(home) = var("HOME") {
+        return PathBuf::from(home).join(".cache").join("stylus-trace");
+    }
+
+    PathBuf::from(".stylus-trace-cache")
+}
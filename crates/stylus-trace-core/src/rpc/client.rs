@@ -1,28 +1,37 @@
-//! HTTP client for communicating with Arbitrum Nitro node RPC endpoint.
+//! Client for communicating with Arbitrum Nitro node RPC endpoint over HTTP or WebSocket.
 
-use super::types::{JsonRpcResponse, RawTraceData};
+use super::types::{CallRequest, JsonRpcResponse, RawTraceData, TransactionInfo};
+use super::websocket;
 use crate::utils::config::DEFAULT_RPC_TIMEOUT;
 use crate::utils::error::RpcError;
 use log::{debug, info};
 use reqwest::blocking::Client;
+use std::time::Duration;
 
 /// RPC client for fetching trace data from Nitro node
 pub struct RpcClient {
     client: Client,
     rpc_url: String,
+    timeout: Duration,
 }
 
 impl RpcClient {
     /// Create a new RPC client
     pub fn new(rpc_url: impl Into<String>) -> Result<Self, RpcError> {
+        Self::with_timeout(rpc_url, DEFAULT_RPC_TIMEOUT)
+    }
+
+    /// Create a new RPC client with a custom request timeout
+    pub fn with_timeout(rpc_url: impl Into<String>, timeout: Duration) -> Result<Self, RpcError> {
         let client = Client::builder()
-            .timeout(DEFAULT_RPC_TIMEOUT)
+            .timeout(timeout)
             .build()
             .map_err(RpcError::RequestFailed)?;
 
         Ok(Self {
             client,
             rpc_url: rpc_url.into(),
+            timeout,
         })
     }
 
@@ -31,19 +40,25 @@ impl RpcClient {
         &self,
         tx_hash: &str,
         tracer: Option<&str>,
+    ) -> Result<RawTraceData, RpcError> {
+        self.debug_trace_transaction_with_config(tx_hash, tracer, None)
+    }
+
+    /// Fetch trace with optional tracer and tracer config
+    ///
+    /// `tracer_config` is sent as the `tracerConfig` params field
+    /// (e.g. `{"onlyTopCall": true}`).
+    pub fn debug_trace_transaction_with_config(
+        &self,
+        tx_hash: &str,
+        tracer: Option<&str>,
+        tracer_config: Option<&serde_json::Value>,
     ) -> Result<RawTraceData, RpcError> {
         let tx_hash = normalize_tx_hash(tx_hash);
 
         info!("Fetching trace for transaction: {}", tx_hash);
 
-        // Build params based on tracer (defaulting to stylusTracer)
-        let mut params_obj = serde_json::Map::new();
-        params_obj.insert(
-            "tracer".to_string(),
-            serde_json::json!(tracer.unwrap_or("stylusTracer")),
-        );
-
-        let params = serde_json::json!([tx_hash, params_obj]);
+        let params = serde_json::json!([tx_hash, tracer_options(tracer, tracer_config)]);
 
         // Build RPC request
         let request = serde_json::json!({
@@ -55,13 +70,169 @@ impl RpcClient {
 
         debug!("RPC request: {:?}", request);
 
+        let rpc_response = self.send_request(&request)?;
+
+        // Handle JSON-RPC error
+        if let Some(error) = rpc_response.error {
+            return Err(map_rpc_error(error, &tx_hash));
+        }
+
+        // Extract result
+        rpc_response
+            .result
+            .map(unwrap_stringified_result)
+            .ok_or_else(|| RpcError::InvalidResponse("Missing result field".to_string()))
+    }
+
+    /// Trace a simulated call (`debug_traceCall`) at the given block
+    ///
+    /// Runs the call as an `eth_call` on top of `block` without mining it, so
+    /// candidate transactions can be profiled before they are submitted.
+    ///
+    /// # Arguments
+    /// * `call` - Call object (`to`, optional `from`, `data`, `value`)
+    /// * `block` - Block tag or hex number (e.g. `latest`, `0x10`)
+    /// * `tracer` - Tracer name (defaults to `stylusTracer`)
+    /// * `tracer_config` - Optional `tracerConfig` object
+    ///
+    /// # Errors
+    /// * `RpcError::TracerNotSupported` - Node does not support `debug_traceCall`
+    /// * `RpcError::InvalidResponse` - Call reverted or the node returned an error
+    pub fn debug_trace_call(
+        &self,
+        call: &CallRequest,
+        block: &str,
+        tracer: Option<&str>,
+        tracer_config: Option<&serde_json::Value>,
+    ) -> Result<RawTraceData, RpcError> {
+        info!("Simulating call to {} at block {}", call.to, block);
+
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": "debug_traceCall",
+            "params": [call, block, tracer_options(tracer, tracer_config)],
+            "id": 4
+        });
+
+        debug!("RPC request: {:?}", request);
+
+        let rpc_response = self.send_request(&request)?;
+
+        if let Some(error) = rpc_response.error {
+            return Err(match error.code {
+                -32601 => RpcError::TracerNotSupported,
+                _ if is_unknown_tracer_message(&error.message) => RpcError::TracerNotSupported,
+                code => RpcError::InvalidResponse(format!("{}: {}", code, error.message)),
+            });
+        }
+
+        rpc_response
+            .result
+            .map(unwrap_stringified_result)
+            .ok_or_else(|| RpcError::InvalidResponse("Missing result field".to_string()))
+    }
+
+    /// Fetch the transaction's `to` address and block number
+    ///
+    /// # Errors
+    /// * `RpcError::TransactionNotFound` - Node does not know the transaction
+    /// * `RpcError::InvalidResponse` - Response could not be decoded
+    pub fn get_transaction_info(&self, tx_hash: &str) -> Result<TransactionInfo, RpcError> {
+        let tx_hash = normalize_tx_hash(tx_hash);
+
+        debug!("Fetching transaction metadata for: {}", tx_hash);
+
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": "eth_getTransactionByHash",
+            "params": [tx_hash],
+            "id": 2
+        });
+
+        let rpc_response = self.send_request(&request)?;
+
+        if let Some(error) = rpc_response.error {
+            return Err(map_rpc_error(error, &tx_hash));
+        }
+
+        // A null result means the node does not know the transaction
+        match rpc_response.result {
+            Some(value) if !value.is_null() => serde_json::from_value(value)
+                .map_err(|e| RpcError::InvalidResponse(format!("Malformed transaction: {}", e))),
+            _ => Err(RpcError::TransactionNotFound(tx_hash)),
+        }
+    }
+
+    /// Fetch the node's client version (`web3_clientVersion`)
+    ///
+    /// Lightweight call used as a connectivity health check.
+    ///
+    /// # Errors
+    /// * `RpcError::ConnectionFailed` / `RpcError::Timeout` - Node unreachable
+    /// * `RpcError::InvalidResponse` - Node returned an error or a non-string result
+    pub fn client_version(&self) -> Result<String, RpcError> {
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": "web3_clientVersion",
+            "params": [],
+            "id": 3
+        });
+
+        let rpc_response = self.send_request(&request)?;
+
+        if let Some(error) = rpc_response.error {
+            return Err(RpcError::InvalidResponse(format!(
+                "web3_clientVersion failed: {}",
+                error.message
+            )));
+        }
+
+        match rpc_response.result {
+            Some(serde_json::Value::String(version)) => Ok(version),
+            other => Err(RpcError::InvalidResponse(format!(
+                "Unexpected web3_clientVersion result: {}",
+                other.unwrap_or_default()
+            ))),
+        }
+    }
+
+    /// Whether a `web3_clientVersion` string identifies an Arbitrum Nitro node
+    ///
+    /// Nitro reports versions such as `nitro/v3.1.0-7d1d84c/linux-amd64/go1.21.10`.
+    pub fn is_nitro_client_version(version: &str) -> bool {
+        version.to_ascii_lowercase().starts_with("nitro")
+    }
+
+    /// Send a JSON-RPC request over the transport matching the URL scheme
+    ///
+    /// **Private** - shared by all RPC methods
+    fn send_request(
+        &self,
+        request: &serde_json::Value,
+    ) -> Result<JsonRpcResponse<serde_json::Value>, RpcError> {
+        if websocket::is_websocket_url(&self.rpc_url) {
+            let value = websocket::send_request(&self.rpc_url, request, self.timeout)?;
+            serde_json::from_value(value)
+                .map_err(|e| RpcError::InvalidResponse(format!("Malformed response: {}", e)))
+        } else {
+            self.send_http_request(request)
+        }
+    }
+
+    /// Send a JSON-RPC request over HTTP
+    ///
+    /// **Private** - HTTP transport for send_request
+    fn send_http_request(
+        &self,
+        request: &serde_json::Value,
+    ) -> Result<JsonRpcResponse<serde_json::Value>, RpcError> {
         // Make HTTP POST request
         let response = self
             .client
             .post(&self.rpc_url)
-            .json(&request)
+            .json(request)
             .send()
-            .map_err(RpcError::RequestFailed)?;
+            .map_err(|e| self.classify_request_error(e))?;
 
         // Check HTTP status
         if !response.status().is_success() {
@@ -73,18 +244,20 @@ impl RpcClient {
         }
 
         // Parse JSON-RPC response
-        let rpc_response: JsonRpcResponse<RawTraceData> =
-            response.json().map_err(RpcError::RequestFailed)?;
+        response.json().map_err(|e| self.classify_request_error(e))
+    }
 
-        // Handle JSON-RPC error
-        if let Some(error) = rpc_response.error {
-            return Err(map_rpc_error(error, &tx_hash));
+    /// Map a reqwest failure to a specific error variant
+    ///
+    /// **Private** - distinguishes timeouts and connection failures from other errors
+    fn classify_request_error(&self, error: reqwest::Error) -> RpcError {
+        if error.is_timeout() {
+            RpcError::Timeout(self.timeout)
+        } else if error.is_connect() {
+            RpcError::ConnectionFailed(self.rpc_url.clone())
+        } else {
+            RpcError::RequestFailed(error)
         }
-
-        // Extract result
-        rpc_response
-            .result
-            .ok_or_else(|| RpcError::InvalidResponse("Missing result field".to_string()))
     }
 }
 
@@ -97,9 +270,58 @@ pub fn normalize_tx_hash(tx_hash: &str) -> String {
     }
 }
 
+/// Decode a trace result that a node or proxy double-encoded as a JSON string
+///
+/// **Private** - some gateways return `"result": "{\"gas\":...}"` instead of
+/// the object itself. Strings that do not hold a JSON object or array are
+/// returned unchanged.
+fn unwrap_stringified_result(result: serde_json::Value) -> serde_json::Value {
+    let serde_json::Value::String(encoded) = &result else {
+        return result;
+    };
+    match serde_json::from_str::<serde_json::Value>(encoded) {
+        Ok(decoded @ (serde_json::Value::Object(_) | serde_json::Value::Array(_))) => {
+            debug!("Unwrapped string-encoded trace result");
+            decoded
+        }
+        _ => result,
+    }
+}
+
+/// Build the tracer options object shared by the trace methods
+///
+/// **Private** - defaults the tracer to `stylusTracer`
+fn tracer_options(
+    tracer: Option<&str>,
+    tracer_config: Option<&serde_json::Value>,
+) -> serde_json::Value {
+    let mut options = serde_json::Map::new();
+    options.insert(
+        "tracer".to_string(),
+        serde_json::json!(tracer.unwrap_or("stylusTracer")),
+    );
+    if let Some(config) = tracer_config {
+        options.insert("tracerConfig".to_string(), config.clone());
+    }
+    serde_json::Value::Object(options)
+}
+
+/// Whether a node error message reports an unknown tracer name
+///
+/// **Private** - geth-based nodes answer `-32000 tracer not found` rather
+/// than `-32601` when only the requested tracer is missing
+fn is_unknown_tracer_message(message: &str) -> bool {
+    let message = message.to_lowercase();
+    message.contains("tracer")
+        && (message.contains("not found")
+            || message.contains("not supported")
+            || message.contains("unknown"))
+}
+
 /// Map JSON-RPC error to our error type
 fn map_rpc_error(error: super::types::JsonRpcError, tx_hash: &str) -> RpcError {
     match error.code {
+        _ if is_unknown_tracer_message(&error.message) => RpcError::TracerNotSupported,
         -32000 => {
             if error.message.to_lowercase().contains("not found") {
                 RpcError::TransactionNotFound(tx_hash.to_string())
@@ -1,7 +1,11 @@
 //! RPC client for communicating with Arbitrum Nitro nodes.
 
+pub mod cache;
 pub mod client;
 pub mod types;
+pub mod websocket;
 
 // Re-export main types
+pub use cache::{default_cache_dir, TraceCache, TraceCacheKey};
 pub use client::RpcClient;
+pub use types::CallRequest;
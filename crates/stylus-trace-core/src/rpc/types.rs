@@ -62,3 +62,37 @@ pub struct JsonRpcError {
 /// We keep this as `serde_json::Value` because the exact schema
 /// may vary between Nitro versions. The parser will handle validation.
 pub type RawTraceData = serde_json::Value;
+
+/// Subset of `eth_getTransactionByHash` fields used to attribute a profile
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct TransactionInfo {
+    /// Called contract address (`None` for contract creation)
+    #[serde(default)]
+    pub to: Option<String>,
+
+    /// Block number as a hex quantity (`None` while pending)
+    #[serde(default, rename = "blockNumber")]
+    pub block_number: Option<String>,
+}
+
+/// Call object for `debug_traceCall` (simulating an un-mined transaction)
+///
+/// Fields follow the `eth_call` transaction object; quantities and data are
+/// `0x`-prefixed hex strings.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CallRequest {
+    /// Sender address (defaults to the zero address on most nodes)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub from: Option<String>,
+
+    /// Contract address to call
+    pub to: String,
+
+    /// Calldata (selector and ABI-encoded arguments)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<String>,
+
+    /// Value in wei as a hex quantity
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+}
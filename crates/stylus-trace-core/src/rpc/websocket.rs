@@ -0,0 +1,108 @@
+//! WebSocket transport for JSON-RPC requests.
+//!
+//! Some providers (and local nodes) only expose `ws://`/`wss://` endpoints.
+//! Each request opens a connection, sends a single JSON-RPC frame and waits
+//! for the response carrying the matching `id`.
+
+use crate::utils::error::RpcError;
+use log::debug;
+use std::io::ErrorKind;
+use std::net::TcpStream;
+use std::time::Duration;
+use tungstenite::stream::MaybeTlsStream;
+use tungstenite::{Message, WebSocket};
+
+/// Check whether a URL uses a WebSocket scheme
+///
+/// **Public** - used by the client and argument validation
+pub fn is_websocket_url(url: &str) -> bool {
+    url.starts_with("ws://") || url.starts_with("wss://")
+}
+
+/// Send a JSON-RPC request over WebSocket and return the matching response
+///
+/// **Internal** - used by `RpcClient` for `ws://`/`wss://` endpoints
+///
+/// # Arguments
+/// * `url` - WebSocket endpoint
+/// * `request` - JSON-RPC request object (must contain an `id`)
+/// * `timeout` - Read/write timeout for the socket
+///
+/// # Errors
+/// * `RpcError::ConnectionFailed` - Endpoint could not be reached
+/// * `RpcError::Timeout` - No matching response within `timeout`
+/// * `RpcError::InvalidResponse` - Socket closed or response was not valid JSON
+pub(crate) fn send_request(
+    url: &str,
+    request: &serde_json::Value,
+    timeout: Duration,
+) -> Result<serde_json::Value, RpcError> {
+    let (mut socket, _) = tungstenite::connect(url).map_err(|e| match e {
+        tungstenite::Error::Io(_) | tungstenite::Error::Tls(_) => {
+            RpcError::ConnectionFailed(url.to_string())
+        }
+        other => RpcError::InvalidResponse(format!("WebSocket handshake failed: {}", other)),
+    })?;
+
+    set_socket_timeout(&socket, timeout);
+
+    let map_err = |e: tungstenite::Error| classify_socket_error(e, timeout);
+
+    socket
+        .send(Message::Text(request.to_string()))
+        .map_err(map_err)?;
+
+    let expected_id = &request["id"];
+
+    loop {
+        let text = match socket.read().map_err(map_err)? {
+            Message::Text(text) => text,
+            Message::Binary(bytes) => String::from_utf8_lossy(&bytes).into_owned(),
+            Message::Close(_) => {
+                return Err(RpcError::InvalidResponse(
+                    "WebSocket closed before response was received".to_string(),
+                ))
+            }
+            // Ping/pong frames are answered by tungstenite automatically
+            _ => continue,
+        };
+
+        let response: serde_json::Value = serde_json::from_str(&text)
+            .map_err(|e| RpcError::InvalidResponse(format!("Invalid JSON: {}", e)))?;
+
+        if &response["id"] == expected_id {
+            let _ = socket.close(None);
+            return Ok(response);
+        }
+
+        debug!("Ignoring WebSocket message with unrelated id");
+    }
+}
+
+/// Apply read/write timeouts to the underlying TCP stream
+///
+/// **Private** - helper for send_request
+fn set_socket_timeout(socket: &WebSocket<MaybeTlsStream<TcpStream>>, timeout: Duration) {
+    let stream = match socket.get_ref() {
+        MaybeTlsStream::Plain(stream) => stream,
+        MaybeTlsStream::NativeTls(stream) => stream.get_ref(),
+        _ => return,
+    };
+
+    let _ = stream.set_read_timeout(Some(timeout));
+    let _ = stream.set_write_timeout(Some(timeout));
+}
+
+/// Map a socket error to a specific error variant
+///
+/// **Private** - distinguishes timeouts from other failures
+fn classify_socket_error(error: tungstenite::Error, timeout: Duration) -> RpcError {
+    match error {
+        tungstenite::Error::Io(e)
+            if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) =>
+        {
+            RpcError::Timeout(timeout)
+        }
+        other => RpcError::InvalidResponse(format!("WebSocket error: {}", other)),
+    }
+}
@@ -2,6 +2,9 @@
 
 use std::time::Duration;
 
+/// RPC endpoint of a local nitro-testnode (used by `--testnode`)
+pub const DEFAULT_TESTNODE_RPC: &str = "http://localhost:8547";
+
 /// Default timeout for RPC requests
 pub const DEFAULT_RPC_TIMEOUT: Duration = Duration::from_secs(30);
 
@@ -12,8 +15,60 @@ pub const SCHEMA_VERSION: &str = "1.0.0";
 // Stylus uses "Ink" as the unit, which is 10,000x smaller than EVM gas
 // 1 gas = 10,000 ink
 pub const GAS_TO_INK_MULTIPLIER: u64 = 10_000;
+/// Unit of every gas value stored in profiles and diff reports
+pub const PROFILE_GAS_UNIT: &str = "ink";
 pub const MAX_REASONABLE_GAS: u64 = 100_000_000; // 100M gas limit
 
+// Environment variables that override threshold settings in CI
+pub const ENV_GAS_MAX_INCREASE_PERCENT: &str = "STYLUS_GAS_MAX_INCREASE_PERCENT";
+pub const ENV_GAS_MAX_INCREASE_ABSOLUTE: &str = "STYLUS_GAS_MAX_INCREASE_ABSOLUTE";
+pub const ENV_HOSTIO_MAX_CALLS_PERCENT: &str = "STYLUS_HOSTIO_MAX_CALLS_PERCENT";
+pub const ENV_HOT_PATH_WARN_PERCENT: &str = "STYLUS_HOT_PATH_WARN_PERCENT";
+
+/// Transaction label used for profiles of simulated (`debug_traceCall`) calls
+pub const SIMULATED_CALL_LABEL: &str = "simulated-call";
+
+/// HostIO type rows shown in the diff summary unless `--top-hostio` is given
+pub const DEFAULT_TOP_HOSTIO: usize = 5;
+
+/// Share of total gas (percent) spent in read_args/write_result before the
+/// analyzer flags large calldata or return-data copies
+pub const DEFAULT_MEMORY_GAS_SHARE_PERCENT: f64 = 10.0;
+
+/// Share of total gas (percent) spent emitting logs before the analyzer
+/// flags excessive event emission
+pub const DEFAULT_LOG_GAS_SHARE_PERCENT: f64 = 5.0;
+
+/// Share of HostIO calls (percent) that are log emissions before the
+/// analyzer flags excessive event emission
+pub const DEFAULT_LOG_CALL_SHARE_PERCENT: f64 = 25.0;
+
+/// Execution steps parsed from one trace before capture gives up (`0` = no limit)
+pub const DEFAULT_MAX_TRACE_STEPS: usize = 5_000_000;
+
+/// Decimal places for percentages in the text summary and flamegraph tooltips
+pub const DEFAULT_SUMMARY_PERCENT_PRECISION: usize = 1;
+
+/// Decimal places for percentages in diff reports and diff flamegraph tooltips
+pub const DEFAULT_DIFF_PERCENT_PRECISION: usize = 2;
+
+/// Decimal places for the per-contract and per-source-file gas shares
+pub const DEFAULT_BREAKDOWN_PERCENT_PRECISION: usize = 2;
+
+/// Tracers this tool knows by name; others are passed to the node as-is
+pub const KNOWN_TRACERS: &[&str] = &[
+    "stylusTracer",
+    "callTracer",
+    "prestateTracer",
+    "4byteTracer",
+];
+
+/// Environment variable overriding the default trace cache directory
+pub const ENV_TRACE_CACHE_DIR: &str = "STYLUS_TRACE_CACHE_DIR";
+
+/// Reproducible-builds convention: fixed timestamp (seconds since the Unix epoch)
+pub const ENV_SOURCE_DATE_EPOCH: &str = "SOURCE_DATE_EPOCH";
+
 // Field names for trace parsing (different RPC implementations use different names)
 pub const GAS_FIELD_NAMES: &[&str] = &["gas", "gasUsed", "gas_used", "totalGas", "total_gas"];
 pub const STEP_FIELD_NAMES: &[&str] = &[
@@ -24,3 +79,38 @@ pub const STEP_FIELD_NAMES: &[&str] = &[
     "result",
     "logs",
 ];
+
+/// Timestamp for `generated_at` fields (RFC 3339)
+///
+/// **Public** - used for profiles and diff reports
+///
+/// Honors `SOURCE_DATE_EPOCH` so identical inputs produce byte-identical
+/// output; falls back to the current time when unset.
+pub fn generated_at() -> String {
+    generated_at_from(std::env::var(ENV_SOURCE_DATE_EPOCH).ok())
+}
+
+/// Timestamp for `generated_at` fields from an explicit `SOURCE_DATE_EPOCH` value
+///
+/// **Public** - testable core of `generated_at`
+///
+/// An unparsable or out-of-range value is ignored with a warning.
+pub fn generated_at_from(source_date_epoch: Option<String>) -> String {
+    let fixed = source_date_epoch.and_then(|raw| {
+        let parsed = raw
+            .trim()
+            .parse::<i64>()
+            .ok()
+            .and_then(|secs| chrono::DateTime::from_timestamp(secs, 0));
+        if parsed.is_none() {
+            log::warn!(
+                "Ignoring invalid {} value '{}'; using the current time",
+                ENV_SOURCE_DATE_EPOCH,
+                raw
+            );
+        }
+        parsed
+    });
+
+    fixed.unwrap_or_else(chrono::Utc::now).to_rfc3339()
+}
@@ -11,6 +11,12 @@ pub enum RpcError {
     #[error("HTTP request failed: {0}")]
     RequestFailed(#[from] reqwest::Error),
 
+    #[error("RPC timed out after {}s — try increasing --timeout", .0.as_secs())]
+    Timeout(std::time::Duration),
+
+    #[error("Could not connect to RPC endpoint {0} — check the URL and that the node is running")]
+    ConnectionFailed(String),
+
     #[error("Invalid RPC response: {0}")]
     InvalidResponse(String),
 
@@ -29,6 +35,17 @@ pub enum ParseError {
 
     #[error("Invalid trace format: {0}")]
     InvalidFormat(String),
+
+    #[error("Failed to read ABI file: {0}")]
+    AbiReadFailed(std::io::Error),
+
+    #[error("Invalid ABI: {0}")]
+    InvalidAbi(String),
+
+    #[error(
+        "Trace has {steps} execution steps, more than the limit of {limit}; raise it with --max-trace-steps (0 disables the limit)"
+    )]
+    TooManySteps { steps: usize, limit: usize },
     /*
         #[error("Unsupported schema version: {0}")]
         UnsupportedVersion(String),
@@ -38,6 +55,22 @@ pub enum ParseError {
     */
 }
 
+/// Errors that can occur while loading a WASM binary for source mapping
+#[derive(Error, Debug)]
+pub enum SourceMapError {
+    #[error("Failed to read WASM binary: {0}")]
+    ReadFailed(#[from] std::io::Error),
+
+    #[error("Not a valid WASM binary ({0}); check the --wasm path")]
+    InvalidWasm(String),
+
+    #[error("No DWARF debug info in the WASM binary; build with `debug = true` in the release profile to enable source mapping")]
+    NoDebugInfo,
+
+    #[error("DWARF debug info is present but unreadable ({0}); the WASM binary may be damaged, try rebuilding it")]
+    CorruptDebugInfo(String),
+}
+
 /// Errors that can occur during flamegraph generation
 #[derive(Error, Debug)]
 pub enum FlamegraphError {
@@ -48,6 +81,12 @@ pub enum FlamegraphError {
     #[error("Empty stack data")]
     EmptyStacks,
 
+    #[error(
+        "No gas attributed: all {stacks} stacks have zero weight (the tracer's step costs may \
+         have parsed as 0)"
+    )]
+    NoGasAttributed { stacks: usize },
+
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
 }
@@ -63,6 +102,12 @@ pub enum OutputError {
 
     #[error("Invalid output path: {0}")]
     InvalidPath(String),
+
+    #[error("Invalid redaction config: {0}")]
+    InvalidRedactionConfig(String),
+
+    #[error("Generated SVG is malformed: {0}")]
+    InvalidSvg(String),
 }
 
 /// Errors that can occur during profile comparison (diff)
@@ -80,6 +125,15 @@ pub enum DiffError {
     #[error("Threshold TOML parse error: {0}")]
     ThresholdParseFailed(#[from] toml::de::Error),
 
+    #[error("Budget file JSON error: {0}")]
+    BudgetParseFailed(#[from] serde_json::Error),
+
+    #[error("Invalid budget: {0}")]
+    InvalidBudget(String),
+
+    #[error("Invalid cost table: {0}")]
+    InvalidCostTable(String),
+
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
 }
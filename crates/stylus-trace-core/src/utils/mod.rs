@@ -2,6 +2,7 @@
 
 pub mod config;
 pub mod error;
+pub mod text;
 
 // Re-export commonly used error types for convenience
 pub use error::FlamegraphError;
@@ -0,0 +1,55 @@
+//! Character-safe text truncation for labels and tables.
+//!
+//! Frame names can contain multibyte UTF-8 (demangled or non-ASCII symbols),
+//! so truncation counts and cuts on `char` boundaries rather than bytes.
+
+/// Marker appended or prepended to truncated text
+const ELLIPSIS: &str = "...";
+
+/// Keep the start of `text`, ending with `...` when it exceeds `max_chars`
+///
+/// **Public** - used for flamegraph frame labels
+///
+/// # Returns
+/// `text` unchanged if it fits, otherwise at most `max_chars` characters
+/// (the ellipsis included)
+pub fn truncate_end(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    let keep = max_chars.saturating_sub(ELLIPSIS.len());
+    let cut = text.char_indices().nth(keep).map_or(text.len(), |(i, _)| i);
+    format!("{}{}", &text[..cut], ELLIPSIS)
+}
+
+/// Keep the end of `text`, starting with `...` when it exceeds `max_chars`
+///
+/// **Public** - used for stack columns, where the leaf frames matter most
+///
+/// # Returns
+/// `text` unchanged if it fits, otherwise at most `max_chars` characters
+/// (the ellipsis included)
+pub fn truncate_start(text: &str, max_chars: usize) -> String {
+    let count = text.chars().count();
+    if count <= max_chars {
+        return text.to_string();
+    }
+    let skip = count - max_chars.saturating_sub(ELLIPSIS.len());
+    let cut = text.char_indices().nth(skip).map_or(text.len(), |(i, _)| i);
+    format!("{}{}", ELLIPSIS, &text[cut..])
+}
+
+/// Format a percentage with `precision` decimal places, e.g. `12.5%`
+///
+/// **Public** - shared by the text summary, diff reports and tooltips so
+/// `--precision` applies everywhere
+pub fn format_percent(value: f64, precision: usize) -> String {
+    format!("{:.precision$}%", value)
+}
+
+/// Format a percent change with an explicit sign, e.g. `+12.50%`
+///
+/// **Public** - signed variant of `format_percent` for deltas
+pub fn format_percent_change(value: f64, precision: usize) -> String {
+    format!("{:+.precision$}%", value)
+}
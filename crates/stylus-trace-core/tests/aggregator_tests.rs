@@ -1,8 +1,20 @@
+use stylus_trace_core::aggregator::call_tree::build_call_tree_from_stacks;
 use stylus_trace_core::aggregator::metrics::{
     calculate_gas_distribution, calculate_hot_paths, create_hot_path,
 };
-use stylus_trace_core::aggregator::stack_builder::{map_hostio_to_label, CollapsedStack};
-use stylus_trace_core::parser::HostIoType;
+use stylus_trace_core::aggregator::source_files::UNKNOWN_SOURCE_FILE;
+use stylus_trace_core::aggregator::stack_builder::{
+    build_collapsed_stacks, build_contract_stacks, build_stacks_with, collapse_recursion,
+    contract_gas_breakdown, is_synthetic_hostio_stack, label_selectors, map_hostio_to_label,
+    prune_to_heaviest_subtrees, with_hostio_stacks, with_intrinsic_frame, AnonymousFrames,
+    CollapsedStack, StackOptions, DELEGATECALL_FRAME_PREFIX,
+};
+use stylus_trace_core::aggregator::{
+    gas_by_source_file, merge_profiles, sort_hot_paths, FrameFilter, HotPathSort,
+};
+use stylus_trace_core::parser::hostio::{HostIoEvent, HostIoStats};
+use stylus_trace_core::parser::schema::{HostIoSummary, Profile};
+use stylus_trace_core::parser::{parse_trace, HostIoType, SelectorMap};
 
 #[test]
 fn test_map_hostio_to_label() {
@@ -67,3 +79,458 @@ fn test_create_hot_path() {
         Some("0x42".to_string())
     );
 }
+
+#[test]
+fn test_with_intrinsic_frame() {
+    let stacks = vec![
+        CollapsedStack::new("main;execute".to_string(), 6000, Some(0x1)),
+        CollapsedStack::new("main;storage".to_string(), 1000, Some(0x2)),
+    ];
+
+    let result = with_intrinsic_frame(&stacks, 10000);
+
+    assert_eq!(result.len(), 3);
+    assert_eq!(result[1].stack, "root;intrinsic");
+    assert_eq!(result[1].weight, 3000);
+    assert_eq!(result.iter().map(|s| s.weight).sum::<u64>(), 10000);
+
+    // No intrinsic gas left over: stacks are unchanged
+    let unchanged = with_intrinsic_frame(&stacks, 7000);
+    assert_eq!(unchanged.len(), 2);
+}
+
+#[test]
+fn test_build_call_tree_from_stacks() {
+    let stacks = vec![
+        CollapsedStack::new("main;execute;storage".to_string(), 3000, None),
+        CollapsedStack::new("main;execute".to_string(), 2000, None),
+        CollapsedStack::new("root;main;compute".to_string(), 1000, None),
+    ];
+
+    let tree = build_call_tree_from_stacks(&stacks);
+
+    assert_eq!(tree.name, "root");
+    assert_eq!(tree.total_gas, 6000);
+    assert_eq!(tree.self_gas, 0);
+
+    let main = &tree.children[0];
+    assert_eq!(main.name, "main");
+    assert_eq!(main.total_gas, 6000);
+    assert_eq!(main.children.len(), 2);
+
+    let execute = &main.children[0];
+    assert_eq!(execute.name, "execute");
+    assert_eq!(execute.total_gas, 5000);
+    assert_eq!(execute.self_gas, 2000);
+    assert_eq!(execute.children[0].self_gas, 3000);
+}
+
+#[test]
+fn test_hot_path_cumulative_gas() {
+    let stacks = vec![
+        CollapsedStack::new("main;execute;storage".to_string(), 3000, None),
+        CollapsedStack::new("main;execute".to_string(), 2000, None),
+        CollapsedStack::new("main;executeX".to_string(), 1000, None),
+    ];
+
+    let hot_paths = calculate_hot_paths(&stacks, 0, 3);
+
+    // "main;execute" includes its own weight and its child, but not the sibling prefix match
+    let execute = hot_paths
+        .iter()
+        .find(|hp| hp.stack == "main;execute")
+        .unwrap();
+    assert_eq!(execute.gas, 2000);
+    assert_eq!(execute.cumulative_gas, 5000);
+
+    let storage = &hot_paths[0];
+    assert_eq!(storage.cumulative_gas, storage.gas);
+}
+
+#[test]
+fn test_sort_hot_paths() {
+    let stacks = vec![
+        CollapsedStack::new("b;storage_flush_cache".to_string(), 5000, None),
+        CollapsedStack::new("c;compute".to_string(), 3000, None),
+        CollapsedStack::new("a;storage_load_bytes32".to_string(), 2000, None),
+    ];
+    let mut hot_paths = calculate_hot_paths(&stacks, 0, 3);
+
+    sort_hot_paths(&mut hot_paths, HotPathSort::Name, false);
+    assert_eq!(hot_paths[0].stack, "a;storage_load_bytes32");
+    assert_eq!(hot_paths[2].stack, "c;compute");
+
+    sort_hot_paths(&mut hot_paths, HotPathSort::Category, false);
+    assert_eq!(hot_paths[0].stack, "b;storage_flush_cache");
+    assert_eq!(hot_paths[2].stack, "c;compute");
+
+    sort_hot_paths(&mut hot_paths, HotPathSort::Gas, true);
+    assert_eq!(hot_paths[0].gas, 2000);
+    assert_eq!(hot_paths[2].gas, 5000);
+}
+
+#[test]
+fn test_hostio_stacks_do_not_inflate_distribution() {
+    let stacks = vec![
+        CollapsedStack::new("main;execute".to_string(), 6000, Some(0x1)),
+        CollapsedStack::new("main;storage_load_bytes32".to_string(), 4000, Some(0x2)),
+    ];
+
+    let mut stats = HostIoStats::new();
+    stats.add_event(HostIoEvent {
+        io_type: HostIoType::StorageLoad,
+        gas_cost: 4000,
+    });
+
+    let with_synthetic = with_hostio_stacks(&stacks, &stats);
+
+    assert_eq!(with_synthetic.len(), 3);
+    let synthetic: Vec<_> = with_synthetic
+        .iter()
+        .filter(|s| is_synthetic_hostio_stack(s))
+        .collect();
+    assert_eq!(synthetic.len(), 1);
+    assert_eq!(synthetic[0].stack, "hostio (approx);storage_load");
+    assert_eq!(synthetic[0].weight, 4000);
+
+    let dist = calculate_gas_distribution(&with_synthetic);
+    assert_eq!(dist.total_gas, 10000);
+    assert_eq!(dist.stack_count, 2);
+}
+
+#[test]
+fn test_collapse_recursion_folds_and_merges() {
+    let stacks = vec![
+        CollapsedStack::new("main;f;f;f;g".to_string(), 300, Some(3)),
+        CollapsedStack::new("main;f;g".to_string(), 100, Some(1)),
+        CollapsedStack::new("main;h".to_string(), 50, None),
+    ];
+
+    let folded = collapse_recursion(&stacks, false);
+    assert_eq!(folded.len(), 2);
+    assert_eq!(folded[0].stack, "main;f;g");
+    assert_eq!(folded[0].weight, 400);
+    assert_eq!(folded[0].last_pc, Some(3));
+    assert_eq!(folded[1].stack, "main;h");
+
+    let annotated = collapse_recursion(&stacks, true);
+    let names: Vec<&str> = annotated.iter().map(|s| s.stack.as_str()).collect();
+    assert_eq!(names, vec!["main;f (x3);g", "main;f;g", "main;h"]);
+}
+
+#[test]
+fn test_prune_to_heaviest_subtrees_merges_the_rest() {
+    let stacks = vec![
+        CollapsedStack::new("root;transfer;storage_load".to_string(), 300, Some(1)),
+        CollapsedStack::new("root;transfer;emit_log".to_string(), 100, Some(2)),
+        CollapsedStack::new("root;approve;storage_store".to_string(), 200, Some(3)),
+        CollapsedStack::new("root;name".to_string(), 20, Some(4)),
+        CollapsedStack::new("root;symbol;read_args".to_string(), 10, Some(5)),
+    ];
+
+    let pruned = prune_to_heaviest_subtrees(&stacks, 2);
+    let names: Vec<(&str, u64)> = pruned
+        .iter()
+        .map(|s| (s.stack.as_str(), s.weight))
+        .collect();
+    assert_eq!(
+        names,
+        vec![
+            ("root;transfer;storage_load", 300),
+            ("root;approve;storage_store", 200),
+            ("root;transfer;emit_log", 100),
+            ("root;other", 30),
+        ]
+    );
+    assert_eq!(pruned[3].last_pc, None);
+
+    assert_eq!(prune_to_heaviest_subtrees(&stacks, 0).len(), stacks.len());
+    assert_eq!(prune_to_heaviest_subtrees(&stacks, 4).len(), stacks.len());
+}
+
+#[test]
+fn test_split_by_contract_attributes_gas_to_callee() {
+    let callee = "0x00000000000000000000000000000000000000bb";
+    let raw = serde_json::json!({
+        "gas": 100,
+        "structLogs": [
+            { "op": "SLOAD", "gasCost": 10, "depth": 1 },
+            { "op": "CALL", "gasCost": 5, "depth": 1,
+              "stack": ["0x0", "0x0", "0x0", "0x0", "0x0", "0xbb", "0x1388"] },
+            { "op": "SSTORE", "gasCost": 20, "depth": 2 },
+            { "op": "SLOAD", "gasCost": 7, "depth": 2 },
+            { "op": "SLOAD", "gasCost": 3, "depth": 1 }
+        ]
+    });
+    let mut parsed = parse_trace("0xabc", &raw).unwrap();
+    parsed.contract_address = Some("0xaa".to_string());
+
+    let stacks = build_contract_stacks(&parsed);
+    assert!(stacks
+        .iter()
+        .any(|s| s.stack == format!("0xaa;{};storage_flush_cache", callee)));
+    assert!(stacks
+        .iter()
+        .any(|s| s.stack == "0xaa;storage_load_bytes32"));
+
+    let contracts = contract_gas_breakdown(&stacks);
+    assert_eq!(contracts.len(), 2);
+    assert_eq!(contracts[0].address, callee);
+    assert_eq!(contracts[0].gas, 270_000);
+    assert_eq!(contracts[1].address, "0xaa");
+    assert_eq!(contracts[1].gas, 180_000);
+}
+
+#[test]
+fn test_follow_delegatecalls_labels_implementation() {
+    let implementation = "0x00000000000000000000000000000000000000cc";
+    let raw = serde_json::json!({
+        "gas": 100,
+        "structLogs": [
+            { "op": "DELEGATECALL", "gasCost": 5, "depth": 1,
+              "stack": ["0x0", "0x0", "0x0", "0x0", "0xcc", "0x1388"] },
+            { "op": "SSTORE", "gasCost": 20, "depth": 2 },
+            { "op": "SLOAD", "gasCost": 3, "depth": 1 }
+        ]
+    });
+    let mut parsed = parse_trace("0xabc", &raw).unwrap();
+    parsed.contract_address = Some("0xaa".to_string());
+
+    // Without following, the delegatecall is an opaque call frame
+    let plain = build_collapsed_stacks(&parsed);
+    assert!(plain
+        .iter()
+        .any(|s| s.stack == "call;call;storage_flush_cache"));
+
+    let followed = build_stacks_with(
+        &parsed,
+        StackOptions {
+            follow_delegatecalls: true,
+            ..StackOptions::default()
+        },
+    );
+    let frame = format!("{}{}", DELEGATECALL_FRAME_PREFIX, implementation);
+    assert!(followed
+        .iter()
+        .any(|s| s.stack == format!("call;{};storage_flush_cache", frame)));
+
+    // Split by contract: the implementation runs in the proxy's context
+    let split = build_stacks_with(
+        &parsed,
+        StackOptions {
+            split_by_contract: true,
+            follow_delegatecalls: true,
+            ..StackOptions::default()
+        },
+    );
+    assert!(split
+        .iter()
+        .any(|s| s.stack == format!("0xaa;{};storage_flush_cache", frame)));
+    let contracts = contract_gas_breakdown(&split);
+    assert_eq!(contracts.len(), 1);
+    assert_eq!(contracts[0].address, "0xaa");
+    assert_eq!(contracts[0].gas, 280_000);
+}
+
+#[test]
+fn test_anonymous_frames_label_and_hide() {
+    let raw = serde_json::json!({
+        "gas": 100,
+        "structLogs": [
+            { "op": "SLOAD", "gasCost": 3, "depth": 2 },
+            { "gasCost": 7, "depth": 2, "pc": 26 }
+        ]
+    });
+    let parsed = parse_trace("0xabc", &raw).unwrap();
+    let with = |anonymous_frames| {
+        let mut stacks: Vec<String> = build_stacks_with(
+            &parsed,
+            StackOptions {
+                anonymous_frames,
+                ..StackOptions::default()
+            },
+        )
+        .into_iter()
+        .map(|s| s.stack)
+        .collect();
+        stacks.sort();
+        stacks
+    };
+
+    assert_eq!(
+        with(AnonymousFrames::Keep),
+        vec!["call;call;storage_load_bytes32", "call;call;unknown"]
+    );
+    assert_eq!(
+        with(AnonymousFrames::Label),
+        vec![
+            "call (depth 1);call (depth 2);storage_load_bytes32",
+            "call (depth 1);call (depth 2);unknown (pc 0x1a)"
+        ]
+    );
+    // Hidden frames leave the unknown step with no frame to charge
+    assert_eq!(
+        with(AnonymousFrames::Hide),
+        vec!["storage_load_bytes32", "unknown"]
+    );
+}
+
+#[test]
+fn test_gas_by_source_file_groups_stacks_by_pc() {
+    let stacks = vec![
+        CollapsedStack::new("main;a".to_string(), 500, Some(0x10)),
+        CollapsedStack::new("main;b".to_string(), 300, Some(0x20)),
+        CollapsedStack::new("main;c".to_string(), 100, Some(0x90)),
+        CollapsedStack::new("main;d".to_string(), 100, None),
+    ];
+
+    let files = gas_by_source_file(&stacks, |pc| match pc {
+        0x00..=0x3f => Some("src/lib.rs".to_string()),
+        _ => None,
+    });
+
+    assert_eq!(files.len(), 2);
+    assert_eq!(files[0].file, "src/lib.rs");
+    assert_eq!(files[0].gas, 800);
+    assert_eq!(files[0].percentage, 80.0);
+    assert_eq!(files[1].file, UNKNOWN_SOURCE_FILE);
+    assert_eq!(files[1].gas, 200);
+}
+
+#[test]
+fn test_label_selectors_relabels_known_frames() {
+    let selectors = SelectorMap::from_abi(&serde_json::json!([{
+        "type": "function",
+        "name": "transfer",
+        "inputs": [{ "type": "address" }, { "type": "uint256" }]
+    }]))
+    .unwrap();
+
+    let mut stacks = vec![
+        CollapsedStack::new("main;0xa9059cbb;storage_load".to_string(), 10, None),
+        CollapsedStack::new("main;0x12345678".to_string(), 5, None),
+    ];
+    label_selectors(&mut stacks, &selectors);
+
+    assert_eq!(
+        stacks[0].stack,
+        "main;transfer(address,uint256);storage_load"
+    );
+    assert_eq!(stacks[1].stack, "main;0x12345678");
+}
+
+#[test]
+fn test_frame_filter_reattributes_gas_to_parent() {
+    let stacks = vec![
+        CollapsedStack::new("main;alloc;memcpy".to_string(), 10, None),
+        CollapsedStack::new("main;alloc".to_string(), 5, None),
+        CollapsedStack::new("main;memcpy".to_string(), 3, None),
+        CollapsedStack::new("alloc".to_string(), 2, None),
+    ];
+
+    let exclude = FrameFilter::new(&["^alloc$".to_string()], &[]).unwrap();
+    let filtered = exclude.apply(&stacks);
+    let names: Vec<(&str, u64)> = filtered
+        .iter()
+        .map(|s| (s.stack.as_str(), s.weight))
+        .collect();
+    assert_eq!(names, vec![("main;memcpy", 13), ("main", 5)]);
+
+    let include = FrameFilter::new(&[], &["^main$".to_string()]).unwrap();
+    let filtered = include.apply(&stacks);
+    assert_eq!(filtered.len(), 1);
+    assert_eq!(filtered[0].weight, 18);
+
+    assert!(FrameFilter::new(&["(".to_string()], &[]).is_err());
+}
+
+#[test]
+fn test_gas_sums_saturate_instead_of_overflowing() {
+    let stacks = vec![
+        CollapsedStack::new("main;a".to_string(), u64::MAX - 10, Some(0x1)),
+        CollapsedStack::new("main;b".to_string(), u64::MAX - 20, Some(0x2)),
+        CollapsedStack::new("main;c".to_string(), 100, Some(0x3)),
+    ];
+
+    let dist = calculate_gas_distribution(&stacks);
+    assert_eq!(dist.total_gas, u64::MAX);
+    assert_eq!(dist.stack_count, 3);
+    assert_eq!(dist.mean_gas_per_stack, u64::MAX / 3);
+    assert!(dist.top_10_percent_percentage <= 100.0);
+
+    let hot_paths = calculate_hot_paths(&stacks, u64::MAX, 1);
+    assert_eq!(hot_paths[0].cumulative_gas, u64::MAX - 10);
+
+    let merged = collapse_recursion(
+        &[
+            CollapsedStack::new("f;f;g".to_string(), u64::MAX - 1, None),
+            CollapsedStack::new("f;g".to_string(), 5, None),
+        ],
+        false,
+    );
+    assert_eq!(merged.len(), 1);
+    assert_eq!(merged[0].weight, u64::MAX);
+
+    let svg = stylus_trace_core::flamegraph::generate_flamegraph(&stacks, None, None).unwrap();
+    assert!(svg.contains("main"));
+}
+
+fn sample_profile(total_gas: u64, block: u64, paths: &[(&str, u64)]) -> Profile {
+    let stacks: Vec<CollapsedStack> = paths
+        .iter()
+        .map(|(stack, gas)| CollapsedStack::new(stack.to_string(), *gas, None))
+        .collect();
+    Profile {
+        version: "1.0.0".to_string(),
+        transaction_hash: "0xabc".to_string(),
+        label: None,
+        contract_address: None,
+        block_number: Some(block),
+        total_gas,
+        total_gas_source: Default::default(),
+        hostio_summary: HostIoSummary {
+            total_calls: total_gas / 100,
+            by_type: [("storage_load".to_string(), total_gas / 100)].into(),
+            total_hostio_gas: 0,
+            gas_by_type: Default::default(),
+            avg_gas_per_call: Default::default(),
+        },
+        contracts: None,
+        repeated_slot_reads: None,
+        hot_paths: calculate_hot_paths(&stacks, total_gas, 10),
+        all_stacks: Some(stacks),
+        samples: None,
+        generated_at: String::new(),
+    }
+}
+
+#[test]
+fn test_merge_profiles_averages_samples() {
+    let profiles = vec![
+        sample_profile(1000, 1, &[("main;a", 600), ("main;b", 100)]),
+        sample_profile(1100, 1, &[("main;a", 700)]),
+        sample_profile(1200, 2, &[("main;a", 800), ("main;b", 200)]),
+    ];
+
+    let merged = merge_profiles(&profiles).unwrap();
+    let samples = merged.samples.as_ref().unwrap();
+
+    assert_eq!(merged.total_gas, 1100);
+    assert_eq!(merged.transaction_hash, "0xabc");
+    assert_eq!(merged.block_number, None); // samples disagree
+    assert_eq!(merged.hostio_summary.total_calls, 11);
+    assert_eq!(merged.hostio_summary.by_type["storage_load"], 11);
+    assert_eq!(samples.count, 3);
+    assert!((samples.total_gas_stddev - 81.65).abs() < 0.01);
+
+    // main;b is missing from one sample: dropped from hot paths, averaged in stacks
+    assert_eq!(merged.hot_paths.len(), 1);
+    assert_eq!(merged.hot_paths[0].gas, 700);
+    assert_eq!(samples.dropped_hot_paths, 1);
+    assert!((samples.hot_path_gas_stddev["main;a"] - 81.65).abs() < 0.01);
+    let stacks = merged.all_stacks.unwrap();
+    assert_eq!(stacks[1].stack, "main;b");
+    assert_eq!(stacks[1].weight, 100);
+
+    assert!(merge_profiles(&[]).is_none());
+}
@@ -1,4 +1,6 @@
-use stylus_trace_core::commands::{validate_args, CaptureArgs};
+use stylus_trace_core::commands::{
+    execute_capture, normalize_tracer_name, suggest_tracer_name, validate_args, CaptureArgs,
+};
 
 #[test]
 fn test_validate_args_valid() {
@@ -36,6 +38,62 @@ fn test_validate_args_invalid_rpc_scheme() {
     assert!(validate_args(&args).is_err());
 }
 
+#[test]
+fn test_validate_args_websocket_rpc() {
+    for rpc_url in ["ws://localhost:8548", "wss://arb.example.com"] {
+        let args = CaptureArgs {
+            rpc_url: rpc_url.to_string(),
+            transaction_hash: "0x1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef"
+                .to_string(),
+            ..Default::default()
+        };
+
+        assert!(
+            validate_args(&args).is_ok(),
+            "{} should be accepted",
+            rpc_url
+        );
+    }
+}
+
+#[test]
+fn test_validate_args_tracer_config() {
+    let args = |config: &str| CaptureArgs {
+        rpc_url: "http://localhost:8547".to_string(),
+        transaction_hash: "0x1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef"
+            .to_string(),
+        tracer_config: Some(config.to_string()),
+        ..Default::default()
+    };
+
+    assert!(validate_args(&args(r#"{"onlyTopCall": true}"#)).is_ok());
+    assert!(validate_args(&args("{onlyTopCall: true}")).is_err());
+    assert!(validate_args(&args("[1, 2]")).is_err());
+}
+
+#[test]
+fn test_tracer_name_normalization() {
+    assert_eq!(normalize_tracer_name("stylustracer"), Some("stylusTracer"));
+    assert_eq!(normalize_tracer_name("4BYTETRACER"), Some("4byteTracer"));
+    assert_eq!(normalize_tracer_name("myTracer"), None);
+
+    assert_eq!(suggest_tracer_name("calTracer"), Some("callTracer"));
+    assert_eq!(suggest_tracer_name("prestateTrace"), Some("prestateTracer"));
+    assert_eq!(suggest_tracer_name("opcodeLogger"), None);
+
+    // Unknown names warn but are still allowed as custom tracers
+    for tracer in ["stylustracer", "calTracer", "opcodeLogger"] {
+        let args = CaptureArgs {
+            rpc_url: "http://localhost:8547".to_string(),
+            transaction_hash: "0x1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef"
+                .to_string(),
+            tracer: Some(tracer.to_string()),
+            ..Default::default()
+        };
+        assert!(validate_args(&args).is_ok(), "{}", tracer);
+    }
+}
+
 #[test]
 fn test_validate_args_empty_tx_hash() {
     let args = CaptureArgs {
@@ -107,3 +165,817 @@ fn test_validate_args_top_paths_too_large() {
 
     assert!(validate_args(&args).is_err());
 }
+
+#[test]
+fn test_validate_args_baseline_tx_valid() {
+    let args = CaptureArgs {
+        rpc_url: "http://localhost:8547".to_string(),
+        transaction_hash: "0x1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef"
+            .to_string(),
+        baseline_tx: Some(
+            "0xabcdef1234567890abcdef1234567890abcdef1234567890abcdef1234567890".to_string(),
+        ),
+        ..Default::default()
+    };
+
+    assert!(validate_args(&args).is_ok());
+}
+
+#[test]
+fn test_validate_args_baseline_tx_invalid_hex() {
+    let args = CaptureArgs {
+        rpc_url: "http://localhost:8547".to_string(),
+        transaction_hash: "0x1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef"
+            .to_string(),
+        baseline_tx: Some("0x1234".to_string()),
+        ..Default::default()
+    };
+
+    assert!(validate_args(&args).is_err());
+}
+
+#[test]
+fn test_validate_args_baseline_and_baseline_tx_conflict() {
+    let args = CaptureArgs {
+        rpc_url: "http://localhost:8547".to_string(),
+        transaction_hash: "0x1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef"
+            .to_string(),
+        baseline: Some("baseline.json".into()),
+        baseline_tx: Some(
+            "0xabcdef1234567890abcdef1234567890abcdef1234567890abcdef1234567890".to_string(),
+        ),
+        ..Default::default()
+    };
+
+    assert!(validate_args(&args).is_err());
+}
+
+#[test]
+fn test_validate_args_zero_timeout() {
+    let args = CaptureArgs {
+        rpc_url: "http://localhost:8547".to_string(),
+        transaction_hash: "0x1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef"
+            .to_string(),
+        timeout_secs: 0,
+        ..Default::default()
+    };
+
+    assert!(validate_args(&args).is_err());
+}
+
+/// Spawn a WebSocket JSON-RPC node that serves `trace` for debug_traceTransaction
+/// and `null` for every other method
+fn spawn_mock_node(trace: serde_json::Value) -> String {
+    spawn_mock_node_with(move |request| {
+        let result = if request["method"] == "debug_traceTransaction"
+            || request["method"] == "debug_traceCall"
+        {
+            trace.clone()
+        } else if request["method"] == "web3_clientVersion" {
+            serde_json::json!("MockNode/v1.0")
+        } else {
+            serde_json::Value::Null
+        };
+        serde_json::json!({ "result": result })
+    })
+}
+
+/// Spawn a WebSocket JSON-RPC node whose reply body (`result` or `error`) is
+/// built by `respond` for each request
+fn spawn_mock_node_with(
+    respond: impl Fn(&serde_json::Value) -> serde_json::Value + Send + 'static,
+) -> String {
+    use std::net::TcpListener;
+    use tungstenite::Message;
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("ws://{}", listener.local_addr().unwrap());
+
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(mut socket) = tungstenite::accept(stream.unwrap()) else {
+                continue;
+            };
+            let Ok(message) = socket.read() else {
+                continue;
+            };
+            let request: serde_json::Value =
+                serde_json::from_str(message.to_text().unwrap()).unwrap();
+
+            let mut response = respond(&request);
+            response["jsonrpc"] = serde_json::json!("2.0");
+            response["id"] = request["id"].clone();
+            let _ = socket.send(Message::Text(response.to_string()));
+        }
+    });
+
+    url
+}
+
+#[test]
+fn test_capture_zero_step_trace_skips_flamegraph() {
+    let rpc_url = spawn_mock_node(serde_json::json!({"gasUsed": "0x5208", "structLogs": []}));
+    let dir = tempfile::tempdir().unwrap();
+    let output_json = dir.path().join("profile.json");
+    let output_svg = dir.path().join("flamegraph.svg");
+
+    let args = CaptureArgs {
+        rpc_url,
+        transaction_hash: "0x1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef"
+            .to_string(),
+        output_json: output_json.clone(),
+        output_svg: Some(output_svg.clone()),
+        print_summary: true,
+        ..Default::default()
+    };
+
+    execute_capture(args).unwrap();
+
+    let profile = stylus_trace_core::output::read_profile(&output_json).unwrap();
+    assert_eq!(profile.total_gas, 21_000 * 10_000);
+    assert!(profile.hot_paths.is_empty());
+    assert!(!output_svg.exists());
+}
+
+#[test]
+fn test_capture_observer_sees_stages_in_order() {
+    use std::cell::RefCell;
+    use stylus_trace_core::commands::{execute_capture_with_observer, CaptureStage};
+
+    let trace = serde_json::json!({
+        "gas": 100,
+        "structLogs": [{ "op": "SLOAD", "gasCost": 10, "depth": 1 }]
+    });
+    let rpc_url = spawn_mock_node(trace);
+    let dir = tempfile::tempdir().unwrap();
+    let output_json = dir.path().join("profile.json");
+
+    let args = CaptureArgs {
+        rpc_url,
+        transaction_hash: "0x1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef"
+            .to_string(),
+        output_json: output_json.clone(),
+        output_svg: Some(dir.path().join("flamegraph.svg")),
+        ..Default::default()
+    };
+
+    let stages = RefCell::new(Vec::new());
+    execute_capture_with_observer(args, &|stage: &CaptureStage| {
+        stages.borrow_mut().push(stage.clone())
+    })
+    .unwrap();
+
+    let stages = stages.into_inner();
+    assert_eq!(stages.len(), 6);
+    assert!(matches!(stages[0], CaptureStage::Fetched { .. }));
+    assert_eq!(
+        stages[1],
+        CaptureStage::Parsed {
+            steps: 1,
+            total_gas: 1_000_000
+        }
+    );
+    assert_eq!(stages[2], CaptureStage::StacksBuilt { stacks: 1 });
+    assert_eq!(stages[3], CaptureStage::FlamegraphGenerated);
+    assert_eq!(
+        stages[4],
+        CaptureStage::Written {
+            profile: output_json
+        }
+    );
+    assert!(matches!(stages[5], CaptureStage::Completed { .. }));
+}
+
+#[test]
+fn test_capture_summary_saturates_near_max_gas() {
+    let huge = u64::MAX / 2;
+    let trace = serde_json::json!({
+        "gas": 0,
+        "structLogs": [
+            { "op": "SLOAD", "gasCost": huge, "depth": 1 },
+            { "op": "SSTORE", "gasCost": huge, "depth": 1 },
+            { "op": "LOG1", "gasCost": huge, "depth": 1 }
+        ]
+    });
+    let rpc_url = spawn_mock_node(trace);
+    let dir = tempfile::tempdir().unwrap();
+    let summary_json = dir.path().join("summary.json");
+
+    let args = CaptureArgs {
+        rpc_url,
+        transaction_hash: "0x1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef"
+            .to_string(),
+        output_json: dir.path().join("profile.json"),
+        output_svg: Some(dir.path().join("flamegraph.svg")),
+        output_summary_json: Some(summary_json.clone()),
+        print_summary: true,
+        ..Default::default()
+    };
+
+    execute_capture(args).unwrap();
+
+    let summary: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&summary_json).unwrap()).unwrap();
+    assert_eq!(summary["execution_gas"], u64::MAX);
+    assert_eq!(summary["intrinsic_gas"], 0);
+}
+
+#[test]
+fn test_capture_prunes_svg_below_call_frame() {
+    let trace = serde_json::json!({
+        "gas": 0,
+        "structLogs": [
+            { "op": "SLOAD", "gasCost": 50, "depth": 1 },
+            { "op": "SSTORE", "gasCost": 30, "depth": 1 },
+            { "op": "LOG1", "gasCost": 10, "depth": 1 },
+            { "op": "SHA3", "gasCost": 5, "depth": 1 }
+        ]
+    });
+    let rpc_url = spawn_mock_node(trace);
+    let dir = tempfile::tempdir().unwrap();
+    let output_svg = dir.path().join("flamegraph.svg");
+
+    execute_capture(CaptureArgs {
+        rpc_url,
+        transaction_hash: "0x1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef"
+            .to_string(),
+        output_json: dir.path().join("profile.json"),
+        output_svg: Some(output_svg.clone()),
+        max_svg_subtrees: Some(2),
+        ..Default::default()
+    })
+    .unwrap();
+
+    // Every stack starts with `call`, so the ranking happens below it
+    let svg = std::fs::read_to_string(&output_svg).unwrap();
+    assert!(svg.contains("<title>storage_load_bytes32:"));
+    assert!(svg.contains("<title>storage_flush_cache:"));
+    assert!(svg.contains("<title>other: 150000 ink"));
+}
+
+#[test]
+fn test_capture_redact_hides_repeated_slot_reads() {
+    let callee = format!("0x{}", "cd".repeat(20));
+    let slot = format!("0x{}", "ab".repeat(32));
+    let sload = serde_json::json!({ "op": "SLOAD", "gasCost": 100, "depth": 2, "stack": [slot] });
+    let trace = serde_json::json!({
+        "gas": 1000,
+        "structLogs": [
+            { "op": "CALL", "gasCost": 100, "depth": 1,
+              "stack": ["0x0", "0x0", "0x0", "0x0", callee, "0x1388"] },
+            sload,
+            sload
+        ]
+    });
+    let rpc_url = spawn_mock_node(trace);
+    let dir = tempfile::tempdir().unwrap();
+    let output_json = dir.path().join("profile.json");
+
+    execute_capture(CaptureArgs {
+        rpc_url,
+        transaction_hash: "0x1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef"
+            .to_string(),
+        output_json: output_json.clone(),
+        output_svg: None,
+        split_by_contract: true,
+        redact: true,
+        print_summary: true,
+        ..Default::default()
+    })
+    .unwrap();
+
+    let json = std::fs::read_to_string(&output_json).unwrap();
+    let profile = stylus_trace_core::output::read_profile(&output_json).unwrap();
+    let slots = profile.repeated_slot_reads.unwrap();
+    assert_eq!(slots.len(), 1);
+    assert_eq!(slots[0].reads, 2);
+    assert!(!json.contains(&callee[2..]));
+    assert!(!json.contains(&slot[2..]));
+}
+
+#[test]
+fn test_capture_insights_see_hostio_types_beyond_the_cap() {
+    let sload = serde_json::json!({ "op": "SLOAD", "gasCost": 10, "depth": 1 });
+    let trace = serde_json::json!({
+        "gas": 1000,
+        "structLogs": [sload, sload, sload, { "op": "LOG1", "gasCost": 500, "depth": 1 }]
+    });
+    let rpc_url = spawn_mock_node(trace);
+    let dir = tempfile::tempdir().unwrap();
+    let output_json = dir.path().join("profile.json");
+    let output_svg = dir.path().join("flamegraph.svg");
+
+    execute_capture(CaptureArgs {
+        rpc_url,
+        transaction_hash: "0x1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef"
+            .to_string(),
+        output_json: output_json.clone(),
+        output_svg: Some(output_svg.clone()),
+        top_hostio_types: Some(1),
+        annotate_insights: true,
+        ..Default::default()
+    })
+    .unwrap();
+
+    // The written profile folds emit_log into `other`...
+    let profile = stylus_trace_core::output::read_profile(&output_json).unwrap();
+    assert!(!profile.hostio_summary.by_type.contains_key("emit_log"));
+    assert!(profile.hostio_summary.by_type.contains_key("other"));
+    // ...but the insights ran on the full summary
+    let svg = std::fs::read_to_string(&output_svg).unwrap();
+    assert!(svg.contains("Excessive event emission"));
+}
+
+#[test]
+fn test_capture_watermark_matches_profile_and_redaction() {
+    let tx_hash = "0x1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef";
+    let trace = serde_json::json!({
+        "gas": 100,
+        "structLogs": [{ "op": "SLOAD", "gasCost": 10, "depth": 1 }]
+    });
+    let rpc_url = spawn_mock_node(trace);
+    let dir = tempfile::tempdir().unwrap();
+    let output_json = dir.path().join("profile.json");
+    let output_svg = dir.path().join("flamegraph.svg");
+
+    execute_capture(CaptureArgs {
+        rpc_url,
+        transaction_hash: tx_hash.to_string(),
+        output_json: output_json.clone(),
+        output_svg: Some(output_svg.clone()),
+        redact: true,
+        ..Default::default()
+    })
+    .unwrap();
+
+    let profile = stylus_trace_core::output::read_profile(&output_json).unwrap();
+    let svg = std::fs::read_to_string(&output_svg).unwrap();
+    assert!(svg.contains(&format!(r#"generated-at="{}""#, profile.generated_at)));
+    assert!(svg.contains(r#"transaction="[REDACTED]""#));
+    assert!(!svg.contains(&tx_hash[2..]));
+}
+
+#[test]
+fn test_capture_annotates_collapsed_recursion() {
+    let trace = serde_json::json!({
+        "gas": 0,
+        "structLogs": [{ "op": "SLOAD", "gasCost": 50, "depth": 3 }]
+    });
+    let rpc_url = spawn_mock_node(trace);
+    let dir = tempfile::tempdir().unwrap();
+    let output_svg = dir.path().join("flamegraph.svg");
+
+    execute_capture(CaptureArgs {
+        rpc_url,
+        transaction_hash: "0x1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef"
+            .to_string(),
+        output_json: dir.path().join("profile.json"),
+        output_svg: Some(output_svg.clone()),
+        collapse_recursion: true,
+        annotate_recursion: true,
+        ..Default::default()
+    })
+    .unwrap();
+
+    let svg = std::fs::read_to_string(&output_svg).unwrap();
+    assert!(svg.contains("<title>call (x3):"));
+}
+
+#[test]
+fn test_capture_to_profile_writes_nothing() {
+    use stylus_trace_core::commands::capture_to_profile;
+
+    let trace = serde_json::json!({
+        "gas": 100,
+        "structLogs": [{ "op": "SLOAD", "gasCost": 10, "depth": 1 }]
+    });
+    let rpc_url = spawn_mock_node(trace);
+    let dir = tempfile::tempdir().unwrap();
+    let output_json = dir.path().join("profile.json");
+
+    let args = CaptureArgs {
+        rpc_url,
+        transaction_hash: "0x1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef"
+            .to_string(),
+        output_json: output_json.clone(),
+        label: Some("in-memory".to_string()),
+        ..Default::default()
+    };
+
+    let captured = capture_to_profile(&args).unwrap();
+
+    assert_eq!(captured.profile.total_gas, 1_000_000);
+    assert_eq!(captured.profile.label.as_deref(), Some("in-memory"));
+    assert_eq!(captured.profile.hot_paths.len(), 1);
+    assert!(captured.profile.all_stacks.is_some());
+    assert!(captured.svg.unwrap().contains("<svg"));
+    assert!(!output_json.exists());
+}
+
+#[test]
+fn test_capture_strict_ink_rejects_increasing_ink() {
+    let trace = serde_json::json!([
+        {"name": "call", "startInk": 1000, "endInk": 400},
+        {"name": "refund", "startInk": 400, "endInk": 900}
+    ]);
+    let rpc_url = spawn_mock_node(trace);
+    let dir = tempfile::tempdir().unwrap();
+
+    let args = CaptureArgs {
+        rpc_url,
+        transaction_hash: "0x1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef"
+            .to_string(),
+        output_json: dir.path().join("profile.json"),
+        strict_ink: true,
+        ..Default::default()
+    };
+
+    let err = execute_capture(args).unwrap_err();
+    assert!(err.to_string().contains("non-decreasing ink"));
+}
+
+#[test]
+fn test_capture_uses_trace_cache() {
+    let rpc_url = spawn_mock_node(serde_json::json!({"gasUsed": "0x5208", "structLogs": []}));
+    let dir = tempfile::tempdir().unwrap();
+    let cache_dir = dir.path().join("cache");
+    let output_json = dir.path().join("profile.json");
+
+    let args = CaptureArgs {
+        rpc_url,
+        transaction_hash: "0x1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef"
+            .to_string(),
+        output_json: output_json.clone(),
+        cache_dir: Some(cache_dir.clone()),
+        ..Default::default()
+    };
+
+    execute_capture(args.clone()).unwrap();
+    let entries: Vec<_> = std::fs::read_dir(&cache_dir).unwrap().collect();
+    assert_eq!(entries.len(), 1);
+
+    // Tamper with the cached entry: a cache hit must not touch the network
+    let entry = entries[0].as_ref().unwrap().path();
+    std::fs::write(&entry, r#"{"gasUsed": "0x1", "structLogs": []}"#).unwrap();
+    execute_capture(args.clone()).unwrap();
+    let profile = stylus_trace_core::output::read_profile(&output_json).unwrap();
+    assert_eq!(profile.total_gas, 10_000);
+
+    // --refresh-cache goes back to the node and rewrites the entry
+    execute_capture(CaptureArgs {
+        refresh_cache: true,
+        ..args
+    })
+    .unwrap();
+    let profile = stylus_trace_core::output::read_profile(&output_json).unwrap();
+    assert_eq!(profile.total_gas, 21_000 * 10_000);
+}
+
+#[test]
+fn test_capture_dry_run_writes_nothing() {
+    let rpc_url = spawn_mock_node(serde_json::json!({"gasUsed": "0x5208", "structLogs": []}));
+    let dir = tempfile::tempdir().unwrap();
+
+    let args = CaptureArgs {
+        rpc_url,
+        transaction_hash: "0x1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef"
+            .to_string(),
+        output_json: dir.path().join("profile.json"),
+        output_svg: Some(dir.path().join("flamegraph.svg")),
+        cache_dir: Some(dir.path().join("cache")),
+        dry_run: true,
+        ..Default::default()
+    };
+
+    execute_capture(args.clone()).unwrap();
+    assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+
+    // An unreachable node fails the health check
+    let err = execute_capture(CaptureArgs {
+        rpc_url: "http://127.0.0.1:1".to_string(),
+        ..args
+    })
+    .unwrap_err();
+    assert!(err.to_string().contains("not reachable"));
+}
+
+#[test]
+fn test_capture_testnode_skips_cache() {
+    let rpc_url = spawn_mock_node(serde_json::json!({"gasUsed": "0x5208", "structLogs": []}));
+    let dir = tempfile::tempdir().unwrap();
+    let cache_dir = dir.path().join("cache");
+
+    let args = CaptureArgs {
+        rpc_url,
+        transaction_hash: "0x1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef"
+            .to_string(),
+        output_json: dir.path().join("profile.json"),
+        cache_dir: Some(cache_dir.clone()),
+        testnode: true,
+        ..Default::default()
+    };
+
+    // A non-Nitro node only warns
+    execute_capture(args.clone()).unwrap();
+    assert!(dir.path().join("profile.json").exists());
+    assert!(!cache_dir.exists());
+
+    let err = execute_capture(CaptureArgs {
+        rpc_url: "http://127.0.0.1:1".to_string(),
+        ..args
+    })
+    .unwrap_err();
+    assert!(err.to_string().contains("nitro-testnode"));
+}
+
+#[test]
+fn test_capture_unsupported_tracer_guidance_and_fallback() {
+    // A node without stylusTracer that still serves callTracer
+    let rpc_url = spawn_mock_node_with(|request| {
+        if request["params"][1]["tracer"] == "stylusTracer" {
+            serde_json::json!({"error": {"code": -32000, "message": "tracer not found"}})
+        } else {
+            serde_json::json!({"result": {"gasUsed": "0x5208", "structLogs": []}})
+        }
+    });
+    let dir = tempfile::tempdir().unwrap();
+    let output_json = dir.path().join("profile.json");
+
+    let args = CaptureArgs {
+        rpc_url,
+        transaction_hash: "0x1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef"
+            .to_string(),
+        output_json: output_json.clone(),
+        ..Default::default()
+    };
+
+    let err = format!("{:#}", execute_capture(args.clone()).unwrap_err());
+    assert!(err.contains("--tracer callTracer"));
+    assert!(err.contains("--auto-tracer"));
+    assert!(!output_json.exists());
+
+    execute_capture(CaptureArgs {
+        auto_tracer: true,
+        ..args
+    })
+    .unwrap();
+    let profile = stylus_trace_core::output::read_profile(&output_json).unwrap();
+    assert_eq!(profile.total_gas, 21_000 * 10_000);
+}
+
+#[test]
+fn test_capture_auto_tracer_lists_each_tracer_once() {
+    use std::sync::{Arc, Mutex};
+
+    let traced = Arc::new(Mutex::new(Vec::new()));
+    let seen = Arc::clone(&traced);
+    let rpc_url = spawn_mock_node_with(move |request| {
+        if request["method"] == "debug_traceTransaction" {
+            let tracer = request["params"][1]["tracer"].as_str().unwrap_or_default();
+            seen.lock().unwrap().push(tracer.to_string());
+        }
+        serde_json::json!({"error": {"code": -32000, "message": "tracer not found"}})
+    });
+    let dir = tempfile::tempdir().unwrap();
+
+    let err = execute_capture(CaptureArgs {
+        rpc_url,
+        transaction_hash: "0x1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef"
+            .to_string(),
+        output_json: dir.path().join("profile.json"),
+        tracer: Some("callTracer".to_string()),
+        auto_tracer: true,
+        ..Default::default()
+    })
+    .unwrap_err();
+
+    assert!(format!("{:#}", err).contains("(tried callTracer, stylusTracer)"));
+    assert_eq!(*traced.lock().unwrap(), ["callTracer", "stylusTracer"]);
+}
+
+#[test]
+fn test_capture_simulated_call() {
+    use stylus_trace_core::rpc::CallRequest;
+
+    let rpc_url = spawn_mock_node(serde_json::json!({"gasUsed": "0x5208", "structLogs": []}));
+    let dir = tempfile::tempdir().unwrap();
+    let output_json = dir.path().join("profile.json");
+    let to = "0x00000000000000000000000000000000000000aa";
+
+    let mut args = CaptureArgs {
+        rpc_url,
+        transaction_hash: "simulated-call".to_string(),
+        call: Some(CallRequest {
+            to: to.to_string(),
+            data: Some("a9059cbb".to_string()),
+            value: Some("1000000000000000000".to_string()),
+            ..Default::default()
+        }),
+        output_json: output_json.clone(),
+        output_svg: None,
+        ..Default::default()
+    };
+    validate_args(&args).unwrap();
+    execute_capture(args.clone()).unwrap();
+
+    let profile = stylus_trace_core::output::json::read_profile(&output_json).unwrap();
+    assert_eq!(profile.transaction_hash, "simulated-call");
+    assert_eq!(profile.contract_address.as_deref(), Some(to));
+    assert_eq!(profile.total_gas, 21_000 * 10_000);
+
+    args.call.as_mut().unwrap().value = Some("lots".to_string());
+    assert!(validate_args(&args).is_err());
+}
+
+fn write_test_profile(path: &std::path::Path, contract: Option<&str>, total_gas: u64) {
+    use stylus_trace_core::parser::schema::{HostIoSummary, Profile};
+
+    let profile = Profile {
+        version: "1.0.0".to_string(),
+        transaction_hash: "0xabc".to_string(),
+        label: None,
+        contract_address: contract.map(str::to_string),
+        block_number: None,
+        total_gas,
+        total_gas_source: Default::default(),
+        hostio_summary: HostIoSummary {
+            total_calls: 0,
+            by_type: Default::default(),
+            total_hostio_gas: 0,
+            gas_by_type: Default::default(),
+            avg_gas_per_call: Default::default(),
+        },
+        contracts: None,
+        repeated_slot_reads: None,
+        hot_paths: vec![],
+        all_stacks: None,
+        samples: None,
+        generated_at: "now".to_string(),
+    };
+    stylus_trace_core::output::write_profile(&profile, path).unwrap();
+}
+
+#[test]
+fn test_select_baseline_by_contract_and_file_name() {
+    use stylus_trace_core::commands::diff::select_baseline;
+    use stylus_trace_core::output::read_profile;
+
+    let dir = tempfile::tempdir().unwrap();
+    let baselines = dir.path().join("baselines");
+    std::fs::create_dir(&baselines).unwrap();
+    write_test_profile(&baselines.join("token.json"), Some("0xAA"), 100);
+    write_test_profile(&baselines.join("vault.json"), Some("0xbb"), 100);
+    write_test_profile(&baselines.join("legacy.json"), None, 100);
+
+    // Contract address match (case-insensitive), regardless of file name
+    let target_path = dir.path().join("profile.json");
+    write_test_profile(&target_path, Some("0xaa"), 120);
+    let target = read_profile(&target_path).unwrap();
+    assert_eq!(
+        select_baseline(&baselines, &target_path, &target).unwrap(),
+        baselines.join("token.json")
+    );
+
+    // File name fallback when no contract metadata is recorded
+    let legacy_path = dir.path().join("legacy.json");
+    write_test_profile(&legacy_path, None, 120);
+    let legacy = read_profile(&legacy_path).unwrap();
+    assert_eq!(
+        select_baseline(&baselines, &legacy_path, &legacy).unwrap(),
+        baselines.join("legacy.json")
+    );
+
+    // No match is a clear error
+    let other_path = dir.path().join("other.json");
+    write_test_profile(&other_path, Some("0xcc"), 120);
+    let other = read_profile(&other_path).unwrap();
+    let err = select_baseline(&baselines, &other_path, &other).unwrap_err();
+    assert!(err.to_string().contains("No baseline found"));
+}
+
+#[test]
+fn test_select_latest_baseline_by_glob() {
+    use stylus_trace_core::commands::diff::select_latest_baseline;
+
+    let dir = tempfile::tempdir().unwrap();
+    for name in [
+        "mytx-20240101.json",
+        "mytx-20240315.json",
+        "mytx-20240220.json",
+        "other-20250101.json",
+    ] {
+        write_test_profile(&dir.path().join(name), None, 100);
+    }
+
+    let pattern = dir.path().join("mytx-*.json");
+    assert_eq!(
+        select_latest_baseline(pattern.to_str().unwrap()).unwrap(),
+        dir.path().join("mytx-20240315.json")
+    );
+
+    let none = dir.path().join("missing-*.json");
+    let err = select_latest_baseline(none.to_str().unwrap()).unwrap_err();
+    assert!(err.to_string().contains("No baseline matches"));
+}
+
+#[test]
+fn test_diff_update_baseline_on_pass() {
+    use stylus_trace_core::commands::diff::execute_diff;
+    use stylus_trace_core::commands::models::DiffArgs;
+    use stylus_trace_core::output::read_profile;
+
+    let dir = tempfile::tempdir().unwrap();
+    let baselines = dir.path().join("baselines");
+    std::fs::create_dir(&baselines).unwrap();
+    write_test_profile(&baselines.join("token.json"), Some("0xaa"), 100);
+
+    let target_path = dir.path().join("profile.json");
+    write_test_profile(&target_path, Some("0xaa"), 90);
+
+    let args = DiffArgs {
+        target: target_path,
+        baseline_dir: Some(baselines.clone()),
+        update_baseline: true,
+        threshold_percent: Some(5.0),
+        summary: false,
+        ..Default::default()
+    };
+    execute_diff(args).unwrap();
+
+    let updated = read_profile(baselines.join("token.json")).unwrap();
+    assert_eq!(updated.total_gas, 90);
+}
+
+#[test]
+fn test_diff_fail_on_warning() {
+    use stylus_trace_core::commands::diff::execute_diff;
+    use stylus_trace_core::commands::models::DiffArgs;
+    use stylus_trace_core::parser::schema::{GasCategory, HotPath};
+
+    let dir = tempfile::tempdir().unwrap();
+    let baseline = dir.path().join("baseline.json");
+    let target = dir.path().join("target.json");
+    write_test_profile(&baseline, None, 100);
+    write_test_profile(&target, None, 100);
+
+    // A hot-path increase only produces a warning-severity violation
+    let with_path = |path: &std::path::Path, gas: u64| {
+        let mut profile = stylus_trace_core::output::read_profile(path).unwrap();
+        profile.hot_paths = vec![HotPath {
+            stack: "main;loop".to_string(),
+            gas,
+            cumulative_gas: gas,
+            percentage: 100.0,
+            category: GasCategory::UserCode,
+            source_hint: None,
+        }];
+        stylus_trace_core::output::write_profile(&profile, path).unwrap();
+    };
+    with_path(&baseline, 100);
+    with_path(&target, 150);
+
+    let args = |fail_on_warning| DiffArgs {
+        baseline: baseline.clone(),
+        target: target.clone(),
+        threshold_percent: Some(10.0),
+        summary: false,
+        fail_on_warning,
+        ..Default::default()
+    };
+
+    assert!(execute_diff(args(false)).is_ok());
+    assert!(execute_diff(args(true)).is_err());
+}
+
+#[test]
+fn test_read_git_baseline_at_ref() {
+    use stylus_trace_core::commands::diff::read_git_baseline;
+
+    let dir = tempfile::tempdir().unwrap();
+    let git = |args: &[&str]| {
+        let status = std::process::Command::new("git")
+            .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .current_dir(dir.path())
+            .output()
+            .unwrap()
+            .status;
+        assert!(status.success(), "git {:?}", args);
+    };
+    git(&["init", "-q"]);
+    write_test_profile(&dir.path().join("baseline.json"), Some("0xaa"), 100);
+    git(&["add", "baseline.json"]);
+    git(&["commit", "-q", "-m", "baseline"]);
+
+    // The committed version is read even after the working copy changes
+    write_test_profile(&dir.path().join("baseline.json"), Some("0xaa"), 999);
+    let profile = read_git_baseline("HEAD:baseline.json", dir.path()).unwrap();
+    assert_eq!(profile.total_gas, 100);
+
+    let err = read_git_baseline("HEAD:missing.json", dir.path()).unwrap_err();
+    assert!(err.to_string().contains("did not exist at git ref 'HEAD'"));
+    let err = read_git_baseline("no-such-branch:baseline.json", dir.path()).unwrap_err();
+    assert!(err.to_string().contains("Unknown git ref 'no-such-branch'"));
+
+    assert!(read_git_baseline("baseline.json", dir.path()).is_err());
+}
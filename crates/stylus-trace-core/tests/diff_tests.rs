@@ -22,14 +22,23 @@ fn create_full_test_profile(
     Profile {
         version: version.to_string(),
         transaction_hash: tx_hash.to_string(),
+        label: None,
+        contract_address: None,
+        block_number: None,
         total_gas,
+        total_gas_source: Default::default(),
         hostio_summary: HostIoSummary {
             total_calls: hostio_total_calls,
             by_type: hostio_by_type,
             total_hostio_gas: hostio_total_gas,
+            gas_by_type: HashMap::new(),
+            avg_gas_per_call: HashMap::new(),
         },
+        contracts: None,
+        repeated_slot_reads: None,
         hot_paths,
         all_stacks: None,
+        samples: None,
         generated_at: "2025-02-14T10:00:00Z".to_string(),
     }
 }
@@ -61,6 +70,33 @@ mod engine_tests {
         assert!(diff.summary.warning.is_some());
     }
 
+    #[test]
+    fn test_same_hash_different_gas_not_identical() {
+        let b = create_p("0x1", 100);
+        let t = create_p("0x1", 150);
+        assert!(!are_profiles_identical(&b, &t));
+        let diff = generate_diff(&b, &t).unwrap();
+        assert!(diff.summary.warning.is_none());
+    }
+
+    #[test]
+    fn test_different_hash_identical_metrics() {
+        let b = create_p("0x1", 100);
+        let t = create_p("0x2", 100);
+        assert!(!are_profiles_identical(&b, &t));
+        assert!(are_metrics_identical(&b, &t));
+        let warning = generate_diff(&b, &t).unwrap().summary.warning.unwrap();
+        assert!(warning.contains("different transactions"));
+    }
+
+    #[test]
+    fn test_same_gas_different_hostio_not_identical() {
+        let b = create_p("0x1", 100);
+        let mut t = create_p("0x1", 100);
+        t.hostio_summary.total_calls = 3;
+        assert!(!are_profiles_identical(&b, &t));
+    }
+
     #[test]
     fn test_generate_diff_incompatible() {
         let mut b = create_p("0x1", 100);
@@ -69,6 +105,26 @@ mod engine_tests {
         t.version = "2.0.0".to_string();
         assert!(generate_diff(&b, &t).is_err());
     }
+
+    #[test]
+    fn test_diff_matrix_pairwise_changes() {
+        let profiles = vec![
+            ("a".to_string(), create_p("0x1", 1000)),
+            ("b".to_string(), create_p("0x2", 800)),
+            ("c".to_string(), create_p("0x3", 1200)),
+        ];
+        let matrix = build_diff_matrix(&profiles).unwrap();
+
+        assert_eq!(matrix.percent_change[0][0], 0.0);
+        assert!((matrix.percent_change[0][1] - -20.0).abs() < 1e-9);
+        assert!((matrix.percent_change[1][2] - 50.0).abs() < 1e-9);
+        assert_eq!(matrix.cheapest(), Some(1));
+
+        let table = render_diff_matrix(&matrix);
+        assert!(table.contains("-20.00%"));
+        assert!(table.contains("+50.00%"));
+        assert!(table.contains("Lowest total gas: b (800 ink)"));
+    }
 }
 
 // ============================================================================
@@ -101,6 +157,233 @@ mod normalizer_tests {
         assert_eq!(changes.get("load").unwrap().delta, -10);
         assert_eq!(changes.get("store").unwrap().delta, 5);
     }
+
+    #[test]
+    fn test_canonicalize_frame_names() {
+        assert_eq!(canonicalize_frame("SLOAD"), "storage_load_bytes32");
+        assert_eq!(canonicalize_frame("storage_load"), "storage_load_bytes32");
+        assert_eq!(
+            canonicalize_frame("_ZN4core3fmt5write17h0123456789abcdefE"),
+            "core::fmt::write"
+        );
+        assert_eq!(canonicalize_frame("my_function"), "my_function");
+        assert_eq!(
+            canonicalize_stack("root;SSTORE"),
+            "root;storage_store_bytes32"
+        );
+    }
+
+    #[test]
+    fn test_cross_tracer_hot_paths_align() {
+        let path = |stack: &str, gas: u64| HotPath {
+            stack: stack.to_string(),
+            gas,
+            cumulative_gas: gas,
+            percentage: 50.0,
+            category: GasCategory::StorageNormal,
+            source_hint: None,
+        };
+        let b = create_full_test_profile(
+            "0x1",
+            "1.0.0",
+            1000,
+            0,
+            HashMap::new(),
+            0,
+            vec![path("main;SLOAD", 400)],
+        );
+        let t = create_full_test_profile(
+            "0x1",
+            "1.0.0",
+            1100,
+            0,
+            HashMap::new(),
+            0,
+            vec![path("main;storage_load_bytes32", 500)],
+        );
+
+        let report = generate_diff(&b, &t).unwrap();
+        let common = &report.deltas.hot_paths.common_paths;
+        assert_eq!(common.len(), 1);
+        assert_eq!(common[0].stack, "main;storage_load_bytes32");
+        assert_eq!(common[0].gas_change, 100);
+
+        let strict = DiffOptions {
+            normalize_frames: false,
+            ..DiffOptions::default()
+        };
+        let report = generate_diff_with_options(&b, &t, &strict).unwrap();
+        assert!(report.deltas.hot_paths.common_paths.is_empty());
+        assert_eq!(report.deltas.hot_paths.baseline_only.len(), 1);
+    }
+
+    #[test]
+    fn test_mixed_total_gas_sources_warn() {
+        use stylus_trace_core::parser::schema::TotalGasSource;
+
+        let create_p = |tx: &str, gas: u64| {
+            create_full_test_profile(tx, "1.0.0", gas, 0, HashMap::new(), 0, vec![])
+        };
+        let baseline = create_p("0x1", 1000);
+        let mut target = create_p("0x2", 1100);
+        target.total_gas_source = TotalGasSource::DerivedFromSteps;
+
+        let mut report = generate_diff(&baseline, &target).unwrap();
+        check_thresholds(&mut report, &ThresholdConfig::default());
+        let warning = report.summary.warning.as_deref().unwrap();
+        assert!(warning.contains("derived from step costs"));
+        assert!(render_terminal_diff(&report).contains(warning));
+
+        assert!(generate_diff(&baseline, &create_p("0x3", 1100))
+            .unwrap()
+            .summary
+            .warning
+            .is_none());
+    }
+
+    #[test]
+    fn test_min_path_percent_drops_trivial_paths() {
+        let path = |stack: &str, gas: u64| HotPath {
+            stack: stack.to_string(),
+            gas,
+            cumulative_gas: gas,
+            percentage: 0.0,
+            category: GasCategory::StorageNormal,
+            source_hint: None,
+        };
+        let b = create_full_test_profile(
+            "0x1",
+            "1.0.0",
+            1000,
+            0,
+            HashMap::new(),
+            0,
+            vec![
+                path("main;big", 600),
+                path("main;tiny", 5),
+                path("main;grew", 5),
+            ],
+        );
+        let t = create_full_test_profile(
+            "0x2",
+            "1.0.0",
+            1000,
+            0,
+            HashMap::new(),
+            0,
+            vec![
+                path("main;big", 600),
+                path("main;tiny", 9),
+                path("main;grew", 200),
+            ],
+        );
+
+        let options = DiffOptions {
+            min_path_percent: Some(1.0),
+            ..DiffOptions::default()
+        };
+        let mut report = generate_diff_with_options(&b, &t, &options).unwrap();
+        let mut stacks: Vec<&str> = report
+            .deltas
+            .hot_paths
+            .common_paths
+            .iter()
+            .map(|p| p.stack.as_str())
+            .collect();
+        stacks.sort();
+        assert_eq!(stacks, vec!["main;big", "main;grew"]);
+
+        // The 80% increase on the tiny path no longer raises a warning
+        let thresholds = ThresholdConfig {
+            hot_paths: Some(HotPathThresholds {
+                warn_individual_increase_percent: Some(50.0),
+            }),
+            ..Default::default()
+        };
+        check_thresholds(&mut report, &thresholds);
+        assert_eq!(report.threshold_violations.len(), 1);
+        assert_eq!(report.threshold_violations[0].metric, "hot_paths.main;grew");
+    }
+
+    #[test]
+    fn test_category_gas_rollup() {
+        use stylus_trace_core::aggregator::stack_builder::CollapsedStack;
+
+        let with_stacks = |tx: &str, stacks: Vec<(&str, u64)>| {
+            let mut p = create_full_test_profile(tx, "1.0.0", 10_000, 0, HashMap::new(), 0, vec![]);
+            p.all_stacks = Some(
+                stacks
+                    .into_iter()
+                    .map(|(s, w)| CollapsedStack::new(s.to_string(), w, None))
+                    .collect(),
+            );
+            p
+        };
+        let baseline = with_stacks(
+            "0x1",
+            vec![
+                ("main;storage_load_bytes32", 1000),
+                ("main;storage_flush_cache", 1000),
+                ("main;native_keccak256", 500),
+                ("main;compute", 4000),
+            ],
+        );
+        let target = with_stacks(
+            "0x2",
+            vec![
+                ("main;storage_load_bytes32", 1400),
+                ("main;storage_flush_cache", 1000),
+                ("main;native_keccak256", 250),
+                ("main;compute", 9000),
+            ],
+        );
+
+        let changes = calculate_category_gas_changes(&baseline, &target);
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].category, "Storage");
+        assert_eq!(changes[0].baseline_gas, 2000);
+        assert_eq!(changes[0].percent_change, 20.0);
+        assert_eq!(changes[1].category, "Crypto");
+        assert_eq!(changes[1].gas_change, -250);
+
+        let report = generate_diff(&baseline, &target).unwrap();
+        assert_eq!(report.deltas.hostio.by_category.len(), 2);
+        let out = render_terminal_diff(&report);
+        assert!(out.contains("HostIO Gas by Category"));
+        assert!(out.contains("Storage: 2000 ink -> 2400 ink (+20.00%)"));
+    }
+
+    #[test]
+    fn test_avg_gas_per_call_flags_costlier_calls() {
+        let with_hostio = |tx: &str, calls: u64, gas: u64| {
+            let mut p = create_full_test_profile(
+                tx,
+                "1.0.0",
+                10_000,
+                calls,
+                [("storage_load".to_string(), calls)].into(),
+                gas,
+                vec![],
+            );
+            p.hostio_summary.gas_by_type = [("storage_load".to_string(), gas)].into();
+            p
+        };
+        // Fewer loads (10 -> 8), but each one costs 100 -> 150
+        let baseline = with_hostio("0x1", 10, 1000);
+        let target = with_hostio("0x2", 8, 1200);
+
+        let report = generate_diff(&baseline, &target).unwrap();
+        let change = &report.deltas.hostio.by_type_changes["storage_load"];
+        assert_eq!(change.baseline_avg_gas_per_call, Some(100.0));
+        assert_eq!(change.target_avg_gas_per_call, Some(150.0));
+        assert_eq!(change.avg_gas_per_call_percent_change, Some(50.0));
+        assert!(change.per_call_regression);
+
+        let out = render_terminal_diff(&report);
+        assert!(out.contains("HostIO Gas per Call"));
+        assert!(out.contains("storage_load: 100.0 ink -> 150.0 ink avg (+50.00%)"));
+        assert!(out.contains("each call costs more despite fewer calls"));
+    }
 }
 
 // ============================================================================
@@ -117,11 +400,13 @@ mod output_tests {
             generated_at: "now".to_string(),
             baseline: ProfileMetadata {
                 transaction_hash: "0x1".to_string(),
+                label: None,
                 total_gas: 100,
                 generated_at: "now".to_string(),
             },
             target: ProfileMetadata {
                 transaction_hash: "0x2".to_string(),
+                label: None,
                 total_gas: 120,
                 generated_at: "now".to_string(),
             },
@@ -135,6 +420,7 @@ mod output_tests {
                 hostio: HostIoDelta::default(),
                 hot_paths: HotPathsDelta::default(),
             },
+            hostio_only: false,
             threshold_violations: vec![],
             summary: DiffSummary {
                 status: "FAILED".to_string(),
@@ -145,7 +431,7 @@ mod output_tests {
             insights: vec![],
         };
         let out = render_terminal_diff(&report);
-        assert!(out.contains("Total Gas: 100 -> 120 (+20.00%)"));
+        assert!(out.contains("Total Gas: 100 ink -> 120 ink (+20.00%)"));
     }
 
     #[test]
@@ -155,11 +441,13 @@ mod output_tests {
             generated_at: "now".to_string(),
             baseline: ProfileMetadata {
                 transaction_hash: "0x1".to_string(),
+                label: None,
                 total_gas: 1000,
                 generated_at: "now".to_string(),
             },
             target: ProfileMetadata {
                 transaction_hash: "0x2".to_string(),
+                label: None,
                 total_gas: 1200,
                 generated_at: "now".to_string(),
             },
@@ -182,6 +470,7 @@ mod output_tests {
                     ..Default::default()
                 },
             },
+            hostio_only: false,
             threshold_violations: vec![],
             summary: DiffSummary {
                 status: "PASSED".to_string(),
@@ -200,6 +489,8 @@ mod output_tests {
         assert!(out.contains("500"));
         assert!(out.contains("600"));
         assert!(out.contains("20.00%"));
+        assert!(out.contains("CHANGE"));
+        assert!(out.contains(&"█".repeat(10)));
     }
 }
 
@@ -227,6 +518,69 @@ mod threshold_tests {
         assert_eq!(v.len(), 1);
     }
 
+    #[test]
+    fn test_gas_noise_floor_ignores_small_increases() {
+        let thresholds = GasThresholds {
+            max_increase_percent: Some(2.0),
+            noise_floor_gas: Some(1_000),
+            ..Default::default()
+        };
+
+        // +3% but only 600 gas: within noise
+        let small = GasDelta {
+            baseline: 20_000,
+            target: 20_600,
+            absolute_change: 600,
+            percent_change: 3.0,
+        };
+        let mut v = vec![];
+        check_gas_thresholds(&small, &thresholds, &mut v);
+        assert!(v.is_empty());
+
+        // Same +3% but 3000 gas: above the floor
+        let large = GasDelta {
+            baseline: 100_000,
+            target: 103_000,
+            absolute_change: 3_000,
+            percent_change: 3.0,
+        };
+        check_gas_thresholds(&large, &thresholds, &mut v);
+        assert_eq!(v.len(), 1);
+        assert_eq!(v[0].metric, "gas.max_increase_percent");
+    }
+
+    #[test]
+    fn test_threshold_overrides_from_env_source() {
+        let mut config = ThresholdConfig {
+            gas: GasThresholds {
+                max_increase_percent: Some(10.0),
+                max_increase_absolute: Some(500),
+                noise_floor_gas: None,
+            },
+            ..Default::default()
+        };
+
+        let env: HashMap<&str, &str> = [
+            ("STYLUS_GAS_MAX_INCREASE_PERCENT", "2.5"),
+            ("STYLUS_HOSTIO_MAX_CALLS_PERCENT", " 4 "),
+        ]
+        .into_iter()
+        .collect();
+        apply_threshold_overrides(&mut config, |key| env.get(key).map(|v| v.to_string())).unwrap();
+
+        assert_eq!(config.gas.max_increase_percent, Some(2.5));
+        // Unset variables keep the file value
+        assert_eq!(config.gas.max_increase_absolute, Some(500));
+        assert_eq!(config.hostio.max_total_calls_increase_percent, Some(4.0));
+        assert!(config.hot_paths.is_none());
+
+        let err = apply_threshold_overrides(&mut config, |key| {
+            (key == "STYLUS_GAS_MAX_INCREASE_ABSOLUTE").then(|| "lots".to_string())
+        })
+        .unwrap_err();
+        assert!(err.to_string().contains("STYLUS_GAS_MAX_INCREASE_ABSOLUTE"));
+    }
+
     #[test]
     fn test_create_summary_logic() {
         let v = vec![ThresholdViolation {
@@ -283,11 +637,42 @@ fn test_complex_regression_scenario() {
         .any(|violation| violation.metric.contains("storage_load")));
 }
 
+#[test]
+fn test_hostio_only_ignores_gas_regressions() {
+    let baseline =
+        create_full_test_profile("0x1", "1.0.0", 100000, 10, HashMap::new(), 1000, vec![]);
+    let target = create_full_test_profile("0x2", "1.0.0", 200000, 10, HashMap::new(), 1000, vec![]);
+
+    let mut diff = generate_diff(&baseline, &target).unwrap();
+    restrict_to_hostio(&mut diff);
+
+    let config = ThresholdConfig {
+        gas: GasThresholds {
+            max_increase_percent: Some(10.0),
+            ..Default::default()
+        },
+        hostio: HostIOThresholds {
+            max_total_calls_increase_percent: Some(10.0),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    let v = check_thresholds(&mut diff, &config);
+    assert!(v.is_empty());
+    assert_eq!(diff.summary.status, "PASSED");
+
+    let out = render_terminal_diff(&diff);
+    assert!(!out.contains("Total Gas"));
+    assert!(out.contains("HostIO Calls: 10 calls -> 10 calls"));
+}
+
 #[test]
 fn test_hot_paths_comparison_logic() {
     let b_paths = vec![HotPath {
         stack: "A;B".to_string(),
         gas: 100,
+        cumulative_gas: 100,
         percentage: 50.0,
         category: GasCategory::UserCode,
         source_hint: None,
@@ -295,6 +680,7 @@ fn test_hot_paths_comparison_logic() {
     let t_paths = vec![HotPath {
         stack: "A;B".to_string(),
         gas: 150,
+        cumulative_gas: 150,
         percentage: 75.0,
         category: GasCategory::UserCode,
         source_hint: None,
@@ -307,3 +693,405 @@ fn test_hot_paths_comparison_logic() {
     assert_eq!(diff.deltas.hot_paths.common_paths.len(), 1);
     assert_eq!(diff.deltas.hot_paths.common_paths[0].percent_change, 50.0);
 }
+
+#[test]
+fn test_only_regressions_filters_rendered_report() {
+    let path = |stack: &str, gas: u64| HotPath {
+        stack: stack.to_string(),
+        gas,
+        cumulative_gas: gas,
+        percentage: 50.0,
+        category: GasCategory::UserCode,
+        source_hint: None,
+    };
+
+    let mut b_types = HashMap::new();
+    b_types.insert("storage_load".to_string(), 10);
+    b_types.insert("emit_log".to_string(), 5);
+    let mut t_types = HashMap::new();
+    t_types.insert("storage_load".to_string(), 12);
+    t_types.insert("emit_log".to_string(), 2);
+
+    let b = create_full_test_profile(
+        "0x1",
+        "1.0.0",
+        200,
+        15,
+        b_types,
+        0,
+        vec![path("main;slower", 100), path("main;faster", 100)],
+    );
+    let t = create_full_test_profile(
+        "0x2",
+        "1.0.0",
+        200,
+        14,
+        t_types,
+        0,
+        vec![path("main;slower", 150), path("main;faster", 50)],
+    );
+
+    let diff = generate_diff(&b, &t).unwrap();
+
+    let full = render_terminal_diff(&diff);
+    assert!(full.contains("emit_log"));
+    assert!(full.contains("main;faster"));
+
+    let filtered = render_terminal_diff_filtered(&diff, true, 5, 2);
+    assert!(filtered.contains("storage_load"));
+    assert!(filtered.contains("main;slower"));
+    assert!(!filtered.contains("emit_log"));
+    assert!(!filtered.contains("main;faster"));
+
+    // The report data itself is not filtered
+    assert_eq!(diff.deltas.hot_paths.common_paths.len(), 2);
+}
+
+#[test]
+fn test_summary_only_skips_tables() {
+    let mut types = HashMap::new();
+    types.insert("storage_load".to_string(), 10);
+    let hot = vec![HotPath {
+        stack: "main;execute".to_string(),
+        gas: 100,
+        cumulative_gas: 100,
+        percentage: 100.0,
+        category: GasCategory::UserCode,
+        source_hint: None,
+    }];
+    let b = create_full_test_profile("0x1", "1.0.0", 200, 10, types.clone(), 0, hot.clone());
+    let t = create_full_test_profile("0x2", "1.0.0", 220, 10, types, 0, hot);
+    let diff = generate_diff(&b, &t).unwrap();
+
+    let out = render_terminal_diff_summary(&diff, 2);
+    assert!(out.contains("Profile Comparison Summary"));
+    assert!(out.contains("Total Gas: 200 ink -> 220 ink"));
+    assert!(out.contains("HostIO Calls:"));
+    assert!(!out.contains("storage_load"));
+    assert!(!out.contains("main;execute"));
+    assert!(render_terminal_diff(&diff).contains("main;execute"));
+}
+
+#[test]
+fn test_cost_table_flags_out_of_range_hostio() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("costs.toml");
+    std::fs::write(
+        &path,
+        "[sload]\nmin = 2000\nmax = 2200\n\n[native_keccak256]\nmax = 150\n",
+    )
+    .unwrap();
+    let table = load_cost_table(&path).unwrap();
+
+    let mut profile = create_full_test_profile(
+        "0x1",
+        "1.0.0",
+        100_000_000,
+        4,
+        HashMap::from([
+            ("storage_load".to_string(), 2),
+            ("native_keccak256".to_string(), 2),
+        ]),
+        0,
+        vec![],
+    );
+    // storage_load: 6600 gas per call (3x), keccak: 100 gas per call (in range)
+    profile.hostio_summary.gas_by_type = HashMap::from([
+        ("storage_load".to_string(), 132_000_000),
+        ("native_keccak256".to_string(), 2_000_000),
+    ]);
+
+    let insights = check_cost_table(&profile, &table);
+    assert_eq!(insights.len(), 1);
+    assert_eq!(insights[0].tag.as_deref(), Some(COST_TABLE_TAG));
+    assert_eq!(insights[0].severity, InsightSeverity::High);
+    assert!(insights[0]
+        .description
+        .contains("storage_load averaged 6600.0 gas per call, 3.0x the expected maximum"));
+    assert!(insights[0].description.contains("likely all cold"));
+
+    std::fs::write(&path, "[not_a_hostio]\nmax = 1\n").unwrap();
+    assert!(matches!(
+        load_cost_table(&path),
+        Err(DiffError::InvalidCostTable(_))
+    ));
+}
+
+#[test]
+fn test_gas_budget_accumulates_until_exhausted() {
+    let profile = |tx: &str, gas: u64| {
+        create_full_test_profile(tx, "1.0.0", gas, 0, HashMap::new(), 0, vec![])
+    };
+    let budget = GasBudget {
+        max_increase_percent: 2.0,
+        reference_gas: None,
+        spent_gas: 0,
+        runs: 0,
+    };
+
+    // +1.5% passes and starts the budget from the baseline
+    let mut first = generate_diff(&profile("0x1", 100_000), &profile("0x2", 101_500)).unwrap();
+    let budget = check_budget(&mut first, &budget);
+    assert_eq!(first.summary.status, "PASSED");
+    assert_eq!(budget.reference_gas, Some(100_000));
+    assert_eq!(budget.remaining_gas(), 500);
+
+    // Another +1% on its own is fine, but overspends the cumulative budget
+    let mut second = generate_diff(&profile("0x2", 101_500), &profile("0x3", 102_500)).unwrap();
+    let budget = check_budget(&mut second, &budget);
+    assert_eq!(second.summary.status, "FAILED");
+    assert_eq!(second.threshold_violations[0].metric, BUDGET_METRIC);
+    assert_eq!(budget.remaining_gas(), -500);
+    assert_eq!(budget.runs, 2);
+}
+
+#[test]
+fn test_labels_replace_hashes_in_diff_header() {
+    let mut baseline =
+        create_full_test_profile("0xaaa", "1.0.0", 100, 0, HashMap::new(), 0, vec![]);
+    baseline.label = Some("no-cache".to_string());
+    let target = create_full_test_profile("0xbbb", "1.0.0", 90, 0, HashMap::new(), 0, vec![]);
+
+    let report = generate_diff(&baseline, &target).unwrap();
+    assert_eq!(report.baseline.label.as_deref(), Some("no-cache"));
+
+    let out = render_terminal_diff(&report);
+    assert!(out.contains("Baseline: no-cache\n"));
+    assert!(out.contains("Target:   0xbbb\n"));
+}
+
+#[test]
+fn test_top_hostio_limits_terminal_rows() {
+    let types = [
+        "storage_load",
+        "storage_store",
+        "emit_log",
+        "call",
+        "keccak",
+        "account_balance",
+        "msg_value",
+    ];
+    let b_types: HashMap<String, u64> = types.iter().map(|t| (t.to_string(), 1)).collect();
+    let t_types: HashMap<String, u64> = types
+        .iter()
+        .enumerate()
+        .map(|(i, t)| (t.to_string(), 2 + i as u64))
+        .collect();
+
+    let b = create_full_test_profile("0x1", "1.0.0", 200, 7, b_types, 0, vec![]);
+    let t = create_full_test_profile("0x2", "1.0.0", 200, 35, t_types, 0, vec![]);
+    let diff = generate_diff(&b, &t).unwrap();
+
+    let default = render_terminal_diff(&diff);
+    assert!(default.contains("... 2 more"));
+    assert!(!default.contains("storage_load:"));
+
+    let two = render_terminal_diff_filtered(&diff, false, 2, 2);
+    assert!(two.contains("msg_value:"));
+    assert!(two.contains("... 5 more"));
+
+    let all = render_terminal_diff_filtered(&diff, false, 0, 2);
+    assert!(types.iter().all(|t| all.contains(&format!("{}:", t))));
+    assert!(!all.contains("more (use --top-hostio"));
+}
+
+#[test]
+fn test_compare_captures_reports_first_divergence() {
+    use stylus_trace_core::parser::parse_trace;
+
+    let trace = |sstore_cost: u64| {
+        serde_json::json!({
+            "gas": 100,
+            "structLogs": [
+                { "op": "SLOAD", "gasCost": 10, "depth": 1 },
+                { "op": "SSTORE", "gasCost": sstore_cost, "depth": 1 }
+            ]
+        })
+    };
+    let first = parse_trace("0xabc", &trace(20)).unwrap();
+
+    let same = compare_captures(&first, &parse_trace("0xabc", &trace(20)).unwrap());
+    assert!(same.is_identical());
+    assert_eq!(same.gas_variance_percent(), 0.0);
+
+    let noisy = compare_captures(&first, &parse_trace("0xabc", &trace(25)).unwrap());
+    assert!(!noisy.is_identical());
+    let divergence = noisy.divergence.unwrap();
+    assert_eq!(divergence.index, 1);
+    assert!(divergence.second.unwrap().contains("cost 250000"));
+    assert_eq!(noisy.stack_differences.len(), 1);
+    assert_eq!(noisy.stack_differences[0].first_weight, 200_000);
+    assert_eq!(noisy.stack_differences[0].second_weight, 250_000);
+}
+
+// ============================================================================
+// COMPONENT TESTS: ANALYZER
+// ============================================================================
+
+mod analyzer_tests {
+    use super::*;
+    use stylus_trace_core::aggregator::stack_builder::CollapsedStack;
+
+    fn profile_with_stacks(stacks: Vec<CollapsedStack>, total_gas: u64) -> Profile {
+        let mut profile =
+            create_full_test_profile("0x1", "1.0.0", total_gas, 0, HashMap::new(), 0, vec![]);
+        profile.all_stacks = Some(stacks);
+        profile
+    }
+
+    fn recursion_insights(profile: &Profile) -> Vec<AnalysisInsight> {
+        analyze_profile(profile)
+            .into_iter()
+            .filter(|i| i.tag.as_deref() == Some("deep_recursion"))
+            .collect()
+    }
+
+    #[test]
+    fn test_insights_carry_offending_stacks() {
+        let profile = profile_with_stacks(
+            vec![
+                CollapsedStack::new("root;main;storage_load_bytes32".to_string(), 6000, None),
+                CollapsedStack::new(
+                    "root;main;helper;storage_load_bytes32".to_string(),
+                    4000,
+                    None,
+                ),
+                CollapsedStack::new("root;main;warm;storage_load_bytes32".to_string(), 100, None),
+            ],
+            20_000,
+        );
+
+        let insights = analyze_profile(&profile);
+        let cold = insights
+            .iter()
+            .find(|i| i.tag.as_deref() == Some("storage_tax"))
+            .unwrap();
+        assert_eq!(
+            cold.stacks,
+            vec![
+                "root;main;storage_load_bytes32".to_string(),
+                "root;main;helper;storage_load_bytes32".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_flags_large_memory_copies() {
+        let profile = profile_with_stacks(
+            vec![
+                CollapsedStack::new("root;main;read_args".to_string(), 1500, None),
+                CollapsedStack::new("root;main;write_result".to_string(), 700, None),
+                CollapsedStack::new("root;main;compute".to_string(), 7800, None),
+            ],
+            10_000,
+        );
+        let memory = |options: &AnalyzerOptions| {
+            analyze_profile_with(&profile, options)
+                .into_iter()
+                .find(|i| i.tag.as_deref() == Some("memory_copy"))
+        };
+
+        let insight = memory(&AnalyzerOptions::default()).unwrap();
+        assert!(insight.description.contains("22.0%"));
+        assert_eq!(insight.severity, InsightSeverity::High);
+        assert_eq!(insight.stacks.len(), 2);
+
+        let lenient = AnalyzerOptions {
+            memory_gas_share_percent: 25.0,
+            ..Default::default()
+        };
+        assert!(memory(&lenient).is_none());
+    }
+
+    #[test]
+    fn test_flags_excessive_log_emission() {
+        let mut profile = profile_with_stacks(
+            vec![
+                CollapsedStack::new("root;main;emit_log".to_string(), 1200, None),
+                CollapsedStack::new("root;main;compute".to_string(), 8800, None),
+            ],
+            10_000,
+        );
+        profile.hostio_summary.total_calls = 10;
+        profile.hostio_summary.by_type =
+            HashMap::from([("emit_log".to_string(), 4), ("storage_load".to_string(), 6)]);
+        profile.hostio_summary.gas_by_type = HashMap::from([("emit_log".to_string(), 1200)]);
+        let logs = |options: &AnalyzerOptions| {
+            analyze_profile_with(&profile, options)
+                .into_iter()
+                .find(|i| i.tag.as_deref() == Some("log_emission"))
+        };
+
+        let insight = logs(&AnalyzerOptions::default()).unwrap();
+        assert!(insight.description.contains("12.0%"));
+        assert_eq!(insight.severity, InsightSeverity::High);
+        assert_eq!(insight.stacks, vec!["root;main;emit_log".to_string()]);
+
+        let lenient = AnalyzerOptions {
+            log_gas_share_percent: 20.0,
+            log_call_share_percent: 50.0,
+            ..Default::default()
+        };
+        assert!(logs(&lenient).is_none());
+    }
+
+    #[test]
+    fn test_detects_direct_recursion() {
+        let deep = format!("root;main;{}storage_load", "fib;".repeat(6));
+        let profile = profile_with_stacks(
+            vec![
+                CollapsedStack::new(deep, 800, None),
+                CollapsedStack::new("root;main;fib;fib".to_string(), 200, None),
+            ],
+            1000,
+        );
+
+        let insights = recursion_insights(&profile);
+        assert_eq!(insights.len(), 1);
+        assert!(insights[0]
+            .description
+            .contains("`fib` calls itself 6 times"));
+        assert_eq!(insights[0].severity, InsightSeverity::High);
+    }
+
+    #[test]
+    fn test_detects_mutual_recursion_cycle() {
+        let cycle = format!("root;{}done", "is_even;is_odd;".repeat(5));
+        let profile = profile_with_stacks(vec![CollapsedStack::new(cycle, 5, None)], 1000);
+
+        let insights = recursion_insights(&profile);
+        assert_eq!(insights.len(), 1);
+        assert!(insights[0]
+            .description
+            .contains("`is_even -> is_odd` recurs 5 times"));
+        assert_eq!(insights[0].severity, InsightSeverity::Low);
+    }
+
+    #[test]
+    fn test_suppress_insights_by_tag() {
+        let deep = format!("root;{}leaf", "fib;".repeat(6));
+        let profile = profile_with_stacks(vec![CollapsedStack::new(deep, 800, None)], 1000);
+
+        let mut report = generate_diff(&profile, &profile).unwrap();
+        assert!(report
+            .insights
+            .iter()
+            .any(|i| i.tag.as_deref() == Some("deep_recursion")));
+
+        suppress_insights(&mut report, &["deep_recursion".to_string()]);
+        assert!(report.insights.is_empty());
+
+        // Insights are always present in the JSON report, even when empty
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["insights"], serde_json::json!([]));
+    }
+
+    #[test]
+    fn test_shallow_repetition_is_ignored() {
+        let profile = profile_with_stacks(
+            vec![CollapsedStack::new("root;a;a;a;b".to_string(), 100, None)],
+            100,
+        );
+        assert!(recursion_insights(&profile).is_empty());
+    }
+}
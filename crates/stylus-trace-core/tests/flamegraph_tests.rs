@@ -1,4 +1,13 @@
+use stylus_trace_core::aggregator::metrics::calculate_hot_paths;
+use stylus_trace_core::aggregator::stack_builder::CollapsedStack;
 use stylus_trace_core::flamegraph::generator::{get_truncated_name, NodeCategory};
+use stylus_trace_core::flamegraph::{
+    generate_diff_flamegraph, generate_flamegraph, generate_hostio_histogram,
+    generate_hot_path_explanations, generate_side_by_side_flamegraph, render_hot_path_groups,
+    render_text_summary, ColorMode, FlamegraphConfig,
+};
+use stylus_trace_core::parser::hostio::{HostIoEvent, HostIoStats};
+use stylus_trace_core::parser::HostIoType;
 
 #[test]
 fn test_node_category() {
@@ -29,6 +38,25 @@ fn test_node_category() {
     assert_eq!(NodeCategory::from_name("random_fn"), NodeCategory::UserCode);
 }
 
+#[test]
+fn test_configurable_expensive_hostio() {
+    let expensive = [HostIoType::Call];
+    assert_eq!(
+        NodeCategory::from_name_with("call", &expensive),
+        NodeCategory::StorageExpensive
+    );
+    assert_eq!(
+        NodeCategory::from_name_with("storage_flush", &expensive),
+        NodeCategory::StorageNormal
+    );
+
+    let stacks = vec![CollapsedStack::new("main;call".to_string(), 5000, None)];
+    let config = FlamegraphConfig::new().with_expensive_hostio(vec![HostIoType::Call]);
+    let svg = generate_flamegraph(&stacks, Some(&config), None).unwrap();
+    assert!(svg.contains(">Expensive</text>"));
+    assert!(!svg.contains("Storage (Ex)"));
+}
+
 #[test]
 fn test_get_truncated_name() {
     // Not enough width
@@ -43,3 +71,407 @@ fn test_get_truncated_name() {
     assert!(truncated.ends_with("..."));
     assert!(truncated.len() < name.len());
 }
+
+#[test]
+fn test_truncation_respects_multibyte_names() {
+    // Each of these characters is several bytes; byte slicing would panic
+    let name = "ストレージ読み込み_関数_λ_🔥_処理";
+    for width in [40.0, 50.0, 57.0, 64.0, 71.0, 100.0] {
+        let truncated = get_truncated_name(name, width).unwrap();
+        assert!(truncated.chars().count() <= (width / 7.0) as usize);
+        assert!(truncated.ends_with("..."));
+    }
+
+    let stacks = vec![CollapsedStack::new(
+        format!("main;{};{}", name, name),
+        60_000,
+        None,
+    )];
+    let hot_paths = calculate_hot_paths(&stacks, 60_000, 10);
+    let summary = render_text_summary(&hot_paths, 10, false, false, None);
+    assert!(summary.contains("..."));
+    assert!(summary.contains("処理"));
+}
+
+#[test]
+fn test_flamegraph_tooltips_show_share_of_parent_and_total() {
+    let stacks = vec![
+        CollapsedStack::new("main;execute;storage_load_bytes32".to_string(), 3000, None),
+        CollapsedStack::new("main;execute".to_string(), 3000, None),
+        CollapsedStack::new("main;other".to_string(), 4000, None),
+    ];
+
+    let svg = generate_flamegraph(&stacks, None, None).unwrap();
+    assert!(svg.contains("<title>root: 10000 ink / 1 gas</title>"));
+    assert!(svg.contains("execute: 6000 ink / 0 gas (60.0% of parent, 60.0% of total)"));
+    assert!(
+        svg.contains("storage_load_bytes32: 3000 ink / 0 gas (50.0% of parent, 30.0% of total)")
+    );
+    assert!(svg.contains("execute (self): 3000 ink / 0 gas (50.0% of parent, 30.0% of total)"));
+}
+
+#[test]
+fn test_flamegraph_marks_annotated_frames() {
+    let stacks = vec![
+        CollapsedStack::new("main;storage_load_bytes32".to_string(), 6000, None),
+        CollapsedStack::new("main;native_keccak256".to_string(), 4000, None),
+    ];
+    let config = FlamegraphConfig::new().with_annotation(
+        "root;main;storage_load_bytes32",
+        "Significant 'Cold Tax' & more",
+    );
+
+    let svg = generate_flamegraph(&stacks, Some(&config), None).unwrap();
+    assert_eq!(svg.matches(r#"class="insight""#).count(), 1);
+    assert!(svg.contains("⚠ Significant &apos;Cold Tax&apos; &amp; more"));
+    assert!(svg.contains("marks frames with an insight"));
+    stylus_trace_core::output::validate_svg(&svg).unwrap();
+
+    let plain = generate_flamegraph(&stacks, None, None).unwrap();
+    assert!(!plain.contains("⚠"));
+}
+
+#[test]
+fn test_generate_hostio_histogram() {
+    let mut stats = HostIoStats::new();
+    stats.add_event(HostIoEvent {
+        io_type: HostIoType::StorageFlush,
+        gas_cost: 30_000,
+    });
+    stats.add_event(HostIoEvent {
+        io_type: HostIoType::StorageLoad,
+        gas_cost: 10_000,
+    });
+
+    let histogram = generate_hostio_histogram(&stats, false, 0, None);
+    let lines: Vec<&str> = histogram.lines().collect();
+
+    assert_eq!(lines.len(), 3);
+    assert!(lines[1].contains("storage_flush_cache"));
+    assert!(lines[1].contains("75.0%"));
+    assert!(lines[2].contains("storage_load"));
+    assert!(lines[2].contains("(1 gas)"));
+    assert!(lines[2].contains("1.0 gas/call"));
+
+    let top_one = generate_hostio_histogram(&stats, false, 1, None);
+    assert!(top_one.contains("storage_flush_cache"));
+    assert!(!top_one.contains("storage_load"));
+    assert!(top_one.contains("... 1 more"));
+
+    let empty = generate_hostio_histogram(&HostIoStats::new(), false, 0, None);
+    assert!(empty.contains("no HostIO gas recorded"));
+}
+
+#[test]
+fn test_flamegraph_renders_self_frame() {
+    let stacks = vec![
+        CollapsedStack::new("main;execute".to_string(), 6000, None),
+        CollapsedStack::new("main".to_string(), 4000, None),
+    ];
+
+    let svg = generate_flamegraph(&stacks, None, None).unwrap();
+    assert!(svg.contains("main (self): 4000 ink"));
+    stylus_trace_core::output::validate_svg(&svg).unwrap();
+
+    // No self-gas: no self frame
+    let stacks = vec![CollapsedStack::new("main;execute".to_string(), 6000, None)];
+    let svg = generate_flamegraph(&stacks, None, None).unwrap();
+    assert!(!svg.contains("(self)"));
+}
+
+#[test]
+fn test_flamegraph_escapes_frame_names() {
+    let stacks = vec![CollapsedStack::new(
+        "main;Vec<T>::push&grow".to_string(),
+        6000,
+        None,
+    )];
+
+    let svg = generate_flamegraph(&stacks, None, None).unwrap();
+    assert!(svg.contains("Vec&lt;T&gt;::push&amp;grow"));
+    assert!(!svg.contains("Vec<T>"));
+    stylus_trace_core::output::validate_svg(&svg).unwrap();
+}
+
+#[test]
+fn test_flamegraph_output_is_deterministic() {
+    // Many equal-weight frames exercise the tie-breaking order
+    let stacks: Vec<CollapsedStack> = (0..20)
+        .map(|i| CollapsedStack::new(format!("main;op_{:02}", i), 1000, None))
+        .collect();
+    let reversed: Vec<CollapsedStack> = stacks.iter().rev().cloned().collect();
+
+    let first = generate_flamegraph(&stacks, None, None).unwrap();
+    let second = generate_flamegraph(&reversed, None, None).unwrap();
+    assert_eq!(first, second);
+
+    let first_diff = generate_diff_flamegraph(&stacks, &reversed, None).unwrap();
+    let second_diff = generate_diff_flamegraph(&stacks, &reversed, None).unwrap();
+    assert_eq!(first_diff, second_diff);
+}
+
+#[test]
+fn test_hostio_histogram_has_no_ansi_codes_when_colors_disabled() {
+    colored::control::set_override(false);
+
+    let mut stats = HostIoStats::new();
+    stats.add_event(HostIoEvent {
+        io_type: HostIoType::StorageFlush,
+        gas_cost: 30_000,
+    });
+
+    let histogram = generate_hostio_histogram(&stats, false, 0, None);
+    assert!(histogram.contains("storage_flush_cache"));
+    assert!(!histogram.contains('\x1b'));
+}
+
+#[test]
+fn test_heat_mode_fades_cheap_frames() {
+    let stacks = vec![
+        CollapsedStack::new("main;storage_load_bytes32".to_string(), 9000, None),
+        CollapsedStack::new("main;other;storage_load_bytes32".to_string(), 1000, None),
+    ];
+
+    let category = generate_flamegraph(&stacks, None, None).unwrap();
+    // Both storage loads share the category color
+    assert_eq!(category.matches("rgb(255, 140, 0)").count(), 2 + 1); // + legend
+
+    let config = FlamegraphConfig::new().with_color_by(ColorMode::Heat);
+    let heat = generate_flamegraph(&stacks, Some(&config), None).unwrap();
+    // Hottest frame keeps full color, the cheap one fades toward white
+    assert_eq!(heat.matches("rgb(255, 140, 0)").count(), 1 + 1);
+    assert!(heat.contains("Heat:"));
+}
+
+#[test]
+fn test_text_summary_plain_matches_colored_content() {
+    let stacks = vec![
+        CollapsedStack::new("main;storage_flush_cache".to_string(), 60_000, None),
+        CollapsedStack::new("main;native_keccak256".to_string(), 40_000, None),
+    ];
+    let hot_paths = calculate_hot_paths(&stacks, 100_000, 10);
+
+    let plain = render_text_summary(&hot_paths, 10, false, false, None);
+    let colored = render_text_summary(&hot_paths, 10, false, true, None);
+
+    assert!(!plain.contains('\x1b'));
+    assert!(colored.contains('\x1b'));
+    assert_eq!(strip_ansi(&colored), plain);
+}
+
+#[test]
+fn test_hot_path_groups_roll_up_by_prefix() {
+    let stacks = vec![
+        CollapsedStack::new("main;transfer;storage_load".to_string(), 30_000, None),
+        CollapsedStack::new("main;transfer;emit_log".to_string(), 10_000, None),
+        CollapsedStack::new("main;approve;storage_store".to_string(), 20_000, None),
+    ];
+    let hot_paths = calculate_hot_paths(&stacks, 60_000, 10);
+
+    let out = render_hot_path_groups(&hot_paths, 2, true, false, None);
+    let lines: Vec<&str> = out.lines().collect();
+    assert!(lines[0].contains("depth 2"));
+    assert!(lines[1].trim_start().starts_with("main"));
+    assert!(lines[1].contains("60000 ink") && lines[1].contains("100.0%"));
+    assert!(lines[2].trim_start().starts_with("transfer"));
+    assert!(lines[2].contains("40000 ink") && lines[2].contains("66.7%"));
+    assert!(lines[3].trim_start().starts_with("approve"));
+    assert_eq!(lines.len(), 4);
+}
+
+#[test]
+fn test_hot_path_explanations_cover_hostio_leaves() {
+    let stacks = vec![
+        CollapsedStack::new("main;storage_store_bytes32".to_string(), 50_000, None),
+        CollapsedStack::new("main;compute".to_string(), 30_000, None),
+        CollapsedStack::new("main;keccak256".to_string(), 20_000, None),
+    ];
+    let hot_paths = calculate_hot_paths(&stacks, 100_000, 10);
+
+    let out = generate_hot_path_explanations(&hot_paths, 10);
+    assert!(out.contains(" 1. main;storage_store_bytes32"));
+    assert!(out.contains("storage_store: writing a storage slot"));
+    assert!(out.contains(" 3. main;keccak256"));
+    assert!(out.contains("native_keccak256: hashing"));
+    assert!(!out.contains("compute"));
+
+    for io_type in HostIoType::ALL {
+        assert_eq!(
+            io_type.explanation().is_none(),
+            io_type == HostIoType::Other
+        );
+    }
+}
+
+#[test]
+fn test_side_by_side_flamegraph_shares_gas_scale() {
+    let baseline = vec![CollapsedStack::new("main;work".to_string(), 500_000, None)];
+    let target = vec![CollapsedStack::new(
+        "main;work".to_string(),
+        1_000_000,
+        None,
+    )];
+
+    let svg = generate_side_by_side_flamegraph(&baseline, &target, None).unwrap();
+    stylus_trace_core::output::validate_svg(&svg).unwrap();
+    assert!(svg.contains("Baseline: 50 gas"));
+    assert!(svg.contains("Target (+100.0%): 100 gas"));
+
+    // Default width 1200 leaves two 570px panels; the baseline root is half as wide
+    let root_widths: Vec<&str> = svg
+        .split("<rect ")
+        .filter(|r| r.contains("<title>root"))
+        .filter_map(|r| r.split("width=\"").nth(1)?.split('"').next())
+        .collect();
+    assert_eq!(root_widths, vec!["285.00", "570.00"]);
+
+    assert!(generate_side_by_side_flamegraph(&baseline, &[], None).is_err());
+}
+
+fn strip_ansi(s: &str) -> String {
+    let mut out = String::new();
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // Skip the CSI sequence up to its final `m`
+            chars.by_ref().find(|&c| c == 'm');
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+#[test]
+fn test_diff_flamegraph_marks_new_and_removed_frames() {
+    let baseline = vec![
+        CollapsedStack::new("main;kept".to_string(), 1000, None),
+        CollapsedStack::new("main;dropped".to_string(), 500, None),
+    ];
+    let target = vec![
+        CollapsedStack::new("main;kept".to_string(), 1000, None),
+        CollapsedStack::new("main;added".to_string(), 500, None),
+    ];
+
+    let svg = generate_diff_flamegraph(&baseline, &target, None).unwrap();
+
+    assert!(svg.contains(">New</text>"));
+    assert!(svg.contains(">Removed</text>"));
+    // One frame plus the legend swatch each
+    assert_eq!(svg.matches("rgb(170, 110, 230)").count(), 2);
+    assert_eq!(svg.matches("rgb(90, 160, 240)").count(), 2);
+
+    // Tooltip values carry their unit
+    assert!(svg.contains("<title>added: 500 ink (NEW)</title>"));
+    assert!(svg.contains("<title>kept: 1000 ink -&gt; 1000 ink (+0.00%)</title>"));
+}
+
+#[test]
+fn test_flamegraph_label_gas_appends_values_to_wide_frames() {
+    let stacks = vec![
+        CollapsedStack::new("main;execute".to_string(), 990_000, None),
+        CollapsedStack::new("main;tiny".to_string(), 10_000, None),
+    ];
+
+    let plain = generate_flamegraph(&stacks, None, None).unwrap();
+    assert!(!plain.contains(">execute (99 gas)<"));
+
+    let config = FlamegraphConfig::new().with_label_gas(true);
+    let svg = generate_flamegraph(&stacks, Some(&config), None).unwrap();
+    assert!(svg.contains(">execute (99 gas)<"));
+    // Too narrow for a label at all
+    assert!(!svg.contains("tiny ("));
+
+    let ink = FlamegraphConfig::new().with_label_gas(true).with_ink(true);
+    let svg = generate_flamegraph(&stacks, Some(&ink), None).unwrap();
+    assert!(svg.contains(">execute (990000 ink)<"));
+}
+
+#[test]
+fn test_flamegraph_watermark_records_provenance() {
+    use stylus_trace_core::output::SvgWatermark;
+
+    let stacks = vec![CollapsedStack::new("main;work".to_string(), 100, None)];
+    let plain = generate_flamegraph(&stacks, None, None).unwrap();
+    assert!(!plain.contains("<metadata>"));
+
+    let config = FlamegraphConfig::default().with_watermark(SvgWatermark {
+        transaction_hash: "0xabc".to_string(),
+        generated_at: "2025-01-01T00:00:00Z".to_string(),
+    });
+    let svg = generate_flamegraph(&stacks, Some(&config), None).unwrap();
+    stylus_trace_core::output::validate_svg(&svg).unwrap();
+
+    let version = env!("CARGO_PKG_VERSION");
+    assert!(svg.contains(&format!(r#"version="{}" transaction="0xabc""#, version)));
+    assert!(svg.contains(&format!(
+        "stylus-trace v{} · 0xabc · 2025-01-01T00:00:00Z</text>",
+        version
+    )));
+    // The footer gets its own band below the legend
+    assert!(plain.contains(r#"height="140""#));
+    assert!(svg.contains(r#"height="156""#));
+}
+
+#[test]
+fn test_percent_precision_applies_to_summary_and_tooltips() {
+    let stacks = vec![
+        CollapsedStack::new("main;execute".to_string(), 1234, None),
+        CollapsedStack::new("main;other".to_string(), 8766, None),
+    ];
+    let hot_paths = calculate_hot_paths(&stacks, 10_000, 10);
+
+    let config = FlamegraphConfig::new().with_percent_precision(3);
+    let default = render_text_summary(&hot_paths, 10, false, false, None);
+    assert!(default.contains("12.3%"));
+    let precise = render_text_summary(&hot_paths, 10, false, false, Some(&config));
+    assert!(precise.contains("12.340%"));
+    assert!(!precise.contains("12.3% "));
+
+    let groups = render_hot_path_groups(&hot_paths, 1, true, false, Some(&config));
+    assert!(groups.contains("100.000%"));
+    let mut stats = HostIoStats::new();
+    stats.add_event(HostIoEvent {
+        io_type: HostIoType::StorageLoad,
+        gas_cost: 1,
+    });
+    stats.add_event(HostIoEvent {
+        io_type: HostIoType::Log,
+        gas_cost: 2,
+    });
+    let histogram = generate_hostio_histogram(&stats, true, 0, Some(&config));
+    assert!(histogram.contains("66.667%"));
+
+    let svg = generate_flamegraph(&stacks, Some(&config), None).unwrap();
+    assert!(svg.contains("execute: 1234 ink / 0 gas (12.340% of parent, 12.340% of total)"));
+}
+
+#[test]
+fn test_zero_weight_stacks_report_no_gas_attributed() {
+    use stylus_trace_core::utils::error::FlamegraphError;
+
+    let zero = vec![
+        CollapsedStack::new("main;execute".to_string(), 0, None),
+        CollapsedStack::new("main;storage_load_bytes32".to_string(), 0, None),
+    ];
+    let err = generate_flamegraph(&zero, None, None).unwrap_err();
+    assert!(matches!(
+        err,
+        FlamegraphError::NoGasAttributed { stacks: 2 }
+    ));
+    assert!(err.to_string().contains("zero weight"));
+
+    assert!(matches!(
+        generate_side_by_side_flamegraph(&zero, &zero, None),
+        Err(FlamegraphError::NoGasAttributed { stacks: 4 })
+    ));
+    assert!(matches!(
+        generate_diff_flamegraph(&zero, &zero, None),
+        Err(FlamegraphError::NoGasAttributed { stacks: 4 })
+    ));
+
+    // A zero-weight panel next to a real one renders without NaN geometry
+    let real = vec![CollapsedStack::new("main;execute".to_string(), 500, None)];
+    let svg = generate_side_by_side_flamegraph(&zero, &real, None).unwrap();
+    assert!(!svg.contains("NaN"));
+}
@@ -1,32 +1,82 @@
 use std::collections::HashMap;
 use std::path::Path;
 use stylus_trace_core::output::validate_path;
-use stylus_trace_core::output::{read_profile, write_profile, write_svg};
-use stylus_trace_core::parser::schema::{GasCategory, HostIoSummary, HotPath, Profile};
+use stylus_trace_core::output::{
+    check_profile_schema, is_stdout_path, profile_to_string, read_profile, write_capture_summary,
+    write_collapsed_stacks, write_hot_paths_ndjson, write_profile, write_profile_compact,
+    write_profile_styled, write_profile_to_writer, write_svg, write_svg_validated, JsonStyle,
+    RedactionConfig, Redactor,
+};
+use stylus_trace_core::parser::schema::{
+    CaptureSummary, GasCategory, HostIoSummary, HotPath, Profile,
+};
 use tempfile::NamedTempFile;
 
 fn create_test_profile() -> Profile {
     Profile {
         version: "1.0.0".to_string(),
         transaction_hash: "0xtest123".to_string(),
+        label: None,
+        contract_address: None,
+        block_number: None,
         total_gas: 100000,
+        total_gas_source: Default::default(),
         hostio_summary: HostIoSummary {
             total_calls: 10,
             by_type: HashMap::new(),
             total_hostio_gas: 5000,
+            gas_by_type: HashMap::new(),
+            avg_gas_per_call: HashMap::new(),
         },
+        contracts: None,
+        repeated_slot_reads: None,
         hot_paths: vec![HotPath {
             stack: "main;execute".to_string(),
             gas: 50000,
+            cumulative_gas: 50000,
             percentage: 50.0,
             category: GasCategory::UserCode,
             source_hint: None,
         }],
         all_stacks: None,
+        samples: None,
         generated_at: "2024-01-01T00:00:00Z".to_string(),
     }
 }
 
+#[test]
+fn test_hostio_summary_keeps_top_types_sorted() {
+    let mut summary = HostIoSummary {
+        total_calls: 16,
+        by_type: HashMap::from([
+            ("storage_load".to_string(), 8),
+            ("emit_log".to_string(), 4),
+            ("msg_sender".to_string(), 3),
+            ("other".to_string(), 1),
+        ]),
+        total_hostio_gas: 1600,
+        gas_by_type: HashMap::from([
+            ("storage_load".to_string(), 800),
+            ("emit_log".to_string(), 600),
+            ("msg_sender".to_string(), 150),
+            ("other".to_string(), 50),
+        ]),
+        avg_gas_per_call: HashMap::from([("msg_sender".to_string(), 50.0)]),
+    };
+
+    summary.keep_top_types(1);
+    assert_eq!(summary.by_type.len(), 2);
+    assert_eq!(summary.by_type["storage_load"], 8);
+    assert_eq!(summary.by_type["other"], 8);
+    assert_eq!(summary.gas_by_type["other"], 800);
+    assert_eq!(summary.avg_gas_per_call["other"], 100.0);
+    assert_eq!(summary.by_type.values().sum::<u64>(), summary.total_calls);
+
+    // Keys are written in sorted order
+    let json = serde_json::to_string(&summary).unwrap();
+    assert!(json.find("\"other\"").unwrap() < json.find("\"storage_load\"").unwrap());
+}
+
 #[test]
 fn test_write_and_read_profile() {
     let profile = create_test_profile();
@@ -44,6 +94,111 @@ fn test_write_and_read_profile() {
     assert_eq!(loaded.total_gas, profile.total_gas);
 }
 
+#[test]
+fn test_write_profile_compact_roundtrip() {
+    let profile = create_test_profile();
+    let compact = NamedTempFile::new().unwrap();
+    let pretty = NamedTempFile::new().unwrap();
+
+    write_profile_compact(&profile, compact.path()).unwrap();
+    write_profile(&profile, pretty.path()).unwrap();
+
+    let contents = std::fs::read_to_string(compact.path()).unwrap();
+    assert!(!contents.contains('\n'));
+    assert!(contents.len() < std::fs::read_to_string(pretty.path()).unwrap().len());
+
+    let loaded = read_profile(compact.path()).unwrap();
+    assert_eq!(loaded.total_gas, profile.total_gas);
+    assert_eq!(loaded.hot_paths.len(), profile.hot_paths.len());
+}
+
+#[test]
+fn test_write_profile_styled_indent() {
+    let profile = create_test_profile();
+    let file = NamedTempFile::new().unwrap();
+
+    write_profile_styled(&profile, file.path(), JsonStyle::Pretty, 4).unwrap();
+    let contents = std::fs::read_to_string(file.path()).unwrap();
+    assert!(contents.contains("\n    \"version\""));
+    assert_eq!(
+        read_profile(file.path()).unwrap().total_gas,
+        profile.total_gas
+    );
+
+    write_profile_styled(&profile, file.path(), JsonStyle::Compact, 4).unwrap();
+    assert!(!std::fs::read_to_string(file.path()).unwrap().contains('\n'));
+}
+
+#[test]
+fn test_write_profile_to_writer_matches_file_output() {
+    let profile = create_test_profile();
+    let file = NamedTempFile::new().unwrap();
+    write_profile_styled(&profile, file.path(), JsonStyle::Pretty, 4).unwrap();
+
+    let mut buffer = Vec::new();
+    write_profile_to_writer(&profile, &mut buffer, JsonStyle::Pretty, 4).unwrap();
+    assert_eq!(
+        String::from_utf8(buffer).unwrap(),
+        std::fs::read_to_string(file.path()).unwrap()
+    );
+
+    assert!(is_stdout_path(Path::new("-")));
+    assert!(!is_stdout_path(Path::new("./-")));
+}
+
+#[test]
+fn test_profile_to_string_matches_written_file() {
+    let profile = create_test_profile();
+    let temp_file = NamedTempFile::new().unwrap();
+
+    write_profile(&profile, temp_file.path()).unwrap();
+
+    let json = profile_to_string(&profile).unwrap();
+    assert_eq!(json, std::fs::read_to_string(temp_file.path()).unwrap());
+}
+
+#[test]
+fn test_write_collapsed_stacks_roundtrip() {
+    use stylus_trace_core::aggregator::stack_builder::CollapsedStack;
+
+    let stacks = vec![
+        CollapsedStack::new("main;storage_load_bytes32".to_string(), 5000, Some(0x42)),
+        CollapsedStack::new("main".to_string(), 100, None),
+    ];
+    let temp_file = NamedTempFile::new().unwrap();
+    write_collapsed_stacks(&stacks, temp_file.path()).unwrap();
+
+    let json: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(temp_file.path()).unwrap()).unwrap();
+    assert_eq!(json[0]["stack"], "main;storage_load_bytes32");
+    assert_eq!(json[0]["weight"], 5000);
+    assert_eq!(json[0]["last_pc"], 0x42);
+    assert!(json[1]["last_pc"].is_null());
+
+    let read_back: Vec<CollapsedStack> = serde_json::from_value(json).unwrap();
+    assert_eq!(read_back.len(), 2);
+}
+
+#[test]
+fn test_write_hot_paths_ndjson() {
+    let profile = create_test_profile();
+    let temp_file = NamedTempFile::new().unwrap();
+
+    write_hot_paths_ndjson(&profile, temp_file.path()).unwrap();
+
+    let contents = std::fs::read_to_string(temp_file.path()).unwrap();
+    let lines: Vec<&str> = contents.lines().collect();
+    assert_eq!(lines.len(), 1 + profile.hot_paths.len());
+
+    let metadata: serde_json::Value = serde_json::from_str(lines[0]).unwrap();
+    assert_eq!(metadata["transaction_hash"], "0xtest123");
+    assert_eq!(metadata["hot_path_count"], 1);
+
+    let path: HotPath = serde_json::from_str(lines[1]).unwrap();
+    assert_eq!(path.stack, "main;execute");
+    assert_eq!(path.gas, 50000);
+}
+
 #[test]
 fn test_validate_output_path_empty() {
     let result = validate_path(Path::new(""));
@@ -94,3 +249,225 @@ fn test_svg_write_creates_parent_dirs() {
 
     assert!(nested_path.exists());
 }
+
+#[test]
+fn test_write_svg_validated_rejects_malformed_svg() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let path = temp_dir.path().join("flamegraph.svg");
+
+    let valid_svg = r#"<?xml version="1.0"?>
+<!-- flamegraph -->
+<svg xmlns="http://www.w3.org/2000/svg"><style>text { fill: red; }</style>
+  <g><title>a &amp; b (10 gas)</title><rect width="10"/></g>
+</svg>"#;
+    write_svg_validated(valid_svg, &path).unwrap();
+    assert!(path.exists());
+
+    for malformed in [
+        r#"<svg><g><rect/></svg>"#,
+        r#"<svg><title>a & b</title></svg>"#,
+        r#"<svg><text>1 < 2</text></svg>"#,
+        r#"<html></html>"#,
+    ] {
+        let bad_path = temp_dir.path().join("bad.svg");
+        let err = write_svg_validated(malformed, &bad_path).unwrap_err();
+        assert!(err.to_string().contains("malformed"), "{}", malformed);
+        assert!(!bad_path.exists());
+    }
+}
+
+#[test]
+fn test_redact_long_hex_blobs() {
+    let redactor = Redactor::new(&RedactionConfig::default()).unwrap();
+
+    let address = "0x5FbDB2315678afecb367f032d93F642f64180aa3";
+    let redacted = redactor.redact(&format!("main;transfer_{};storage_load", address));
+    assert_eq!(redacted, "main;transfer_[REDACTED];storage_load");
+
+    // Short hex values (e.g. PCs) are left untouched
+    assert_eq!(redactor.redact("0x42"), "0x42");
+}
+
+#[test]
+fn test_redact_stacks_merges_collapsed_frames() {
+    use stylus_trace_core::aggregator::stack_builder::CollapsedStack;
+
+    let redactor = Redactor::new(&RedactionConfig::default()).unwrap();
+    let a = format!("main;transfer_0x{};storage_load", "a".repeat(40));
+    let b = format!("main;transfer_0x{};storage_load", "b".repeat(40));
+    let mut stacks = vec![
+        CollapsedStack::new(a, 300, Some(1)),
+        CollapsedStack::new(b, 500, Some(2)),
+    ];
+
+    redactor.redact_stacks(&mut stacks);
+    assert_eq!(stacks.len(), 1);
+    assert_eq!(stacks[0].stack, "main;transfer_[REDACTED];storage_load");
+    assert_eq!(stacks[0].weight, 800);
+    assert_eq!(stacks[0].last_pc, Some(2));
+}
+
+#[test]
+fn test_redact_profile_custom_patterns() {
+    let config = RedactionConfig {
+        patterns: vec!["execute".to_string()],
+        replacement: "***".to_string(),
+        ..Default::default()
+    };
+    let redactor = Redactor::new(&config).unwrap();
+
+    let mut profile = create_test_profile();
+    redactor.redact_profile(&mut profile);
+    assert_eq!(profile.hot_paths[0].stack, "main;***");
+
+    let invalid = RedactionConfig {
+        patterns: vec!["(".to_string()],
+        ..Default::default()
+    };
+    assert!(Redactor::new(&invalid).is_err());
+}
+
+#[test]
+fn test_write_capture_summary_compact() {
+    let profile = create_test_profile();
+    let summary = CaptureSummary {
+        transaction_hash: profile.transaction_hash.clone(),
+        total_gas: 100000,
+        execution_gas: 80000,
+        intrinsic_gas: 20000,
+        hostio_calls: 10,
+        unique_paths: 1,
+        top_paths: profile.hot_paths.clone(),
+    };
+
+    let temp_file = NamedTempFile::new().unwrap();
+    write_capture_summary(&summary, temp_file.path()).unwrap();
+
+    let contents = std::fs::read_to_string(temp_file.path()).unwrap();
+    assert!(!contents.contains('\n'), "summary should be compact");
+
+    let value: serde_json::Value = serde_json::from_str(&contents).unwrap();
+    assert_eq!(value["intrinsic_gas"], 20000);
+    assert_eq!(value["hostio_calls"], 10);
+    assert_eq!(value["top_paths"].as_array().unwrap().len(), 1);
+}
+
+#[test]
+fn test_profile_metadata_roundtrip() {
+    let mut profile = create_test_profile();
+    profile.contract_address = Some("0x00000000000000000000000000000000000000aa".to_string());
+    profile.block_number = Some(436);
+
+    let temp_file = NamedTempFile::new().unwrap();
+    write_profile(&profile, temp_file.path()).unwrap();
+    let loaded = read_profile(temp_file.path()).unwrap();
+
+    assert_eq!(loaded.contract_address, profile.contract_address);
+    assert_eq!(loaded.block_number, Some(436));
+
+    // Profiles captured before metadata existed still load
+    let mut value = serde_json::to_value(&profile).unwrap();
+    let obj = value.as_object_mut().unwrap();
+    obj.remove("contract_address");
+    obj.remove("block_number");
+    let legacy: Profile = serde_json::from_value(value).unwrap();
+    assert!(legacy.contract_address.is_none());
+    assert!(legacy.block_number.is_none());
+}
+
+#[test]
+fn test_schema_check_reports_defaulted_and_unknown_fields() {
+    let current = serde_json::to_value(create_test_profile()).unwrap();
+    let check = check_profile_schema(&current);
+    assert!(check.is_clean(), "{:?}", check);
+
+    let skewed = serde_json::json!({
+        "version": "1.2.0",
+        "transaction_hash": "0xabc",
+        "total_gas": 100,
+        "hostio_summary": {
+            "total_calls": 1,
+            "by_type": { "storage_load": 1 },
+            "total_hostio_gas": 10
+        },
+        "hot_paths": [
+            { "stack": "main", "gas": 100, "percentage": 100.0, "self_time": 3 }
+        ],
+        "generated_at": "2024-01-01T00:00:00Z",
+        "receipts": []
+    });
+    let check = check_profile_schema(&skewed);
+    assert!(check.newer_than_tool);
+    assert!(check
+        .defaulted
+        .contains(&"hostio_summary.gas_by_type".to_string()));
+    assert!(check
+        .defaulted
+        .contains(&"hot_paths[].cumulative_gas (1 of 1 entries)".to_string()));
+    assert_eq!(check.unknown, vec!["receipts", "hot_paths[].self_time"]);
+    assert!(check.not_recorded.contains(&"block_number".to_string()));
+}
+
+#[test]
+fn test_speedscope_export_shares_frames() {
+    use stylus_trace_core::aggregator::stack_builder::CollapsedStack;
+    use stylus_trace_core::output::speedscope;
+
+    let stacks = vec![
+        CollapsedStack::new("main;execute;storage_load_bytes32".to_string(), 5000, None),
+        CollapsedStack::new("main;execute".to_string(), 300, None),
+        CollapsedStack::new("main;idle".to_string(), 0, None),
+    ];
+
+    let file = NamedTempFile::new().unwrap();
+    speedscope::write(&stacks, "0xabc", file.path()).unwrap();
+    let json: serde_json::Value =
+        serde_json::from_reader(std::fs::File::open(file.path()).unwrap()).unwrap();
+
+    assert_eq!(json["$schema"], speedscope::SPEEDSCOPE_SCHEMA);
+    let names: Vec<&str> = json["shared"]["frames"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|f| f["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, vec!["main", "execute", "storage_load_bytes32"]);
+
+    let profile = &json["profiles"][0];
+    assert_eq!(profile["type"], "sampled");
+    assert_eq!(profile["name"], "0xabc");
+    assert_eq!(profile["samples"], serde_json::json!([[0, 1, 2], [0, 1]]));
+    assert_eq!(profile["weights"], serde_json::json!([5000, 300]));
+    assert_eq!(profile["endValue"], 5300);
+}
+
+#[test]
+fn test_outputs_are_replaced_atomically() {
+    use std::io::Write;
+    use stylus_trace_core::output::AtomicFile;
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("profile.json");
+    let files = || std::fs::read_dir(dir.path()).unwrap().count();
+
+    // An abandoned write leaves neither the file nor a temporary behind
+    let mut pending = AtomicFile::create(&path).unwrap();
+    pending.write_all(b"{\"version\":").unwrap();
+    drop(pending);
+    assert!(!path.exists());
+    assert_eq!(files(), 0);
+
+    let mut profile = create_test_profile();
+    write_profile(&profile, &path).unwrap();
+    profile.total_gas = 42;
+    write_profile(&profile, &path).unwrap();
+    assert_eq!(read_profile(&path).unwrap().total_gas, 42);
+
+    write_svg("<svg></svg>", dir.path().join("flamegraph.svg")).unwrap();
+    assert_eq!(files(), 2);
+
+    let matrix = dir.path().join("matrix.json");
+    AtomicFile::write(&matrix, "{}").unwrap();
+    assert_eq!(std::fs::read_to_string(&matrix).unwrap(), "{}");
+    assert_eq!(files(), 3);
+}
@@ -1,6 +1,15 @@
 use serde_json::json;
 use stylus_trace_core::parser::hostio::{parse_hostio_event, HostIoEvent, HostIoStats, HostIoType};
-use stylus_trace_core::parser::stylus_trace::{extract_total_gas, parse_gas_value, parse_trace};
+use stylus_trace_core::parser::selector_map::SelectorMap;
+use stylus_trace_core::parser::source_map::SourceMapper;
+use stylus_trace_core::parser::stylus_trace::{
+    extract_total_gas, normalize_to_ink, parse_gas_value, parse_trace, parse_trace_with_limit,
+    parse_trace_with_unit, GasUnit,
+};
+use stylus_trace_core::utils::config::{
+    generated_at_from, GAS_TO_INK_MULTIPLIER, MAX_REASONABLE_GAS,
+};
+use stylus_trace_core::utils::error::{ParseError, SourceMapError};
 
 #[test]
 fn test_hostio_event_parsing() {
@@ -27,6 +36,19 @@ fn test_hostio_type_parsing() {
     assert_eq!("unknown".parse::<HostIoType>().unwrap(), HostIoType::Other);
 }
 
+#[test]
+fn test_hostio_type_canonical_name_round_trips() {
+    for io_type in HostIoType::ALL {
+        let name = io_type.as_str();
+        assert_eq!(name.parse::<HostIoType>().unwrap(), io_type, "{}", name);
+        assert_eq!(io_type.to_string(), name);
+
+        let json = serde_json::to_string(&io_type).unwrap();
+        assert_eq!(json, format!("\"{}\"", name));
+        assert_eq!(serde_json::from_str::<HostIoType>(&json).unwrap(), io_type);
+    }
+}
+
 #[test]
 fn test_hostio_stats() {
     let mut stats = HostIoStats::new();
@@ -46,6 +68,37 @@ fn test_hostio_stats() {
     assert_eq!(stats.total_calls(), 2);
 }
 
+#[test]
+fn test_hostio_stats_gas_per_type() {
+    let mut stats = HostIoStats::new();
+
+    stats.add_event(HostIoEvent {
+        io_type: HostIoType::StorageLoad,
+        gas_cost: 100,
+    });
+
+    stats.add_event(HostIoEvent {
+        io_type: HostIoType::StorageLoad,
+        gas_cost: 200,
+    });
+
+    stats.add_event(HostIoEvent {
+        io_type: HostIoType::NativeKeccak256,
+        gas_cost: 500,
+    });
+
+    assert_eq!(stats.gas_for_type(HostIoType::StorageLoad), 300);
+    assert_eq!(stats.total_gas(), 800);
+    assert_eq!(stats.total_calls(), 3);
+    assert_eq!(
+        stats.gas_breakdown(),
+        vec![
+            (HostIoType::NativeKeccak256, 500),
+            (HostIoType::StorageLoad, 300)
+        ]
+    );
+}
+
 #[test]
 fn test_parse_gas_value() {
     assert_eq!(parse_gas_value("1000").unwrap(), 1000);
@@ -53,6 +106,25 @@ fn test_parse_gas_value() {
     assert!(parse_gas_value("invalid").is_err());
 }
 
+#[test]
+fn test_parse_gas_value_float_and_scientific() {
+    assert_eq!(parse_gas_value("150000.0").unwrap(), 150_000);
+    assert_eq!(parse_gas_value("1.5e5").unwrap(), 150_000);
+    assert!(parse_gas_value("-1.5e5").is_err());
+    assert!(parse_gas_value("NaN").is_err());
+
+    let trace = json!({ "gasUsed": 150000.0 });
+    assert_eq!(
+        extract_total_gas(trace.as_object().unwrap()).unwrap(),
+        150_000
+    );
+    let trace = json!({ "gasUsed": "1.5e5" });
+    assert_eq!(
+        extract_total_gas(trace.as_object().unwrap()).unwrap(),
+        150_000
+    );
+}
+
 #[test]
 fn test_extract_total_gas() {
     let trace = json!({
@@ -73,6 +145,152 @@ fn test_extract_total_gas_hex() {
     assert_eq!(gas, 50000);
 }
 
+#[test]
+fn test_total_gas_source_records_derived_totals() {
+    use stylus_trace_core::parser::schema::TotalGasSource;
+    use stylus_trace_core::parser::to_profile;
+
+    let reported = parse_trace("0xabc", &json!({ "gasUsed": 10, "structLogs": [] })).unwrap();
+    assert_eq!(reported.total_gas_source, TotalGasSource::Reported);
+
+    let raw = json!({ "structLogs": [{ "op": "SLOAD", "gasCost": 7, "depth": 1 }] });
+    let derived = parse_trace("0xabc", &raw).unwrap();
+    assert_eq!(derived.total_gas_used, 7 * GAS_TO_INK_MULTIPLIER);
+    assert_eq!(derived.total_gas_source, TotalGasSource::DerivedFromSteps);
+
+    // Only derived totals are written, so reported profiles keep their shape
+    let json = |trace| serde_json::to_value(to_profile(trace, vec![], None, None)).unwrap();
+    assert!(json(&reported).get("total_gas_source").is_none());
+    assert_eq!(json(&derived)["total_gas_source"], "derived_from_steps");
+}
+
+#[test]
+fn test_repeated_slot_reads_become_insights() {
+    use stylus_trace_core::diff::analyze_profile;
+    use stylus_trace_core::parser::to_profile;
+
+    let raw = json!({
+        "gasUsed": 20000,
+        "structLogs": [
+            { "op": "SLOAD", "gasCost": 2100, "depth": 1, "stack": ["0x5", "0x1"] },
+            { "op": "SLOAD", "gasCost": 100, "depth": 1, "stack": ["0x1"] },
+            { "op": "SLOAD", "gasCost": 100, "depth": 1, "stack": ["0x01"] },
+            { "op": "SLOAD", "gasCost": 2100, "depth": 1, "stack": ["0x2"] },
+            { "op": "ADD", "gasCost": 3, "depth": 1, "stack": ["0x1", "0x1"] }
+        ]
+    });
+    let profile = to_profile(&parse_trace("0xabc", &raw).unwrap(), vec![], None, None);
+
+    let slots = profile.repeated_slot_reads.as_ref().unwrap();
+    assert_eq!(slots.len(), 1);
+    assert_eq!(slots[0].slot, format!("0x{:0>64}", "1"));
+    assert_eq!(slots[0].reads, 3);
+    assert_eq!(slots[0].cacheable_gas, 200 * GAS_TO_INK_MULTIPLIER);
+
+    let insight = analyze_profile(&profile)
+        .into_iter()
+        .find(|i| i.tag.as_deref() == Some("repeated_slot_read"))
+        .unwrap();
+    assert!(insight.description.contains("read 3 times"));
+    assert!(insight.description.contains("save 2000000 ink"));
+
+    // Without slot keys nothing is recorded or reported
+    let bare = json!({ "gasUsed": 10, "structLogs": [{ "op": "SLOAD", "gasCost": 7 }] });
+    let profile = to_profile(&parse_trace("0xabc", &bare).unwrap(), vec![], None, None);
+    assert!(profile.repeated_slot_reads.is_none());
+}
+
+#[test]
+fn test_slot_key_with_multibyte_char_is_ignored() {
+    let args = format!("{}é{}", "a".repeat(63), "b".repeat(10));
+    let raw = json!([
+        { "name": "storage_load_bytes32", "startInk": 1000, "endInk": 900, "args": args }
+    ]);
+
+    let parsed = parse_trace("0xabc", &raw).unwrap();
+    assert_eq!(parsed.execution_steps.len(), 1);
+    assert!(parsed.execution_steps[0].slot.is_none());
+}
+
+#[test]
+fn test_repeated_slot_reads_are_keyed_per_contract() {
+    use stylus_trace_core::aggregator::StackOptions;
+    use stylus_trace_core::parser::repeated_slot_reads;
+
+    let call = |op: &str, target: &str| {
+        json!({ "op": op, "gasCost": 100, "depth": 1,
+                "stack": ["0x0", "0x0", "0x0", "0x0", target, "0x1388"] })
+    };
+    let sload =
+        |depth: u32| json!({ "op": "SLOAD", "gasCost": 100, "depth": depth, "stack": ["0x0"] });
+    let raw = json!({
+        "gasUsed": 20000,
+        "structLogs": [
+            sload(1),
+            call("CALL", "0xbb"),
+            sload(2),
+            // A delegatecall reads the caller's storage
+            call("DELEGATECALL", "0xcc"),
+            sload(2),
+            // An unknown callee cannot be told apart from other contracts
+            { "op": "CALL", "gasCost": 100, "depth": 1 },
+            sload(2),
+            sload(2)
+        ]
+    });
+    let mut parsed = parse_trace("0xabc", &raw).unwrap();
+    parsed.contract_address = Some("0xaa".to_string());
+
+    let slots = repeated_slot_reads(&parsed, StackOptions::default());
+    assert_eq!(slots.len(), 1);
+    assert_eq!(slots[0].contract.as_deref(), Some("0xaa"));
+    assert_eq!(slots[0].reads, 2);
+}
+
+#[test]
+fn test_slot_read_insights_point_at_their_own_stacks() {
+    use stylus_trace_core::aggregator::build_collapsed_stacks;
+    use stylus_trace_core::diff::analyze_profile;
+    use stylus_trace_core::parser::to_profile;
+
+    let sload = |depth: u32, slot: &str| json!({ "op": "SLOAD", "gasCost": 2100, "depth": depth, "stack": [slot] });
+    let raw = json!({
+        "gasUsed": 20000,
+        "structLogs": [
+            sload(1, "0x1"),
+            sload(1, "0x1"),
+            { "op": "CALL", "gasCost": 100, "depth": 1,
+              "stack": ["0x0", "0x0", "0x0", "0x0", "0xbb", "0x1388"] },
+            sload(2, "0x2"),
+            sload(2, "0x2")
+        ]
+    });
+    let parsed = parse_trace("0xabc", &raw).unwrap();
+    let stacks = build_collapsed_stacks(&parsed);
+    let profile = to_profile(&parsed, vec![], Some(stacks.clone()), None);
+
+    let slots = profile.repeated_slot_reads.as_ref().unwrap();
+    assert_eq!(slots.len(), 2);
+    for slot in slots {
+        assert_eq!(slot.stacks.len(), 1);
+        assert!(stacks.iter().any(|s| s.stack == slot.stacks[0]));
+    }
+    assert_ne!(slots[0].stacks, slots[1].stacks);
+
+    let insights: Vec<_> = analyze_profile(&profile)
+        .into_iter()
+        .filter(|i| i.tag.as_deref() == Some("repeated_slot_read"))
+        .collect();
+    assert_eq!(insights.len(), 2);
+    for insight in insights {
+        let slot = slots
+            .iter()
+            .find(|s| insight.description.contains(&s.slot))
+            .unwrap();
+        assert_eq!(insight.stacks, slot.stacks);
+    }
+}
+
 #[test]
 fn test_parse_trace_minimal() {
     let raw_trace = json!({
@@ -104,3 +322,182 @@ fn test_parse_camelcase_gas_cost() {
     assert_eq!(parsed.execution_steps.len(), 1);
     assert_eq!(parsed.execution_steps[0].gas_cost, 30_000);
 }
+
+#[test]
+fn test_standard_evm_gas_around_max_reasonable_gas() {
+    // Standard EVM traces are always gas, regardless of magnitude
+    for gas in [
+        MAX_REASONABLE_GAS - 1,
+        MAX_REASONABLE_GAS,
+        MAX_REASONABLE_GAS + 1,
+    ] {
+        let raw_trace = json!({ "gasUsed": gas, "structLogs": [] });
+        let parsed = parse_trace("0xtest", &raw_trace).unwrap();
+        assert_eq!(parsed.total_gas_used, gas * GAS_TO_INK_MULTIPLIER);
+    }
+}
+
+#[test]
+fn test_stylus_tracer_small_values_stay_ink() {
+    // Stylus tracer values are ink even when they are small
+    let raw_trace = json!({ "gasUsed": 500, "result": [] });
+    let parsed = parse_trace("0xtest", &raw_trace).unwrap();
+    assert_eq!(parsed.total_gas_used, 500);
+}
+
+#[test]
+fn test_assume_unit_overrides_detected_format() {
+    let raw_trace = json!({
+        "gasUsed": MAX_REASONABLE_GAS + 1,
+        "structLogs": [{ "op": "PUSH1", "gasCost": 3, "depth": 1 }]
+    });
+
+    let parsed = parse_trace_with_unit("0xtest", &raw_trace, Some(GasUnit::Ink)).unwrap();
+    assert_eq!(parsed.total_gas_used, MAX_REASONABLE_GAS + 1);
+    assert_eq!(parsed.execution_steps[0].gas_cost, 3);
+
+    let raw_trace = json!({ "gasUsed": 500, "result": [] });
+    let parsed = parse_trace_with_unit("0xtest", &raw_trace, Some(GasUnit::Gas)).unwrap();
+    assert_eq!(parsed.total_gas_used, 500 * GAS_TO_INK_MULTIPLIER);
+}
+
+#[test]
+fn test_normalize_to_ink_is_unit_based() {
+    assert_eq!(
+        normalize_to_ink(MAX_REASONABLE_GAS, GasUnit::Gas),
+        MAX_REASONABLE_GAS * GAS_TO_INK_MULTIPLIER
+    );
+    assert_eq!(normalize_to_ink(1, GasUnit::Ink), 1);
+    assert_eq!(normalize_to_ink(u64::MAX, GasUnit::Gas), u64::MAX);
+}
+
+#[test]
+fn test_non_decreasing_ink_steps_are_counted() {
+    let trace = json!([
+        {"name": "call", "startInk": 1000, "endInk": 400},
+        {"name": "refund", "startInk": 400, "endInk": 900},
+        {"name": "noop", "startInk": 900, "endInk": 900}
+    ]);
+
+    let parsed = parse_trace("0xabc", &trace).unwrap();
+    assert_eq!(parsed.execution_steps[0].gas_cost, 600);
+    assert_eq!(parsed.execution_steps[1].gas_cost, 0);
+    assert_eq!(parsed.zeroed_ink_steps, 1);
+}
+
+#[test]
+fn test_max_trace_steps_rejects_oversized_traces() {
+    let trace = json!([
+        {"name": "a", "startInk": 1000, "endInk": 900},
+        {"name": "b", "startInk": 900, "endInk": 800},
+        {"name": "c", "startInk": 800, "endInk": 700}
+    ]);
+
+    let err = parse_trace_with_limit("0xabc", &trace, None, 2).unwrap_err();
+    assert!(matches!(
+        err,
+        ParseError::TooManySteps { steps: 3, limit: 2 }
+    ));
+    assert!(err.to_string().contains("--max-trace-steps"));
+
+    assert_eq!(
+        parse_trace_with_limit("0xabc", &trace, None, 3)
+            .unwrap()
+            .execution_steps
+            .len(),
+        3
+    );
+    assert!(parse_trace_with_limit("0xabc", &trace, None, 0).is_ok());
+}
+
+#[test]
+fn test_source_mapper_distinguishes_missing_and_corrupt_dwarf() {
+    // Magic, version, an empty type section and a custom `pad` section, since
+    // format detection needs at least 16 bytes
+    const WASM_HEADER: &[u8] = b"\0asm\x01\0\0\0\x01\x01\0\0\x04\x03pad";
+    let load = |bytes: &[u8]| {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(file.path(), bytes).unwrap();
+        SourceMapper::new(file.path()).err()
+    };
+
+    assert!(matches!(
+        load(b"not wasm"),
+        Some(SourceMapError::InvalidWasm(_))
+    ));
+    assert!(matches!(
+        load(WASM_HEADER),
+        Some(SourceMapError::NoDebugInfo)
+    ));
+
+    // Custom section `.debug_info` holding a truncated unit header
+    let mut corrupt = WASM_HEADER.to_vec();
+    let name = b".debug_info";
+    let payload = [0xff, 0xff];
+    corrupt.push(0);
+    corrupt.push((1 + name.len() + payload.len()) as u8);
+    corrupt.push(name.len() as u8);
+    corrupt.extend_from_slice(name);
+    corrupt.extend_from_slice(&payload);
+    assert!(matches!(
+        load(&corrupt),
+        Some(SourceMapError::CorruptDebugInfo(_))
+    ));
+}
+
+#[test]
+fn test_selector_map_from_abi() {
+    let abi = json!({
+        "abi": [
+            {
+                "type": "function",
+                "name": "transfer",
+                "inputs": [
+                    { "name": "to", "type": "address" },
+                    { "name": "amount", "type": "uint256" }
+                ]
+            },
+            {
+                "type": "function",
+                "name": "submit",
+                "inputs": [{
+                    "name": "orders",
+                    "type": "tuple[]",
+                    "components": [
+                        { "name": "maker", "type": "address" },
+                        { "name": "amount", "type": "uint256" }
+                    ]
+                }]
+            },
+            { "type": "event", "name": "Transfer", "inputs": [] }
+        ]
+    });
+
+    let selectors = SelectorMap::from_abi(&abi).unwrap();
+    assert_eq!(selectors.len(), 2);
+    assert_eq!(
+        selectors.lookup("0xA9059CBB"),
+        Some("transfer(address,uint256)")
+    );
+    assert!(selectors.lookup("0xdeadbeef").is_none());
+    assert!(selectors.lookup("transfer").is_none());
+
+    assert!(SelectorMap::from_abi(&json!({ "bytecode": "0x" })).is_err());
+}
+
+#[test]
+fn test_generated_at_honors_source_date_epoch() {
+    assert_eq!(
+        generated_at_from(Some("1700000000".to_string())),
+        "2023-11-14T22:13:20+00:00"
+    );
+
+    // Invalid or unset values fall back to the current time
+    for fallback in [
+        generated_at_from(Some("soon".to_string())),
+        generated_at_from(None),
+    ] {
+        assert!(chrono::DateTime::parse_from_rfc3339(&fallback).is_ok());
+        assert!(!fallback.starts_with("2023-11-14"));
+    }
+}
@@ -1,7 +1,275 @@
 use stylus_trace_core::rpc::client::normalize_tx_hash;
+use stylus_trace_core::rpc::{CallRequest, RpcClient};
+use stylus_trace_core::utils::error::RpcError;
 
 #[test]
 fn test_normalize_tx_hash() {
     assert_eq!(normalize_tx_hash("abc123"), "0xabc123");
     assert_eq!(normalize_tx_hash("0xdef456"), "0xdef456");
 }
+
+#[test]
+fn test_is_nitro_client_version() {
+    assert!(RpcClient::is_nitro_client_version(
+        "nitro/v3.1.0-7d1d84c/linux-amd64/go1.21.10"
+    ));
+    assert!(!RpcClient::is_nitro_client_version(
+        "Geth/v1.13.5-stable/linux-amd64/go1.21.4"
+    ));
+    assert!(!RpcClient::is_nitro_client_version("MockNode/v1.0"));
+}
+
+#[test]
+fn test_unknown_tracer_maps_to_tracer_not_supported() {
+    use std::net::TcpListener;
+    use tungstenite::Message;
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("ws://{}", listener.local_addr().unwrap());
+    let server = std::thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let mut socket = tungstenite::accept(stream).unwrap();
+        let request: serde_json::Value =
+            serde_json::from_str(socket.read().unwrap().to_text().unwrap()).unwrap();
+        let response = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": request["id"],
+            "error": {"code": -32000, "message": "tracer not found"}
+        });
+        socket.send(Message::Text(response.to_string())).unwrap();
+    });
+
+    let client = RpcClient::new(url).unwrap();
+    let err = client
+        .debug_trace_transaction_with_tracer("0xabc", Some("stylusTracer"))
+        .unwrap_err();
+
+    assert!(matches!(err, RpcError::TracerNotSupported));
+    server.join().unwrap();
+}
+
+#[test]
+fn test_connection_refused_maps_to_connection_failed() {
+    // Port 1 is reserved and never has an RPC node listening
+    let client = RpcClient::new("http://127.0.0.1:1").unwrap();
+    let err = client
+        .debug_trace_transaction_with_tracer("0xabc", None)
+        .unwrap_err();
+
+    assert!(matches!(err, RpcError::ConnectionFailed(_)));
+    assert!(err.to_string().contains("127.0.0.1:1"));
+}
+
+#[test]
+fn test_timeout_error_message() {
+    let err = RpcError::Timeout(std::time::Duration::from_secs(30));
+    assert_eq!(
+        err.to_string(),
+        "RPC timed out after 30s — try increasing --timeout"
+    );
+}
+
+/// Spawn a one-shot WebSocket JSON-RPC server that answers a single request
+fn spawn_ws_server(
+    respond: impl FnOnce(&serde_json::Value) -> serde_json::Value + Send + 'static,
+) -> (String, std::thread::JoinHandle<()>) {
+    use std::net::TcpListener;
+    use tungstenite::Message;
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("ws://{}", listener.local_addr().unwrap());
+
+    let handle = std::thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let mut socket = tungstenite::accept(stream).unwrap();
+
+        let request: serde_json::Value =
+            serde_json::from_str(socket.read().unwrap().to_text().unwrap()).unwrap();
+
+        // An unrelated notification must be skipped by the client
+        let notification = serde_json::json!({"jsonrpc": "2.0", "id": 99, "result": null});
+        socket
+            .send(Message::Text(notification.to_string()))
+            .unwrap();
+
+        let response = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": request["id"],
+            "result": respond(&request)
+        });
+        socket.send(Message::Text(response.to_string())).unwrap();
+    });
+
+    (url, handle)
+}
+
+#[test]
+fn test_websocket_transport_matches_response_id() {
+    let (url, server) = spawn_ws_server(|request| {
+        assert_eq!(request["method"], "debug_traceTransaction");
+        serde_json::json!({"gasUsed": "0x64", "structLogs": []})
+    });
+
+    let client = RpcClient::new(url).unwrap();
+    let trace = client
+        .debug_trace_transaction_with_tracer("0xabc", None)
+        .unwrap();
+
+    assert_eq!(trace["gasUsed"], "0x64");
+    server.join().unwrap();
+}
+
+#[test]
+fn test_string_encoded_trace_result_is_unwrapped() {
+    let (url, server) =
+        spawn_ws_server(|_| serde_json::json!(r#"{"gasUsed": "0x64", "structLogs": []}"#));
+
+    let client = RpcClient::new(url).unwrap();
+    let trace = client
+        .debug_trace_transaction_with_tracer("0xabc", None)
+        .unwrap();
+
+    assert_eq!(trace["gasUsed"], "0x64");
+    assert!(trace["structLogs"].is_array());
+    server.join().unwrap();
+}
+
+#[test]
+fn test_debug_trace_call_sends_call_object() {
+    let (url, server) = spawn_ws_server(|request| {
+        assert_eq!(request["method"], "debug_traceCall");
+        let params = request["params"].as_array().unwrap();
+        assert_eq!(
+            params[0]["to"],
+            "0x00000000000000000000000000000000000000aa"
+        );
+        assert_eq!(params[0]["data"], "0xa9059cbb");
+        assert!(params[0].get("from").is_none());
+        assert_eq!(params[1], "latest");
+        assert_eq!(params[2]["tracer"], "stylusTracer");
+        serde_json::json!({"gasUsed": "0x64", "structLogs": []})
+    });
+
+    let call = CallRequest {
+        to: "0x00000000000000000000000000000000000000aa".to_string(),
+        data: Some("0xa9059cbb".to_string()),
+        ..Default::default()
+    };
+    let client = RpcClient::new(url).unwrap();
+    let trace = client
+        .debug_trace_call(&call, "latest", None, None)
+        .unwrap();
+
+    assert_eq!(trace["gasUsed"], "0x64");
+    server.join().unwrap();
+}
+
+#[test]
+fn test_get_transaction_info() {
+    let (url, server) = spawn_ws_server(|request| {
+        assert_eq!(request["method"], "eth_getTransactionByHash");
+        serde_json::json!({
+            "to": "0x00000000000000000000000000000000000000aa",
+            "blockNumber": "0x1b4"
+        })
+    });
+
+    let client = RpcClient::new(url).unwrap();
+    let info = client.get_transaction_info("0xabc").unwrap();
+
+    assert_eq!(
+        info.to.as_deref(),
+        Some("0x00000000000000000000000000000000000000aa")
+    );
+    assert_eq!(info.block_number.as_deref(), Some("0x1b4"));
+    server.join().unwrap();
+}
+
+#[test]
+fn test_get_transaction_info_not_found() {
+    let (url, server) = spawn_ws_server(|_| serde_json::Value::Null);
+
+    let client = RpcClient::new(url).unwrap();
+    let err = client.get_transaction_info("0xabc").unwrap_err();
+
+    assert!(matches!(err, RpcError::TransactionNotFound(_)));
+    server.join().unwrap();
+}
+
+#[test]
+fn test_tracer_config_is_sent_as_tracer_config() {
+    let (url, server) = spawn_ws_server(|request| {
+        let params = &request["params"][1];
+        assert_eq!(params["tracer"], "callTracer");
+        assert_eq!(params["tracerConfig"]["onlyTopCall"], true);
+        serde_json::json!({"gasUsed": "0x1"})
+    });
+
+    let client = RpcClient::new(url).unwrap();
+    let config = serde_json::json!({"onlyTopCall": true});
+    client
+        .debug_trace_transaction_with_config("0xabc", Some("callTracer"), Some(&config))
+        .unwrap();
+    server.join().unwrap();
+}
+
+#[test]
+fn test_trace_cache_roundtrip_and_keying() {
+    use stylus_trace_core::rpc::{TraceCache, TraceCacheKey};
+
+    let dir = tempfile::tempdir().unwrap();
+    let cache = TraceCache::new(dir.path().join("cache"));
+    let key = TraceCacheKey {
+        rpc_url: "http://localhost:8547",
+        tx_hash: "0xabc",
+        tracer: None,
+        tracer_config: None,
+    };
+    let trace = serde_json::json!({"gasUsed": "0x5208"});
+
+    assert!(cache.load(&key).is_none());
+    cache.store(&key, &trace).unwrap();
+    assert_eq!(cache.load(&key), Some(trace));
+
+    let other_tracer = TraceCacheKey {
+        tracer: Some("callTracer"),
+        ..key
+    };
+    let other_rpc = TraceCacheKey {
+        rpc_url: "http://localhost:8548",
+        ..key
+    };
+    assert!(cache.load(&other_tracer).is_none());
+    assert!(cache.load(&other_rpc).is_none());
+}
+
+#[test]
+fn test_trace_cache_concurrent_stores_leave_one_entry() {
+    use stylus_trace_core::rpc::{TraceCache, TraceCacheKey};
+
+    let dir = tempfile::tempdir().unwrap();
+    let cache = TraceCache::new(dir.path().join("cache"));
+    let key = TraceCacheKey {
+        rpc_url: "http://localhost:8547",
+        tx_hash: "0xabc",
+        tracer: None,
+        tracer_config: None,
+    };
+
+    std::thread::scope(|scope| {
+        for i in 0..8 {
+            let (cache, key) = (&cache, &key);
+            scope.spawn(move || {
+                for _ in 0..20 {
+                    cache
+                        .store(key, &serde_json::json!({ "writer": i }))
+                        .unwrap();
+                }
+            });
+        }
+    });
+
+    assert!(cache.load(&key).unwrap()["writer"].is_u64());
+    let entries = std::fs::read_dir(dir.path().join("cache")).unwrap().count();
+    assert_eq!(entries, 1);
+}